@@ -0,0 +1,186 @@
+/// Aider バックエンド
+///
+/// Aider はプレーンテキストを出力するため、編集・コミット行のみ検出して履歴に残す。
+use anyhow::Result;
+use portable_pty::CommandBuilder;
+use time::OffsetDateTime;
+use time::format_description::well_known::Rfc3339;
+
+use crate::state::{SessionEvent, SessionHistory};
+
+use super::{Agent, AgentInvocation, AgentOutputParser, resolve_binary};
+
+/// Aider CLI
+pub struct AiderAgent;
+
+impl AiderAgent {
+    fn build_args(invocation: &AgentInvocation<'_>) -> Vec<String> {
+        let step = invocation.step;
+        let mut args = vec![
+            "--message".to_string(),
+            invocation.prompt.to_string(),
+            "--no-pretty".to_string(),
+            "--no-stream".to_string(),
+            "--yes-always".to_string(),
+        ];
+
+        if let Some(model) = &step.model {
+            args.push("--model".to_string());
+            args.push(model.clone());
+        }
+
+        if invocation.permission_mode() == "plan" {
+            args.push("--dry-run".to_string());
+        }
+
+        // Aider has no session ids; restoring the chat history is the closest equivalent
+        if invocation.session_id.is_some() {
+            args.push("--restore-chat-history".to_string());
+        }
+
+        args.extend(invocation.rendered_extra_args());
+        args
+    }
+}
+
+impl Agent for AiderAgent {
+    fn display_name(&self) -> &str {
+        "Aider"
+    }
+
+    fn build_command(&self, invocation: &AgentInvocation<'_>) -> Result<CommandBuilder> {
+        let mut cmd = CommandBuilder::new(resolve_binary("GENSUI_AIDER_BIN", "aider"));
+        cmd.cwd(invocation.workdir);
+        cmd.env("TERM", "dumb");
+        cmd.args(Self::build_args(invocation));
+        Ok(cmd)
+    }
+
+    fn output_parser(&self) -> Box<dyn AgentOutputParser> {
+        Box::new(AiderOutputParser::default())
+    }
+}
+
+/// Aider のプレーンテキスト出力パーサー
+#[derive(Debug, Default)]
+struct AiderOutputParser {
+    events: Vec<SessionEvent>,
+    total_tool_uses: usize,
+    files_modified: Vec<String>,
+}
+
+impl AgentOutputParser for AiderOutputParser {
+    fn feed_line(&mut self, line: &str, log_fn: &mut dyn FnMut(String)) {
+        let line = line.trim_end();
+        if line.is_empty() {
+            return;
+        }
+
+        let timestamp = OffsetDateTime::now_utc()
+            .format(&Rfc3339)
+            .unwrap_or_else(|_| "unknown".to_string());
+
+        if let Some(file) = line.strip_prefix("Applied edit to ") {
+            let file = file.trim();
+            log_fn(format!("🔧 Using tool: Edit ({})", file));
+            self.total_tool_uses += 1;
+            if !self.files_modified.iter().any(|f| f == file) {
+                self.files_modified.push(file.to_string());
+            }
+            self.events.push(SessionEvent::ToolUse {
+                name: "Edit".to_string(),
+                timestamp,
+                input: Some(serde_json::json!({ "file_path": file })),
+            });
+            return;
+        }
+
+        if line.starts_with("Commit ") {
+            self.events.push(SessionEvent::AssistantMessage {
+                text: line.to_string(),
+                timestamp,
+            });
+        }
+
+        log_fn(line.to_string());
+    }
+
+    fn session_id(&self) -> Option<&str> {
+        None
+    }
+
+    fn into_history(
+        self: Box<Self>,
+        prompt: &str,
+        started_at: OffsetDateTime,
+        ended_at: OffsetDateTime,
+    ) -> SessionHistory {
+        SessionHistory {
+            session_id: "unknown".to_string(),
+            started_at: started_at
+                .format(&Rfc3339)
+                .unwrap_or_else(|_| "unknown".to_string()),
+            ended_at: ended_at.format(&Rfc3339).ok(),
+            prompt: prompt.to_string(),
+            events: self.events,
+            total_tool_uses: self.total_tool_uses,
+            files_modified: self.files_modified,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+    use crate::config::ClaudeStep;
+
+    #[test]
+    fn test_build_args_plan_and_continue() {
+        let step = ClaudeStep {
+            permission_mode: Some("plan".to_string()),
+            ..Default::default()
+        };
+        let invocation = AgentInvocation {
+            prompt: "refactor",
+            workdir: Path::new("/wt"),
+            session_id: Some("prev"),
+            step: &step,
+            default_sandbox_mode: true,
+        };
+        let args = AiderAgent::build_args(&invocation);
+        assert_eq!(&args[..2], ["--message", "refactor"]);
+        assert!(args.contains(&"--dry-run".to_string()));
+        assert!(args.contains(&"--restore-chat-history".to_string()));
+    }
+
+    #[test]
+    fn test_parse_aider_output() {
+        let mut parser: Box<dyn AgentOutputParser> = Box::new(AiderOutputParser::default());
+        let mut logged = Vec::new();
+        for line in [
+            "Aider v0.80.0",
+            "Applied edit to src/lib.rs",
+            "Commit abc1234 fix: handle empty input",
+        ] {
+            parser.feed_line(line, &mut |l| logged.push(l));
+        }
+
+        assert_eq!(
+            logged,
+            vec![
+                "Aider v0.80.0",
+                "🔧 Using tool: Edit (src/lib.rs)",
+                "Commit abc1234 fix: handle empty input",
+            ]
+        );
+        assert!(parser.session_id().is_none());
+
+        let now = OffsetDateTime::now_utc();
+        let history = parser.into_history("p", now, now);
+        assert_eq!(history.files_modified, vec!["src/lib.rs"]);
+        assert_eq!(history.total_tool_uses, 1);
+        assert_eq!(history.events.len(), 2);
+    }
+}
@@ -0,0 +1,458 @@
+/// Claude Code バックエンド
+///
+/// `claude --print --output-format stream-json` を起動し、PTYから届いた行を
+/// 到着順に処理してログ行の送出とSessionHistoryの組み立てを行う。
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use portable_pty::CommandBuilder;
+use time::OffsetDateTime;
+use time::format_description::well_known::Rfc3339;
+
+use crate::state::{SessionEvent, SessionHistory};
+
+use super::{Agent, AgentInvocation, AgentOutputParser};
+
+/// Claude Code CLI
+pub struct ClaudeAgent;
+
+impl ClaudeAgent {
+    fn binary() -> String {
+        env::var("GENSUI_CLAUDE_BIN").unwrap_or_else(|_| {
+            // Try to find claude in common locations
+            let home = env::var("HOME").unwrap_or_else(|_| ".".to_string());
+            let local_claude = format!("{}/.claude/local/claude", home);
+            if Path::new(&local_claude).exists() {
+                local_claude
+            } else {
+                "claude".to_string()
+            }
+        })
+    }
+
+    fn build_args(invocation: &AgentInvocation<'_>) -> Vec<String> {
+        let step = invocation.step;
+        let mut args = vec!["--print".to_string(), invocation.prompt.to_string()];
+        args.push("--output-format".to_string());
+        args.push("stream-json".to_string());
+        args.push("--verbose".to_string());
+
+        // Continue existing session if session_id is provided
+        if invocation.session_id.is_some() {
+            args.push("--continue".to_string());
+        }
+
+        if let Some(model) = &step.model {
+            args.push("--model".to_string());
+            args.push(model.clone());
+        }
+
+        // If permission_mode is not set, use bypassPermissions by default
+        // This allows Claude to execute tools freely after user approves the step
+        args.push("--permission-mode".to_string());
+        args.push(invocation.permission_mode().to_string());
+
+        if let Some(tools) = &step.allowed_tools
+            && !tools.is_empty()
+        {
+            args.push("--allowedTools".to_string());
+            args.push(tools.join(","));
+        }
+
+        args.extend(invocation.rendered_extra_args());
+
+        // Sandboxing is controlled via .claude/settings.json
+        // Claude CLI automatically loads this file from the working directory
+        // The default_sandbox_mode and step.sandbox_mode settings are kept for
+        // future extensibility and documentation purposes, but currently have no effect
+        // on the CLI arguments (sandboxing is configured through settings file)
+        let _sandbox_enabled = invocation.sandbox_enabled();
+
+        args
+    }
+}
+
+impl Agent for ClaudeAgent {
+    fn display_name(&self) -> &str {
+        "Claude Code"
+    }
+
+    fn build_command(&self, invocation: &AgentInvocation<'_>) -> Result<CommandBuilder> {
+        let mut cmd = CommandBuilder::new(Self::binary());
+        cmd.cwd(invocation.workdir);
+
+        // Set environment variables
+        cmd.env("TERM", "xterm-256color");
+
+        // Only set custom CLAUDE_CONFIG_DIR if GENSUI_CLAUDE_HOME is explicitly set
+        // Otherwise, use the user's default Claude configuration (including API keys)
+        if let Ok(custom_home) = env::var("GENSUI_CLAUDE_HOME") {
+            let claude_home = PathBuf::from(custom_home);
+            fs::create_dir_all(&claude_home).with_context(|| {
+                format!(
+                    "failed to prepare Claude config directory at {}",
+                    claude_home.display()
+                )
+            })?;
+            cmd.env("CLAUDE_CONFIG_DIR", claude_home.to_string_lossy().to_string());
+        }
+
+        cmd.args(Self::build_args(invocation));
+        Ok(cmd)
+    }
+
+    fn output_parser(&self) -> Box<dyn AgentOutputParser> {
+        Box::new(ClaudeStreamParser::new())
+    }
+}
+
+/// stream-json パーサーの状態
+#[derive(Debug, Default)]
+pub struct ClaudeStreamParser {
+    session_id: Option<String>,
+    events: Vec<SessionEvent>,
+    total_tool_uses: usize,
+    files_modified: Vec<String>,
+}
+
+impl ClaudeStreamParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl AgentOutputParser for ClaudeStreamParser {
+    fn feed_line(&mut self, line: &str, log_fn: &mut dyn FnMut(String)) {
+        let line = line.trim_end();
+        if line.is_empty() {
+            return;
+        }
+
+        let Ok(json) = serde_json::from_str::<serde_json::Value>(line) else {
+            // Non-JSON output
+            log_fn(line.to_string());
+            return;
+        };
+
+        let event_time = OffsetDateTime::now_utc()
+            .format(&Rfc3339)
+            .unwrap_or_else(|_| "unknown".to_string());
+
+        // Extract thinking/analysis
+        let thought_lines = extract_thinking_lines(&json);
+        if !thought_lines.is_empty() {
+            log_fn("[THOUGHT_START]".to_string());
+            let mut thinking_content = String::new();
+            for thought_line in thought_lines {
+                log_fn(thought_line.clone());
+                thinking_content.push_str(&thought_line);
+                thinking_content.push('\n');
+            }
+            log_fn("[THOUGHT_END]".to_string());
+
+            if !thinking_content.is_empty() {
+                self.events.push(SessionEvent::ThinkingBlock {
+                    content: thinking_content.trim().to_string(),
+                    timestamp: event_time.clone(),
+                });
+            }
+        }
+
+        match json.get("type").and_then(|v| v.as_str()) {
+            Some("system") => {
+                // Session initialization - extract session_id but don't log
+                if let Some(sid) = json.get("session_id").and_then(|v| v.as_str()) {
+                    self.session_id = Some(sid.to_string());
+                }
+            }
+            Some("result") => {
+                let is_error = json
+                    .get("is_error")
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false);
+
+                if is_error {
+                    log_fn("⚠️  Claude encountered an error".to_string());
+                }
+
+                if let Some(result_text) = json.get("result").and_then(|v| v.as_str())
+                    && !result_text.is_empty()
+                {
+                    log_fn("─── Result ───".to_string());
+                    for result_line in result_text.lines() {
+                        log_fn(result_line.to_string());
+                    }
+
+                    self.events.push(SessionEvent::Result {
+                        text: result_text.to_string(),
+                        is_error,
+                        timestamp: event_time,
+                    });
+                }
+            }
+            Some("error") => {
+                // API error response
+                log_fn("❌ API Error:".to_string());
+                let mut error_message = String::new();
+                if let Some(message) = json
+                    .get("error")
+                    .and_then(|error_obj| error_obj.get("message"))
+                    .and_then(|v| v.as_str())
+                {
+                    log_fn(format!("  {}", message));
+                    error_message = message.to_string();
+                }
+
+                self.events.push(SessionEvent::Error {
+                    message: error_message,
+                    timestamp: event_time,
+                });
+            }
+            Some("assistant") => {
+                if let Some(text) = json.get("text").and_then(|v| v.as_str())
+                    && !text.trim().is_empty()
+                {
+                    log_fn(format!("💬 {}", text));
+
+                    self.events.push(SessionEvent::AssistantMessage {
+                        text: text.to_string(),
+                        timestamp: event_time,
+                    });
+                }
+            }
+            Some("tool_use") => {
+                if let Some(tool_name) = json.get("name").and_then(|v| v.as_str()) {
+                    log_fn(format!("🔧 Using tool: {}", tool_name));
+
+                    self.total_tool_uses += 1;
+
+                    // Extract file path for file modification tools
+                    if matches!(tool_name, "Edit" | "Write")
+                        && let Some(file_path) = json
+                            .get("input")
+                            .and_then(|input| input.get("file_path"))
+                            .and_then(|v| v.as_str())
+                        && !self.files_modified.iter().any(|f| f == file_path)
+                    {
+                        self.files_modified.push(file_path.to_string());
+                    }
+
+                    self.events.push(SessionEvent::ToolUse {
+                        name: tool_name.to_string(),
+                        timestamp: event_time,
+                        input: json.get("input").cloned(),
+                    });
+                }
+            }
+            Some("tool_result") => {
+                if let Some(tool_name) = json.get("tool_use_id").and_then(|v| v.as_str()) {
+                    let output = json
+                        .get("content")
+                        .and_then(|v| v.as_str())
+                        .map(|s| s.to_string());
+
+                    self.events.push(SessionEvent::ToolResult {
+                        name: tool_name.to_string(),
+                        timestamp: event_time,
+                        output,
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn session_id(&self) -> Option<&str> {
+        self.session_id.as_deref()
+    }
+
+    fn into_history(
+        self: Box<Self>,
+        prompt: &str,
+        started_at: OffsetDateTime,
+        ended_at: OffsetDateTime,
+    ) -> SessionHistory {
+        SessionHistory {
+            session_id: self
+                .session_id
+                .unwrap_or_else(|| "unknown".to_string()),
+            started_at: started_at
+                .format(&Rfc3339)
+                .unwrap_or_else(|_| "unknown".to_string()),
+            ended_at: Some(
+                ended_at
+                    .format(&Rfc3339)
+                    .unwrap_or_else(|_| "unknown".to_string()),
+            ),
+            prompt: prompt.to_string(),
+            events: self.events,
+            total_tool_uses: self.total_tool_uses,
+            files_modified: self.files_modified,
+        }
+    }
+}
+
+fn extract_thinking_lines(json: &serde_json::Value) -> Vec<String> {
+    fn walk(value: &serde_json::Value, acc: &mut Vec<String>, in_thinking: bool) {
+        match value {
+            serde_json::Value::String(text) if in_thinking => {
+                for line in text.lines() {
+                    let trimmed = line.trim_end();
+                    if trimmed.is_empty() {
+                        continue;
+                    }
+                    acc.push(trimmed.to_string());
+                }
+            }
+            serde_json::Value::Array(items) => {
+                for item in items {
+                    walk(item, acc, in_thinking);
+                }
+            }
+            serde_json::Value::Object(map) => {
+                let mut next_flag = in_thinking;
+                if let Some(ty) = map.get("type").and_then(|v| v.as_str()) {
+                    let ty_lc = ty.to_ascii_lowercase();
+                    if matches!(
+                        ty_lc.as_str(),
+                        "thinking" | "analysis" | "plan" | "reasoning"
+                    ) {
+                        next_flag = true;
+                    }
+                }
+
+                if let Some(thinking) = map.get("thinking") {
+                    walk(thinking, acc, true);
+                }
+
+                if let Some(text) = map.get("text") {
+                    walk(text, acc, next_flag);
+                }
+
+                if let Some(content) = map.get("content") {
+                    walk(content, acc, next_flag);
+                }
+
+                if let Some(message) = map.get("message") {
+                    walk(message, acc, next_flag);
+                }
+
+                for (key, value) in map {
+                    if matches!(key.as_str(), "thinking" | "analysis" | "reasoning" | "plan") {
+                        walk(value, acc, true);
+                    } else if !matches!(key.as_str(), "text" | "content" | "message") {
+                        walk(value, acc, next_flag);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut acc = Vec::new();
+    walk(json, &mut acc, false);
+    acc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ClaudeStep;
+
+    fn feed_all(parser: &mut ClaudeStreamParser, lines: &[&str]) -> Vec<String> {
+        let mut logged = Vec::new();
+        for line in lines {
+            parser.feed_line(line, &mut |l| logged.push(l));
+        }
+        logged
+    }
+
+    #[test]
+    fn test_lines_are_logged_as_they_arrive() {
+        let mut parser = ClaudeStreamParser::new();
+        let mut logged = Vec::new();
+
+        parser.feed_line(r#"{"type":"tool_use","name":"Read"}"#, &mut |l| logged.push(l));
+        assert_eq!(logged, vec!["🔧 Using tool: Read"]);
+
+        parser.feed_line(r#"{"type":"assistant","text":"done"}"#, &mut |l| logged.push(l));
+        assert_eq!(logged.last().map(String::as_str), Some("💬 done"));
+    }
+
+    #[test]
+    fn test_session_id_and_history() {
+        let mut parser = ClaudeStreamParser::new();
+        feed_all(
+            &mut parser,
+            &[
+                r#"{"type":"system","session_id":"abc-123"}"#,
+                r#"{"type":"tool_use","name":"Edit","input":{"file_path":"src/lib.rs"}}"#,
+                r#"{"type":"tool_use","name":"Write","input":{"file_path":"src/lib.rs"}}"#,
+                r#"{"type":"result","result":"All good","is_error":false}"#,
+            ],
+        );
+
+        assert_eq!(parser.session_id(), Some("abc-123"));
+
+        let now = OffsetDateTime::now_utc();
+        let history = Box::new(parser).into_history("prompt", now, now);
+        assert_eq!(history.session_id, "abc-123");
+        assert_eq!(history.total_tool_uses, 2);
+        assert_eq!(history.files_modified, vec!["src/lib.rs"]);
+        assert!(matches!(
+            history.events.last(),
+            Some(SessionEvent::Result { text, .. }) if text == "All good"
+        ));
+    }
+
+    #[test]
+    fn test_build_args_with_session_and_tools() {
+        let step = ClaudeStep {
+            prompt: "ignored".to_string(),
+            model: Some("sonnet".to_string()),
+            allowed_tools: Some(vec!["Read".to_string(), "Edit".to_string()]),
+            permission_mode: Some("acceptEdits".to_string()),
+            extra_args: Some(vec!["--add-dir".to_string(), "{{workdir}}".to_string()]),
+            sandbox_mode: None,
+            auto_commit: None,
+        };
+        let invocation = AgentInvocation {
+            prompt: "do it",
+            workdir: Path::new("/wt"),
+            session_id: Some("abc"),
+            step: &step,
+            default_sandbox_mode: true,
+        };
+
+        let args = ClaudeAgent::build_args(&invocation);
+        assert_eq!(
+            args,
+            vec![
+                "--print", "do it", "--output-format", "stream-json", "--verbose",
+                "--continue", "--model", "sonnet", "--permission-mode", "acceptEdits",
+                "--allowedTools", "Read,Edit", "--add-dir", "/wt",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_non_json_lines_pass_through() {
+        let mut parser = ClaudeStreamParser::new();
+        let logged = feed_all(&mut parser, &["plain output\r", "", "   "]);
+        assert_eq!(logged, vec!["plain output"]);
+    }
+
+    #[test]
+    fn test_thinking_blocks_are_wrapped() {
+        let mut parser = ClaudeStreamParser::new();
+        let logged = feed_all(
+            &mut parser,
+            &[r#"{"type":"assistant","message":{"content":[{"type":"thinking","thinking":"step one"}]}}"#],
+        );
+        assert_eq!(logged.first().map(String::as_str), Some("[THOUGHT_START]"));
+        assert!(logged.iter().any(|l| l == "step one"));
+        assert_eq!(logged.last().map(String::as_str), Some("[THOUGHT_END]"));
+    }
+}
@@ -0,0 +1,311 @@
+/// OpenAI Codex CLI バックエンド
+///
+/// `codex exec --json` の JSON Lines 出力（thread/turn/item イベント）を解析する。
+use anyhow::Result;
+use portable_pty::CommandBuilder;
+use time::OffsetDateTime;
+use time::format_description::well_known::Rfc3339;
+
+use crate::state::{SessionEvent, SessionHistory};
+
+use super::{Agent, AgentInvocation, AgentOutputParser, resolve_binary};
+
+/// Codex CLI
+pub struct CodexAgent;
+
+impl CodexAgent {
+    fn build_args(invocation: &AgentInvocation<'_>) -> Vec<String> {
+        let step = invocation.step;
+        let mut args = vec!["exec".to_string(), "--json".to_string()];
+
+        if let Some(model) = &step.model {
+            args.push("--model".to_string());
+            args.push(model.clone());
+        }
+
+        // Map gensui permission modes onto Codex sandbox/approval flags
+        match invocation.permission_mode() {
+            "plan" => {
+                args.push("--sandbox".to_string());
+                args.push("read-only".to_string());
+            }
+            "bypassPermissions" if !invocation.sandbox_enabled() => {
+                args.push("--dangerously-bypass-approvals-and-sandbox".to_string());
+            }
+            _ => args.push("--full-auto".to_string()),
+        }
+
+        args.extend(invocation.rendered_extra_args());
+
+        if let Some(session_id) = invocation.session_id {
+            args.push("resume".to_string());
+            args.push(session_id.to_string());
+        }
+
+        args.push(invocation.prompt.to_string());
+        args
+    }
+}
+
+impl Agent for CodexAgent {
+    fn display_name(&self) -> &str {
+        "Codex"
+    }
+
+    fn build_command(&self, invocation: &AgentInvocation<'_>) -> Result<CommandBuilder> {
+        let mut cmd = CommandBuilder::new(resolve_binary("GENSUI_CODEX_BIN", "codex"));
+        cmd.cwd(invocation.workdir);
+        cmd.env("TERM", "xterm-256color");
+        cmd.args(Self::build_args(invocation));
+        Ok(cmd)
+    }
+
+    fn output_parser(&self) -> Box<dyn AgentOutputParser> {
+        Box::new(CodexStreamParser::default())
+    }
+}
+
+/// `codex exec --json` 出力のパーサー
+#[derive(Debug, Default)]
+struct CodexStreamParser {
+    thread_id: Option<String>,
+    events: Vec<SessionEvent>,
+    total_tool_uses: usize,
+    files_modified: Vec<String>,
+    last_message: Option<String>,
+}
+
+impl CodexStreamParser {
+    fn handle_item(
+        &mut self,
+        item: &serde_json::Value,
+        timestamp: String,
+        log_fn: &mut dyn FnMut(String),
+    ) {
+        match item.get("type").and_then(|v| v.as_str()) {
+            Some("agent_message") => {
+                if let Some(text) = item.get("text").and_then(|v| v.as_str()) {
+                    log_fn(format!("💬 {}", text));
+                    self.last_message = Some(text.to_string());
+                    self.events.push(SessionEvent::AssistantMessage {
+                        text: text.to_string(),
+                        timestamp,
+                    });
+                }
+            }
+            Some("reasoning") => {
+                if let Some(text) = item.get("text").and_then(|v| v.as_str()) {
+                    log_fn("[THOUGHT_START]".to_string());
+                    for line in text.lines().filter(|l| !l.trim().is_empty()) {
+                        log_fn(line.to_string());
+                    }
+                    log_fn("[THOUGHT_END]".to_string());
+                    self.events.push(SessionEvent::ThinkingBlock {
+                        content: text.trim().to_string(),
+                        timestamp,
+                    });
+                }
+            }
+            Some("command_execution") => {
+                let command = item
+                    .get("command")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or_default();
+                log_fn(format!("🔧 Using tool: Bash ({})", command));
+                self.total_tool_uses += 1;
+                self.events.push(SessionEvent::ToolUse {
+                    name: "Bash".to_string(),
+                    timestamp: timestamp.clone(),
+                    input: Some(serde_json::json!({ "command": command })),
+                });
+                self.events.push(SessionEvent::ToolResult {
+                    name: "Bash".to_string(),
+                    timestamp,
+                    output: item
+                        .get("aggregated_output")
+                        .and_then(|v| v.as_str())
+                        .map(|s| s.to_string()),
+                });
+            }
+            Some("file_change") => {
+                let changes = item
+                    .get("changes")
+                    .and_then(|v| v.as_array())
+                    .cloned()
+                    .unwrap_or_default();
+                for change in &changes {
+                    if let Some(path) = change.get("path").and_then(|v| v.as_str()) {
+                        log_fn(format!("🔧 Using tool: Edit ({})", path));
+                        if !self.files_modified.iter().any(|f| f == path) {
+                            self.files_modified.push(path.to_string());
+                        }
+                    }
+                }
+                self.total_tool_uses += 1;
+                self.events.push(SessionEvent::ToolUse {
+                    name: "Edit".to_string(),
+                    timestamp,
+                    input: Some(serde_json::Value::Array(changes)),
+                });
+            }
+            _ => {}
+        }
+    }
+}
+
+impl AgentOutputParser for CodexStreamParser {
+    fn feed_line(&mut self, line: &str, log_fn: &mut dyn FnMut(String)) {
+        let line = line.trim_end();
+        if line.is_empty() {
+            return;
+        }
+
+        let Ok(json) = serde_json::from_str::<serde_json::Value>(line) else {
+            log_fn(line.to_string());
+            return;
+        };
+
+        let timestamp = OffsetDateTime::now_utc()
+            .format(&Rfc3339)
+            .unwrap_or_else(|_| "unknown".to_string());
+
+        match json.get("type").and_then(|v| v.as_str()) {
+            Some("thread.started") => {
+                if let Some(id) = json.get("thread_id").and_then(|v| v.as_str()) {
+                    self.thread_id = Some(id.to_string());
+                }
+            }
+            Some("item.completed") => {
+                if let Some(item) = json.get("item") {
+                    self.handle_item(item, timestamp, log_fn);
+                }
+            }
+            Some("turn.completed") => {
+                if let Some(text) = self.last_message.take() {
+                    log_fn("─── Result ───".to_string());
+                    for line in text.lines() {
+                        log_fn(line.to_string());
+                    }
+                    self.events.push(SessionEvent::Result {
+                        text,
+                        is_error: false,
+                        timestamp,
+                    });
+                }
+            }
+            Some("turn.failed") | Some("error") => {
+                let message = json
+                    .get("error")
+                    .and_then(|e| e.get("message"))
+                    .or_else(|| json.get("message"))
+                    .and_then(|v| v.as_str())
+                    .unwrap_or_default()
+                    .to_string();
+                log_fn("❌ API Error:".to_string());
+                log_fn(format!("  {}", message));
+                self.events.push(SessionEvent::Error { message, timestamp });
+            }
+            _ => {}
+        }
+    }
+
+    fn session_id(&self) -> Option<&str> {
+        self.thread_id.as_deref()
+    }
+
+    fn into_history(
+        self: Box<Self>,
+        prompt: &str,
+        started_at: OffsetDateTime,
+        ended_at: OffsetDateTime,
+    ) -> SessionHistory {
+        SessionHistory {
+            session_id: self.thread_id.unwrap_or_else(|| "unknown".to_string()),
+            started_at: started_at
+                .format(&Rfc3339)
+                .unwrap_or_else(|_| "unknown".to_string()),
+            ended_at: ended_at.format(&Rfc3339).ok(),
+            prompt: prompt.to_string(),
+            events: self.events,
+            total_tool_uses: self.total_tool_uses,
+            files_modified: self.files_modified,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+    use crate::config::ClaudeStep;
+
+    #[test]
+    fn test_build_args_resume_session() {
+        let step = ClaudeStep {
+            prompt: String::new(),
+            model: Some("gpt-5-codex".to_string()),
+            permission_mode: Some("acceptEdits".to_string()),
+            ..Default::default()
+        };
+        let invocation = AgentInvocation {
+            prompt: "fix it",
+            workdir: Path::new("/wt"),
+            session_id: Some("thread-1"),
+            step: &step,
+            default_sandbox_mode: true,
+        };
+        assert_eq!(
+            CodexAgent::build_args(&invocation),
+            vec![
+                "exec", "--json", "--model", "gpt-5-codex", "--full-auto", "resume",
+                "thread-1", "fix it",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_plan_mode_is_read_only() {
+        let step = ClaudeStep {
+            permission_mode: Some("plan".to_string()),
+            ..Default::default()
+        };
+        let invocation = AgentInvocation {
+            prompt: "look",
+            workdir: Path::new("/wt"),
+            session_id: None,
+            step: &step,
+            default_sandbox_mode: false,
+        };
+        let args = CodexAgent::build_args(&invocation);
+        assert!(args.windows(2).any(|w| w == ["--sandbox", "read-only"]));
+    }
+
+    #[test]
+    fn test_parse_codex_stream() {
+        let mut parser: Box<dyn AgentOutputParser> = Box::new(CodexStreamParser::default());
+        let mut logged = Vec::new();
+        for line in [
+            r#"{"type":"thread.started","thread_id":"t-42"}"#,
+            r#"{"type":"item.completed","item":{"type":"command_execution","command":"cargo test","aggregated_output":"ok","exit_code":0}}"#,
+            r#"{"type":"item.completed","item":{"type":"file_change","changes":[{"path":"src/main.rs","kind":"update"}]}}"#,
+            r#"{"type":"item.completed","item":{"type":"agent_message","text":"Fixed"}}"#,
+            r#"{"type":"turn.completed","usage":{}}"#,
+        ] {
+            parser.feed_line(line, &mut |l| logged.push(l));
+        }
+
+        assert_eq!(parser.session_id(), Some("t-42"));
+        assert!(logged.contains(&"─── Result ───".to_string()));
+
+        let now = OffsetDateTime::now_utc();
+        let history = parser.into_history("p", now, now);
+        assert_eq!(history.session_id, "t-42");
+        assert_eq!(history.total_tool_uses, 2);
+        assert_eq!(history.files_modified, vec!["src/main.rs"]);
+        assert!(matches!(
+            history.events.last(),
+            Some(SessionEvent::Result { text, .. }) if text == "Fixed"
+        ));
+    }
+}
@@ -0,0 +1,290 @@
+/// 設定ファイルで定義する汎用CLIエージェント
+///
+/// コマンドテンプレートと正規表現ルールだけで任意のCLIをワーカーから実行できる。
+use anyhow::{Context, Result};
+use portable_pty::CommandBuilder;
+use regex::Regex;
+use time::OffsetDateTime;
+use time::format_description::well_known::Rfc3339;
+
+use crate::config::{GenericAgentConfig, OutputKind};
+use crate::state::{SessionEvent, SessionHistory};
+
+use super::{Agent, AgentInvocation, AgentOutputParser};
+
+/// 汎用CLIエージェント
+pub struct GenericAgent {
+    config: GenericAgentConfig,
+    session_id_pattern: Option<Regex>,
+    rules: Vec<(Regex, OutputKind)>,
+}
+
+impl GenericAgent {
+    pub fn new(config: GenericAgentConfig) -> Result<Self> {
+        let session_id_pattern = config
+            .session_id_pattern
+            .as_deref()
+            .map(Regex::new)
+            .transpose()
+            .with_context(|| format!("invalid session_id_pattern for agent '{}'", config.name))?;
+
+        let rules = config
+            .output
+            .iter()
+            .map(|rule| {
+                Regex::new(&rule.pattern)
+                    .map(|re| (re, rule.kind))
+                    .with_context(|| {
+                        format!(
+                            "invalid output pattern '{}' for agent '{}'",
+                            rule.pattern, config.name
+                        )
+                    })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self {
+            config,
+            session_id_pattern,
+            rules,
+        })
+    }
+
+    fn build_args(&self, invocation: &AgentInvocation<'_>) -> Vec<String> {
+        let template = match (invocation.session_id, &self.config.continue_args) {
+            (Some(_), Some(continue_args)) => continue_args,
+            _ => &self.config.args,
+        };
+        let workdir = invocation.workdir.to_string_lossy();
+        let model = invocation.step.model.as_deref().unwrap_or_default();
+        let session_id = invocation.session_id.unwrap_or_default();
+
+        let mut args: Vec<String> = template
+            .iter()
+            .map(|arg| {
+                arg.replace("{{prompt}}", invocation.prompt)
+                    .replace("{{workdir}}", &workdir)
+                    .replace("{{model}}", model)
+                    .replace("{{session_id}}", session_id)
+            })
+            .collect();
+        args.extend(invocation.rendered_extra_args());
+        args
+    }
+}
+
+impl Agent for GenericAgent {
+    fn display_name(&self) -> &str {
+        &self.config.name
+    }
+
+    fn build_command(&self, invocation: &AgentInvocation<'_>) -> Result<CommandBuilder> {
+        let mut cmd = CommandBuilder::new(&self.config.command);
+        cmd.cwd(invocation.workdir);
+        for (key, value) in &self.config.env {
+            cmd.env(key, value);
+        }
+        cmd.args(self.build_args(invocation));
+        Ok(cmd)
+    }
+
+    fn output_parser(&self) -> Box<dyn AgentOutputParser> {
+        Box::new(GenericOutputParser {
+            session_id_pattern: self.session_id_pattern.clone(),
+            rules: self.rules.clone(),
+            session_id: None,
+            events: Vec::new(),
+            total_tool_uses: 0,
+        })
+    }
+}
+
+/// 正規表現ルールに基づく出力パーサー
+struct GenericOutputParser {
+    session_id_pattern: Option<Regex>,
+    rules: Vec<(Regex, OutputKind)>,
+    session_id: Option<String>,
+    events: Vec<SessionEvent>,
+    total_tool_uses: usize,
+}
+
+impl AgentOutputParser for GenericOutputParser {
+    fn feed_line(&mut self, line: &str, log_fn: &mut dyn FnMut(String)) {
+        let line = line.trim_end();
+        if line.is_empty() {
+            return;
+        }
+
+        if self.session_id.is_none()
+            && let Some(caps) = self
+                .session_id_pattern
+                .as_ref()
+                .and_then(|re| re.captures(line))
+            && let Some(id) = caps.get(1)
+        {
+            self.session_id = Some(id.as_str().to_string());
+        }
+
+        let matched = self.rules.iter().find_map(|(re, kind)| {
+            re.captures(line).map(|caps| {
+                let text = caps
+                    .get(1)
+                    .map(|m| m.as_str())
+                    .unwrap_or(line)
+                    .to_string();
+                (*kind, text)
+            })
+        });
+
+        let Some((kind, text)) = matched else {
+            log_fn(line.to_string());
+            return;
+        };
+
+        let timestamp = OffsetDateTime::now_utc()
+            .format(&Rfc3339)
+            .unwrap_or_else(|_| "unknown".to_string());
+
+        match kind {
+            OutputKind::ToolUse => {
+                log_fn(format!("🔧 Using tool: {}", text));
+                self.total_tool_uses += 1;
+                self.events.push(SessionEvent::ToolUse {
+                    name: text,
+                    timestamp,
+                    input: None,
+                });
+            }
+            OutputKind::Assistant => {
+                log_fn(format!("💬 {}", text));
+                self.events.push(SessionEvent::AssistantMessage { text, timestamp });
+            }
+            OutputKind::Thinking => {
+                log_fn("[THOUGHT_START]".to_string());
+                log_fn(text.clone());
+                log_fn("[THOUGHT_END]".to_string());
+                self.events.push(SessionEvent::ThinkingBlock {
+                    content: text,
+                    timestamp,
+                });
+            }
+            OutputKind::Result => {
+                log_fn("─── Result ───".to_string());
+                log_fn(text.clone());
+                self.events.push(SessionEvent::Result {
+                    text,
+                    is_error: false,
+                    timestamp,
+                });
+            }
+            OutputKind::Error => {
+                log_fn(format!("❌ {}", text));
+                self.events.push(SessionEvent::Error {
+                    message: text,
+                    timestamp,
+                });
+            }
+        }
+    }
+
+    fn session_id(&self) -> Option<&str> {
+        self.session_id.as_deref()
+    }
+
+    fn into_history(
+        self: Box<Self>,
+        prompt: &str,
+        started_at: OffsetDateTime,
+        ended_at: OffsetDateTime,
+    ) -> SessionHistory {
+        SessionHistory {
+            session_id: self.session_id.unwrap_or_else(|| "unknown".to_string()),
+            started_at: started_at
+                .format(&Rfc3339)
+                .unwrap_or_else(|_| "unknown".to_string()),
+            ended_at: ended_at.format(&Rfc3339).ok(),
+            prompt: prompt.to_string(),
+            events: self.events,
+            total_tool_uses: self.total_tool_uses,
+            files_modified: Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+    use crate::config::{ClaudeStep, OutputRule};
+
+    fn sample_config() -> GenericAgentConfig {
+        GenericAgentConfig {
+            name: "my-agent".to_string(),
+            command: "my-agent".to_string(),
+            args: vec!["run".to_string(), "--model={{model}}".to_string(), "{{prompt}}".to_string()],
+            continue_args: Some(vec!["resume".to_string(), "{{session_id}}".to_string(), "{{prompt}}".to_string()]),
+            session_id_pattern: Some(r"^session: (\S+)".to_string()),
+            output: vec![
+                OutputRule {
+                    pattern: r"^TOOL (.+)$".to_string(),
+                    kind: OutputKind::ToolUse,
+                },
+                OutputRule {
+                    pattern: r"^DONE (.+)$".to_string(),
+                    kind: OutputKind::Result,
+                },
+            ],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_build_args_uses_continue_template() {
+        let agent = GenericAgent::new(sample_config()).unwrap();
+        let step = ClaudeStep {
+            model: Some("m1".to_string()),
+            ..Default::default()
+        };
+        let mut invocation = AgentInvocation {
+            prompt: "go",
+            workdir: Path::new("/wt"),
+            session_id: None,
+            step: &step,
+            default_sandbox_mode: true,
+        };
+        assert_eq!(agent.build_args(&invocation), vec!["run", "--model=m1", "go"]);
+
+        invocation.session_id = Some("s-1");
+        assert_eq!(agent.build_args(&invocation), vec!["resume", "s-1", "go"]);
+    }
+
+    #[test]
+    fn test_output_rules_and_session_id() {
+        let agent = GenericAgent::new(sample_config()).unwrap();
+        let mut parser = agent.output_parser();
+        let mut logged = Vec::new();
+        for line in ["session: abc", "TOOL grep", "plain text", "DONE all good"] {
+            parser.feed_line(line, &mut |l| logged.push(l));
+        }
+
+        assert_eq!(parser.session_id(), Some("abc"));
+        assert_eq!(
+            logged,
+            vec![
+                "session: abc",
+                "🔧 Using tool: grep",
+                "plain text",
+                "─── Result ───",
+                "all good",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_invalid_pattern_is_rejected() {
+        let mut config = sample_config();
+        config.session_id_pattern = Some("(".to_string());
+        assert!(GenericAgent::new(config).is_err());
+    }
+}
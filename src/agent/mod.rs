@@ -0,0 +1,335 @@
+//! エージェントバックエンドモジュール
+//!
+//! Claude Code / Codex CLI / Aider / 任意のCLIを共通の `Agent` トレイトで扱い、
+//! ワークフローステップごとに実行バックエンドを切り替えられるようにする。
+
+mod aider;
+mod claude;
+mod codex;
+mod generic;
+
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::mpsc;
+use std::thread;
+
+use anyhow::{Context, Result, anyhow};
+use portable_pty::{CommandBuilder, PtySize, native_pty_system};
+use time::OffsetDateTime;
+
+use crate::config::{ClaudeStep, Config};
+use crate::state::SessionHistory;
+use crate::worker::{CommandFailed, ProcessTracker};
+
+pub use aider::AiderAgent;
+pub use claude::ClaudeAgent;
+pub use codex::CodexAgent;
+pub use generic::GenericAgent;
+
+/// 既定のエージェント名
+pub const DEFAULT_AGENT: &str = "claude";
+
+/// Agents that are always registered, in addition to those defined in the config.
+pub const BUILTIN_AGENTS: [&str; 3] = [DEFAULT_AGENT, "codex", "aider"];
+
+/// エージェント起動時のパラメータ
+pub struct AgentInvocation<'a> {
+    pub prompt: &'a str,
+    pub workdir: &'a Path,
+    pub session_id: Option<&'a str>,
+    pub step: &'a ClaudeStep,
+    pub default_sandbox_mode: bool,
+}
+
+impl AgentInvocation<'_> {
+    /// パーミッションモード（未指定時は bypassPermissions）
+    pub fn permission_mode(&self) -> &str {
+        self.step
+            .permission_mode
+            .as_deref()
+            .unwrap_or("bypassPermissions")
+    }
+
+    /// サンドボックスが有効か（ステップ設定がグローバル既定を上書き）
+    pub fn sandbox_enabled(&self) -> bool {
+        self.step.sandbox_mode.unwrap_or(self.default_sandbox_mode)
+    }
+
+    /// `extra_args` のプレースホルダを展開して返す
+    pub fn rendered_extra_args(&self) -> Vec<String> {
+        self.step
+            .extra_args
+            .iter()
+            .flatten()
+            .map(|arg| {
+                arg.replace("{{prompt}}", self.prompt)
+                    .replace("{{workdir}}", &self.workdir.to_string_lossy())
+            })
+            .collect()
+    }
+}
+
+/// エージェントバックエンド
+pub trait Agent: Send + Sync {
+    /// 表示用の名前
+    fn display_name(&self) -> &str;
+
+    /// PTY上で起動するコマンドを組み立てる
+    ///
+    /// `invocation.session_id` が与えられた場合はセッションを継続する引数を付与する。
+    fn build_command(&self, invocation: &AgentInvocation<'_>) -> Result<CommandBuilder>;
+
+    /// 出力ストリーム用のパーサーを生成
+    fn output_parser(&self) -> Box<dyn AgentOutputParser>;
+}
+
+/// エージェント出力を逐次解析するパーサー
+pub trait AgentOutputParser: Send {
+    /// 1行を解析し、表示用のログ行を `log_fn` に送る
+    fn feed_line(&mut self, line: &str, log_fn: &mut dyn FnMut(String));
+
+    /// これまでに取得したセッションID
+    fn session_id(&self) -> Option<&str>;
+
+    /// 収集したイベントからSessionHistoryを組み立てる
+    fn into_history(
+        self: Box<Self>,
+        prompt: &str,
+        started_at: OffsetDateTime,
+        ended_at: OffsetDateTime,
+    ) -> SessionHistory;
+}
+
+/// 名前からエージェントを解決するレジストリ
+#[derive(Clone)]
+pub struct AgentRegistry {
+    agents: HashMap<String, Arc<dyn Agent>>,
+}
+
+impl AgentRegistry {
+    /// 組み込みエージェントと設定ファイルの汎用エージェントを登録
+    pub fn from_config(config: &Config) -> Self {
+        let mut agents: HashMap<String, Arc<dyn Agent>> = HashMap::new();
+        agents.insert(DEFAULT_AGENT.to_string(), Arc::new(ClaudeAgent));
+        agents.insert("codex".to_string(), Arc::new(CodexAgent));
+        agents.insert("aider".to_string(), Arc::new(AiderAgent));
+        for agent_cfg in &config.agents {
+            match GenericAgent::new(agent_cfg.clone()) {
+                Ok(agent) => {
+                    agents.insert(agent_cfg.name.clone(), Arc::new(agent));
+                }
+                Err(err) => {
+                    eprintln!("Failed to load agent '{}': {err}", agent_cfg.name);
+                }
+            }
+        }
+        Self { agents }
+    }
+
+    pub fn get(&self, name: &str) -> Option<Arc<dyn Agent>> {
+        self.agents.get(name).cloned()
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.agents.contains_key(name)
+    }
+
+    /// 登録済みのエージェント名（ソート済み）
+    pub fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.agents.keys().cloned().collect();
+        names.sort();
+        names
+    }
+}
+
+/// エージェントをPTY上で実行し、出力を逐次 `log_fn` に流す
+///
+/// 子プロセスは `processes` に登録され、ワーカーのキャンセル時に終了される。
+/// 戻り値は (セッションID, セッション履歴)。
+pub fn run_agent_command<F>(
+    agent: &dyn Agent,
+    invocation: &AgentInvocation<'_>,
+    processes: &ProcessTracker,
+    mut log_fn: F,
+) -> Result<(Option<String>, SessionHistory)>
+where
+    F: FnMut(String),
+{
+    let start_time = OffsetDateTime::now_utc();
+
+    // Initialize PTY system
+    let pty_system = native_pty_system();
+    let pty_size = PtySize {
+        rows: 24,
+        cols: 120,
+        pixel_width: 0,
+        pixel_height: 0,
+    };
+    let pty_pair = pty_system.openpty(pty_size)
+        .with_context(|| "failed to open PTY")?;
+
+    let cmd = agent.build_command(invocation)?;
+
+    // Spawn command through PTY
+    let mut child = pty_pair.slave.spawn_command(cmd)
+        .with_context(|| format!("failed to spawn {} process in PTY", agent.display_name()))?;
+
+    // The PTY child runs in its own session, so its pid is also its process group id
+    let _process_guard = child.process_id().map(|pid| processes.register(pid));
+
+    // Close our copy of the slave so the reader sees EOF once the child exits
+    drop(pty_pair.slave);
+
+    // Read from PTY master in a separate thread and forward complete lines
+    let mut reader = pty_pair.master.try_clone_reader()
+        .with_context(|| "failed to clone PTY reader")?;
+
+    let (line_tx, line_rx) = mpsc::channel::<String>();
+    let reader_thread = thread::spawn(move || {
+        let mut pending = Vec::new();
+        let mut read_buf = [0u8; 8192];
+
+        loop {
+            match reader.read(&mut read_buf) {
+                Ok(0) => break, // EOF
+                Ok(n) => {
+                    pending.extend_from_slice(&read_buf[..n]);
+                    while let Some(pos) = pending.iter().position(|&b| b == b'\n') {
+                        let line: Vec<u8> = pending.drain(..=pos).collect();
+                        if line_tx
+                            .send(String::from_utf8_lossy(&line).into_owned())
+                            .is_err()
+                        {
+                            return;
+                        }
+                    }
+                }
+                Err(_) => break,
+            }
+        }
+
+        if !pending.is_empty() {
+            let _ = line_tx.send(String::from_utf8_lossy(&pending).into_owned());
+        }
+    });
+
+    // Parse and forward each line as soon as it arrives
+    let mut parser = agent.output_parser();
+    for line in line_rx {
+        parser.feed_line(&line, &mut log_fn);
+    }
+
+    let exit_status = child.wait()
+        .with_context(|| format!("failed to wait for {} process", agent.display_name()))?;
+
+    reader_thread.join()
+        .map_err(|_| anyhow!("PTY reader thread panicked"))?;
+
+    let end_time = OffsetDateTime::now_utc();
+
+    let exit_code = exit_status.exit_code();
+    if exit_code != 0 {
+        return Err(CommandFailed {
+            message: format!("{} exited with status {}", agent.display_name(), exit_code),
+            exit_code: i32::try_from(exit_code).ok(),
+            output: Vec::new(),
+        }
+        .into());
+    }
+
+    let session_id = parser.session_id().map(|sid| sid.to_string());
+    let session_history = parser.into_history(invocation.prompt, start_time, end_time);
+
+    Ok((session_id, session_history))
+}
+
+/// 環境変数で上書き可能な実行ファイル名を解決
+fn resolve_binary(env_var: &str, default: &str) -> String {
+    std::env::var(env_var).unwrap_or_else(|_| default.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::GenericAgentConfig;
+
+    #[test]
+    fn test_registry_contains_builtin_agents() {
+        let registry = AgentRegistry::from_config(&Config::default());
+        assert!(registry.contains("claude"));
+        assert!(registry.contains("codex"));
+        assert!(registry.contains("aider"));
+        assert!(!registry.contains("unknown"));
+    }
+
+    #[test]
+    fn test_registry_loads_generic_agents() {
+        let mut config = Config::default();
+        config.agents.push(GenericAgentConfig {
+            name: "echo-agent".to_string(),
+            command: "echo".to_string(),
+            args: vec!["{{prompt}}".to_string()],
+            ..Default::default()
+        });
+
+        let registry = AgentRegistry::from_config(&config);
+        assert!(registry.contains("echo-agent"));
+        assert_eq!(
+            registry.names(),
+            vec!["aider", "claude", "codex", "echo-agent"]
+        );
+    }
+
+    #[test]
+    fn test_rendered_extra_args() {
+        let step = ClaudeStep {
+            prompt: "hi".to_string(),
+            extra_args: Some(vec!["--msg={{prompt}}".to_string(), "{{workdir}}".to_string()]),
+            ..Default::default()
+        };
+        let invocation = AgentInvocation {
+            prompt: "hello",
+            workdir: Path::new("/tmp/wt"),
+            session_id: None,
+            step: &step,
+            default_sandbox_mode: true,
+        };
+        assert_eq!(
+            invocation.rendered_extra_args(),
+            vec!["--msg=hello", "/tmp/wt"]
+        );
+        assert_eq!(invocation.permission_mode(), "bypassPermissions");
+        assert!(invocation.sandbox_enabled());
+    }
+
+    #[test]
+    fn test_run_agent_command_streams_generic_output() {
+        let agent = GenericAgent::new(GenericAgentConfig {
+            name: "sh".to_string(),
+            command: "sh".to_string(),
+            args: vec!["-c".to_string(), "echo first; echo second".to_string()],
+            ..Default::default()
+        })
+        .unwrap();
+        let step = ClaudeStep::default();
+        let invocation = AgentInvocation {
+            prompt: "",
+            workdir: Path::new("."),
+            session_id: None,
+            step: &step,
+            default_sandbox_mode: true,
+        };
+
+        let mut lines = Vec::new();
+        let (session_id, history) =
+            run_agent_command(&agent, &invocation, &ProcessTracker::new(), |line| {
+                lines.push(line)
+            })
+            .unwrap();
+        assert_eq!(lines, vec!["first", "second"]);
+        assert!(session_id.is_none());
+        assert_eq!(history.session_id, "unknown");
+    }
+}
@@ -1,8 +1,15 @@
-use tui_textarea::TextArea;
-use crate::ui::{permission_mode_label, LogViewMode};
-use crate::worker::{CreateWorkerRequest, WorkerId, WorkerStatus, list_existing_worktrees};
+use std::path::Path;
 
-use super::types::{InputMode, NameInputNextAction, InteractiveRequest};
+use anyhow::{Result, bail};
+use tui_textarea::TextArea;
+use crate::diff::{self, WorkerDiff};
+use crate::ui::{delete_options, permission_mode_label, DiffView, LogViewMode, MERGE_OPTIONS};
+use crate::worker::{
+    BatchDefaults, CreateWorkerRequest, UnsavedWork, WorkerId, WorkerStatus, determine_base_ref,
+    list_existing_worktrees, list_local_refs, load_batch,
+};
+
+use super::types::{InputMode, NameInputNextAction, NameInputState, InteractiveRequest};
 use super::App;
 
 impl App {
@@ -13,25 +20,35 @@ impl App {
             .get(self.selected_workflow_idx)
             .map(|wf| wf.name.clone());
 
-        self.input_mode = Some(InputMode::NameInput {
-            textarea: TextArea::default(),
-            workflow_name,
-            next_action: NameInputNextAction::CreateWithWorkflow,
-        });
+        self.show_name_input(workflow_name, NameInputNextAction::CreateWithWorkflow);
     }
 
     pub fn show_name_input_for_free_prompt(&mut self) {
-        self.input_mode = Some(InputMode::NameInput {
+        self.show_name_input(None, NameInputNextAction::CreateWithFreePrompt);
+    }
+
+    fn show_name_input(&mut self, workflow_name: Option<String>, next_action: NameInputNextAction) {
+        // Completion is a convenience; without it any ref can still be typed
+        let refs = list_local_refs(&self.repo_root).unwrap_or_default();
+        self.input_mode = Some(InputMode::NameInput(Box::new(NameInputState {
             textarea: TextArea::default(),
-            workflow_name: None,
-            next_action: NameInputNextAction::CreateWithFreePrompt,
-        });
+            base_ref: TextArea::default(),
+            editing_base: false,
+            refs,
+            workflow_name,
+            next_action,
+        })));
     }
 
-    pub fn create_worker_with_default_name(&mut self, workflow_name: Option<String>) {
+    pub fn create_worker_with_default_name(
+        &mut self,
+        workflow_name: Option<String>,
+        base_ref: Option<String>,
+    ) {
         let mut request = CreateWorkerRequest::default();
         request.workflow = workflow_name;
         request.name = None; // Use default name
+        request.base_ref = base_ref;
 
         if let Err(err) = self.manager.create_worker(request) {
             self.push_log(format!("ワーカー作成に失敗しました: {err}"));
@@ -40,10 +57,16 @@ impl App {
         }
     }
 
-    pub fn create_worker_with_name(&mut self, name: String, workflow_name: Option<String>) {
+    pub fn create_worker_with_name(
+        &mut self,
+        name: String,
+        workflow_name: Option<String>,
+        base_ref: Option<String>,
+    ) {
         let mut request = CreateWorkerRequest::default();
         request.workflow = workflow_name;
         request.name = Some(name.clone());
+        request.base_ref = base_ref;
 
         if let Err(err) = self.manager.create_worker(request) {
             self.push_log(format!("ワーカー作成に失敗しました: {err}"));
@@ -71,32 +94,50 @@ impl App {
         }
     }
 
-    pub fn enqueue_delete_worker(&mut self) {
-        if let Some(id) = self.selected_worker_id() {
-            // Check if this is an archived worker
-            if let Some(worker) = self.workers.iter().find(|w| w.snapshot.id == id) {
-                if worker.snapshot.status == WorkerStatus::Archived {
-                    // For archived workers, just delete the state file
-                    if let Err(err) = self.state_store.delete_worker(&worker.snapshot.name) {
-                        self.push_log(format!("アーカイブ削除に失敗しました: {err}"));
-                    } else {
-                        // Remove from UI
-                        if let Some(pos) = self.workers.iter().position(|w| w.snapshot.id == id) {
-                            let worker = self.workers.remove(pos);
-                            self.push_log(format!(
-                                "アーカイブを削除しました: {}",
-                                worker.snapshot.name
-                            ));
-                            self.clamp_selection();
-                        }
-                    }
-                    return;
-                }
+    /// 削除で失われる作業を調べ、削除方法の確認モーダルを開く
+    pub fn show_delete_confirmation(&mut self) {
+        let Some(id) = self.selected_worker_id() else {
+            return;
+        };
+        let Some(worker) = self.workers.iter().find(|w| w.snapshot.id == id) else {
+            return;
+        };
+        let archived = worker.snapshot.status == WorkerStatus::Archived;
+        let worktree_path = self.repo_root.join(&worker.snapshot.worktree);
+        let base_ref = worker
+            .snapshot
+            .base_ref
+            .clone()
+            .or_else(|| determine_base_ref(&self.repo_root));
+        let unsaved = match UnsavedWork::inspect(
+            &self.repo_root,
+            (!archived).then_some(worktree_path.as_path()),
+            &worker.snapshot.branch,
+            base_ref.as_deref(),
+        ) {
+            Ok(unsaved) => unsaved,
+            Err(err) => {
+                self.push_log(format!("未保存の作業を確認できませんでした: {err}"));
+                return;
             }
+        };
+        self.input_mode = Some(InputMode::DeleteConfirmation {
+            worker_id: id,
+            worker_name: worker.snapshot.name.clone(),
+            archived,
+            unsaved,
+            selected: 0,
+        });
+    }
 
-            if let Err(err) = self.manager.delete_worker(id) {
-                self.push_log(format!("ワーカー削除に失敗しました ({:?}): {err}", id));
-            }
+    /// 確認モーダルで選ばれた方法でワーカーを削除する
+    pub fn delete_worker(&mut self, id: WorkerId, archived: bool, selected: usize) {
+        let Some(option) = delete_options(archived).get(selected) else {
+            return;
+        };
+        // The unsaved work was shown in the modal, so the choice is confirmed
+        if let Err(err) = self.manager.delete_worker(id, option.mode, true) {
+            self.push_log(format!("ワーカー削除に失敗しました ({:?}): {err}", id));
         }
     }
 
@@ -105,7 +146,9 @@ impl App {
             // Check if this is an archived worker
             if let Some(worker) = self.workers.iter().find(|w| w.snapshot.id == id) {
                 if worker.snapshot.status == WorkerStatus::Archived {
-                    self.push_log("アーカイブされたワーカーは再起動できません".to_string());
+                    if let Err(err) = self.manager.reactivate_worker(id) {
+                        self.push_log(format!("アーカイブからの再有効化に失敗しました ({:?}): {err}", id));
+                    }
                     return;
                 }
             }
@@ -116,6 +159,145 @@ impl App {
         }
     }
 
+    pub fn enqueue_cancel_worker(&mut self) {
+        if let Some(id) = self.selected_worker_id() {
+            if let Some(worker) = self.workers.iter().find(|w| w.snapshot.id == id)
+                && !matches!(
+                    worker.snapshot.status,
+                    WorkerStatus::Running | WorkerStatus::Queued
+                )
+            {
+                self.push_log("実行中・キュー待ちのワーカーのみキャンセルできます".to_string());
+                return;
+            }
+
+            if let Err(err) = self.manager.cancel_worker(id) {
+                self.push_log(format!("ワーカーのキャンセルに失敗しました ({:?}): {err}", id));
+            }
+        }
+    }
+
+    /// 選択中のワーカーの優先度を変更する（キュー待ちの順番に反映される）
+    pub fn adjust_priority(&mut self, delta: i32) {
+        let Some(view) = self.selected_worker_view() else {
+            return;
+        };
+        let id = view.snapshot.id;
+        let name = view.snapshot.name.clone();
+        let priority = view.snapshot.priority.saturating_add(delta);
+        match self.manager.set_priority(id, priority) {
+            Ok(()) => self.push_log_with_worker(Some(&name), format!("優先度を {priority} に変更しました")),
+            Err(err) => self.push_log(format!("優先度の変更に失敗しました ({:?}): {err}", id)),
+        }
+    }
+
+    pub fn toggle_pause_worker(&mut self) {
+        let Some(id) = self.selected_worker_id() else {
+            return;
+        };
+        let Some(status) = self
+            .workers
+            .iter()
+            .find(|w| w.snapshot.id == id)
+            .map(|w| w.snapshot.status)
+        else {
+            return;
+        };
+
+        let result = match status {
+            WorkerStatus::Running | WorkerStatus::Queued => self.manager.pause_worker(id),
+            WorkerStatus::Paused => self.manager.resume_worker(id),
+            WorkerStatus::Interrupted => self.manager.resume_interrupted_worker(id, true),
+            _ => {
+                self.push_log("実行中・キュー待ち・一時停止中・中断されたワーカーのみ操作できます".to_string());
+                return;
+            }
+        };
+
+        if let Err(err) = result {
+            self.push_log(format!("ワーカーの一時停止/再開に失敗しました ({:?}): {err}", id));
+        }
+    }
+
+    /// 中断されたワーカーを、中断したステップから新規セッションでやり直す
+    pub fn resume_interrupted_fresh(&mut self) {
+        let Some(id) = self.selected_worker_id() else {
+            return;
+        };
+        let interrupted = self
+            .workers
+            .iter()
+            .any(|w| w.snapshot.id == id && w.snapshot.status == WorkerStatus::Interrupted);
+        if !interrupted {
+            self.push_log("中断されたワーカーのみ再開できます".to_string());
+            return;
+        }
+
+        if let Err(err) = self.manager.resume_interrupted_worker(id, false) {
+            self.push_log(format!("ワーカーの再開に失敗しました ({:?}): {err}", id));
+        }
+    }
+
+    pub fn open_pull_request(&mut self) {
+        let Some(id) = self.selected_worker_id() else {
+            return;
+        };
+        let Some(worker) = self.workers.iter().find(|w| w.snapshot.id == id) else {
+            return;
+        };
+        match worker.snapshot.status {
+            WorkerStatus::Running | WorkerStatus::Queued | WorkerStatus::Archived => {
+                self.push_log("実行中・キュー待ち・アーカイブのワーカーからはプルリクエストを作成できません".to_string());
+                return;
+            }
+            _ => {}
+        }
+
+        if let Err(err) = self.manager.open_pull_request(id) {
+            self.push_log(format!("プルリクエストの作成に失敗しました ({:?}): {err}", id));
+        } else {
+            self.push_log(format!("プルリクエストを作成しています ({:?})", id));
+        }
+    }
+
+    /// ブランチ取り込み方法の選択モーダルを開く
+    pub fn show_merge_selection(&mut self) {
+        let Some(id) = self.selected_worker_id() else {
+            return;
+        };
+        let Some(worker) = self.workers.iter().find(|w| w.snapshot.id == id) else {
+            return;
+        };
+        match worker.snapshot.status {
+            WorkerStatus::Running | WorkerStatus::Queued | WorkerStatus::Archived => {
+                self.push_log("実行中・キュー待ち・アーカイブのワーカーは取り込めません".to_string());
+                return;
+            }
+            _ => {}
+        }
+        self.input_mode = Some(InputMode::MergeSelection {
+            worker_id: id,
+            worker_name: worker.snapshot.name.clone(),
+            selected: 0,
+            resolve_conflicts: false,
+        });
+    }
+
+    /// 選択された方法でワーカーのブランチをベースブランチに取り込む
+    pub fn merge_worker(&mut self, id: WorkerId, selected: usize, resolve_conflicts: bool) {
+        let Some(option) = MERGE_OPTIONS.get(selected) else {
+            return;
+        };
+        if let Err(err) =
+            self.manager
+                .merge(id, option.strategy, option.dry_run, resolve_conflicts)
+        {
+            self.push_log(format!("ブランチの取り込みに失敗しました ({:?}): {err}", id));
+        } else {
+            self.push_log(format!("{} ({:?})", option.label, id));
+        }
+    }
+
     pub fn toggle_help(&mut self) {
         self.show_help = !self.show_help;
     }
@@ -295,6 +477,47 @@ impl App {
         self.input_mode = Some(InputMode::CreateWorkerSelection { selected: 0 });
     }
 
+    pub fn show_batch_file_input(&mut self) {
+        self.input_mode = Some(InputMode::BatchFile {
+            textarea: TextArea::default(),
+        });
+    }
+
+    /// Issue 一覧ファイルの各行からワーカーを作成する（失敗した行はアクションログに記録して続行）
+    pub fn create_workers_from_batch(&mut self, path: String) {
+        let path = self.repo_root.join(path);
+        let defaults = BatchDefaults {
+            workflow: self
+                .workflows
+                .get(self.selected_workflow_idx)
+                .map(|wf| wf.name.clone()),
+            agent: None,
+        };
+        let entries = match load_batch(&path, &defaults) {
+            Ok(entries) => entries,
+            Err(err) => {
+                self.push_log(format!("一括作成ファイルを読み込めませんでした: {err:#}"));
+                return;
+            }
+        };
+
+        let total = entries.len();
+        let mut failures = 0;
+        for entry in entries {
+            let result = entry
+                .request
+                .and_then(|request| self.manager.create_worker(request).map_err(|err| err.to_string()));
+            if let Err(message) = result {
+                self.push_log(format!("{}行目: {message}", entry.line));
+                failures += 1;
+            }
+        }
+        self.push_log(format!(
+            "{} 件のワーカー作成を要求しました（失敗 {failures} 件）",
+            total - failures
+        ));
+    }
+
     pub fn show_worktree_selection(&mut self) {
         match list_existing_worktrees(&self.repo_root) {
             Ok(worktrees) => {
@@ -341,6 +564,7 @@ impl App {
             force_new: false,
             permission_mode: None,
             worker_name: None,
+            base_ref: None,
         });
     }
 
@@ -378,6 +602,7 @@ impl App {
         force_new: bool,
         permission_mode: Option<String>,
         worker_name: Option<String>,
+        base_ref: Option<String>,
     ) {
         let trimmed = prompt.trim();
         if trimmed.is_empty() {
@@ -427,6 +652,7 @@ impl App {
         request.free_prompt = Some(trimmed.to_string());
         request.permission_mode = permission_mode.clone();
         request.name = worker_name;
+        request.base_ref = base_ref;
 
         match self.manager.create_worker(request) {
             Ok(_) => {
@@ -501,4 +727,53 @@ impl App {
     pub fn scroll_session_history_down(&mut self) {
         self.session_history_scroll += 1;
     }
+
+    /// 選択中ワーカーの差分ビューを開く
+    pub fn open_diff_view(&mut self) {
+        let Some(worker) = self.selected_worker_view() else {
+            return;
+        };
+        let name = worker.snapshot.name.clone();
+        let worktree = self.repo_root.join(&worker.snapshot.worktree);
+        match self.load_worker_diff(&worktree) {
+            Ok(diff) => self.diff_view = Some(DiffView::new(name, diff)),
+            Err(err) => {
+                self.push_log_with_worker(Some(&name), format!("差分の取得に失敗しました: {err:#}"))
+            }
+        }
+    }
+
+    /// 表示中の差分を取得し直す
+    pub fn reload_diff_view(&mut self) {
+        let Some(name) = self.diff_view.as_ref().map(|view| view.worker_name.clone()) else {
+            return;
+        };
+        let Some(worktree) = self
+            .workers
+            .iter()
+            .find(|view| view.snapshot.name == name)
+            .map(|view| self.repo_root.join(&view.snapshot.worktree))
+        else {
+            self.diff_view = None;
+            return;
+        };
+        match self.load_worker_diff(&worktree) {
+            Ok(diff) => {
+                if let Some(view) = self.diff_view.as_mut() {
+                    view.replace_diff(diff);
+                }
+            }
+            Err(err) => {
+                self.push_log_with_worker(Some(&name), format!("差分の取得に失敗しました: {err:#}"))
+            }
+        }
+    }
+
+    fn load_worker_diff(&self, worktree: &Path) -> Result<WorkerDiff> {
+        if !worktree.exists() {
+            bail!("worktree {} does not exist", worktree.display());
+        }
+        let base = determine_base_ref(&self.repo_root).unwrap_or_else(|| "HEAD".into());
+        diff::load(worktree, &base)
+    }
 }
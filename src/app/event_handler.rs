@@ -3,10 +3,10 @@ use std::collections::HashMap;
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use tui_textarea::Input;
 
-use crate::ui::{permission_mode_label, describe_allowed_tools, AVAILABLE_TOOLS, LogViewMode};
+use crate::ui::{complete_ref, delete_options, permission_mode_label, describe_allowed_tools, AVAILABLE_TOOLS, LogViewMode, MERGE_OPTIONS};
 use crate::worker::{PermissionDecision, PermissionRequest, WorkerId, WorkerEvent, WorkerStatus};
 
-use super::types::{InputMode, NameInputNextAction};
+use super::types::{InputMode, NameInputNextAction, NameInputState};
 use super::App;
 
 /// Convert crossterm KeyEvent to ratatui's crossterm KeyEvent for tui-textarea
@@ -185,6 +185,7 @@ impl App {
                     force_new,
                     permission_mode,
                     worker_name,
+                    base_ref,
                 } => {
                     // Handle Ctrl+P for permission mode cycling
                     if key_event.code == KeyCode::Char('p') && key_event.modifiers.contains(KeyModifiers::CONTROL) {
@@ -216,9 +217,10 @@ impl App {
                         let is_force_new = *force_new;
                         let mode = permission_mode.clone();
                         let name = worker_name.clone();
+                        let base = base_ref.clone();
                         self.input_mode = None;
                         if !prompt.trim().is_empty() {
-                            self.submit_free_prompt(prompt, is_force_new, mode, name);
+                            self.submit_free_prompt(prompt, is_force_new, mode, name, base);
                         } else {
                             self.push_log("空の指示は送信されませんでした".into());
                         }
@@ -242,16 +244,66 @@ impl App {
                         } else if choice == 1 {
                             // Free input - show name input first, then free prompt
                             self.show_name_input_for_free_prompt();
-                        } else {
+                        } else if choice == 2 {
                             // Use existing worktree
                             self.show_worktree_selection();
+                        } else {
+                            // Batch creation from an issue list file
+                            self.show_batch_file_input();
                         }
                     }
                     KeyCode::Up | KeyCode::Char('k') => {
                         *selected = selected.saturating_sub(1);
                     }
                     KeyCode::Down | KeyCode::Char('j') => {
-                        *selected = (*selected + 1).min(2);
+                        *selected = (*selected + 1).min(3);
+                    }
+                    _ => {}
+                },
+                InputMode::MergeSelection {
+                    worker_id,
+                    selected,
+                    resolve_conflicts,
+                    ..
+                } => match key_event.code {
+                    KeyCode::Esc => {
+                        self.input_mode = None;
+                    }
+                    KeyCode::Enter => {
+                        let (id, choice, resolve) = (*worker_id, *selected, *resolve_conflicts);
+                        self.input_mode = None;
+                        self.merge_worker(id, choice, resolve);
+                    }
+                    KeyCode::Char(' ') => {
+                        *resolve_conflicts = !*resolve_conflicts;
+                    }
+                    KeyCode::Up | KeyCode::Char('k') => {
+                        *selected = selected.saturating_sub(1);
+                    }
+                    KeyCode::Down | KeyCode::Char('j') => {
+                        *selected = (*selected + 1).min(MERGE_OPTIONS.len() - 1);
+                    }
+                    _ => {}
+                },
+                InputMode::DeleteConfirmation {
+                    worker_id,
+                    archived,
+                    selected,
+                    ..
+                } => match key_event.code {
+                    KeyCode::Esc => {
+                        self.input_mode = None;
+                    }
+                    KeyCode::Enter => {
+                        let (id, archived, choice) = (*worker_id, *archived, *selected);
+                        self.input_mode = None;
+                        self.delete_worker(id, archived, choice);
+                    }
+                    KeyCode::Up | KeyCode::Char('k') => {
+                        *selected = selected.saturating_sub(1);
+                    }
+                    KeyCode::Down | KeyCode::Char('j') => {
+                        *selected = (*selected + 1).min(delete_options(*archived).len() - 1);
                     }
                     _ => {}
                 },
@@ -355,8 +407,16 @@ impl App {
                         _ => {}
                     }
                 }
-                InputMode::NameInput { textarea, workflow_name, next_action } => {
-                    // Handle Esc to cancel and use default name
+                InputMode::NameInput(state) => {
+                    let NameInputState {
+                        textarea,
+                        base_ref,
+                        editing_base,
+                        refs,
+                        workflow_name,
+                        next_action,
+                    } = &mut **state;
+                    // Handle Esc to cancel and use default name and base
                     if key_event.code == KeyCode::Esc {
                         let workflow = workflow_name.clone();
                         let action = next_action.clone();
@@ -364,7 +424,7 @@ impl App {
 
                         match action {
                             NameInputNextAction::CreateWithWorkflow => {
-                                self.create_worker_with_default_name(workflow);
+                                self.create_worker_with_default_name(workflow, None);
                             }
                             NameInputNextAction::CreateWithFreePrompt => {
                                 // Show free prompt modal with default name
@@ -373,6 +433,7 @@ impl App {
                                     force_new: true,
                                     permission_mode: None,
                                     worker_name: None,
+                                    base_ref: None,
                                 });
                             }
                         }
@@ -382,6 +443,8 @@ impl App {
                     // Handle Enter to submit
                     if key_event.code == KeyCode::Enter {
                         let name = textarea.lines().join("").trim().to_string();
+                        let base = base_ref.lines().join("").trim().to_string();
+                        let base = if base.is_empty() { None } else { Some(base) };
                         let workflow = workflow_name.clone();
                         let action = next_action.clone();
                         self.input_mode = None;
@@ -390,10 +453,10 @@ impl App {
                             NameInputNextAction::CreateWithWorkflow => {
                                 if name.is_empty() {
                                     // Use default name
-                                    self.create_worker_with_default_name(workflow);
+                                    self.create_worker_with_default_name(workflow, base);
                                 } else {
                                     // Use user-provided name
-                                    self.create_worker_with_name(name, workflow);
+                                    self.create_worker_with_name(name, workflow, base);
                                 }
                             }
                             NameInputNextAction::CreateWithFreePrompt => {
@@ -404,15 +467,35 @@ impl App {
                                     force_new: true,
                                     permission_mode: None,
                                     worker_name,
+                                    base_ref: base,
                                 });
                             }
                         }
                         return false;
                     }
 
-                    // Pass all other keys to TextArea
-                    let input = key_event_to_input(key_event);
-                    textarea.input(input);
+                    match key_event.code {
+                        // Tab moves to the base ref field, then completes it
+                        KeyCode::Tab if !*editing_base => *editing_base = true,
+                        KeyCode::Tab => {
+                            let input = base_ref.lines().join("");
+                            if let Some(completed) = complete_ref(&input, refs) {
+                                *base_ref = tui_textarea::TextArea::new(vec![completed]);
+                                base_ref.move_cursor(tui_textarea::CursorMove::End);
+                            }
+                        }
+                        KeyCode::Up | KeyCode::BackTab => *editing_base = false,
+                        KeyCode::Down => *editing_base = true,
+                        _ => {
+                            // Pass all other keys to the focused TextArea
+                            let input = key_event_to_input(key_event);
+                            if *editing_base {
+                                base_ref.input(input);
+                            } else {
+                                textarea.input(input);
+                            }
+                        }
+                    }
                 },
                 InputMode::RenameWorker { textarea, worker_id } => {
                     // Handle Esc to cancel
@@ -438,6 +521,50 @@ impl App {
                     let input = key_event_to_input(key_event);
                     textarea.input(input);
                 },
+                InputMode::BatchFile { textarea } => {
+                    // Handle Esc to cancel
+                    if key_event.code == KeyCode::Esc {
+                        self.input_mode = None;
+                        return false;
+                    }
+
+                    // Handle Enter to submit
+                    if key_event.code == KeyCode::Enter {
+                        let path = textarea.lines().join("").trim().to_string();
+                        self.input_mode = None;
+                        if !path.is_empty() {
+                            self.create_workers_from_batch(path);
+                        } else {
+                            self.push_log("ファイルパスが入力されていません".into());
+                        }
+                        return false;
+                    }
+
+                    // Pass all other keys to TextArea
+                    let input = key_event_to_input(key_event);
+                    textarea.input(input);
+                },
+            }
+            return false;
+        }
+
+        if let Some(view) = self.diff_view.as_mut() {
+            match key_event.code {
+                KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('v') => self.diff_view = None,
+                KeyCode::Tab | KeyCode::Right | KeyCode::Char('l') => view.next_file(),
+                KeyCode::BackTab | KeyCode::Left | KeyCode::Char('h') => view.prev_file(),
+                KeyCode::Down | KeyCode::Char('j') => view.move_cursor(1),
+                KeyCode::Up | KeyCode::Char('k') => view.move_cursor(-1),
+                KeyCode::PageDown => view.move_cursor(20),
+                KeyCode::PageUp => view.move_cursor(-20),
+                KeyCode::Home | KeyCode::Char('g') => view.cursor = 0,
+                KeyCode::End | KeyCode::Char('G') => view.cursor_to_end(),
+                KeyCode::Char('n') => view.next_hunk(),
+                KeyCode::Char('N') => view.prev_hunk(),
+                KeyCode::Char(' ') | KeyCode::Enter => view.toggle_fold(),
+                KeyCode::Char('z') => view.toggle_fold_all(),
+                KeyCode::Char('r') => self.reload_diff_view(),
+                _ => {}
             }
             return false;
         }
@@ -445,15 +572,20 @@ impl App {
         match key_event.code {
             KeyCode::Char('q') => return true,
             KeyCode::Char('c') => self.show_create_selection(),
-            KeyCode::Char('d') => self.enqueue_delete_worker(),
+            KeyCode::Char('d') => self.show_delete_confirmation(),
             KeyCode::Char('r') => self.enqueue_restart_worker(),
+            KeyCode::Char('x') => self.enqueue_cancel_worker(),
+            KeyCode::Char('p') => self.toggle_pause_worker(),
             KeyCode::Char('n') => self.show_rename_modal(),
             KeyCode::Char('i') => self.start_free_prompt(),
             KeyCode::Char('h') => self.toggle_help(),
             KeyCode::Char('l') => self.toggle_logs(),
             KeyCode::Char('s') => self.toggle_session_history(),
+            KeyCode::Char('v') => self.open_diff_view(),
             KeyCode::Char('w') => self.cycle_workflow(),
             KeyCode::Char('a') => self.cycle_filter(),
+            KeyCode::Char('+') => self.adjust_priority(1),
+            KeyCode::Char('-') => self.adjust_priority(-1),
             KeyCode::Tab => {
                 if self.show_logs {
                     self.switch_log_tab_next();
@@ -536,6 +668,15 @@ impl App {
             KeyCode::Char('A') if key_event.modifiers.contains(KeyModifiers::SHIFT) => {
                 self.toggle_auto_scroll()
             }
+            KeyCode::Char('R') if key_event.modifiers.contains(KeyModifiers::SHIFT) => {
+                self.resume_interrupted_fresh()
+            }
+            KeyCode::Char('P') if key_event.modifiers.contains(KeyModifiers::SHIFT) => {
+                self.open_pull_request()
+            }
+            KeyCode::Char('M') if key_event.modifiers.contains(KeyModifiers::SHIFT) => {
+                self.show_merge_selection()
+            }
             _ => {}
         }
 
@@ -727,6 +868,8 @@ impl App {
                 } => {
                     self.handle_permission_resolved(id, request_id, decision);
                 }
+                // デーモン内部でのみ扱うイベント
+                WorkerEvent::Attached { .. } | WorkerEvent::Ready => {}
             }
         }
     }
@@ -747,9 +890,11 @@ impl App {
     fn cycle_filter(&mut self) {
         self.status_filter = match self.status_filter {
             None => Some(WorkerStatus::Running),
-            Some(WorkerStatus::Running) => Some(WorkerStatus::Paused),
+            Some(WorkerStatus::Running) => Some(WorkerStatus::Queued),
+            Some(WorkerStatus::Queued) => Some(WorkerStatus::Paused),
             Some(WorkerStatus::Paused) => Some(WorkerStatus::Failed),
-            Some(WorkerStatus::Failed) => Some(WorkerStatus::Idle),
+            Some(WorkerStatus::Failed) => Some(WorkerStatus::Interrupted),
+            Some(WorkerStatus::Interrupted) => Some(WorkerStatus::Idle),
             Some(WorkerStatus::Idle) => Some(WorkerStatus::Archived),
             Some(WorkerStatus::Archived) => None,
         };
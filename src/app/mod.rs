@@ -10,9 +10,10 @@ use std::path::PathBuf;
 use anyhow::{Context, Result};
 use time::{format_description::well_known::Rfc3339, OffsetDateTime};
 
-use crate::config::{Config, Workflow};
+use crate::config::{Config, ConfigWatcher, Workflow};
+use crate::daemon;
 use crate::state::{ActionLogEntry, SessionHistory, StateStore};
-use crate::ui::{format_action_log, LogViewMode};
+use crate::ui::{format_action_log, DiffView, LogViewMode};
 use crate::worker::{
     spawn_worker_system, WorkerEventReceiver, WorkerHandle, WorkerId, WorkerSnapshot,
     WorkerStatus,
@@ -51,22 +52,23 @@ pub struct App {
     pub permission_tracker: HashMap<u64, types::PermissionTrackerEntry>,
     pub pending_interactive_mode: Option<types::InteractiveRequest>,
     pub imported_session_history: Option<(String, SessionHistory)>,
+    /// 選択中ワーカーの差分ビュー（表示中のみ）
+    pub diff_view: Option<DiffView>,
     pub auto_scroll_logs: bool,
+    pub config_watcher: ConfigWatcher,
+    /// gensui daemon に接続しているか（終了してもワーカーは動き続ける）
+    pub attached: bool,
 }
 
 impl App {
     pub fn new() -> Result<Self> {
         let repo_root = std::env::current_dir().context("failed to determine repository root")?;
-        let config_path = repo_root.join("workflows.json");
-        let loaded =
-            Config::load(&config_path).context("failed to load workflow configuration")?;
-        let config = if loaded.workflows.is_empty() {
-            Config::default()
-        } else {
-            loaded
-        };
+        // 設定にエラーがある場合はワーカーを起動せずに終了する
+        let (config, warnings) =
+            Config::discover(&repo_root).context("failed to load workflow configuration")?;
 
         let state_store = StateStore::new(repo_root.join(".gensui/state"))?;
+        let config_watcher = ConfigWatcher::new(&repo_root);
 
         let mut workflows = config.workflows.clone();
         let default_idx = config
@@ -91,9 +93,16 @@ impl App {
             log_messages.push_back(format_action_log(&entry));
         }
 
-        let (manager, event_rx) = spawn_worker_system(repo_root.clone(), config)?;
+        // デーモンが起動していれば接続し、無ければこのプロセス内でワーカーを管理する
+        let (manager, event_rx, attached) = match daemon::connect(&repo_root)? {
+            Some((manager, event_rx)) => (manager, event_rx, true),
+            None => {
+                let (manager, event_rx) = spawn_worker_system(repo_root.clone(), config)?;
+                (manager, event_rx, false)
+            }
+        };
 
-        Ok(Self {
+        let mut app = Self {
             repo_root,
             manager,
             event_rx,
@@ -118,11 +127,25 @@ impl App {
             permission_tracker: HashMap::new(),
             pending_interactive_mode: None,
             imported_session_history: None,
+            diff_view: None,
             auto_scroll_logs: true,
-        })
+            config_watcher,
+            attached,
+        };
+        if attached {
+            app.push_log("gensui daemon に接続しました（q で切断してもワーカーは継続します）".to_string());
+        }
+        for warning in &warnings {
+            let message = format!("設定の警告: {}", warning.display_relative(&app.repo_root));
+            app.push_log(message);
+        }
+        Ok(app)
     }
 
     pub fn on_tick(&mut self) {
+        if self.config_watcher.poll() {
+            self.reload_config();
+        }
         self.poll_events();
         self.clamp_selection();
         self.animation_frame = self.animation_frame.wrapping_add(1);
@@ -236,6 +259,49 @@ impl App {
         }
     }
 
+    /// 設定ファイルを再読み込みし、新しく作成するワーカーに反映する
+    ///
+    /// 実行中のワーカーは開始時のワークフローのまま動作を続ける。
+    pub fn reload_config(&mut self) {
+        let (config, warnings) = match Config::discover(&self.repo_root) {
+            Ok(loaded) => loaded,
+            Err(err) => {
+                let report = format!("{err:#}");
+                let mut lines = report.lines();
+                self.push_log(format!(
+                    "設定の再読み込みに失敗しました（以前の設定を使用します）: {}",
+                    lines.next().unwrap_or_default()
+                ));
+                for line in lines {
+                    self.push_log(format!("  {line}"));
+                }
+                return;
+            }
+        };
+
+        // 選択中のワークフローは名前で引き継ぐ
+        let current = self.current_workflow_name().to_string();
+        self.workflows = config.workflows.clone();
+        self.selected_workflow_idx = self
+            .workflows
+            .iter()
+            .position(|wf| wf.name == current)
+            .unwrap_or(0);
+
+        if let Err(err) = self.manager.reload_config(config) {
+            self.push_log(format!("設定の再読み込みに失敗しました: {err}"));
+            return;
+        }
+        self.push_log(format!(
+            "ワークフロー設定を再読み込みしました（{}件）",
+            self.workflows.len()
+        ));
+        for warning in &warnings {
+            let message = format!("設定の警告: {}", warning.display_relative(&self.repo_root));
+            self.push_log(message);
+        }
+    }
+
     pub fn current_workflow_name(&self) -> &str {
         self.workflows
             .get(self.selected_workflow_idx)
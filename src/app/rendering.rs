@@ -4,13 +4,13 @@ use ratatui::layout::Rect;
 use ratatui::text::Line;
 
 use crate::ui::{
-    centered_rect, help_lines, prepare_raw_log_data, render_create_selection_modal,
+    centered_rect, help_lines, render_diff_view, render_batch_file_modal, prepare_raw_log_data, render_create_selection_modal,
     render_detail_tab, render_footer, render_header, render_log_modal, render_modal,
-    render_name_input_modal, render_overview_tab, render_permission_modal, render_prompt_modal,
+    render_delete_confirmation_modal, render_merge_selection_modal, render_name_input_modal, render_overview_tab, render_permission_modal, render_prompt_modal,
     render_rename_worker_modal, render_session_history_modal, render_table, render_tool_selection_modal,
     render_worktree_selection_modal, LogViewMode,
 };
-use crate::worker::{ExistingWorktree, WorkerSnapshot};
+use crate::worker::{ExistingWorktree, UnsavedWork, WorkerSnapshot};
 
 use super::types::InputMode;
 use super::App;
@@ -31,6 +31,10 @@ impl App {
         self.render_table(frame, layout[1]);
         self.render_footer(frame, layout[2]);
 
+        if let Some(view) = &self.diff_view {
+            render_diff_view(frame, view);
+        }
+
         if self.show_logs {
             self.render_log_modal(frame);
         }
@@ -59,6 +63,23 @@ impl App {
                 InputMode::CreateWorkerSelection { selected } => {
                     self.render_create_selection_modal(frame, *selected);
                 }
+                InputMode::MergeSelection {
+                    worker_name,
+                    selected,
+                    resolve_conflicts,
+                    ..
+                } => {
+                    self.render_merge_selection_modal(frame, worker_name, *selected, *resolve_conflicts);
+                }
+                InputMode::DeleteConfirmation {
+                    worker_name,
+                    archived,
+                    unsaved,
+                    selected,
+                    ..
+                } => {
+                    self.render_delete_confirmation_modal(frame, worker_name, *archived, unsaved, *selected);
+                }
                 InputMode::WorktreeSelection {
                     worktrees,
                     selected,
@@ -73,14 +94,25 @@ impl App {
                 } => {
                     self.render_tool_selection_modal(frame, tools, *selected_idx, permission_mode);
                 }
-                InputMode::NameInput { textarea, workflow_name, .. } => {
-                    self.render_name_input_modal(frame, textarea, workflow_name);
+                InputMode::NameInput(state) => {
+                    self.render_name_input_modal(
+                        frame,
+                        &state.textarea,
+                        &state.base_ref,
+                        state.editing_base,
+                        &state.refs,
+                        &state.workflow_name,
+                    );
                 }
                 InputMode::RenameWorker { textarea, worker_id } => {
                     if let Some(worker) = self.workers.iter().find(|w| w.snapshot.id == *worker_id) {
                         self.render_rename_worker_modal(frame, textarea, &worker.snapshot.name);
                     }
                 }
+                InputMode::BatchFile { textarea } => {
+                    let area = centered_rect(60, 40, frame.area());
+                    render_batch_file_modal(frame, area, textarea, self.current_workflow_name());
+                }
             }
         }
     }
@@ -118,7 +150,7 @@ impl App {
     }
 
     fn render_footer(&self, frame: &mut ratatui::Frame<'_>, area: Rect) {
-        render_footer(frame, area, self.current_workflow_name());
+        render_footer(frame, area, self.current_workflow_name(), self.attached);
     }
 
     fn render_modal(
@@ -164,6 +196,29 @@ impl App {
         render_create_selection_modal(frame, area, selected, workflow_name);
     }
 
+    fn render_merge_selection_modal(
+        &self,
+        frame: &mut ratatui::Frame<'_>,
+        worker_name: &str,
+        selected: usize,
+        resolve_conflicts: bool,
+    ) {
+        let area = centered_rect(60, 40, frame.area());
+        render_merge_selection_modal(frame, area, worker_name, selected, resolve_conflicts);
+    }
+
+    fn render_delete_confirmation_modal(
+        &self,
+        frame: &mut ratatui::Frame<'_>,
+        worker_name: &str,
+        archived: bool,
+        unsaved: &UnsavedWork,
+        selected: usize,
+    ) {
+        let area = centered_rect(70, 60, frame.area());
+        render_delete_confirmation_modal(frame, area, worker_name, archived, unsaved, selected);
+    }
+
     fn render_tool_selection_modal(
         &self,
         frame: &mut ratatui::Frame<'_>,
@@ -189,10 +244,13 @@ impl App {
         &self,
         frame: &mut ratatui::Frame<'_>,
         textarea: &tui_textarea::TextArea<'_>,
+        base_ref: &tui_textarea::TextArea<'_>,
+        editing_base: bool,
+        refs: &[String],
         workflow_name: &Option<String>,
     ) {
-        let area = centered_rect(60, 40, frame.area());
-        render_name_input_modal(frame, area, textarea, workflow_name);
+        let area = centered_rect(60, 60, frame.area());
+        render_name_input_modal(frame, area, textarea, base_ref, editing_base, refs, workflow_name);
     }
 
     fn render_rename_worker_modal(
@@ -2,7 +2,7 @@ use std::collections::HashMap;
 use std::path::PathBuf;
 
 use tui_textarea::TextArea;
-use crate::worker::{ExistingWorktree, PermissionDecision, PermissionRequest, WorkerId};
+use crate::worker::{ExistingWorktree, PermissionDecision, PermissionRequest, UnsavedWork, WorkerId};
 
 /// Input modes for the TUI
 pub enum InputMode {
@@ -11,6 +11,7 @@ pub enum InputMode {
         force_new: bool,
         permission_mode: Option<String>,
         worker_name: Option<String>,
+        base_ref: Option<String>,
     },
     CreateWorkerSelection {
         selected: usize,
@@ -26,17 +27,42 @@ pub enum InputMode {
         worker_id: WorkerId,            // worker requesting permission
         request_id: u64,                // permission request ID
     },
-    NameInput {
+    NameInput(Box<NameInputState>),
+    RenameWorker {
         textarea: TextArea<'static>,
-        workflow_name: Option<String>,
-        next_action: NameInputNextAction,
+        worker_id: WorkerId,
     },
-    RenameWorker {
+    BatchFile {
         textarea: TextArea<'static>,
+    },
+    MergeSelection {
+        worker_id: WorkerId,
+        worker_name: String,
+        selected: usize,
+        resolve_conflicts: bool,
+    },
+    DeleteConfirmation {
         worker_id: WorkerId,
+        worker_name: String,
+        archived: bool,
+        unsaved: UnsavedWork,
+        selected: usize,
     },
 }
 
+/// Name input state (worker name and base ref)
+pub struct NameInputState {
+    pub textarea: TextArea<'static>,
+    /// Branch, tag or commit to create the worker branch from
+    pub base_ref: TextArea<'static>,
+    /// Whether the base ref field has the focus
+    pub editing_base: bool,
+    /// Local branches and tags offered for completion
+    pub refs: Vec<String>,
+    pub workflow_name: Option<String>,
+    pub next_action: NameInputNextAction,
+}
+
 /// Next action after name input
 #[derive(Clone)]
 pub enum NameInputNextAction {
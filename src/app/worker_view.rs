@@ -137,6 +137,7 @@ impl WorkerView {
                     result_lines,
                     thought_lines,
                     status: StepStatus::Success, // ToolResultがあればSuccess
+                    attempt: None,
                 });
 
                 step_index += 1;
@@ -176,6 +177,7 @@ impl WorkerView {
                     result_lines,
                     thought_lines,
                     status: StepStatus::Success,
+                    attempt: None,
                 });
             }
         }
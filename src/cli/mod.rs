@@ -0,0 +1,403 @@
+//! TUI を使わずに gensui を操作するサブコマンド
+
+mod session;
+
+use std::io::{self, IsTerminal, Write};
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::thread;
+use std::time::Duration;
+
+use anyhow::{Context, Result, anyhow};
+use clap::{Args, Parser, Subcommand};
+
+use crate::config::Config;
+use crate::state::{StateStore, WorkerRecord};
+use crate::daemon;
+use crate::ui::helpers::pr_label;
+use crate::worker::{BatchDefaults, CreateWorkerRequest, DeleteMode, load_batch};
+
+use session::Session;
+
+const FOLLOW_INTERVAL: Duration = Duration::from_millis(500);
+
+/// git worktree ごとに AI エージェントのワーカーを管理する TUI
+#[derive(Debug, Parser)]
+#[command(name = "gensui", version, about)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Commands>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Commands {
+    /// ワークフロー設定を検証し、問題をすべて表示する
+    Validate,
+    /// ワーカーの一覧を表示する
+    List {
+        /// 1 行 1 ワーカーの JSON で出力する
+        #[arg(long)]
+        json: bool,
+    },
+    /// ワーカーを作成し、ワークフローが終わるまでログを表示する
+    Create {
+        /// 使用するワークフロー（省略時は default_workflow）
+        #[arg(long)]
+        workflow: Option<String>,
+        /// 紐付ける Issue
+        #[arg(long)]
+        issue: Option<String>,
+        /// ワーカー名（省略時は自動生成）
+        #[arg(long)]
+        name: Option<String>,
+        /// 使用するエージェント
+        #[arg(long)]
+        agent: Option<String>,
+        /// ワークフローの代わりに実行する自由指示
+        #[arg(long, conflicts_with = "workflow")]
+        prompt: Option<String>,
+        /// ワーカーのブランチを作成する元のブランチ・タグ・コミット（省略時は現在のブランチ）
+        #[arg(long, value_name = "REF")]
+        base: Option<String>,
+        /// キュー待ちの順番を決める優先度（大きいほど先に開始）
+        #[arg(long, default_value_t = 0, allow_negative_numbers = true)]
+        priority: i32,
+        #[command(flatten)]
+        approval: Approval,
+    },
+    /// Issue 一覧ファイル（改行区切り / CSV / JSONL）から複数のワーカーを作成する
+    Batch {
+        /// Issue 一覧ファイル（拡張子 .csv / .jsonl で形式を判定）
+        file: PathBuf,
+        /// ワークフローも指示も指定していない行で使うワークフロー
+        #[arg(long)]
+        workflow: Option<String>,
+        /// エージェントを指定していない行で使うエージェント
+        #[arg(long)]
+        agent: Option<String>,
+        #[command(flatten)]
+        approval: Approval,
+    },
+    /// ワーカーのログを表示する
+    Logs {
+        worker: String,
+        /// 新しいログを待ち続ける
+        #[arg(short, long)]
+        follow: bool,
+    },
+    /// ワーカーのセッションに指示を送り、完了までログを表示する
+    Continue {
+        worker: String,
+        prompt: String,
+        #[arg(long)]
+        permission_mode: Option<String>,
+        #[command(flatten)]
+        approval: Approval,
+    },
+    /// ワーカーと worktree を削除する（未コミット・未 push の作業があれば中止）
+    Delete {
+        worker: String,
+        /// worktree だけを削除し、ブランチと状態を残してアーカイブする
+        #[arg(long)]
+        archive: bool,
+        /// 未コミット・未 push の作業があっても削除する
+        #[arg(long, conflicts_with = "archive")]
+        force: bool,
+    },
+    /// アーカイブしたワーカーの worktree を残したブランチから作り直す
+    Reactivate { worker: String },
+    /// 中断されたワーカーを再開し、すべて終わるまでログを表示する
+    Run {
+        #[command(flatten)]
+        approval: Approval,
+    },
+    /// ワーカーを管理するデーモンを起動する（TUI と CLI は自動で接続する）
+    Daemon {
+        /// 端末から切り離してバックグラウンドで起動する
+        #[arg(long)]
+        detach: bool,
+        /// 起動中のデーモンを停止する
+        #[arg(long, conflicts_with = "detach")]
+        stop: bool,
+        /// 外部ツール向けの HTTP API を有効にする（localhost のみ）
+        #[arg(
+            long,
+            value_name = "ADDR",
+            num_args = 0..=1,
+            default_missing_value = daemon::DEFAULT_ADDR,
+            conflicts_with = "stop"
+        )]
+        http: Option<SocketAddr>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, Args)]
+pub struct Approval {
+    /// 権限確認をすべて自動で許可する
+    #[arg(short = 'y', long = "yes")]
+    pub auto_approve: bool,
+}
+
+/// TUI を起動せずにサブコマンドを実行する
+pub fn run(command: Commands) -> Result<ExitCode> {
+    let repo_root = std::env::current_dir().context("failed to determine repository root")?;
+    match command {
+        Commands::Validate => Ok(validate(&repo_root)),
+        Commands::List { json } => list(&repo_root, json),
+        Commands::Create {
+            workflow,
+            issue,
+            name,
+            agent,
+            prompt,
+            base,
+            priority,
+            approval,
+        } => {
+            let request = CreateWorkerRequest {
+                name,
+                issue,
+                agent,
+                workflow,
+                free_prompt: prompt,
+                priority,
+                base_ref: base,
+                ..Default::default()
+            };
+            let mut session = Session::start(&repo_root, approval)?;
+            session.create(request)
+        }
+        Commands::Batch {
+            file,
+            workflow,
+            agent,
+            approval,
+        } => {
+            let entries = load_batch(&file, &BatchDefaults { workflow, agent })?;
+            let mut session = Session::start(&repo_root, approval)?;
+            session.create_batch(entries)
+        }
+        Commands::Logs { worker, follow } => logs(&repo_root, &worker, follow),
+        Commands::Continue {
+            worker,
+            prompt,
+            permission_mode,
+            approval,
+        } => {
+            let mut session = Session::start(&repo_root, approval)?;
+            session.continue_worker(&worker, prompt, permission_mode)
+        }
+        Commands::Delete {
+            worker,
+            archive,
+            force,
+        } => {
+            let mode = if archive {
+                DeleteMode::Archive
+            } else {
+                DeleteMode::Destroy
+            };
+            let mut session = Session::start(&repo_root, Approval { auto_approve: false })?;
+            session.delete(&worker, mode, force)
+        }
+        Commands::Reactivate { worker } => {
+            let mut session = Session::start(&repo_root, Approval { auto_approve: false })?;
+            session.reactivate(&worker)
+        }
+        Commands::Run { approval } => {
+            let mut session = Session::start(&repo_root, approval)?;
+            session.resume_all()
+        }
+        Commands::Daemon { detach, stop, http } => daemon(&repo_root, detach, stop, http),
+    }
+}
+
+fn daemon(
+    repo_root: &Path,
+    detach: bool,
+    stop: bool,
+    http: Option<SocketAddr>,
+) -> Result<ExitCode> {
+    if stop {
+        if daemon::stop(repo_root)? {
+            eprintln!("gensui daemon を停止しました");
+            return Ok(ExitCode::SUCCESS);
+        }
+        eprintln!("gensui daemon は起動していません");
+        return Ok(ExitCode::FAILURE);
+    }
+    if detach {
+        let pid = daemon::spawn_detached(repo_root, http)?;
+        eprintln!(
+            "gensui daemon をバックグラウンドで起動しました (pid {pid}, {})",
+            daemon::socket_path(repo_root).display()
+        );
+        return Ok(ExitCode::SUCCESS);
+    }
+    daemon::run(repo_root, http)?;
+    Ok(ExitCode::SUCCESS)
+}
+
+fn validate(repo_root: &Path) -> ExitCode {
+    let diagnostics = Config::validate(repo_root);
+    for diagnostic in &diagnostics {
+        println!("{}", diagnostic.display_relative(repo_root));
+    }
+
+    let errors = diagnostics.iter().filter(|d| d.is_error()).count();
+    let warnings = diagnostics.len() - errors;
+    if errors > 0 {
+        println!("{errors} error(s), {warnings} warning(s)");
+        ExitCode::FAILURE
+    } else {
+        println!("configuration OK ({warnings} warning(s))");
+        ExitCode::SUCCESS
+    }
+}
+
+fn list(repo_root: &Path, json: bool) -> Result<ExitCode> {
+    let mut records = state_store(repo_root)?.load_workers()?;
+    records.sort_by_key(|record| record.snapshot.id);
+
+    if json {
+        for record in &records {
+            println!("{}", serde_json::to_string(&record.snapshot)?);
+        }
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    println!(
+        "{:<24} {:<12} {:<14} {:<10} {:<24} {:<6} LAST EVENT",
+        "NAME", "STATUS", "WORKFLOW", "ISSUE", "BRANCH", "PR"
+    );
+    for record in &records {
+        let snapshot = &record.snapshot;
+        println!(
+            "{:<24} {:<12} {:<14} {:<10} {:<24} {:<6} {}",
+            snapshot.name,
+            snapshot.status,
+            snapshot.workflow,
+            snapshot.issue.as_deref().unwrap_or("-"),
+            snapshot.branch,
+            pr_label(snapshot.pr_url.as_deref()),
+            snapshot.last_event
+        );
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn logs(repo_root: &Path, worker: &str, follow: bool) -> Result<ExitCode> {
+    if follow && let Some(mut session) = Session::attach(repo_root)? {
+        return session.follow(worker);
+    }
+
+    let store = state_store(repo_root)?;
+    let mut printed = find_worker(repo_root, worker)?.logs;
+    print_lines(&printed)?;
+    if !follow {
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    // Without a daemon the only source is the saved state, which is written
+    // after every step
+    loop {
+        thread::sleep(FOLLOW_INTERVAL);
+        let Some(record) = store.load_worker(worker)? else {
+            eprintln!("ワーカー '{worker}' は削除されました");
+            return Ok(ExitCode::SUCCESS);
+        };
+        print_lines(new_lines(&printed, &record.logs))?;
+        printed = record.logs;
+    }
+}
+
+fn state_store(repo_root: &Path) -> Result<StateStore> {
+    StateStore::new(repo_root.join(".gensui/state"))
+}
+
+fn find_worker(repo_root: &Path, name: &str) -> Result<WorkerRecord> {
+    state_store(repo_root)?
+        .load_worker(name)?
+        .ok_or_else(|| anyhow!("worker '{name}' not found"))
+}
+
+fn print_lines(lines: &[String]) -> Result<()> {
+    let mut stdout = io::stdout().lock();
+    for line in lines {
+        writeln!(stdout, "{line}")?;
+    }
+    stdout.flush()?;
+    Ok(())
+}
+
+/// Lines of `current` that were not in `previous`. The persisted log is capped,
+/// so old lines may have been dropped from the front in the meantime.
+fn new_lines<'a>(previous: &[String], current: &'a [String]) -> &'a [String] {
+    for dropped in 0..=previous.len() {
+        let kept = &previous[dropped..];
+        if current.starts_with(kept) {
+            return &current[kept.len()..];
+        }
+    }
+    current
+}
+
+/// 標準入力が端末なら権限確認を対話的に行えるか
+fn can_prompt() -> bool {
+    io::stdin().is_terminal()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(values: &[&str]) -> Vec<String> {
+        values.iter().map(|v| v.to_string()).collect()
+    }
+
+    #[test]
+    fn test_new_lines_handles_rotated_logs() {
+        let previous = lines(&["a", "b", "c"]);
+        assert_eq!(new_lines(&previous, &lines(&["a", "b", "c", "d"])), lines(&["d"]));
+        assert_eq!(new_lines(&previous, &lines(&["b", "c", "d", "e"])), lines(&["d", "e"]));
+        assert_eq!(new_lines(&previous, &lines(&["x", "y"])), lines(&["x", "y"]));
+        assert!(new_lines(&previous, &previous).is_empty());
+    }
+
+    #[test]
+    fn test_parse_subcommands() {
+        let cli = Cli::try_parse_from([
+            "gensui", "create", "--workflow", "review", "--issue", "123", "--name", "foo", "-y",
+        ])
+        .unwrap();
+        match cli.command {
+            Some(Commands::Create {
+                workflow,
+                issue,
+                name,
+                approval,
+                ..
+            }) => {
+                assert_eq!(workflow.as_deref(), Some("review"));
+                assert_eq!(issue.as_deref(), Some("123"));
+                assert_eq!(name.as_deref(), Some("foo"));
+                assert!(approval.auto_approve);
+            }
+            other => panic!("unexpected command: {other:?}"),
+        }
+
+        let cli = Cli::try_parse_from(["gensui", "batch", "issues.csv", "--agent", "codex"]).unwrap();
+        match cli.command {
+            Some(Commands::Batch { file, agent, .. }) => {
+                assert_eq!(file, PathBuf::from("issues.csv"));
+                assert_eq!(agent.as_deref(), Some("codex"));
+            }
+            other => panic!("unexpected command: {other:?}"),
+        }
+
+        let cli = Cli::try_parse_from(["gensui", "logs", "foo", "--follow"]).unwrap();
+        assert!(matches!(cli.command, Some(Commands::Logs { follow: true, .. })));
+        assert!(Cli::try_parse_from(["gensui"]).unwrap().command.is_none());
+    }
+}
@@ -0,0 +1,443 @@
+//! コマンドラインから操作するワーカーマネージャのセッション
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::io::{self, Write};
+use std::path::Path;
+use std::process::ExitCode;
+use std::time::Duration;
+
+use anyhow::{Result, anyhow, bail};
+
+use crate::config::Config;
+use crate::daemon;
+use crate::worker::{
+    BatchEntry, CreateWorkerRequest, DeleteMode, PermissionDecision, PermissionRequest, WorkerEvent, WorkerEventReceiver,
+    WorkerHandle, WorkerId, WorkerSnapshot, WorkerStatus, spawn_worker_system,
+};
+
+use super::{Approval, can_prompt};
+
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Connection to a worker manager that relays its events to the terminal.
+///
+/// When a daemon is running the session attaches to it and workers keep
+/// running after the command exits. Otherwise the manager runs on threads of
+/// this process, so every command waits until the workers it started finish.
+pub(super) struct Session {
+    handle: WorkerHandle,
+    events: WorkerEventReceiver,
+    /// Whether the manager lives in a daemon rather than in this process.
+    remote: bool,
+    /// Whether the existing workers have been announced.
+    synced: bool,
+    approval: Approval,
+    /// Latest known snapshot of every worker.
+    workers: HashMap<WorkerId, WorkerSnapshot>,
+    /// Workers whose completion is awaited.
+    watched: HashSet<WorkerId>,
+    /// Watched workers seen running since the command was sent. Logs replayed
+    /// while announcing existing workers arrive before that and are not shown.
+    started: HashSet<WorkerId>,
+    /// Events received while waiting for a creation to be acknowledged. They
+    /// are handed out again by `next_event` before anything new.
+    backlog: VecDeque<WorkerEvent>,
+}
+
+impl Session {
+    pub fn start(repo_root: &Path, approval: Approval) -> Result<Self> {
+        if let Some(mut session) = Self::attach(repo_root)? {
+            session.approval = approval;
+            return Ok(session);
+        }
+
+        let (config, warnings) = Config::discover(repo_root)?;
+        for warning in &warnings {
+            eprintln!("{}", warning.display_relative(repo_root));
+        }
+        let (handle, events) = spawn_worker_system(repo_root.to_path_buf(), config)?;
+        let mut session = Self::new(handle, events, false, approval);
+        session.sync(|_| {})?;
+        Ok(session)
+    }
+
+    /// Attach to a running daemon, if there is one.
+    pub fn attach(repo_root: &Path) -> Result<Option<Self>> {
+        let Some((handle, events)) = daemon::connect(repo_root)? else {
+            return Ok(None);
+        };
+        let approval = Approval {
+            auto_approve: false,
+        };
+        Ok(Some(Self::new(handle, events, true, approval)))
+    }
+
+    fn new(
+        handle: WorkerHandle,
+        events: WorkerEventReceiver,
+        remote: bool,
+        approval: Approval,
+    ) -> Self {
+        Self {
+            handle,
+            events,
+            remote,
+            synced: false,
+            approval,
+            workers: HashMap::new(),
+            watched: HashSet::new(),
+            started: HashSet::new(),
+            backlog: VecDeque::new(),
+        }
+    }
+
+    /// Consume the announcement of existing workers, up to `WorkerEvent::Ready`.
+    /// Replayed log lines are passed to `on_log`.
+    fn sync(&mut self, mut on_log: impl FnMut(&(WorkerId, String))) -> Result<()> {
+        loop {
+            match self.next_event()? {
+                WorkerEvent::Ready => {
+                    self.synced = true;
+                    return Ok(());
+                }
+                WorkerEvent::Created(snapshot) | WorkerEvent::Updated(snapshot) => {
+                    self.workers.insert(snapshot.id, snapshot);
+                }
+                WorkerEvent::Log { id, line } => on_log(&(id, line)),
+                _ => {}
+            }
+        }
+    }
+
+    fn ensure_synced(&mut self) -> Result<()> {
+        if !self.synced {
+            self.sync(|_| {})?;
+        }
+        Ok(())
+    }
+
+    fn find(&self, name: &str) -> Result<WorkerId> {
+        self.workers
+            .values()
+            .find(|snapshot| snapshot.name == name)
+            .map(|snapshot| snapshot.id)
+            .ok_or_else(|| anyhow!("worker '{name}' not found"))
+    }
+
+    pub fn create(&mut self, request: CreateWorkerRequest) -> Result<ExitCode> {
+        self.ensure_synced()?;
+        let id = self.submit(request)?.map_err(|message| anyhow!(message))?;
+        self.watched.insert(id);
+        self.started.insert(id);
+        self.wait()
+    }
+
+    /// Create a worker for every line of a batch file and wait for all of
+    /// them. Lines that fail are reported and skipped; the rest still run.
+    pub fn create_batch(&mut self, entries: Vec<BatchEntry>) -> Result<ExitCode> {
+        self.ensure_synced()?;
+        if entries.is_empty() {
+            eprintln!("作成するワーカーがありません");
+            return Ok(ExitCode::SUCCESS);
+        }
+
+        let mut failures = 0;
+        for entry in entries {
+            let outcome = match entry.request {
+                Ok(request) => self.submit(request)?,
+                Err(message) => Err(message),
+            };
+            match outcome {
+                Ok(id) => {
+                    self.watched.insert(id);
+                    self.started.insert(id);
+                }
+                Err(message) => {
+                    eprintln!("{}行目: {message}", entry.line);
+                    failures += 1;
+                }
+            }
+        }
+        if failures > 0 {
+            eprintln!("{failures} 行のワーカー作成に失敗しました");
+        }
+
+        let code = self.wait()?;
+        Ok(if failures > 0 { ExitCode::FAILURE } else { code })
+    }
+
+    /// Send a creation request and wait until the manager accepts or rejects
+    /// it. Unrelated events that arrive meanwhile are kept for later.
+    fn submit(&mut self, request: CreateWorkerRequest) -> Result<Result<WorkerId, String>> {
+        let name = request.name.clone();
+        self.handle.create_worker(request)?;
+
+        // Existing workers have all been announced, so the next creation is
+        // ours unless another client created one at the same moment
+        let mut deferred = Vec::new();
+        let outcome = loop {
+            match self.next_event()? {
+                WorkerEvent::Created(snapshot) => {
+                    let ours = name.as_ref().is_none_or(|name| *name == snapshot.name);
+                    let id = snapshot.id;
+                    if ours {
+                        eprintln!("ワーカー '{}' を作成しました", snapshot.name);
+                    }
+                    self.workers.insert(id, snapshot);
+                    if ours {
+                        break Ok(id);
+                    }
+                }
+                WorkerEvent::Error { id: None, message } => break Err(message),
+                event => deferred.push(event),
+            }
+        };
+        for event in deferred.into_iter().rev() {
+            self.backlog.push_front(event);
+        }
+        Ok(outcome)
+    }
+
+    pub fn continue_worker(
+        &mut self,
+        name: &str,
+        prompt: String,
+        permission_mode: Option<String>,
+    ) -> Result<ExitCode> {
+        self.ensure_synced()?;
+        let id = self.find(name)?;
+        self.handle.continue_worker(id, prompt, permission_mode)?;
+        self.watched.insert(id);
+        self.wait()
+    }
+
+    /// Resume every interrupted worker.
+    pub fn resume_all(&mut self) -> Result<ExitCode> {
+        self.ensure_synced()?;
+        let interrupted: Vec<WorkerId> = self
+            .workers
+            .values()
+            .filter(|snapshot| snapshot.status == WorkerStatus::Interrupted)
+            .map(|snapshot| snapshot.id)
+            .collect();
+        if interrupted.is_empty() {
+            eprintln!("再開するワーカーはありません");
+            return Ok(ExitCode::SUCCESS);
+        }
+        for id in interrupted {
+            self.handle.resume_interrupted_worker(id, true)?;
+            self.watched.insert(id);
+        }
+        self.wait()
+    }
+
+    /// Delete a worker and wait until the manager has destroyed or archived it.
+    pub fn delete(&mut self, name: &str, mode: DeleteMode, force: bool) -> Result<ExitCode> {
+        self.ensure_synced()?;
+        let id = self.find(name)?;
+        self.handle.delete_worker(id, mode, force)?;
+        loop {
+            match self.next_event()? {
+                WorkerEvent::Deleted { id: deleted, message } if deleted == id => {
+                    eprintln!("{message}");
+                    return Ok(ExitCode::SUCCESS);
+                }
+                WorkerEvent::Updated(snapshot)
+                    if snapshot.id == id && snapshot.status == WorkerStatus::Archived =>
+                {
+                    eprintln!("{}", snapshot.last_event);
+                    return Ok(ExitCode::SUCCESS);
+                }
+                WorkerEvent::Error {
+                    id: Some(failed),
+                    message,
+                } if failed == id => bail!(message),
+                _ => {}
+            }
+        }
+    }
+
+    /// Recreate the worktree of an archived worker.
+    pub fn reactivate(&mut self, name: &str) -> Result<ExitCode> {
+        self.ensure_synced()?;
+        let id = self.find(name)?;
+        self.handle.reactivate_worker(id)?;
+        loop {
+            match self.next_event()? {
+                WorkerEvent::Updated(snapshot)
+                    if snapshot.id == id && snapshot.status != WorkerStatus::Archived =>
+                {
+                    eprintln!("{}", snapshot.last_event);
+                    return Ok(ExitCode::SUCCESS);
+                }
+                WorkerEvent::Error {
+                    id: Some(failed),
+                    message,
+                } if failed == id => bail!(message),
+                _ => {}
+            }
+        }
+    }
+
+    /// Print the log of a worker as it grows, until the worker is deleted.
+    pub fn follow(&mut self, name: &str) -> Result<ExitCode> {
+        let mut replayed = Vec::new();
+        self.sync(|entry| replayed.push(entry.clone()))?;
+        let id = self.find(name)?;
+
+        let mut stdout = io::stdout().lock();
+        for (_, line) in replayed.iter().filter(|(worker, _)| *worker == id) {
+            writeln!(stdout, "{line}")?;
+        }
+        stdout.flush()?;
+        loop {
+            match self.next_event()? {
+                WorkerEvent::Log { id: worker, line } if worker == id => {
+                    writeln!(stdout, "{line}")?;
+                    stdout.flush()?;
+                }
+                WorkerEvent::Deleted { id: deleted, .. } if deleted == id => {
+                    eprintln!("ワーカー '{name}' は削除されました");
+                    return Ok(ExitCode::SUCCESS);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Relay events until every watched worker has stopped running. Paused
+    /// workers count as stopped since nothing here can resume them.
+    fn wait(&mut self) -> Result<ExitCode> {
+        let prefix = self.watched.len() > 1;
+        let mut failed = false;
+
+        while !self.watched.is_empty() {
+            match self.next_event()? {
+                WorkerEvent::Created(snapshot) => {
+                    self.workers.insert(snapshot.id, snapshot);
+                }
+                WorkerEvent::Log { id, line } if self.started.contains(&id) => {
+                    let mut stdout = io::stdout().lock();
+                    if prefix {
+                        writeln!(stdout, "[{}] {line}", self.name(id))?;
+                    } else {
+                        writeln!(stdout, "{line}")?;
+                    }
+                    stdout.flush()?;
+                }
+                WorkerEvent::Updated(snapshot) if self.watched.contains(&snapshot.id) => {
+                    match snapshot.status {
+                        WorkerStatus::Running => {
+                            self.started.insert(snapshot.id);
+                        }
+                        WorkerStatus::Queued => {
+                            eprintln!("[{}] {}", snapshot.name, snapshot.last_event);
+                        }
+                        status if self.started.contains(&snapshot.id) => {
+                            eprintln!(
+                                "[{}] {}: {}",
+                                snapshot.name,
+                                status.label(),
+                                snapshot.last_event
+                            );
+                            failed |= status != WorkerStatus::Idle;
+                            self.watched.remove(&snapshot.id);
+                        }
+                        _ => {}
+                    }
+                }
+                WorkerEvent::PermissionRequested { id, request }
+                    if self.watched.contains(&id) =>
+                {
+                    let decision = self.decide_permission(id, &request)?;
+                    self.handle
+                        .respond_permission(id, request.request_id, decision)?;
+                }
+                WorkerEvent::Error {
+                    id: Some(id),
+                    message,
+                } if self.watched.contains(&id) => {
+                    eprintln!("[{}] error: {message}", self.name(id));
+                    failed = true;
+                    self.watched.remove(&id);
+                }
+                WorkerEvent::Deleted { id, .. } => {
+                    self.watched.remove(&id);
+                }
+                _ => {}
+            }
+        }
+
+        Ok(if failed {
+            ExitCode::FAILURE
+        } else {
+            ExitCode::SUCCESS
+        })
+    }
+
+    fn decide_permission(
+        &self,
+        id: WorkerId,
+        request: &PermissionRequest,
+    ) -> Result<PermissionDecision> {
+        // Allow without overrides runs the step with its configured settings
+        let allow = PermissionDecision::Allow {
+            permission_mode: None,
+            allowed_tools: None,
+        };
+        if self.approval.auto_approve {
+            return Ok(allow);
+        }
+        if !can_prompt() {
+            eprintln!(
+                "[{}] ステップ '{}' の権限確認を拒否しました（--yes で自動許可）",
+                self.name(id),
+                request.step_name
+            );
+            return Ok(PermissionDecision::Deny);
+        }
+
+        eprint!(
+            "[{}] ステップ '{}' の実行を許可しますか？ [y/N] ",
+            self.name(id),
+            request.step_name
+        );
+        io::stderr().flush()?;
+        let mut answer = String::new();
+        io::stdin().read_line(&mut answer)?;
+        Ok(if answer.trim().eq_ignore_ascii_case("y") {
+            allow
+        } else {
+            PermissionDecision::Deny
+        })
+    }
+
+    fn next_event(&mut self) -> Result<WorkerEvent> {
+        if let Some(event) = self.backlog.pop_front() {
+            return Ok(event);
+        }
+        self.events
+            .recv()
+            .map_err(|_| anyhow!("worker manager stopped unexpectedly"))
+    }
+
+    fn name(&self, id: WorkerId) -> String {
+        self.workers
+            .get(&id)
+            .map(|snapshot| snapshot.name.clone())
+            .unwrap_or_else(|| format!("worker-{}", id.0))
+    }
+}
+
+impl Drop for Session {
+    /// Save the final state of every worker before the process exits. A
+    /// daemon keeps its workers running instead.
+    fn drop(&mut self) {
+        if self.remote {
+            return;
+        }
+        if let Err(err) = self.handle.shutdown(SHUTDOWN_TIMEOUT) {
+            eprintln!("{err:#}");
+        }
+    }
+}
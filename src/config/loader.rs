@@ -0,0 +1,789 @@
+//! ワークフロー設定ファイルの探索とレイヤーの合成（ユーザー設定 → リポジトリ設定）
+
+use std::collections::{BTreeMap, HashMap};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+
+use super::validate::{
+    Diagnostic, Origin, Reporter, ResolvedWorkflow, Severity, SourceFile, check_branch_template,
+    check_concurrency, check_config, check_forge, check_issues,
+};
+use super::{
+    Config, ForgeConfig, GenericAgentConfig, IssueTrackerConfig, Workflow, WorkflowStep,
+    default_sandbox_mode,
+};
+
+const CONFIG_STEM: &str = "workflows";
+const EXTENSIONS: [&str; 4] = ["json", "yaml", "yml", "toml"];
+const REPO_WORKFLOWS_DIR: &str = ".gensui/workflows";
+
+/// File formats understood by the loader, chosen by extension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Json,
+    Yaml,
+    Toml,
+}
+
+impl Format {
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "json" => Some(Self::Json),
+            "yaml" | "yml" => Some(Self::Yaml),
+            "toml" => Some(Self::Toml),
+            _ => None,
+        }
+    }
+
+    pub fn parse<T: DeserializeOwned>(self, data: &str) -> Result<T> {
+        Ok(match self {
+            Self::Json => serde_json::from_str(data)?,
+            Self::Yaml => serde_yaml::from_str(data)?,
+            Self::Toml => toml::from_str(data)?,
+        })
+    }
+}
+
+/// One configuration file as written, before layering.
+#[derive(Debug, Default, Deserialize)]
+pub(super) struct ConfigLayer {
+    #[serde(default)]
+    workflows: Vec<Workflow>,
+    #[serde(default)]
+    default_workflow: Option<String>,
+    #[serde(default)]
+    default_sandbox_mode: Option<bool>,
+    #[serde(default)]
+    agents: Vec<GenericAgentConfig>,
+    #[serde(default)]
+    max_concurrent_workers: Option<usize>,
+    #[serde(default)]
+    max_concurrent_workers_per_agent: BTreeMap<String, usize>,
+    #[serde(default)]
+    issues: Option<IssueTrackerConfig>,
+    #[serde(default)]
+    forge: Option<ForgeConfig>,
+    #[serde(default)]
+    auto_commit: Option<bool>,
+    #[serde(default)]
+    branch_template: Option<String>,
+}
+
+/// A configuration layer with the origin of every entry.
+#[derive(Default)]
+struct Layer {
+    workflows: Vec<(Workflow, Origin)>,
+    default_workflow: Option<(String, Origin)>,
+    default_sandbox_mode: Option<bool>,
+    agents: Vec<(GenericAgentConfig, Origin)>,
+    max_concurrent_workers: Option<(usize, Origin)>,
+    max_concurrent_workers_per_agent: BTreeMap<String, (usize, Origin)>,
+    issues: Option<(IssueTrackerConfig, Origin)>,
+    forge: Option<(ForgeConfig, Origin)>,
+    auto_commit: Option<bool>,
+    branch_template: Option<(String, Origin)>,
+}
+
+impl Layer {
+    fn from_file(file: ConfigLayer, source: usize) -> Self {
+        let root = Origin {
+            source,
+            pointer: String::new(),
+        };
+        Self {
+            workflows: file
+                .workflows
+                .into_iter()
+                .enumerate()
+                .map(|(idx, wf)| (wf, root.child(format!("workflows/{idx}"))))
+                .collect(),
+            default_workflow: file
+                .default_workflow
+                .map(|name| (name, root.child("default_workflow"))),
+            default_sandbox_mode: file.default_sandbox_mode,
+            agents: file
+                .agents
+                .into_iter()
+                .enumerate()
+                .map(|(idx, agent)| (agent, root.child(format!("agents/{idx}"))))
+                .collect(),
+            max_concurrent_workers: file
+                .max_concurrent_workers
+                .map(|limit| (limit, root.child("max_concurrent_workers"))),
+            max_concurrent_workers_per_agent: file
+                .max_concurrent_workers_per_agent
+                .into_iter()
+                .map(|(agent, limit)| {
+                    let origin = root.child(format!("max_concurrent_workers_per_agent/{agent}"));
+                    (agent, (limit, origin))
+                })
+                .collect(),
+            issues: file.issues.map(|issues| (issues, root.child("issues"))),
+            forge: file.forge.map(|forge| (forge, root.child("forge"))),
+            auto_commit: file.auto_commit,
+            branch_template: file
+                .branch_template
+                .map(|template| (template, root.child("branch_template"))),
+        }
+    }
+
+    /// Overlay `upper` on top of `self`. Workflows, agents and per-agent
+    /// limits are replaced by name; the issue tracker and forge sections as a whole.
+    fn merge(&mut self, upper: Layer) {
+        for workflow in upper.workflows {
+            match self.workflows.iter_mut().find(|(wf, _)| wf.name == workflow.0.name) {
+                Some(existing) => *existing = workflow,
+                None => self.workflows.push(workflow),
+            }
+        }
+        for agent in upper.agents {
+            match self.agents.iter_mut().find(|(a, _)| a.name == agent.0.name) {
+                Some(existing) => *existing = agent,
+                None => self.agents.push(agent),
+            }
+        }
+        if upper.default_workflow.is_some() {
+            self.default_workflow = upper.default_workflow;
+        }
+        if upper.default_sandbox_mode.is_some() {
+            self.default_sandbox_mode = upper.default_sandbox_mode;
+        }
+        if upper.max_concurrent_workers.is_some() {
+            self.max_concurrent_workers = upper.max_concurrent_workers;
+        }
+        self.max_concurrent_workers_per_agent
+            .extend(upper.max_concurrent_workers_per_agent);
+        if upper.issues.is_some() {
+            self.issues = upper.issues;
+        }
+        if upper.forge.is_some() {
+            self.forge = upper.forge;
+        }
+        if upper.auto_commit.is_some() {
+            self.auto_commit = upper.auto_commit;
+        }
+        if upper.branch_template.is_some() {
+            self.branch_template = upper.branch_template;
+        }
+    }
+}
+
+/// The usable configuration together with every problem found while loading it.
+pub struct Loaded {
+    pub config: Config,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// Directory holding the user-level configuration layer, if it can be determined.
+pub fn user_config_dir() -> Option<PathBuf> {
+    if let Some(dir) = env::var_os("XDG_CONFIG_HOME").filter(|d| !d.is_empty()) {
+        return Some(PathBuf::from(dir).join("gensui"));
+    }
+    env::var_os("HOME").map(|home| PathBuf::from(home).join(".config").join("gensui"))
+}
+
+/// Load the layered configuration for `repo_root`, using `user_dir` as the
+/// user layer when given. Files that fail to parse are skipped and reported.
+pub fn discover(repo_root: &Path, user_dir: Option<&Path>) -> Loaded {
+    let mut reporter = Reporter::default();
+    let mut merged = Layer::default();
+    if let Some(user_dir) = user_dir {
+        merged.merge(load_layer(&mut reporter, user_dir, &user_dir.join(CONFIG_STEM)));
+    }
+    merged.merge(load_layer(
+        &mut reporter,
+        repo_root,
+        &repo_root.join(REPO_WORKFLOWS_DIR),
+    ));
+
+    let resolved = resolve_workflows(&mut reporter, &merged.workflows);
+    check_config(
+        &mut reporter,
+        &resolved,
+        merged.default_workflow.as_ref(),
+        &merged.agents,
+    );
+    check_concurrency(
+        &mut reporter,
+        merged.max_concurrent_workers.as_ref(),
+        &merged.max_concurrent_workers_per_agent,
+        &merged.agents,
+    );
+    if let Some((issues, origin)) = &merged.issues {
+        check_issues(&mut reporter, issues, origin);
+    }
+    if let Some((forge, origin)) = &merged.forge {
+        let issues = merged
+            .issues
+            .as_ref()
+            .map(|(issues, _)| issues.clone())
+            .unwrap_or_default();
+        check_forge(&mut reporter, forge, origin, &issues);
+    }
+    if let Some((template, origin)) = &merged.branch_template {
+        check_branch_template(&mut reporter, template, origin);
+    }
+
+    let mut config = Config {
+        workflows: resolved.into_iter().map(|r| r.workflow).collect(),
+        default_workflow: merged.default_workflow.map(|(name, _)| name),
+        default_sandbox_mode: merged
+            .default_sandbox_mode
+            .unwrap_or_else(default_sandbox_mode),
+        agents: merged.agents.into_iter().map(|(agent, _)| agent).collect(),
+        max_concurrent_workers: merged.max_concurrent_workers.map(|(limit, _)| limit),
+        max_concurrent_workers_per_agent: merged
+            .max_concurrent_workers_per_agent
+            .into_iter()
+            .map(|(agent, (limit, _))| (agent, limit))
+            .collect(),
+        issues: merged.issues.map(|(issues, _)| issues).unwrap_or_default(),
+        forge: merged.forge.map(|(forge, _)| forge).unwrap_or_default(),
+        auto_commit: merged.auto_commit.unwrap_or_default(),
+        branch_template: merged.branch_template.map(|(template, _)| template),
+    };
+    if config.workflows.is_empty() {
+        if !reporter.sources.is_empty() {
+            reporter.diagnostics.push(Diagnostic {
+                severity: Severity::Warning,
+                file: None,
+                line: None,
+                pointer: String::new(),
+                message: "no workflows defined; using the built-in default workflow".to_string(),
+            });
+        }
+        let fallback = Config::default();
+        config.workflows = fallback.workflows;
+        config.default_workflow = fallback.default_workflow;
+    }
+
+    Loaded {
+        config,
+        diagnostics: reporter.diagnostics,
+    }
+}
+
+/// Every file or directory whose contents make up the configuration, whether
+/// or not it currently exists.
+pub fn watched_paths(repo_root: &Path, user_dir: Option<&Path>) -> Vec<PathBuf> {
+    let mut layers = vec![(repo_root.to_path_buf(), repo_root.join(REPO_WORKFLOWS_DIR))];
+    if let Some(user_dir) = user_dir {
+        layers.push((user_dir.to_path_buf(), user_dir.join(CONFIG_STEM)));
+    }
+
+    let mut paths = Vec::new();
+    for (config_dir, workflows_dir) in layers {
+        paths.extend(
+            EXTENSIONS
+                .iter()
+                .map(|ext| config_dir.join(format!("{CONFIG_STEM}.{ext}"))),
+        );
+        paths.push(workflows_dir);
+    }
+    paths
+}
+
+fn load_layer(reporter: &mut Reporter, config_dir: &Path, workflows_dir: &Path) -> Layer {
+    let candidates: Vec<PathBuf> = EXTENSIONS
+        .iter()
+        .map(|ext| config_dir.join(format!("{CONFIG_STEM}.{ext}")))
+        .filter(|path| path.is_file())
+        .collect();
+
+    let mut layer = Layer::default();
+    match candidates.as_slice() {
+        [] => {}
+        [path] => {
+            if let Some(source) = read_source(reporter, path)
+                && let Some(file) = reporter.parse::<ConfigLayer>(source)
+            {
+                layer = Layer::from_file(file, source);
+            }
+        }
+        _ => {
+            let names: Vec<String> = candidates
+                .iter()
+                .filter_map(|p| p.file_name().map(|n| n.to_string_lossy().into_owned()))
+                .collect();
+            reporter.file_error(
+                config_dir,
+                None,
+                format!("multiple configuration files found: {}", names.join(", ")),
+            );
+        }
+    }
+
+    if workflows_dir.is_dir() {
+        let workflows = Layer {
+            workflows: read_workflows_dir(reporter, workflows_dir),
+            ..Default::default()
+        };
+        layer.merge(workflows);
+    }
+
+    layer
+}
+
+fn read_source(reporter: &mut Reporter, path: &Path) -> Option<usize> {
+    let format = Format::from_path(path)?;
+    match fs::read_to_string(path) {
+        Ok(text) => Some(reporter.add_source(SourceFile {
+            path: path.to_path_buf(),
+            format,
+            value: format.parse(&text).ok(),
+            text,
+        })),
+        Err(err) => {
+            reporter.file_error(path, None, format!("failed to read file: {err}"));
+            None
+        }
+    }
+}
+
+/// Read every workflow file in `dir`, sorted by file name. A workflow without
+/// a `name` is named after its file.
+fn read_workflows_dir(reporter: &mut Reporter, dir: &Path) -> Vec<(Workflow, Origin)> {
+    let mut paths: Vec<PathBuf> = match fs::read_dir(dir) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.is_file() && Format::from_path(path).is_some())
+            .collect(),
+        Err(err) => {
+            reporter.file_error(dir, None, format!("failed to read directory: {err}"));
+            return Vec::new();
+        }
+    };
+    paths.sort();
+
+    paths
+        .iter()
+        .filter_map(|path| {
+            let source = read_source(reporter, path)?;
+            let mut workflow: Workflow = reporter.parse(source)?;
+            if workflow.name.is_empty() {
+                workflow.name = path
+                    .file_stem()
+                    .map(|stem| stem.to_string_lossy().into_owned())
+                    .unwrap_or_default();
+            }
+            let origin = Origin {
+                source,
+                pointer: String::new(),
+            };
+            Some((workflow, origin))
+        })
+        .collect()
+}
+
+/// Expand `extends` and step `include`s into plain step lists. Workflows that
+/// cannot be resolved are reported and left out.
+fn resolve_workflows(
+    reporter: &mut Reporter,
+    workflows: &[(Workflow, Origin)],
+) -> Vec<ResolvedWorkflow> {
+    let by_name: HashMap<&str, &(Workflow, Origin)> =
+        workflows.iter().map(|entry| (entry.0.name.as_str(), entry)).collect();
+
+    let mut resolved = Vec::new();
+    for (workflow, origin) in workflows {
+        let mut stack = Vec::new();
+        match resolve_steps(workflow, origin, &by_name, &mut stack) {
+            Ok(steps) => {
+                let description = workflow.description.clone().or_else(|| {
+                    workflow
+                        .extends
+                        .as_deref()
+                        .and_then(|base| by_name.get(base))
+                        .and_then(|(base, _)| base.description.clone())
+                });
+                let (steps, step_origins) = steps.into_iter().unzip();
+                resolved.push(ResolvedWorkflow {
+                    workflow: Workflow {
+                        name: workflow.name.clone(),
+                        description,
+                        steps,
+                        extends: None,
+                    },
+                    origin: origin.clone(),
+                    step_origins,
+                });
+            }
+            Err((origin, message)) => reporter.error(&origin, message),
+        }
+    }
+    resolved
+}
+
+type ResolveResult = std::result::Result<Vec<(WorkflowStep, Origin)>, (Origin, String)>;
+
+fn resolve_steps<'a>(
+    workflow: &'a Workflow,
+    origin: &Origin,
+    by_name: &HashMap<&str, &'a (Workflow, Origin)>,
+    stack: &mut Vec<&'a str>,
+) -> ResolveResult {
+    if stack.contains(&workflow.name.as_str()) {
+        stack.push(&workflow.name);
+        return Err((
+            origin.clone(),
+            format!("include/extends cycle: {}", stack.join(" -> ")),
+        ));
+    }
+    stack.push(&workflow.name);
+
+    let lookup = |name: &str, at: Origin| {
+        by_name
+            .get(name)
+            .copied()
+            .ok_or_else(|| (at, format!("unknown workflow '{name}'")))
+    };
+
+    let mut steps = match &workflow.extends {
+        Some(base) => {
+            let (base, base_origin) = lookup(base, origin.child("extends"))?;
+            resolve_steps(base, base_origin, by_name, stack)?
+        }
+        None => Vec::new(),
+    };
+
+    for (idx, step) in workflow.steps().iter().enumerate() {
+        let step_origin = origin.child(format!("steps/{idx}"));
+        let own = match &step.include {
+            Some(included) => {
+                let (included, included_origin) = lookup(included, step_origin.child("include"))?;
+                resolve_steps(included, included_origin, by_name, stack)?
+            }
+            None => vec![(step.clone(), step_origin)],
+        };
+        // Steps overriding an inherited step keep its position
+        for (step, step_origin) in own {
+            match steps.iter_mut().find(|(s, _)| s.key() == step.key()) {
+                Some(existing) => *existing = (step, step_origin),
+                None => steps.push((step, step_origin)),
+            }
+        }
+    }
+
+    stack.pop();
+    Ok(steps)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{ForgeKind, IssueProviderKind};
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("gensui-config-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn step_names(workflow: &Workflow) -> Vec<&str> {
+        workflow.steps().iter().map(|s| s.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_load_yaml_with_multiline_prompt() {
+        let dir = temp_dir("yaml");
+        fs::write(
+            dir.join("workflows.yaml"),
+            r#"
+default_workflow: review
+workflows:
+  - name: review
+    steps:
+      - name: レビュー
+        claude:
+          prompt: |
+            差分をレビューしてください。
+            {{git.diff}}
+"#,
+        )
+        .unwrap();
+
+        let loaded = discover(&dir, None);
+        assert!(loaded.diagnostics.is_empty());
+        let config = loaded.config;
+        let prompt = &config.workflows[0].steps()[0].claude.as_ref().unwrap().prompt;
+        assert_eq!(prompt, "差分をレビューしてください。\n{{git.diff}}\n");
+        assert!(config.default_sandbox_mode);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_layers_and_workflow_directory() {
+        let root = temp_dir("layers");
+        let user = root.join("user");
+        let repo = root.join("repo");
+        fs::create_dir_all(user.join("workflows")).unwrap();
+        fs::create_dir_all(repo.join(REPO_WORKFLOWS_DIR)).unwrap();
+
+        fs::write(
+            user.join("workflows.toml"),
+            r#"
+default_workflow = "shared"
+default_sandbox_mode = false
+
+[[workflows]]
+name = "shared"
+[[workflows.steps]]
+name = "user-step"
+command = "echo user"
+"#,
+        )
+        .unwrap();
+        fs::write(
+            user.join("workflows").join("personal.yaml"),
+            "steps:\n  - name: mine\n    command: echo mine\n",
+        )
+        .unwrap();
+        fs::write(
+            repo.join("workflows.json"),
+            r#"{ "workflows": [{ "name": "shared", "steps": [{ "name": "repo-step" }] }] }"#,
+        )
+        .unwrap();
+        fs::write(
+            repo.join(REPO_WORKFLOWS_DIR).join("ci.yml"),
+            "name: ci\nsteps:\n  - name: test\n    command: cargo test\n",
+        )
+        .unwrap();
+
+        let loaded = discover(&repo, Some(&user));
+        assert!(loaded.diagnostics.is_empty());
+        let config = loaded.config;
+        let names: Vec<&str> = config.workflows.iter().map(|wf| wf.name.as_str()).collect();
+        assert_eq!(names, vec!["shared", "personal", "ci"]);
+        assert_eq!(step_names(&config.workflows[0]), vec!["repo-step"]);
+        assert_eq!(config.default_workflow.as_deref(), Some("shared"));
+        assert!(!config.default_sandbox_mode);
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn test_concurrency_limits_are_layered_and_checked() {
+        let root = temp_dir("limits");
+        let user = root.join("user");
+        let repo = root.join("repo");
+        fs::create_dir_all(&user).unwrap();
+        fs::create_dir_all(&repo).unwrap();
+        fs::write(
+            user.join("workflows.yaml"),
+            "max_concurrent_workers: 4\nmax_concurrent_workers_per_agent:\n  claude: 2\n  codex: 1\n",
+        )
+        .unwrap();
+        fs::write(
+            repo.join("workflows.toml"),
+            "[max_concurrent_workers_per_agent]\nclaude = 3\nclaud = 1\n",
+        )
+        .unwrap();
+
+        let loaded = discover(&repo, Some(&user));
+        let config = loaded.config;
+        assert_eq!(config.max_concurrent_workers, Some(4));
+        let limits: Vec<(&str, usize)> = config
+            .max_concurrent_workers_per_agent
+            .iter()
+            .map(|(agent, limit)| (agent.as_str(), *limit))
+            .collect();
+        assert_eq!(limits, vec![("claud", 1), ("claude", 3), ("codex", 1)]);
+        // Besides the warning about the missing workflows
+        let limit_diagnostics: Vec<&Diagnostic> = loaded
+            .diagnostics
+            .iter()
+            .filter(|d| d.pointer.starts_with("/max_concurrent"))
+            .collect();
+        assert_eq!(limit_diagnostics.len(), 1);
+        assert!(!limit_diagnostics[0].is_error());
+        assert_eq!(limit_diagnostics[0].pointer, "/max_concurrent_workers_per_agent/claud");
+
+        fs::write(repo.join("workflows.toml"), "max_concurrent_workers = 0\n").unwrap();
+        let loaded = discover(&repo, Some(&user));
+        let error = loaded.diagnostics.iter().find(|d| d.is_error()).unwrap();
+        assert_eq!(error.pointer, "/max_concurrent_workers");
+        assert!(error.message.contains("at least 1"));
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn test_issue_tracker_section_is_replaced_and_checked() {
+        let root = temp_dir("issues");
+        let user = root.join("user");
+        let repo = root.join("repo");
+        fs::create_dir_all(&user).unwrap();
+        fs::create_dir_all(&repo).unwrap();
+        fs::write(
+            user.join("workflows.yaml"),
+            "issues:\n  provider: gitlab\n  repo: group/project\n  token_env: MY_TOKEN\n",
+        )
+        .unwrap();
+
+        let loaded = discover(&repo, Some(&user));
+        assert_eq!(loaded.config.issues.provider, IssueProviderKind::Gitlab);
+        assert_eq!(loaded.config.issues.token_env.as_deref(), Some("MY_TOKEN"));
+        assert!(!loaded.diagnostics.iter().any(|d| d.pointer.starts_with("/issues")));
+
+        fs::write(
+            repo.join("workflows.yaml"),
+            "issues:\n  provider: github\n  base_url: ghe.example.com/api/v3\n",
+        )
+        .unwrap();
+        let loaded = discover(&repo, Some(&user));
+        assert_eq!(loaded.config.issues.provider, IssueProviderKind::Github);
+        assert!(loaded.config.issues.token_env.is_none());
+        let errors: Vec<&str> = loaded
+            .diagnostics
+            .iter()
+            .filter(|d| d.is_error())
+            .map(|d| d.pointer.as_str())
+            .collect();
+        assert_eq!(errors, vec!["/issues/repo", "/issues/base_url"]);
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn test_forge_section_inherits_from_issues() {
+        let dir = temp_dir("forge");
+        fs::write(
+            dir.join("workflows.yaml"),
+            "issues:\n  provider: github\n  repo: owner/repo\n\
+             forge:\n  base_url: http://127.0.0.1:8080\n  base: develop\n\
+             workflows:\n  - name: ship\n    steps:\n      - name: open\n        pr:\n          title: \"{{issue.titel}}\"\n",
+        )
+        .unwrap();
+
+        let loaded = discover(&dir, None);
+        let forge = loaded.config.forge.effective(&loaded.config.issues).unwrap();
+        assert_eq!(forge.provider, Some(ForgeKind::Github));
+        assert_eq!(forge.repo.as_deref(), Some("owner/repo"));
+        assert_eq!(forge.base_url.as_deref(), Some("http://127.0.0.1:8080"));
+        assert_eq!(forge.base.as_deref(), Some("develop"));
+        assert!(!loaded.diagnostics.iter().any(|d| d.is_error()));
+        let warning = loaded.diagnostics.iter().find(|d| !d.is_error()).unwrap();
+        assert_eq!(warning.pointer, "/workflows/0/steps/0/pr/title");
+
+        // A different provider does not inherit the issue tracker's repository
+        fs::write(
+            dir.join("workflows.yaml"),
+            "issues:\n  provider: github\n  repo: owner/repo\nforge:\n  provider: gitlab\n",
+        )
+        .unwrap();
+        let loaded = discover(&dir, None);
+        let error = loaded.diagnostics.iter().find(|d| d.is_error()).unwrap();
+        assert_eq!(error.pointer, "/forge/repo");
+
+        fs::write(dir.join("workflows.yaml"), "forge:\n  remote: upstream\n").unwrap();
+        let loaded = discover(&dir, None);
+        let error = loaded.diagnostics.iter().find(|d| d.is_error()).unwrap();
+        assert_eq!(error.pointer, "/forge/provider");
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_branch_template_is_layered_and_checked() {
+        let root = temp_dir("branch");
+        let user = root.join("user");
+        let repo = root.join("repo");
+        fs::create_dir_all(&user).unwrap();
+        fs::create_dir_all(&repo).unwrap();
+        fs::write(user.join("workflows.yaml"), "branch_template: fix/{{issue}}-{{worker}}\n").unwrap();
+
+        let loaded = discover(&repo, Some(&user));
+        assert_eq!(
+            loaded.config.branch_template.as_deref(),
+            Some("fix/{{issue}}-{{worker}}")
+        );
+        assert!(!loaded.diagnostics.iter().any(|d| d.pointer == "/branch_template"));
+
+        fs::write(repo.join("workflows.toml"), "branch_template = \"fix {{ticket}}\"\n").unwrap();
+        let loaded = discover(&repo, Some(&user));
+        let messages: Vec<&str> = loaded
+            .diagnostics
+            .iter()
+            .filter(|d| d.is_error() && d.pointer == "/branch_template")
+            .map(|d| d.message.as_str())
+            .collect();
+        assert_eq!(messages.len(), 2);
+        assert!(messages[0].contains("{{ticket}}"));
+        assert!(messages[1].contains("not a valid branch name"));
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn test_multiple_config_files_are_rejected() {
+        let dir = temp_dir("ambiguous");
+        fs::write(dir.join("workflows.json"), "{}").unwrap();
+        fs::write(dir.join("workflows.yaml"), "{}").unwrap();
+
+        let loaded = discover(&dir, None);
+        assert!(loaded.diagnostics[0].is_error());
+        assert!(loaded.diagnostics[0].message.contains("multiple configuration files"));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_extends_and_include() {
+        let layer: ConfigLayer = serde_yaml::from_str(
+            r#"
+workflows:
+  - name: setup
+    steps:
+      - name: install
+      - name: build
+  - name: base
+    description: 標準
+    steps:
+      - include: setup
+      - name: test
+        command: cargo test
+  - name: strict
+    extends: base
+    steps:
+      - name: test
+        command: cargo test -- --include-ignored
+      - name: lint
+"#,
+        )
+        .unwrap();
+
+        let layer = Layer::from_file(layer, 0);
+        let mut reporter = Reporter::default();
+        let workflows: Vec<Workflow> = resolve_workflows(&mut reporter, &layer.workflows)
+            .into_iter()
+            .map(|r| r.workflow)
+            .collect();
+        assert!(reporter.diagnostics.is_empty());
+        let strict = workflows.iter().find(|wf| wf.name == "strict").unwrap();
+        assert_eq!(step_names(strict), vec!["install", "build", "test", "lint"]);
+        assert_eq!(
+            strict.steps()[2].command.as_deref(),
+            Some("cargo test -- --include-ignored")
+        );
+        assert_eq!(strict.description.as_deref(), Some("標準"));
+        assert!(strict.extends.is_none());
+    }
+
+    #[test]
+    fn test_include_cycle_is_rejected() {
+        let layer: ConfigLayer = serde_json::from_str(
+            r#"{ "workflows": [
+                { "name": "a", "steps": [{ "include": "b" }] },
+                { "name": "b", "extends": "a" }
+            ] }"#,
+        )
+        .unwrap();
+
+        let layer = Layer::from_file(layer, 0);
+        let mut reporter = Reporter::default();
+        let resolved = resolve_workflows(&mut reporter, &layer.workflows);
+        assert!(resolved.is_empty());
+        assert_eq!(reporter.diagnostics.len(), 2);
+        assert!(reporter.diagnostics[0].message.contains("cycle"));
+    }
+}
@@ -0,0 +1,746 @@
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::{Context, Result, bail};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::state::{StepOutcome, StepOutcomeStatus};
+
+mod loader;
+mod validate;
+mod watch;
+
+pub use loader::user_config_dir;
+pub use validate::Diagnostic;
+pub use watch::ConfigWatcher;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Config {
+    #[serde(default)]
+    pub workflows: Vec<Workflow>,
+    #[serde(default)]
+    pub default_workflow: Option<String>,
+    /// Global default for sandbox mode.
+    /// If true (default), Claude Code will run in sandbox mode unless explicitly disabled.
+    /// Individual workflow steps can override this setting.
+    #[serde(default = "default_sandbox_mode")]
+    pub default_sandbox_mode: bool,
+    /// Additional CLI agents described declaratively (see `GenericAgentConfig`).
+    #[serde(default)]
+    pub agents: Vec<GenericAgentConfig>,
+    /// Maximum number of workers running an agent at the same time; further
+    /// workers are queued. Unlimited when unset.
+    #[serde(default)]
+    pub max_concurrent_workers: Option<usize>,
+    /// Limits per agent backend (e.g. `claude: 2`), applied in addition to
+    /// `max_concurrent_workers`.
+    #[serde(default)]
+    pub max_concurrent_workers_per_agent: BTreeMap<String, usize>,
+    /// Where issue metadata for `{{issue.*}}` placeholders comes from.
+    #[serde(default)]
+    pub issues: IssueTrackerConfig,
+    /// Where `pr` steps and the pull request action push and open requests.
+    #[serde(default)]
+    pub forge: ForgeConfig,
+    /// Commit the worktree's changes after every agent step, so restarts and
+    /// deletes never discard them. Steps can override this with `claude.auto_commit`.
+    #[serde(default)]
+    pub auto_commit: bool,
+    /// Name of the branch created for a new worker, e.g. `fix/{{issue}}-{{worker}}`.
+    /// Default: `gensui/worker-{{id}}-{{timestamp}}`
+    #[serde(default)]
+    pub branch_template: Option<String>,
+}
+
+/// Issue tracker the workers' issues are looked up in.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct IssueTrackerConfig {
+    #[serde(default)]
+    pub provider: IssueProviderKind,
+    /// `owner/repo` on GitHub, `group/project` on GitLab.
+    #[serde(default)]
+    pub repo: Option<String>,
+    /// API base URL, e.g. for GitHub Enterprise or a self-hosted GitLab.
+    /// Default: the public API of the provider.
+    #[serde(default)]
+    pub base_url: Option<String>,
+    /// Environment variable holding the API token.
+    /// Default: `GITHUB_TOKEN` or `GITLAB_TOKEN`.
+    #[serde(default)]
+    pub token_env: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IssueProviderKind {
+    /// Markdown files in `.gensui/issues`.
+    #[default]
+    Local,
+    Github,
+    Gitlab,
+}
+
+/// Forge that worker branches are pushed to and pull requests (merge
+/// requests on GitLab) are opened on.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ForgeConfig {
+    /// Default: the issue tracker's provider when it is GitHub or GitLab.
+    #[serde(default)]
+    pub provider: Option<ForgeKind>,
+    /// `owner/repo` on GitHub, `group/project` on GitLab.
+    #[serde(default)]
+    pub repo: Option<String>,
+    /// API base URL, e.g. for GitHub Enterprise, a self-hosted GitLab or a
+    /// local stand-in. Default: the public API of the provider.
+    #[serde(default)]
+    pub base_url: Option<String>,
+    /// Environment variable holding the API token.
+    /// Default: `GITHUB_TOKEN` or `GITLAB_TOKEN`.
+    #[serde(default)]
+    pub token_env: Option<String>,
+    /// Git remote the worker branch is pushed to. Default: `origin`.
+    #[serde(default)]
+    pub remote: Option<String>,
+    /// Branch pull requests target. Default: the branch checked out in the
+    /// repository when gensui starts.
+    #[serde(default)]
+    pub base: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ForgeKind {
+    Github,
+    Gitlab,
+}
+
+impl ForgeConfig {
+    /// The settings in effect once `issues` has filled in the gaps, or None
+    /// when no provider is known. `repo`, `base_url` and `token_env` are only
+    /// inherited when both sections use the same provider.
+    pub fn effective(&self, issues: &IssueTrackerConfig) -> Option<ForgeConfig> {
+        let issues_kind = match issues.provider {
+            IssueProviderKind::Local => None,
+            IssueProviderKind::Github => Some(ForgeKind::Github),
+            IssueProviderKind::Gitlab => Some(ForgeKind::Gitlab),
+        };
+        let provider = self.provider.or(issues_kind)?;
+        let inherit = issues_kind == Some(provider);
+        let fallback = |own: &Option<String>, issues: &Option<String>| {
+            own.clone().or_else(|| issues.clone().filter(|_| inherit))
+        };
+        Some(ForgeConfig {
+            provider: Some(provider),
+            repo: fallback(&self.repo, &issues.repo),
+            base_url: fallback(&self.base_url, &issues.base_url),
+            token_env: fallback(&self.token_env, &issues.token_env),
+            remote: self.remote.clone(),
+            base: self.base.clone(),
+        })
+    }
+}
+
+fn default_sandbox_mode() -> bool {
+    true // Security-first: enable sandbox by default
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Workflow {
+    /// Workflows loaded from `.gensui/workflows/` default to their file name.
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub steps: Vec<WorkflowStep>,
+    /// Workflow whose steps are inherited. Steps with the same id replace the
+    /// inherited ones in place; other steps are appended. Resolved at load time.
+    #[serde(default)]
+    pub extends: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+pub struct WorkflowStep {
+    #[serde(default)]
+    pub name: String,
+    /// Splice in the steps of another workflow at this position. A step with
+    /// `include` carries no other settings. Resolved at load time.
+    #[serde(default)]
+    pub include: Option<String>,
+    #[serde(default)]
+    pub command: Option<String>,
+    #[serde(default)]
+    pub claude: Option<ClaudeStep>,
+    /// Agent backend used for this step ("claude", "codex", "aider" or a name from `agents`).
+    /// The prompt settings under `claude` apply to whichever backend is selected.
+    /// Default: None (inherits the worker's agent)
+    #[serde(default)]
+    pub agent: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Maximum run time of a single attempt, in seconds.
+    /// Default: None (no limit)
+    #[serde(default)]
+    pub timeout: Option<u64>,
+    /// Number of additional attempts after a failure or timeout.
+    /// Default: 0
+    #[serde(default)]
+    pub retries: u32,
+    /// Delay before the first retry, in seconds. Doubles for each further retry.
+    /// Default: None (5 seconds)
+    #[serde(default)]
+    pub retry_backoff: Option<u64>,
+    /// Identifier used by `when`, `goto` and `on_failure`. Default: the step name.
+    #[serde(default)]
+    pub id: Option<String>,
+    /// Run the step only if the condition holds; otherwise it is skipped.
+    #[serde(default)]
+    pub when: Option<StepCondition>,
+    /// What to do once all attempts have failed. Default: abort the workflow.
+    #[serde(default)]
+    pub on_failure: Option<FailureAction>,
+    /// Step to jump to after this step succeeds. Default: the next step.
+    #[serde(default)]
+    pub goto: Option<String>,
+    /// How many times this step may run within one workflow run, which bounds
+    /// loops created with `goto`/`on_failure`. Default: None (10)
+    #[serde(default)]
+    pub max_visits: Option<u32>,
+    /// Sub-steps run concurrently in the same worktree. The group succeeds
+    /// only when every child succeeds; `command`/`claude` are ignored when set.
+    #[serde(default)]
+    pub parallel: Vec<WorkflowStep>,
+    /// Push the worker branch and open a pull request on the configured forge.
+    #[serde(default)]
+    pub pr: Option<PullRequestStep>,
+    /// Bring the worker branch into its base branch.
+    #[serde(default)]
+    pub merge: Option<MergeStep>,
+    /// Stage every change in the worktree and commit it.
+    #[serde(default)]
+    pub commit: Option<CommitStep>,
+}
+
+/// Settings of a `pr` step. Title and body are templates with the same
+/// placeholders as step prompts.
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+pub struct PullRequestStep {
+    /// Default: the issue title, or the worker name for workers without an issue.
+    #[serde(default)]
+    pub title: Option<String>,
+    /// Default: a `Closes <issue>` line followed by the agent's final result.
+    #[serde(default)]
+    pub body: Option<String>,
+    #[serde(default)]
+    pub draft: bool,
+    /// Target branch. Default: `forge.base`.
+    #[serde(default)]
+    pub base: Option<String>,
+}
+
+/// Settings of a `merge` step.
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+pub struct MergeStep {
+    #[serde(default)]
+    pub strategy: MergeStrategy,
+    /// Branch to merge into. Default: the branch checked out in the repository.
+    #[serde(default)]
+    pub into: Option<String>,
+    /// Commit message template for the squash strategy.
+    /// Default: the issue title, or the worker name for workers without an issue.
+    #[serde(default)]
+    pub message: Option<String>,
+    /// Only check whether the merge would conflict; the base branch is left as is.
+    #[serde(default)]
+    pub dry_run: bool,
+    /// Hand conflicts to the step's agent and retry the merge once it has
+    /// resolved them. Default: false (conflicts fail the step)
+    #[serde(default)]
+    pub resolve_conflicts: bool,
+    /// Prompt for the conflict resolution. The conflicting files are appended.
+    #[serde(default)]
+    pub resolve_prompt: Option<String>,
+}
+
+/// Settings of a `commit` step.
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+pub struct CommitStep {
+    /// Commit message template. Default: the step name and issue title as
+    /// the subject and the latest agent result as the body.
+    #[serde(default)]
+    pub message: Option<String>,
+    /// Ask the step's agent to write the message from the staged diff. The
+    /// template above is used if the agent returns nothing.
+    #[serde(default)]
+    pub generate_message: bool,
+}
+
+/// How a worker branch is brought into its base branch.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum MergeStrategy {
+    /// Move the base branch to the worker branch; fails if the base has moved on.
+    #[default]
+    #[serde(alias = "ff")]
+    FastForward,
+    /// One commit on the base branch with all changes of the worker branch.
+    Squash,
+    /// Rebase the worker branch onto the base, then fast-forward.
+    Rebase,
+}
+
+impl MergeStrategy {
+    pub fn label(self) -> &'static str {
+        match self {
+            MergeStrategy::FastForward => "fast-forward",
+            MergeStrategy::Squash => "squash",
+            MergeStrategy::Rebase => "rebase",
+        }
+    }
+}
+
+/// Condition on the latest outcome of another step. All given criteria must match.
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+pub struct StepCondition {
+    /// Id (or name) of the step to inspect.
+    pub step: String,
+    #[serde(default)]
+    pub status: Option<StepOutcomeStatus>,
+    #[serde(default)]
+    pub exit_code: Option<i32>,
+    /// Regex matched against the step's stdout and agent result.
+    #[serde(default)]
+    pub output_matches: Option<String>,
+    /// Invert the whole condition.
+    #[serde(default)]
+    pub negate: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FailureAction {
+    Abort,
+    Continue,
+    Goto(String),
+}
+
+const DEFAULT_MAX_VISITS: u32 = 10;
+
+const DEFAULT_RETRY_BACKOFF_SECS: u64 = 5;
+
+impl WorkflowStep {
+    /// Identifier other steps use to refer to this one.
+    pub fn key(&self) -> &str {
+        self.id.as_deref().unwrap_or(&self.name)
+    }
+
+    pub fn max_visits(&self) -> u32 {
+        self.max_visits.unwrap_or(DEFAULT_MAX_VISITS)
+    }
+
+    /// Total number of attempts including the first run.
+    pub fn max_attempts(&self) -> u32 {
+        self.retries.saturating_add(1)
+    }
+
+    pub fn timeout_duration(&self) -> Option<Duration> {
+        self.timeout.map(Duration::from_secs)
+    }
+
+    /// Delay to wait after the given (1-based) failed attempt.
+    pub fn retry_delay(&self, failed_attempt: u32) -> Duration {
+        let base = self.retry_backoff.unwrap_or(DEFAULT_RETRY_BACKOFF_SECS);
+        let factor = 1u64 << failed_attempt.saturating_sub(1).min(16);
+        Duration::from_secs(base.saturating_mul(factor))
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+pub struct ClaudeStep {
+    pub prompt: String,
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub allowed_tools: Option<Vec<String>>,
+    #[serde(default)]
+    pub permission_mode: Option<String>,
+    #[serde(default)]
+    pub extra_args: Option<Vec<String>>,
+    /// Enable sandbox mode to restrict file system access to worktree.
+    /// Default: None (inherits from global config, which defaults to true)
+    #[serde(default)]
+    pub sandbox_mode: Option<bool>,
+    /// Commit the changes once the agent finished.
+    /// Default: None (inherits `auto_commit` from the global config)
+    #[serde(default)]
+    pub auto_commit: Option<bool>,
+}
+
+/// A CLI agent driven by a command template and regex-based output rules.
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+pub struct GenericAgentConfig {
+    pub name: String,
+    pub command: String,
+    /// Arguments; `{{prompt}}`, `{{workdir}}` and `{{model}}` are substituted.
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Arguments used instead of `args` when continuing a session.
+    /// `{{session_id}}` is substituted in addition to the placeholders above.
+    #[serde(default)]
+    pub continue_args: Option<Vec<String>>,
+    /// Regex whose first capture group extracts the session id from output.
+    #[serde(default)]
+    pub session_id_pattern: Option<String>,
+    /// Rules mapping output lines to log entries. Unmatched lines are logged as-is.
+    #[serde(default)]
+    pub output: Vec<OutputRule>,
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct OutputRule {
+    pub pattern: String,
+    pub kind: OutputKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputKind {
+    ToolUse,
+    Assistant,
+    Thinking,
+    Result,
+    Error,
+}
+
+impl Config {
+    /// Load the repository configuration layered over the user configuration
+    /// in `~/.config/gensui` (see `loader` for the file layout).
+    ///
+    /// Fails with a report listing every error; warnings are returned with the config.
+    pub fn discover(repo_root: &Path) -> Result<(Self, Vec<Diagnostic>)> {
+        let loaded = loader::discover(repo_root, user_config_dir().as_deref());
+        if loaded.diagnostics.iter().any(Diagnostic::is_error) {
+            bail!(
+                "invalid workflow configuration:\n{}",
+                validate::format_report(&loaded.diagnostics, repo_root)
+            );
+        }
+        Ok((loaded.config, loaded.diagnostics))
+    }
+
+    /// Every problem in the configuration of `repo_root`, for `gensui validate`.
+    pub fn validate(repo_root: &Path) -> Vec<Diagnostic> {
+        loader::discover(repo_root, user_config_dir().as_deref()).diagnostics
+    }
+
+    /// The configured default workflow, falling back to the first workflow and
+    /// then to the built-in one.
+    pub fn default_workflow(&self) -> Workflow {
+        self.default_workflow
+            .as_deref()
+            .and_then(|name| self.workflow_by_name(name))
+            .or_else(|| self.workflows.first())
+            .cloned()
+            .unwrap_or_else(|| Config::default().workflows.remove(0))
+    }
+
+    pub fn workflow_by_name(&self, name: &str) -> Option<&Workflow> {
+        self.workflows.iter().find(|wf| wf.name == name)
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            workflows: vec![Workflow {
+                name: "default".to_string(),
+                description: Some("標準的な分析→実装→テストの3ステップ".to_string()),
+                steps: vec![
+                    WorkflowStep {
+                        name: "分析".to_string(),
+                        command: Some("echo 'Analyzing issue context'".to_string()),
+                        description: Some("Issue内容の分析を実施".to_string()),
+                        ..Default::default()
+                    },
+                    WorkflowStep {
+                        name: "実装".to_string(),
+                        command: Some("echo 'Implementing changes'".to_string()),
+                        description: Some("コード変更を適用".to_string()),
+                        ..Default::default()
+                    },
+                    WorkflowStep {
+                        name: "テスト".to_string(),
+                        command: Some("echo 'Running tests'".to_string()),
+                        description: Some("テストスイートを実行".to_string()),
+                        ..Default::default()
+                    },
+                ],
+                extends: None,
+            }],
+            default_workflow: Some("default".to_string()),
+            default_sandbox_mode: default_sandbox_mode(),
+            agents: Vec::new(),
+            max_concurrent_workers: None,
+            max_concurrent_workers_per_agent: BTreeMap::new(),
+            issues: IssueTrackerConfig::default(),
+            forge: ForgeConfig::default(),
+            auto_commit: false,
+            branch_template: None,
+        }
+    }
+}
+
+impl Workflow {
+    pub fn steps(&self) -> &[WorkflowStep] {
+        &self.steps
+    }
+
+    /// Index of the step with the given id, falling back to a name match.
+    pub fn step_index(&self, key: &str) -> Option<usize> {
+        self.steps
+            .iter()
+            .position(|step| step.key() == key)
+            .or_else(|| self.steps.iter().position(|step| step.name == key))
+    }
+}
+
+impl StepCondition {
+    /// Evaluate against the referenced step's latest outcome. A step that has
+    /// not run yet matches nothing.
+    pub fn matches(&self, outcome: Option<&StepOutcome>) -> Result<bool> {
+        let matched = match outcome {
+            None => false,
+            Some(outcome) => {
+                let output_ok = match &self.output_matches {
+                    Some(pattern) => {
+                        let re = Regex::new(pattern)
+                            .with_context(|| format!("invalid output_matches pattern '{pattern}'"))?;
+                        re.is_match(&outcome.stdout)
+                            || outcome.result.as_deref().is_some_and(|r| re.is_match(r))
+                    }
+                    None => true,
+                };
+                self.status.is_none_or(|status| status == outcome.status)
+                    && self.exit_code.is_none_or(|code| Some(code) == outcome.exit_code)
+                    && output_ok
+            }
+        };
+        Ok(matched != self.negate)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_sandbox_mode_is_true() {
+        assert_eq!(default_sandbox_mode(), true);
+    }
+
+    #[test]
+    fn test_config_default_has_sandbox_enabled() {
+        let config = Config::default();
+        assert_eq!(config.default_sandbox_mode, true);
+    }
+
+    #[test]
+    fn test_config_deserialize_with_sandbox_mode() {
+        let json = r#"{
+            "default_workflow": "test",
+            "default_sandbox_mode": false,
+            "workflows": [
+                {
+                    "name": "test",
+                    "steps": []
+                }
+            ]
+        }"#;
+
+        let config: Config = serde_json::from_str(json).unwrap();
+        assert_eq!(config.default_sandbox_mode, false);
+    }
+
+    #[test]
+    fn test_config_deserialize_without_sandbox_mode_defaults_to_true() {
+        let json = r#"{
+            "default_workflow": "test",
+            "workflows": [
+                {
+                    "name": "test",
+                    "steps": []
+                }
+            ]
+        }"#;
+
+        let config: Config = serde_json::from_str(json).unwrap();
+        assert_eq!(config.default_sandbox_mode, true);
+    }
+
+    #[test]
+    fn test_claude_step_sandbox_mode_none() {
+        let json = r#"{
+            "prompt": "test prompt"
+        }"#;
+
+        let step: ClaudeStep = serde_json::from_str(json).unwrap();
+        assert_eq!(step.sandbox_mode, None);
+    }
+
+    #[test]
+    fn test_claude_step_sandbox_mode_true() {
+        let json = r#"{
+            "prompt": "test prompt",
+            "sandbox_mode": true
+        }"#;
+
+        let step: ClaudeStep = serde_json::from_str(json).unwrap();
+        assert_eq!(step.sandbox_mode, Some(true));
+    }
+
+    #[test]
+    fn test_claude_step_sandbox_mode_false() {
+        let json = r#"{
+            "prompt": "test prompt",
+            "sandbox_mode": false
+        }"#;
+
+        let step: ClaudeStep = serde_json::from_str(json).unwrap();
+        assert_eq!(step.sandbox_mode, Some(false));
+    }
+
+    #[test]
+    fn test_sandbox_mode_inheritance() {
+        // Test the intended behavior: step-level overrides global default
+        let global_default = true;
+
+        // Case 1: Step has no sandbox_mode, should inherit global default
+        let step_none: Option<bool> = None;
+        assert_eq!(step_none.unwrap_or(global_default), true);
+
+        // Case 2: Step explicitly sets sandbox_mode to false
+        let step_false: Option<bool> = Some(false);
+        assert_eq!(step_false.unwrap_or(global_default), false);
+
+        // Case 3: Step explicitly sets sandbox_mode to true
+        let step_true: Option<bool> = Some(true);
+        assert_eq!(step_true.unwrap_or(global_default), true);
+    }
+
+    #[test]
+    fn test_workflow_step_retry_policy() {
+        let json = r#"{
+            "name": "flaky",
+            "command": "cargo test",
+            "timeout": 600,
+            "retries": 2,
+            "retry_backoff": 3
+        }"#;
+
+        let step: WorkflowStep = serde_json::from_str(json).unwrap();
+        assert_eq!(step.max_attempts(), 3);
+        assert_eq!(step.timeout_duration(), Some(Duration::from_secs(600)));
+        assert_eq!(step.retry_delay(1), Duration::from_secs(3));
+        assert_eq!(step.retry_delay(2), Duration::from_secs(6));
+    }
+
+    #[test]
+    fn test_workflow_step_retry_policy_defaults() {
+        let step: WorkflowStep = serde_json::from_str(r#"{ "name": "once" }"#).unwrap();
+        assert_eq!(step.max_attempts(), 1);
+        assert_eq!(step.timeout_duration(), None);
+        assert_eq!(
+            step.retry_delay(1),
+            Duration::from_secs(DEFAULT_RETRY_BACKOFF_SECS)
+        );
+    }
+
+    #[test]
+    fn test_workflow_step_branching_fields() {
+        let json = r#"[
+            { "name": "test", "command": "cargo test", "on_failure": { "goto": "fix" } },
+            { "name": "done", "command": "true", "on_failure": "continue" },
+            { "id": "fix", "name": "Fix tests", "goto": "test", "max_visits": 3 }
+        ]"#;
+
+        let steps: Vec<WorkflowStep> = serde_json::from_str(json).unwrap();
+        assert_eq!(steps[0].on_failure, Some(FailureAction::Goto("fix".to_string())));
+        assert_eq!(steps[1].on_failure, Some(FailureAction::Continue));
+        assert_eq!(steps[2].key(), "fix");
+        assert_eq!(steps[2].max_visits(), 3);
+        assert_eq!(steps[0].max_visits(), DEFAULT_MAX_VISITS);
+
+        let workflow = Workflow {
+            name: "loop".to_string(),
+            description: None,
+            steps,
+            extends: None,
+        };
+        assert_eq!(workflow.step_index("fix"), Some(2));
+        assert_eq!(workflow.step_index("Fix tests"), Some(2));
+        assert_eq!(workflow.step_index("missing"), None);
+    }
+
+    #[test]
+    fn test_merge_step_strategies() {
+        let json = r#"[
+            { "name": "ff", "merge": { "strategy": "ff" } },
+            { "name": "squash", "merge": { "strategy": "squash", "into": "develop", "resolve_conflicts": true } },
+            { "name": "check", "merge": { "dry_run": true } }
+        ]"#;
+
+        let steps: Vec<WorkflowStep> = serde_json::from_str(json).unwrap();
+        let merges: Vec<&MergeStep> = steps.iter().filter_map(|step| step.merge.as_ref()).collect();
+        assert_eq!(merges[0].strategy, MergeStrategy::FastForward);
+        assert_eq!(merges[1].strategy, MergeStrategy::Squash);
+        assert_eq!(merges[1].into.as_deref(), Some("develop"));
+        assert!(merges[1].resolve_conflicts);
+        assert_eq!(merges[2].strategy, MergeStrategy::FastForward);
+        assert!(merges[2].dry_run);
+        assert!(serde_json::from_str::<MergeStrategy>(r#""octopus""#).is_err());
+    }
+
+    #[test]
+    fn test_step_condition_matches() {
+        let failed = StepOutcome {
+            status: StepOutcomeStatus::Failed,
+            exit_code: Some(101),
+            stdout: "test result: FAILED. 1 passed; 2 failed".to_string(),
+            result: None,
+            commit: None,
+        };
+
+        let condition: StepCondition =
+            serde_json::from_str(r#"{ "step": "test", "status": "failed" }"#).unwrap();
+        assert!(condition.matches(Some(&failed)).unwrap());
+        assert!(!condition.matches(None).unwrap());
+
+        let condition = StepCondition {
+            step: "test".to_string(),
+            exit_code: Some(101),
+            output_matches: Some(r"\d+ failed".to_string()),
+            ..Default::default()
+        };
+        assert!(condition.matches(Some(&failed)).unwrap());
+
+        let negated = StepCondition {
+            step: "test".to_string(),
+            status: Some(StepOutcomeStatus::Success),
+            negate: true,
+            ..Default::default()
+        };
+        assert!(negated.matches(Some(&failed)).unwrap());
+        assert!(negated.matches(None).unwrap());
+
+        let invalid = StepCondition {
+            step: "test".to_string(),
+            output_matches: Some("(".to_string()),
+            ..Default::default()
+        };
+        assert!(invalid.matches(Some(&failed)).is_err());
+    }
+}
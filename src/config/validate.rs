@@ -0,0 +1,827 @@
+//! ワークフロー設定の検証（ファイル・JSON ポインタ・行番号付きの診断）
+
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+use regex::Regex;
+use serde::de::{Deserialize, DeserializeOwned, Deserializer};
+
+use crate::agent::BUILTIN_AGENTS;
+use crate::worker::{BRANCH_PLACEHOLDERS, BranchContext, check_branch_name, render_branch_name};
+
+use super::loader::Format;
+use super::{
+    ForgeConfig, GenericAgentConfig, IssueProviderKind, IssueTrackerConfig, Workflow, WorkflowStep,
+};
+
+/// Permission modes understood by the agents.
+const KNOWN_PERMISSION_MODES: [&str; 4] = ["default", "plan", "acceptEdits", "bypassPermissions"];
+
+/// Placeholders rendered by `worker::template`, besides `steps.<id>.<field>`.
+const KNOWN_PLACEHOLDERS: [&str; 11] = [
+    "issue",
+    "issue.title",
+    "issue.body",
+    "issue.labels",
+    "issue.acceptance_criteria",
+    "issue.comments",
+    "worker",
+    "branch",
+    "worktree",
+    "git.diff",
+    "session.result",
+];
+
+const STEP_PLACEHOLDER_FIELDS: [&str; 4] = ["result", "stdout", "exit_code", "commit"];
+
+static PLACEHOLDER: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\{\{\s*([^{}]*?)\s*\}\}").expect("placeholder pattern must compile"));
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// A single problem found in the configuration.
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub file: Option<PathBuf>,
+    pub line: Option<usize>,
+    /// JSON pointer to the offending value within `file`.
+    pub pointer: String,
+    pub message: String,
+}
+
+impl Diagnostic {
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+
+    /// Render with `file` shown relative to `base` when possible.
+    pub fn display_relative<'a>(&'a self, base: &'a Path) -> impl fmt::Display + 'a {
+        DisplayRelative {
+            diagnostic: self,
+            base,
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.display_relative(Path::new("")).fmt(f)
+    }
+}
+
+struct DisplayRelative<'a> {
+    diagnostic: &'a Diagnostic,
+    base: &'a Path,
+}
+
+impl fmt::Display for DisplayRelative<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let d = self.diagnostic;
+        let label = match d.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        write!(f, "{label}: ")?;
+        if let Some(file) = &d.file {
+            let shown = file.strip_prefix(self.base).unwrap_or(file);
+            write!(f, "{}", shown.display())?;
+            if let Some(line) = d.line {
+                write!(f, ":{line}")?;
+            }
+            write!(f, ": ")?;
+        }
+        if !d.pointer.is_empty() {
+            write!(f, "{}: ", d.pointer)?;
+        }
+        write!(f, "{}", d.message)
+    }
+}
+
+/// One diagnostic per line, with paths relative to `base`.
+pub fn format_report(diagnostics: &[Diagnostic], base: &Path) -> String {
+    diagnostics
+        .iter()
+        .map(|d| d.display_relative(base).to_string())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// A configuration file as read from disk.
+pub(super) struct SourceFile {
+    pub path: PathBuf,
+    pub format: Format,
+    pub text: String,
+    /// Generic view of the document used to locate pointers.
+    pub value: Option<serde_json::Value>,
+}
+
+/// Location of a value: a source file index and a JSON pointer inside it.
+#[derive(Debug, Clone, Default)]
+pub(super) struct Origin {
+    pub source: usize,
+    pub pointer: String,
+}
+
+impl Origin {
+    pub fn child(&self, suffix: impl fmt::Display) -> Origin {
+        Origin {
+            source: self.source,
+            pointer: format!("{}/{}", self.pointer, suffix),
+        }
+    }
+}
+
+/// Collects diagnostics against the files they refer to.
+#[derive(Default)]
+pub(super) struct Reporter {
+    pub sources: Vec<SourceFile>,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl Reporter {
+    pub fn add_source(&mut self, source: SourceFile) -> usize {
+        self.sources.push(source);
+        self.sources.len() - 1
+    }
+
+    pub fn report(&mut self, severity: Severity, origin: &Origin, message: impl Into<String>) {
+        let source = self.sources.get(origin.source);
+        let line = source.and_then(|source| locate(source, &origin.pointer));
+        self.diagnostics.push(Diagnostic {
+            severity,
+            file: source.map(|source| source.path.clone()),
+            line,
+            pointer: origin.pointer.clone(),
+            message: message.into(),
+        });
+    }
+
+    pub fn error(&mut self, origin: &Origin, message: impl Into<String>) {
+        self.report(Severity::Error, origin, message);
+    }
+
+    pub fn warning(&mut self, origin: &Origin, message: impl Into<String>) {
+        self.report(Severity::Warning, origin, message);
+    }
+
+    /// Report a problem that is not tied to a particular file.
+    pub fn file_error(&mut self, path: &Path, line: Option<usize>, message: impl Into<String>) {
+        self.diagnostics.push(Diagnostic {
+            severity: Severity::Error,
+            file: Some(path.to_path_buf()),
+            line,
+            pointer: String::new(),
+            message: message.into(),
+        });
+    }
+
+    /// Parse `source` into `T`, reporting syntax errors, type errors and
+    /// unknown fields. Returns None if the file could not be deserialized.
+    pub fn parse<T: DeserializeOwned>(&mut self, source_idx: usize) -> Option<T> {
+        let source = &self.sources[source_idx];
+        let mut unknown = Vec::new();
+        let result = parse_tracked::<T>(source.format, &source.text, &mut unknown);
+
+        for pointer in unknown {
+            let field = pointer.rsplit('/').next().unwrap_or_default().to_string();
+            let origin = Origin {
+                source: source_idx,
+                pointer,
+            };
+            self.error(&origin, format!("unknown field '{field}'"));
+        }
+
+        match result {
+            Ok(value) => Some(value),
+            Err(failure) => {
+                let source = &self.sources[source_idx];
+                let line = failure.line.or_else(|| {
+                    failure
+                        .pointer
+                        .as_deref()
+                        .and_then(|pointer| locate(source, pointer))
+                });
+                self.diagnostics.push(Diagnostic {
+                    severity: Severity::Error,
+                    file: Some(source.path.clone()),
+                    line,
+                    pointer: failure.pointer.unwrap_or_default(),
+                    message: failure.message,
+                });
+                None
+            }
+        }
+    }
+}
+
+struct ParseFailure {
+    message: String,
+    line: Option<usize>,
+    pointer: Option<String>,
+}
+
+fn parse_tracked<T: DeserializeOwned>(
+    format: Format,
+    text: &str,
+    unknown: &mut Vec<String>,
+) -> Result<T, ParseFailure> {
+    match format {
+        Format::Json => {
+            let mut de = serde_json::Deserializer::from_str(text);
+            let value = deserialize_tracked(&mut de, unknown)
+                .map_err(|err| path_failure(&err, err.inner().to_string(), Some(err.inner().line())))?;
+            de.end().map_err(|err| ParseFailure {
+                message: err.to_string(),
+                line: Some(err.line()),
+                pointer: None,
+            })?;
+            Ok(value)
+        }
+        Format::Yaml => {
+            let de = serde_yaml::Deserializer::from_str(text);
+            deserialize_tracked(de, unknown).map_err(|err| {
+                let line = err.inner().location().map(|location| location.line());
+                path_failure(&err, err.inner().to_string(), line)
+            })
+        }
+        Format::Toml => {
+            let de = toml::Deserializer::new(text);
+            deserialize_tracked(de, unknown).map_err(|err| {
+                let line = err
+                    .inner()
+                    .span()
+                    .map(|span| line_at(text, span.start));
+                path_failure(&err, err.inner().message().to_string(), line)
+            })
+        }
+    }
+}
+
+fn deserialize_tracked<'de, D, T>(
+    de: D,
+    unknown: &mut Vec<String>,
+) -> Result<T, serde_path_to_error::Error<D::Error>>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    let mut on_ignored = |path: serde_ignored::Path| unknown.push(ignored_pointer(&path));
+    let de = serde_ignored::Deserializer::new(de, &mut on_ignored);
+    serde_path_to_error::deserialize(de)
+}
+
+fn path_failure<E>(
+    err: &serde_path_to_error::Error<E>,
+    message: String,
+    line: Option<usize>,
+) -> ParseFailure {
+    let mut pointer = String::new();
+    for segment in err.path().iter() {
+        match segment {
+            serde_path_to_error::Segment::Seq { index } => pointer.push_str(&format!("/{index}")),
+            serde_path_to_error::Segment::Map { key } => {
+                pointer.push('/');
+                pointer.push_str(&escape_pointer(key));
+            }
+            serde_path_to_error::Segment::Enum { .. } | serde_path_to_error::Segment::Unknown => {}
+        }
+    }
+    ParseFailure {
+        message,
+        line: line.filter(|&line| line > 0),
+        pointer: (!pointer.is_empty()).then_some(pointer),
+    }
+}
+
+fn ignored_pointer(path: &serde_ignored::Path) -> String {
+    match path {
+        serde_ignored::Path::Root => String::new(),
+        serde_ignored::Path::Seq { parent, index } => format!("{}/{}", ignored_pointer(parent), index),
+        serde_ignored::Path::Map { parent, key } => {
+            format!("{}/{}", ignored_pointer(parent), escape_pointer(key))
+        }
+        serde_ignored::Path::Some { parent }
+        | serde_ignored::Path::NewtypeStruct { parent }
+        | serde_ignored::Path::NewtypeVariant { parent } => ignored_pointer(parent),
+    }
+}
+
+fn escape_pointer(segment: &str) -> String {
+    segment.replace('~', "~0").replace('/', "~1")
+}
+
+fn line_at(text: &str, offset: usize) -> usize {
+    text[..offset.min(text.len())].matches('\n').count() + 1
+}
+
+/// Best-effort line lookup for a JSON pointer.
+///
+/// Walks the pointer through the parsed document and searches the text for
+/// each object key in order. Array elements are located through their `name`
+/// (or `id`) value, so the line is only as precise as those are unique.
+fn locate(source: &SourceFile, pointer: &str) -> Option<usize> {
+    let mut node = source.value.as_ref()?;
+    let text = source.text.as_str();
+    let mut pos = 0;
+    let mut found = false;
+
+    for segment in pointer.split('/').skip(1) {
+        let segment = segment.replace("~1", "/").replace("~0", "~");
+        match node {
+            serde_json::Value::Object(map) => {
+                let Some(offset) = find_key(text, pos, source.format, &segment) else {
+                    break;
+                };
+                pos = offset;
+                found = true;
+                match map.get(&segment) {
+                    Some(child) => node = child,
+                    None => break,
+                }
+            }
+            serde_json::Value::Array(items) => {
+                let Some(item) = segment.parse::<usize>().ok().and_then(|idx| items.get(idx)) else {
+                    break;
+                };
+                let label = ["name", "id", "include"]
+                    .iter()
+                    .find_map(|key| item.get(*key).and_then(|v| v.as_str()));
+                if let Some(label) = label.filter(|label| !label.is_empty())
+                    && let Some(offset) = text[pos..].find(label)
+                {
+                    pos += offset;
+                    found = true;
+                }
+                node = item;
+            }
+            _ => break,
+        }
+    }
+
+    found.then(|| line_at(text, pos))
+}
+
+fn find_key(text: &str, from: usize, format: Format, key: &str) -> Option<usize> {
+    let key = regex::escape(key);
+    let pattern = match format {
+        Format::Json => format!(r#""{key}"\s*:"#),
+        Format::Yaml => format!(r#"(?m)(?:^|[\s{{,])["']?{key}["']?\s*:"#),
+        Format::Toml => format!(r#"(?m)(?:^\s*|[{{,]\s*)["']?{key}["']?\s*=|\[\[?(?:[\w.-]+\.)?{key}\]\]?"#),
+    };
+    let re = Regex::new(&pattern).ok()?;
+    re.find_at(text, from).map(|m| m.start())
+}
+
+/// A workflow after `extends`/`include` resolution, with the origin of each step.
+pub(super) struct ResolvedWorkflow {
+    pub workflow: Workflow,
+    pub origin: Origin,
+    pub step_origins: Vec<Origin>,
+}
+
+/// Semantic checks on the merged configuration.
+pub(super) fn check_config(
+    reporter: &mut Reporter,
+    workflows: &[ResolvedWorkflow],
+    default_workflow: Option<&(String, Origin)>,
+    agents: &[(GenericAgentConfig, Origin)],
+) {
+    let mut known_agents: HashSet<&str> = BUILTIN_AGENTS.into_iter().collect();
+    for (agent, origin) in agents {
+        known_agents.insert(agent.name.as_str());
+        check_agent(reporter, agent, origin);
+    }
+
+    if let Some((name, origin)) = default_workflow
+        && !workflows.iter().any(|wf| &wf.workflow.name == name)
+    {
+        reporter.error(origin, format!("default_workflow '{name}' does not exist"));
+    }
+
+    for resolved in workflows {
+        let workflow = &resolved.workflow;
+        if workflow.name.is_empty() {
+            reporter.error(&resolved.origin, "workflow has no name");
+        }
+        if workflow.steps().is_empty() {
+            reporter.warning(&resolved.origin, format!("workflow '{}' has no steps", workflow.name));
+        }
+
+        let keys = step_keys(workflow.steps());
+        let mut seen = HashSet::new();
+        for (step, origin) in workflow.steps().iter().zip(&resolved.step_origins) {
+            if !seen.insert(step.key()) {
+                reporter.warning(
+                    origin,
+                    format!("duplicate step id '{}'; jumps go to the first one", step.key()),
+                );
+            }
+            check_step(reporter, step, origin, &keys, &known_agents);
+        }
+    }
+}
+
+/// Checks on the worker concurrency limits.
+pub(super) fn check_concurrency(
+    reporter: &mut Reporter,
+    global: Option<&(usize, Origin)>,
+    per_agent: &BTreeMap<String, (usize, Origin)>,
+    agents: &[(GenericAgentConfig, Origin)],
+) {
+    if let Some((0, origin)) = global {
+        reporter.error(origin, "max_concurrent_workers must be at least 1");
+    }
+    for (agent, (limit, origin)) in per_agent {
+        if *limit == 0 {
+            reporter.error(origin, format!("limit for agent '{agent}' must be at least 1"));
+        }
+        let known = BUILTIN_AGENTS.contains(&agent.as_str())
+            || agents.iter().any(|(config, _)| &config.name == agent);
+        if !known {
+            reporter.warning(origin, format!("limit for unknown agent '{agent}'"));
+        }
+    }
+}
+
+/// Checks on the issue tracker section.
+pub(super) fn check_issues(reporter: &mut Reporter, issues: &IssueTrackerConfig, origin: &Origin) {
+    let remote = issues.provider != IssueProviderKind::Local;
+    match &issues.repo {
+        None if remote => reporter.error(
+            &origin.child("repo"),
+            "repo is required for the github and gitlab providers",
+        ),
+        Some(repo) if !is_repo_path(repo) => {
+            reporter.error(&origin.child("repo"), format!("invalid repository path '{repo}'"));
+        }
+        Some(_) if !remote => {
+            reporter.warning(&origin.child("repo"), "repo is ignored by the local provider");
+        }
+        _ => {}
+    }
+    check_base_url(reporter, issues.base_url.as_deref(), origin);
+}
+
+/// Checks on the forge section, with `issues` supplying inherited settings.
+pub(super) fn check_forge(
+    reporter: &mut Reporter,
+    forge: &ForgeConfig,
+    origin: &Origin,
+    issues: &IssueTrackerConfig,
+) {
+    match forge.effective(issues) {
+        None => reporter.error(
+            &origin.child("provider"),
+            "provider is required unless issues uses github or gitlab",
+        ),
+        Some(effective) if effective.repo.is_none() => reporter.error(
+            &origin.child("repo"),
+            "repo is required (or set in the issues section for the same provider)",
+        ),
+        Some(_) => {}
+    }
+    if let Some(repo) = &forge.repo
+        && !is_repo_path(repo)
+    {
+        reporter.error(&origin.child("repo"), format!("invalid repository path '{repo}'"));
+    }
+    check_base_url(reporter, forge.base_url.as_deref(), origin);
+    for (field, value) in [("remote", &forge.remote), ("base", &forge.base)] {
+        if value.as_deref().is_some_and(|value| value.trim().is_empty()) {
+            reporter.error(&origin.child(field), format!("{field} must not be empty"));
+        }
+    }
+}
+
+/// Checks on the template of new worker branch names.
+pub(super) fn check_branch_template(reporter: &mut Reporter, template: &str, origin: &Origin) {
+    for caps in PLACEHOLDER.captures_iter(template) {
+        if !BRANCH_PLACEHOLDERS.contains(&&caps[1]) {
+            reporter.error(origin, format!("unknown placeholder '{}' in branch_template", &caps[0]));
+        }
+    }
+    let sample = render_branch_name(
+        template,
+        &BranchContext {
+            worker: "worker",
+            id: 1,
+            issue: Some("1"),
+            timestamp: 0,
+        },
+    );
+    if let Err(reason) = check_branch_name(&sample) {
+        reporter.error(origin, format!("branch_template '{template}' is invalid: {reason}"));
+    }
+}
+
+fn is_repo_path(repo: &str) -> bool {
+    !repo
+        .split('/')
+        .any(|part| part.is_empty() || part == "." || part == "..")
+}
+
+fn check_base_url(reporter: &mut Reporter, base_url: Option<&str>, origin: &Origin) {
+    if let Some(url) = base_url
+        && !(url.starts_with("http://") || url.starts_with("https://"))
+    {
+        reporter.error(
+            &origin.child("base_url"),
+            format!("base_url '{url}' must start with http:// or https://"),
+        );
+    }
+}
+
+fn step_keys(steps: &[WorkflowStep]) -> HashSet<String> {
+    let mut keys = HashSet::new();
+    for step in steps {
+        keys.insert(step.key().to_string());
+        keys.insert(step.name.clone());
+        keys.extend(step_keys(&step.parallel));
+    }
+    keys
+}
+
+fn check_step(
+    reporter: &mut Reporter,
+    step: &WorkflowStep,
+    origin: &Origin,
+    keys: &HashSet<String>,
+    known_agents: &HashSet<&str>,
+) {
+    if step.name.is_empty() {
+        reporter.error(origin, "step has no name");
+    }
+    if step.command.is_some() && step.claude.is_some() {
+        reporter.error(origin, format!("step '{}' sets both 'command' and 'claude'", step.name));
+    }
+    if step.pr.is_some() && (step.command.is_some() || step.claude.is_some()) {
+        reporter.error(
+            origin,
+            format!("step '{}' sets 'pr' together with 'command' or 'claude'", step.name),
+        );
+    }
+    if step.merge.is_some()
+        && (step.command.is_some() || step.claude.is_some() || step.pr.is_some())
+    {
+        reporter.error(
+            origin,
+            format!("step '{}' sets 'merge' together with 'command', 'claude' or 'pr'", step.name),
+        );
+    }
+    if step.commit.is_some()
+        && (step.command.is_some()
+            || step.claude.is_some()
+            || step.pr.is_some()
+            || step.merge.is_some())
+    {
+        reporter.error(
+            origin,
+            format!(
+                "step '{}' sets 'commit' together with 'command', 'claude', 'pr' or 'merge'",
+                step.name
+            ),
+        );
+    }
+    if !step.parallel.is_empty()
+        && (step.command.is_some()
+            || step.claude.is_some()
+            || step.pr.is_some()
+            || step.merge.is_some()
+            || step.commit.is_some())
+    {
+        reporter.error(
+            origin,
+            format!(
+                "parallel step '{}' must not set 'command', 'claude', 'pr', 'merge' or 'commit'",
+                step.name
+            ),
+        );
+    }
+    if step.timeout == Some(0) {
+        reporter.warning(&origin.child("timeout"), "timeout of 0 seconds fails every attempt");
+    }
+
+    if let Some(agent) = &step.agent
+        && !known_agents.contains(agent.as_str())
+    {
+        reporter.error(&origin.child("agent"), format!("unknown agent '{agent}'"));
+    }
+
+    let check_target = |reporter: &mut Reporter, target: &str, origin: Origin| {
+        if !keys.contains(target) {
+            reporter.error(&origin, format!("unknown step '{target}'"));
+        }
+    };
+    if let Some(target) = &step.goto {
+        check_target(reporter, target, origin.child("goto"));
+    }
+    if let Some(super::FailureAction::Goto(target)) = &step.on_failure {
+        check_target(reporter, target, origin.child("on_failure/goto"));
+    }
+    if let Some(condition) = &step.when {
+        check_target(reporter, &condition.step, origin.child("when/step"));
+        if let Some(pattern) = &condition.output_matches
+            && let Err(err) = Regex::new(pattern)
+        {
+            reporter.error(&origin.child("when/output_matches"), format!("invalid regex: {err}"));
+        }
+    }
+
+    if let Some(claude) = &step.claude {
+        if let Some(mode) = &claude.permission_mode
+            && !KNOWN_PERMISSION_MODES.contains(&mode.as_str())
+        {
+            reporter.warning(
+                &origin.child("claude/permission_mode"),
+                format!(
+                    "unknown permission_mode '{mode}' (expected one of {})",
+                    KNOWN_PERMISSION_MODES.join(", ")
+                ),
+            );
+        }
+        for placeholder in unknown_placeholders(&claude.prompt, keys) {
+            reporter.warning(
+                &origin.child("claude/prompt"),
+                format!("unknown placeholder '{{{{{placeholder}}}}}'"),
+            );
+        }
+    }
+
+    if let Some(pr) = &step.pr {
+        for (field, template) in [("title", &pr.title), ("body", &pr.body)] {
+            for placeholder in unknown_placeholders(template.as_deref().unwrap_or_default(), keys) {
+                reporter.warning(
+                    &origin.child(format!("pr/{field}")),
+                    format!("unknown placeholder '{{{{{placeholder}}}}}'"),
+                );
+            }
+        }
+    }
+
+    if let Some(merge) = &step.merge {
+        if merge.into.as_deref().is_some_and(|into| into.trim().is_empty()) {
+            reporter.error(&origin.child("merge/into"), "must not be empty");
+        }
+        if merge.dry_run && merge.resolve_conflicts {
+            reporter.warning(
+                &origin.child("merge/resolve_conflicts"),
+                "has no effect together with dry_run",
+            );
+        }
+        for (field, template) in [("message", &merge.message), ("resolve_prompt", &merge.resolve_prompt)] {
+            for placeholder in unknown_placeholders(template.as_deref().unwrap_or_default(), keys) {
+                reporter.warning(
+                    &origin.child(format!("merge/{field}")),
+                    format!("unknown placeholder '{{{{{placeholder}}}}}'"),
+                );
+            }
+        }
+    }
+
+    if let Some(commit) = &step.commit {
+        for placeholder in unknown_placeholders(commit.message.as_deref().unwrap_or_default(), keys) {
+            reporter.warning(
+                &origin.child("commit/message"),
+                format!("unknown placeholder '{{{{{placeholder}}}}}'"),
+            );
+        }
+    }
+
+    for (idx, child) in step.parallel.iter().enumerate() {
+        check_step(reporter, child, &origin.child(format!("parallel/{idx}")), keys, known_agents);
+    }
+}
+
+fn unknown_placeholders<'a>(prompt: &'a str, keys: &HashSet<String>) -> Vec<&'a str> {
+    PLACEHOLDER
+        .captures_iter(prompt)
+        .filter_map(|caps| caps.get(1))
+        .map(|m| m.as_str())
+        .filter(|name| !is_known_placeholder(name, keys))
+        .collect()
+}
+
+fn is_known_placeholder(name: &str, keys: &HashSet<String>) -> bool {
+    if KNOWN_PLACEHOLDERS.contains(&name) {
+        return true;
+    }
+    let Some(rest) = name.strip_prefix("steps.") else {
+        return false;
+    };
+    rest.rsplit_once('.').is_some_and(|(step, field)| {
+        STEP_PLACEHOLDER_FIELDS.contains(&field) && keys.contains(step.trim())
+    })
+}
+
+fn check_agent(reporter: &mut Reporter, agent: &GenericAgentConfig, origin: &Origin) {
+    if agent.name.is_empty() {
+        reporter.error(origin, "agent has no name");
+    }
+    if BUILTIN_AGENTS.contains(&agent.name.as_str()) {
+        reporter.warning(origin, format!("agent '{}' replaces the built-in agent", agent.name));
+    }
+    if let Some(pattern) = &agent.session_id_pattern
+        && let Err(err) = Regex::new(pattern)
+    {
+        reporter.error(&origin.child("session_id_pattern"), format!("invalid regex: {err}"));
+    }
+    for (idx, rule) in agent.output.iter().enumerate() {
+        if let Err(err) = Regex::new(&rule.pattern) {
+            reporter.error(
+                &origin.child(format!("output/{idx}/pattern")),
+                format!("invalid regex: {err}"),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::loader::ConfigLayer;
+
+    fn source(format: Format, text: &str) -> SourceFile {
+        SourceFile {
+            path: PathBuf::from("workflows.test"),
+            format,
+            text: text.to_string(),
+            value: format.parse(text).ok(),
+        }
+    }
+
+    #[test]
+    fn test_unknown_fields_are_reported_with_line() {
+        let mut reporter = Reporter::default();
+        let idx = reporter.add_source(source(
+            Format::Yaml,
+            "workflows:\n  - name: ci\n    steps:\n      - name: test\n        comand: cargo test\n",
+        ));
+
+        let parsed: Option<ConfigLayer> = reporter.parse(idx);
+        assert!(parsed.is_some());
+        assert_eq!(reporter.diagnostics.len(), 1);
+        let diagnostic = &reporter.diagnostics[0];
+        assert!(diagnostic.is_error());
+        assert_eq!(diagnostic.pointer, "/workflows/0/steps/0/comand");
+        assert_eq!(diagnostic.line, Some(5));
+        assert_eq!(
+            diagnostic.to_string(),
+            "error: workflows.test:5: /workflows/0/steps/0/comand: unknown field 'comand'"
+        );
+    }
+
+    #[test]
+    fn test_type_errors_carry_pointer() {
+        for (format, text, line) in [
+            (Format::Json, "{\n  \"workflows\": [\n    { \"name\": \"ci\", \"steps\": [{ \"name\": \"t\", \"retries\": \"x\" }] }\n  ]\n}", 3),
+            (Format::Toml, "[[workflows]]\nname = \"ci\"\n[[workflows.steps]]\nname = \"t\"\nretries = \"x\"\n", 5),
+            (Format::Yaml, "workflows:\n  - name: ci\n    steps:\n      - name: t\n        retries: x\n", 5),
+        ] {
+            let mut reporter = Reporter::default();
+            let idx = reporter.add_source(source(format, text));
+            let parsed: Option<ConfigLayer> = reporter.parse(idx);
+            assert!(parsed.is_none());
+            let diagnostic = &reporter.diagnostics[0];
+            assert_eq!(diagnostic.pointer, "/workflows/0/steps/0/retries", "{format:?}");
+            assert_eq!(diagnostic.line, Some(line), "{format:?}");
+        }
+    }
+
+    #[test]
+    fn test_locate_step_by_name() {
+        let source = source(
+            Format::Json,
+            r#"{
+  "workflows": [
+    { "name": "a", "steps": [{ "name": "x" }] },
+    {
+      "name": "b",
+      "steps": [
+        { "name": "x" },
+        { "name": "y", "goto": "z" }
+      ]
+    }
+  ]
+}"#,
+        );
+        assert_eq!(locate(&source, "/workflows/1/steps/1/goto"), Some(8));
+        assert_eq!(locate(&source, "/workflows/1"), Some(5));
+    }
+
+    #[test]
+    fn test_unknown_placeholders() {
+        let keys: HashSet<String> = ["test".to_string()].into_iter().collect();
+        let prompt = "{{issue}} {{ steps.test.stdout }} {{steps.nope.result}} {{steps.test.foo}} {{isue}}";
+        assert_eq!(
+            unknown_placeholders(prompt, &keys),
+            vec!["steps.nope.result", "steps.test.foo", "isue"]
+        );
+    }
+}
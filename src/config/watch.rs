@@ -0,0 +1,104 @@
+//! 設定ファイルの変更検知（更新時刻のポーリング）
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+use super::loader;
+use super::user_config_dir;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+type Fingerprint = Vec<(PathBuf, Option<SystemTime>)>;
+
+/// Detects edits, additions and removals of configuration files.
+pub struct ConfigWatcher {
+    paths: Vec<PathBuf>,
+    fingerprint: Fingerprint,
+    last_poll: Instant,
+}
+
+impl ConfigWatcher {
+    pub fn new(repo_root: &Path) -> Self {
+        Self::with_paths(loader::watched_paths(
+            repo_root,
+            user_config_dir().as_deref(),
+        ))
+    }
+
+    fn with_paths(paths: Vec<PathBuf>) -> Self {
+        let fingerprint = fingerprint(&paths);
+        Self {
+            paths,
+            fingerprint,
+            last_poll: Instant::now(),
+        }
+    }
+
+    /// Returns true once per change. Checks the files at most once per second.
+    pub fn poll(&mut self) -> bool {
+        if self.last_poll.elapsed() < POLL_INTERVAL {
+            return false;
+        }
+        self.check()
+    }
+
+    fn check(&mut self) -> bool {
+        self.last_poll = Instant::now();
+        let current = fingerprint(&self.paths);
+        if current == self.fingerprint {
+            return false;
+        }
+        self.fingerprint = current;
+        true
+    }
+}
+
+fn fingerprint(paths: &[PathBuf]) -> Fingerprint {
+    let mut entries = Vec::new();
+    for path in paths {
+        entries.push((path.clone(), modified(path)));
+        if path.is_dir()
+            && let Ok(dir) = fs::read_dir(path)
+        {
+            let mut files: Vec<PathBuf> = dir.filter_map(|e| e.ok().map(|e| e.path())).collect();
+            files.sort();
+            entries.extend(files.into_iter().map(|file| {
+                let time = modified(&file);
+                (file, time)
+            }));
+        }
+    }
+    entries
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|meta| meta.modified()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detects_created_and_removed_files() {
+        let dir = std::env::temp_dir().join(format!("gensui-watch-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("workflows")).unwrap();
+        let config = dir.join("workflows.yaml");
+        let mut watcher = ConfigWatcher::with_paths(vec![config.clone(), dir.join("workflows")]);
+        assert!(!watcher.check());
+
+        fs::write(&config, "workflows: []").unwrap();
+        assert!(watcher.check());
+        assert!(!watcher.check());
+
+        fs::write(dir.join("workflows/review.yaml"), "steps: []").unwrap();
+        assert!(watcher.check());
+
+        fs::remove_file(&config).unwrap();
+        assert!(watcher.check());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
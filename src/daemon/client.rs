@@ -0,0 +1,82 @@
+//! 起動中のデーモンへの TUI / CLI からの接続
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::os::unix::net::UnixStream;
+use std::path::Path;
+use std::sync::mpsc;
+use std::thread;
+
+use anyhow::{Context, Result};
+
+use crate::worker::{WorkerCommand, WorkerEvent, WorkerEventReceiver, WorkerHandle};
+
+use super::{ClientMessage, socket_path};
+
+/// Attach to the daemon serving `repo_root`. Returns None when no daemon is
+/// listening. The handle and receiver behave like those of an in-process
+/// worker system; dropping them detaches.
+pub fn connect(repo_root: &Path) -> Result<Option<(WorkerHandle, WorkerEventReceiver)>> {
+    let Ok(stream) = UnixStream::connect(socket_path(repo_root)) else {
+        return Ok(None);
+    };
+    let reader = stream.try_clone().context("failed to clone daemon socket")?;
+
+    let (cmd_tx, cmd_rx) = mpsc::channel::<WorkerCommand>();
+    let (evt_tx, evt_rx) = mpsc::channel();
+
+    thread::Builder::new()
+        .name("gensui-daemon-writer".into())
+        .spawn(move || {
+            let mut stream = stream;
+            for command in cmd_rx {
+                // The daemon reloads its configuration itself, and internal
+                // commands have no wire representation
+                let Ok(mut line) = serde_json::to_string(&ClientMessage::Command(Box::new(command))) else {
+                    continue;
+                };
+                line.push('\n');
+                if stream.write_all(line.as_bytes()).is_err() {
+                    break;
+                }
+            }
+        })
+        .context("failed to spawn daemon writer thread")?;
+
+    thread::Builder::new()
+        .name("gensui-daemon-reader".into())
+        .spawn(move || {
+            for line in BufReader::new(reader).lines() {
+                let Ok(line) = line else {
+                    break;
+                };
+                let event = serde_json::from_str(&line).unwrap_or_else(|err| WorkerEvent::Error {
+                    id: None,
+                    message: format!("デーモンからの不正なメッセージ: {err}"),
+                });
+                if evt_tx.send(event).is_err() {
+                    return;
+                }
+            }
+            let _ = evt_tx.send(WorkerEvent::Error {
+                id: None,
+                message: "gensui daemon との接続が切れました".to_string(),
+            });
+        })
+        .context("failed to spawn daemon reader thread")?;
+
+    Ok(Some((WorkerHandle::new(cmd_tx), evt_rx)))
+}
+
+/// Ask the daemon serving `repo_root` to stop and wait until it has exited.
+/// Returns false when no daemon is running.
+pub fn stop(repo_root: &Path) -> Result<bool> {
+    let Ok(mut stream) = UnixStream::connect(socket_path(repo_root)) else {
+        return Ok(false);
+    };
+    let mut line = serde_json::to_string(&ClientMessage::Stop)?;
+    line.push('\n');
+    stream.write_all(line.as_bytes())?;
+    // The daemon closes every connection when it exits
+    let _ = stream.read_to_end(&mut Vec::new());
+    Ok(true)
+}
@@ -0,0 +1,645 @@
+//! エディタ拡張やスクリプト向けのローカル HTTP API（トークン認証・SSE）
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fs::{self, OpenOptions};
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use anyhow::{Context, Result, bail};
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use serde_json::json;
+use thiserror::Error;
+
+use crate::config::MergeStrategy;
+use crate::state::StateStore;
+use crate::worker::{
+    CreateWorkerRequest, DeleteMode, PermissionDecision, PermissionRequest, WorkerEvent, WorkerHandle,
+    WorkerId, WorkerSnapshot, list_existing_worktrees,
+};
+
+use super::lock;
+
+pub const DEFAULT_ADDR: &str = "127.0.0.1:7317";
+const TOKEN_FILE: &str = ".gensui/api-token";
+
+const MAX_HEADER_BYTES: usize = 64 * 1024;
+const MAX_BODY_BYTES: usize = 1024 * 1024;
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+/// Matches the per-worker log capacity of the manager.
+const MAX_LOG_LINES: usize = 1000;
+const DEFAULT_ACTION_LOG_LIMIT: usize = 100;
+const KEEPALIVE_INTERVAL: Duration = Duration::from_secs(15);
+
+/// An error answered with a specific HTTP status instead of 500.
+#[derive(Debug, Error)]
+#[error("{message}")]
+struct HttpError {
+    status: u16,
+    message: String,
+}
+
+impl HttpError {
+    fn new(status: u16, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            message: message.into(),
+        }
+    }
+}
+
+struct Request {
+    method: String,
+    path: String,
+    query: HashMap<String, String>,
+    /// Header names are lowercased.
+    headers: HashMap<String, String>,
+    body: Vec<u8>,
+}
+
+impl Request {
+    fn json<T: DeserializeOwned>(&self) -> Result<T> {
+        serde_json::from_slice(&self.body)
+            .map_err(|err| HttpError::new(400, format!("invalid request body: {err}")).into())
+    }
+}
+
+struct Response {
+    status: u16,
+    body: serde_json::Value,
+}
+
+impl Response {
+    fn ok(body: serde_json::Value) -> Self {
+        Self { status: 200, body }
+    }
+
+    fn accepted() -> Self {
+        Self {
+            status: 202,
+            body: json!({ "accepted": true }),
+        }
+    }
+
+    fn error(status: u16, message: &str) -> Self {
+        Self {
+            status,
+            body: json!({ "error": message }),
+        }
+    }
+}
+
+/// Worker state mirrored from the event stream.
+#[derive(Default)]
+struct Mirror {
+    workers: BTreeMap<usize, WorkerSnapshot>,
+    logs: HashMap<usize, VecDeque<String>>,
+    permissions: BTreeMap<u64, (WorkerId, PermissionRequest)>,
+    streams: Vec<Sender<WorkerEvent>>,
+}
+
+impl Mirror {
+    fn apply(&mut self, event: &WorkerEvent) {
+        match event {
+            WorkerEvent::Created(snapshot) | WorkerEvent::Updated(snapshot) => {
+                self.workers.insert(snapshot.id.0, snapshot.clone());
+            }
+            WorkerEvent::Log { id, line } => {
+                let logs = self.logs.entry(id.0).or_default();
+                if logs.len() >= MAX_LOG_LINES {
+                    logs.pop_front();
+                }
+                logs.push_back(line.clone());
+            }
+            WorkerEvent::Deleted { id, .. } => {
+                self.workers.remove(&id.0);
+                self.logs.remove(&id.0);
+                self.permissions.retain(|_, (worker, _)| worker != id);
+            }
+            WorkerEvent::PermissionRequested { id, request } => {
+                self.permissions
+                    .insert(request.request_id, (*id, request.clone()));
+            }
+            WorkerEvent::PermissionResolved { request_id, .. } => {
+                self.permissions.remove(request_id);
+            }
+            WorkerEvent::Renamed { .. }
+            | WorkerEvent::Error { .. }
+            | WorkerEvent::Attached { .. }
+            | WorkerEvent::Ready => {}
+        }
+    }
+}
+
+struct Api {
+    repo_root: PathBuf,
+    token: String,
+    handle: WorkerHandle,
+    store: StateStore,
+    mirror: Arc<Mutex<Mirror>>,
+}
+
+/// Start serving on `addr`. `events` must be a daemon subscription so the
+/// mirror starts from the replayed state. Returns the base URL.
+pub(super) fn serve(
+    repo_root: &Path,
+    addr: SocketAddr,
+    handle: WorkerHandle,
+    events: Receiver<WorkerEvent>,
+) -> Result<String> {
+    let listener =
+        TcpListener::bind(addr).with_context(|| format!("failed to bind HTTP API on {addr}"))?;
+    let url = format!("http://{}", listener.local_addr()?);
+
+    let mirror = Arc::new(Mutex::new(Mirror::default()));
+    {
+        let mirror = Arc::clone(&mirror);
+        thread::Builder::new()
+            .name("gensui-http-mirror".into())
+            .spawn(move || {
+                for event in events {
+                    let mut mirror = lock(&mirror);
+                    mirror.apply(&event);
+                    mirror.streams.retain(|tx| tx.send(event.clone()).is_ok());
+                }
+            })
+            .context("failed to spawn HTTP mirror thread")?;
+    }
+
+    let api = Arc::new(Api {
+        repo_root: repo_root.to_path_buf(),
+        token: write_token(repo_root)?,
+        handle,
+        store: StateStore::new(repo_root.join(".gensui/state"))?,
+        mirror,
+    });
+    thread::Builder::new()
+        .name("gensui-http".into())
+        .spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(stream) = stream else {
+                    continue;
+                };
+                let api = Arc::clone(&api);
+                let _ = thread::Builder::new()
+                    .name("gensui-http-conn".into())
+                    .spawn(move || api.handle_connection(stream));
+            }
+        })
+        .context("failed to spawn HTTP accept thread")?;
+
+    Ok(url)
+}
+
+/// Delete the token file so stale credentials do not outlive the daemon.
+pub(super) fn remove_token(repo_root: &Path) {
+    let _ = fs::remove_file(repo_root.join(TOKEN_FILE));
+}
+
+fn write_token(repo_root: &Path) -> Result<String> {
+    let mut bytes = [0u8; 32];
+    fs::File::open("/dev/urandom")
+        .and_then(|mut random| random.read_exact(&mut bytes))
+        .context("failed to generate API token")?;
+    let token: String = bytes.iter().map(|b| format!("{b:02x}")).collect();
+
+    let path = repo_root.join(TOKEN_FILE);
+    let _ = fs::remove_file(&path);
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .open(&path)
+        .with_context(|| format!("failed to create {}", path.display()))?;
+    writeln!(file, "{token}")?;
+    Ok(token)
+}
+
+impl Api {
+    fn handle_connection(&self, mut stream: TcpStream) {
+        let _ = stream.set_read_timeout(Some(READ_TIMEOUT));
+        let request = match read_request(&mut stream) {
+            Ok(request) => request,
+            Err(err) => {
+                let _ = write_response(&mut stream, &Response::error(400, &format!("{err:#}")));
+                return;
+            }
+        };
+
+        if !self.authorized(&request) {
+            let _ = write_response(&mut stream, &Response::error(401, "missing or invalid token"));
+            return;
+        }
+        if request.method == "GET" && request.path == "/api/events" {
+            self.stream_events(stream);
+            return;
+        }
+
+        let response = self.route(&request).unwrap_or_else(|err| {
+            match err.downcast_ref::<HttpError>() {
+                Some(err) => Response::error(err.status, &err.message),
+                None => Response::error(500, &format!("{err:#}")),
+            }
+        });
+        let _ = write_response(&mut stream, &response);
+    }
+
+    fn authorized(&self, request: &Request) -> bool {
+        let bearer = request
+            .headers
+            .get("authorization")
+            .and_then(|value| value.strip_prefix("Bearer "));
+        let query = (request.path == "/api/events")
+            .then(|| request.query.get("token"))
+            .flatten();
+        bearer
+            .map(str::trim)
+            .or(query.map(String::as_str))
+            .is_some_and(|token| token == self.token)
+    }
+
+    fn route(&self, request: &Request) -> Result<Response> {
+        let Some(path) = request.path.strip_prefix("/api/") else {
+            bail!(HttpError::new(404, "not found"));
+        };
+        let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+
+        match (request.method.as_str(), segments.as_slice()) {
+            ("GET", ["workers"]) => {
+                let workers: Vec<WorkerSnapshot> =
+                    lock(&self.mirror).workers.values().cloned().collect();
+                Ok(Response::ok(json!(workers)))
+            }
+            ("POST", ["workers"]) => {
+                self.handle.create_worker(request.json::<CreateWorkerRequest>()?)?;
+                Ok(Response::accepted())
+            }
+            ("GET", ["workers", id]) => Ok(Response::ok(json!(self.worker(id)?))),
+            ("DELETE", ["workers", id]) => {
+                let mode = match request.query.get("mode").map(String::as_str) {
+                    None | Some("destroy") => DeleteMode::Destroy,
+                    Some("archive") => DeleteMode::Archive,
+                    Some(other) => {
+                        bail!(HttpError::new(400, format!("unknown delete mode '{other}'")))
+                    }
+                };
+                let force = request.query.get("force").is_some_and(|v| v == "true");
+                self.handle.delete_worker(self.worker(id)?.id, mode, force)?;
+                Ok(Response::accepted())
+            }
+            ("GET", ["workers", id, "logs"]) => {
+                let id = self.worker(id)?.id;
+                let logs: Vec<String> = lock(&self.mirror)
+                    .logs
+                    .get(&id.0)
+                    .map(|logs| logs.iter().cloned().collect())
+                    .unwrap_or_default();
+                Ok(Response::ok(json!(logs)))
+            }
+            ("GET", ["workers", id, "sessions"]) => {
+                let name = self.worker(id)?.name;
+                let record = self.store.load_worker(&name)?;
+                let sessions = record.map(|r| r.session_history).unwrap_or_default();
+                Ok(Response::ok(json!(sessions)))
+            }
+            ("POST", ["workers", id, action]) => {
+                self.worker_action(self.worker(id)?.id, action, request)?;
+                Ok(Response::accepted())
+            }
+            ("GET", ["permissions"]) => {
+                let pending: Vec<serde_json::Value> = lock(&self.mirror)
+                    .permissions
+                    .values()
+                    .map(|(id, request)| json!({ "worker_id": id, "request": request }))
+                    .collect();
+                Ok(Response::ok(json!(pending)))
+            }
+            ("POST", ["permissions", request_id]) => {
+                let request_id: u64 = request_id
+                    .parse()
+                    .map_err(|_| HttpError::new(400, "invalid permission request id"))?;
+                let worker = lock(&self.mirror)
+                    .permissions
+                    .get(&request_id)
+                    .map(|(id, _)| *id)
+                    .ok_or_else(|| HttpError::new(404, "no such pending permission request"))?;
+                let decision: PermissionDecision = request.json()?;
+                self.handle.respond_permission(worker, request_id, decision)?;
+                Ok(Response::accepted())
+            }
+            ("GET", ["worktrees"]) => {
+                Ok(Response::ok(json!(list_existing_worktrees(&self.repo_root)?)))
+            }
+            ("GET", ["action-log"]) => {
+                let limit = match request.query.get("limit") {
+                    Some(limit) => limit
+                        .parse()
+                        .map_err(|_| HttpError::new(400, "invalid limit"))?,
+                    None => DEFAULT_ACTION_LOG_LIMIT,
+                };
+                Ok(Response::ok(json!(self.store.load_action_log(limit)?)))
+            }
+            _ => bail!(HttpError::new(404, "not found")),
+        }
+    }
+
+    fn worker_action(&self, id: WorkerId, action: &str, request: &Request) -> Result<()> {
+        #[derive(Deserialize)]
+        struct ResumeInterrupted {
+            #[serde(default = "default_continue_session")]
+            continue_session: bool,
+        }
+        fn default_continue_session() -> bool {
+            true
+        }
+        #[derive(Deserialize)]
+        struct Continue {
+            prompt: String,
+            #[serde(default)]
+            permission_mode: Option<String>,
+        }
+        #[derive(Deserialize)]
+        struct Rename {
+            name: String,
+        }
+        #[derive(Deserialize)]
+        struct Priority {
+            priority: i32,
+        }
+        #[derive(Deserialize, Default)]
+        struct Merge {
+            #[serde(default)]
+            strategy: MergeStrategy,
+            #[serde(default)]
+            dry_run: bool,
+            #[serde(default)]
+            resolve_conflicts: bool,
+        }
+
+        match action {
+            "restart" => self.handle.restart_worker(id),
+            "cancel" => self.handle.cancel_worker(id),
+            "pause" => self.handle.pause_worker(id),
+            "resume" => self.handle.resume_worker(id),
+            "pull-request" => self.handle.open_pull_request(id),
+            "reactivate" => self.handle.reactivate_worker(id),
+            "resume-interrupted" => {
+                let body: ResumeInterrupted = if request.body.is_empty() {
+                    ResumeInterrupted {
+                        continue_session: true,
+                    }
+                } else {
+                    request.json()?
+                };
+                self.handle
+                    .resume_interrupted_worker(id, body.continue_session)
+            }
+            "continue" => {
+                let body: Continue = request.json()?;
+                self.handle
+                    .continue_worker(id, body.prompt, body.permission_mode)
+            }
+            "rename" => {
+                let body: Rename = request.json()?;
+                self.handle.rename_worker(id, body.name)
+            }
+            "priority" => {
+                let body: Priority = request.json()?;
+                self.handle.set_priority(id, body.priority)
+            }
+            "merge" => {
+                let body: Merge = if request.body.is_empty() {
+                    Merge::default()
+                } else {
+                    request.json()?
+                };
+                self.handle
+                    .merge(id, body.strategy, body.dry_run, body.resolve_conflicts)
+            }
+            _ => bail!(HttpError::new(404, format!("unknown action '{action}'"))),
+        }
+    }
+
+    /// Look up a worker by numeric id or by name.
+    fn worker(&self, key: &str) -> Result<WorkerSnapshot> {
+        let mirror = lock(&self.mirror);
+        let found = match key.parse::<usize>() {
+            Ok(id) => mirror.workers.get(&id),
+            Err(_) => mirror.workers.values().find(|w| w.name == key),
+        };
+        found
+            .cloned()
+            .ok_or_else(|| HttpError::new(404, format!("worker '{key}' not found")).into())
+    }
+
+    fn stream_events(&self, mut stream: TcpStream) {
+        let (tx, rx) = mpsc::channel();
+        lock(&self.mirror).streams.push(tx);
+
+        let head = "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\n\
+                    Cache-Control: no-cache\r\nConnection: keep-alive\r\n\r\n";
+        if stream.write_all(head.as_bytes()).is_err() {
+            return;
+        }
+        loop {
+            let chunk = match rx.recv_timeout(KEEPALIVE_INTERVAL) {
+                Ok(event) => sse_message(&event),
+                Err(RecvTimeoutError::Timeout) => ": keepalive\n\n".to_string(),
+                Err(RecvTimeoutError::Disconnected) => return,
+            };
+            if stream.write_all(chunk.as_bytes()).is_err() {
+                return;
+            }
+        }
+    }
+}
+
+/// Format an event as an SSE message named after the event variant.
+fn sse_message(event: &WorkerEvent) -> String {
+    let value = serde_json::to_value(event).unwrap_or_default();
+    let name = match &value {
+        serde_json::Value::Object(map) => map.keys().next().cloned().unwrap_or_default(),
+        serde_json::Value::String(name) => name.clone(),
+        _ => String::new(),
+    };
+    format!("event: {name}\ndata: {value}\n\n")
+}
+
+fn read_request(stream: &mut impl Read) -> Result<Request> {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 4096];
+    loop {
+        let n = stream.read(&mut chunk)?;
+        if n == 0 {
+            bail!("connection closed before the request was complete");
+        }
+        buf.extend_from_slice(&chunk[..n]);
+
+        let mut headers = [httparse::EMPTY_HEADER; 64];
+        let mut parsed = httparse::Request::new(&mut headers);
+        let httparse::Status::Complete(header_len) = parsed.parse(&buf)? else {
+            if buf.len() > MAX_HEADER_BYTES {
+                bail!("request headers too large");
+            }
+            continue;
+        };
+
+        let method = parsed.method.unwrap_or_default().to_string();
+        let target = parsed.path.unwrap_or("/");
+        let (path, query) = target.split_once('?').unwrap_or((target, ""));
+        let (path, query) = (path.to_string(), parse_query(query));
+        let headers: HashMap<String, String> = parsed
+            .headers
+            .iter()
+            .map(|h| {
+                (
+                    h.name.to_ascii_lowercase(),
+                    String::from_utf8_lossy(h.value).into_owned(),
+                )
+            })
+            .collect();
+        let content_length: usize = match headers.get("content-length") {
+            Some(value) => value.trim().parse().context("invalid Content-Length")?,
+            None => 0,
+        };
+        if content_length > MAX_BODY_BYTES {
+            bail!("request body too large");
+        }
+
+        let mut body = buf.split_off(header_len);
+        while body.len() < content_length {
+            let n = stream.read(&mut chunk)?;
+            if n == 0 {
+                bail!("connection closed before the body was complete");
+            }
+            body.extend_from_slice(&chunk[..n]);
+        }
+        body.truncate(content_length);
+
+        return Ok(Request {
+            method,
+            path,
+            query,
+            headers,
+            body,
+        });
+    }
+}
+
+fn parse_query(query: &str) -> HashMap<String, String> {
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (percent_decode(key), percent_decode(value))
+        })
+        .collect()
+}
+
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => out.push(b' '),
+            b'%' if i + 2 < bytes.len() => {
+                let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).unwrap_or_default();
+                match u8::from_str_radix(hex, 16) {
+                    Ok(byte) => {
+                        out.push(byte);
+                        i += 2;
+                    }
+                    Err(_) => out.push(b'%'),
+                }
+            }
+            byte => out.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+fn write_response(stream: &mut impl Write, response: &Response) -> Result<()> {
+    let reason = match response.status {
+        200 => "OK",
+        202 => "Accepted",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        _ => "Internal Server Error",
+    };
+    let body = response.body.to_string();
+    write!(
+        stream,
+        "HTTP/1.1 {} {reason}\r\nContent-Type: application/json\r\n\
+         Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        response.status,
+        body.len()
+    )?;
+    stream.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_request_with_body_and_query() {
+        let raw = b"POST /api/workers?limit=5&name=a%20b HTTP/1.1\r\n\
+                    Authorization: Bearer abc\r\nContent-Length: 17\r\n\r\n{\"name\":\"fix-1\"}\n";
+        let request = read_request(&mut &raw[..]).unwrap();
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.path, "/api/workers");
+        assert_eq!(request.query["limit"], "5");
+        assert_eq!(request.query["name"], "a b");
+        assert_eq!(request.headers["authorization"], "Bearer abc");
+        let body: CreateWorkerRequest = request.json().unwrap();
+        assert_eq!(body.name.as_deref(), Some("fix-1"));
+    }
+
+    #[test]
+    fn test_mirror_tracks_pending_permissions() {
+        let mut mirror = Mirror::default();
+        let request = PermissionRequest {
+            request_id: 9,
+            step_name: "実装".to_string(),
+            description: None,
+            permission_mode: None,
+            allowed_tools: None,
+        };
+        mirror.apply(&WorkerEvent::PermissionRequested {
+            id: WorkerId(1),
+            request,
+        });
+        assert!(mirror.permissions.contains_key(&9));
+
+        mirror.apply(&WorkerEvent::PermissionResolved {
+            id: WorkerId(1),
+            request_id: 9,
+            decision: PermissionDecision::Deny,
+        });
+        assert!(mirror.permissions.is_empty());
+    }
+
+    #[test]
+    fn test_sse_message_is_named_after_variant() {
+        let message = sse_message(&WorkerEvent::Log {
+            id: WorkerId(2),
+            line: "hello".to_string(),
+        });
+        assert_eq!(
+            message,
+            "event: log\ndata: {\"log\":{\"id\":2,\"line\":\"hello\"}}\n\n"
+        );
+        assert_eq!(sse_message(&WorkerEvent::Ready), "event: ready\ndata: \"ready\"\n\n");
+    }
+}
@@ -0,0 +1,425 @@
+//! Unix ドメインソケット越しにワーカーマネージャを提供するデーモン（JSON Lines プロトコル）
+
+mod client;
+mod http;
+
+pub use client::{connect, stop};
+pub use http::DEFAULT_ADDR;
+
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::net::SocketAddr;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::os::unix::process::CommandExt;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result, anyhow, bail};
+use serde::{Deserialize, Serialize};
+
+use crate::config::{Config, ConfigWatcher};
+use crate::worker::{
+    PermissionRequest, WorkerCommand, WorkerEvent, WorkerEventReceiver, WorkerHandle, WorkerId,
+    WorkerReplay, spawn_worker_system,
+};
+
+const SOCKET_FILE: &str = ".gensui/daemon.sock";
+const LOG_FILE: &str = ".gensui/daemon.log";
+
+/// A client that stops reading is dropped rather than stalling every other client.
+const WRITE_TIMEOUT: Duration = Duration::from_secs(5);
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(10);
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(5);
+const CONFIG_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// A line sent from a client to the daemon.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClientMessage {
+    Command(Box<WorkerCommand>),
+    /// Persist and stop every worker, then exit the daemon.
+    Stop,
+}
+
+pub fn socket_path(repo_root: &Path) -> PathBuf {
+    repo_root.join(SOCKET_FILE)
+}
+
+/// Where events for a subscriber are delivered.
+enum Sink {
+    /// A client connected to the control socket.
+    Socket(UnixStream),
+    /// A consumer inside the daemon, such as the HTTP API.
+    Channel(Sender<WorkerEvent>),
+}
+
+struct Subscriber {
+    sink: Sink,
+    /// Set once the initial state has been replayed; live events are only
+    /// forwarded from then on.
+    attached: bool,
+}
+
+impl Subscriber {
+    fn send(&mut self, event: &WorkerEvent) -> Result<()> {
+        match &mut self.sink {
+            Sink::Socket(stream) => {
+                let mut line = serde_json::to_string(event)?;
+                line.push('\n');
+                stream.write_all(line.as_bytes())?;
+            }
+            Sink::Channel(tx) => tx
+                .send(event.clone())
+                .map_err(|_| anyhow!("subscriber has gone away"))?,
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Default)]
+struct Subscribers {
+    inner: Arc<Mutex<HashMap<u64, Subscriber>>>,
+    next_id: Arc<AtomicU64>,
+}
+
+impl Subscribers {
+    /// Register `sink` and ask the manager to replay the current state to it.
+    fn subscribe(&self, handle: &WorkerHandle, sink: Sink) -> Result<u64> {
+        let subscriber = self.next_id.fetch_add(1, Ordering::SeqCst) + 1;
+        lock(&self.inner).insert(
+            subscriber,
+            Subscriber {
+                sink,
+                attached: false,
+            },
+        );
+        handle.send(WorkerCommand::Attach { subscriber })?;
+        Ok(subscriber)
+    }
+
+    fn unsubscribe(&self, subscriber: u64) {
+        lock(&self.inner).remove(&subscriber);
+    }
+}
+
+/// Run the daemon in the foreground until a client sends `ClientMessage::Stop`.
+/// With `http`, the HTTP API is served on that loopback address as well.
+pub fn run(repo_root: &Path, http: Option<SocketAddr>) -> Result<()> {
+    if let Some(addr) = http
+        && !addr.ip().is_loopback()
+    {
+        bail!("the HTTP API only listens on loopback addresses, not {addr}");
+    }
+
+    let path = socket_path(repo_root);
+    if UnixStream::connect(&path).is_ok() {
+        bail!("gensui daemon is already running ({})", path.display());
+    }
+    // A socket nobody answers on was left behind by a daemon that died
+    let _ = fs::remove_file(&path);
+
+    let (config, warnings) =
+        Config::discover(repo_root).context("failed to load workflow configuration")?;
+    for warning in &warnings {
+        eprintln!("{}", warning.display_relative(repo_root));
+    }
+    let (handle, events) = spawn_worker_system(repo_root.to_path_buf(), config)?;
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create {}", parent.display()))?;
+    }
+    let listener = UnixListener::bind(&path)
+        .with_context(|| format!("failed to bind {}", path.display()))?;
+
+    let subscribers = Subscribers::default();
+    {
+        let subscribers = subscribers.clone();
+        thread::Builder::new()
+            .name("gensui-daemon-broadcast".into())
+            .spawn(move || broadcast(events, subscribers))
+            .context("failed to spawn broadcast thread")?;
+    }
+    {
+        let repo_root = repo_root.to_path_buf();
+        let handle = handle.clone();
+        thread::Builder::new()
+            .name("gensui-daemon-config".into())
+            .spawn(move || watch_config(&repo_root, &handle))
+            .context("failed to spawn config watcher thread")?;
+    }
+
+    if let Some(addr) = http {
+        let (tx, rx) = mpsc::channel();
+        subscribers.subscribe(&handle, Sink::Channel(tx))?;
+        let url = http::serve(repo_root, addr, handle.clone(), rx)?;
+        eprintln!("gensui HTTP API listening on {url}");
+    }
+
+    eprintln!("gensui daemon listening on {}", path.display());
+
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(err) => {
+                eprintln!("failed to accept connection: {err}");
+                continue;
+            }
+        };
+        let writer = match stream
+            .set_write_timeout(Some(WRITE_TIMEOUT))
+            .and_then(|()| stream.try_clone())
+        {
+            Ok(writer) => writer,
+            Err(err) => {
+                eprintln!("failed to set up connection: {err}");
+                continue;
+            }
+        };
+        let subscriber = subscribers.subscribe(&handle, Sink::Socket(writer))?;
+
+        let handle = handle.clone();
+        let subscribers = subscribers.clone();
+        let repo_root = repo_root.to_path_buf();
+        thread::Builder::new()
+            .name(format!("gensui-daemon-client-{subscriber}"))
+            .spawn(move || {
+                serve_client(stream, &handle, &repo_root);
+                subscribers.unsubscribe(subscriber);
+            })
+            .context("failed to spawn client thread")?;
+    }
+
+    Ok(())
+}
+
+/// Start the daemon in the background, detached from the terminal, and wait
+/// until it accepts connections. Output goes to `.gensui/daemon.log`.
+pub fn spawn_detached(repo_root: &Path, http: Option<SocketAddr>) -> Result<u32> {
+    let log_path = repo_root.join(LOG_FILE);
+    if let Some(parent) = log_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let log = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&log_path)
+        .with_context(|| format!("failed to open {}", log_path.display()))?;
+
+    let mut command = Command::new(std::env::current_exe()?);
+    command.arg("daemon");
+    if let Some(addr) = http {
+        command.arg(format!("--http={addr}"));
+    }
+    command
+        .current_dir(repo_root)
+        .stdin(Stdio::null())
+        .stdout(log.try_clone()?)
+        .stderr(log);
+    // SAFETY: setsid is async-signal-safe and only detaches the child from
+    // the controlling terminal so closing it does not send SIGHUP.
+    unsafe {
+        command.pre_exec(|| {
+            libc::setsid();
+            Ok(())
+        });
+    }
+    let mut child = command.spawn().context("failed to start gensui daemon")?;
+
+    let started = Instant::now();
+    let path = socket_path(repo_root);
+    while started.elapsed() < STARTUP_TIMEOUT {
+        if UnixStream::connect(&path).is_ok() {
+            return Ok(child.id());
+        }
+        if let Some(status) = child.try_wait()? {
+            bail!(
+                "gensui daemon exited with {status}; see {}",
+                log_path.display()
+            );
+        }
+        thread::sleep(Duration::from_millis(50));
+    }
+    bail!(
+        "gensui daemon did not start listening within {}s; see {}",
+        STARTUP_TIMEOUT.as_secs(),
+        log_path.display()
+    )
+}
+
+fn serve_client(stream: UnixStream, handle: &WorkerHandle, repo_root: &Path) {
+    for line in BufReader::new(stream).lines() {
+        let Ok(line) = line else {
+            break;
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<ClientMessage>(&line) {
+            Ok(ClientMessage::Command(command)) => {
+                if let Err(err) = handle.send(*command) {
+                    eprintln!("{err:#}");
+                }
+            }
+            Ok(ClientMessage::Stop) => shutdown(handle, repo_root),
+            Err(err) => eprintln!("ignoring invalid client message: {err}"),
+        }
+    }
+}
+
+fn shutdown(handle: &WorkerHandle, repo_root: &Path) -> ! {
+    eprintln!("gensui daemon stopping");
+    if let Err(err) = handle.shutdown(SHUTDOWN_TIMEOUT) {
+        eprintln!("{err:#}");
+    }
+    let _ = fs::remove_file(socket_path(repo_root));
+    http::remove_token(repo_root);
+    std::process::exit(0);
+}
+
+fn broadcast(events: WorkerEventReceiver, subscribers: Subscribers) {
+    for event in events {
+        let mut subscribers = lock(&subscribers.inner);
+        match event {
+            WorkerEvent::Attached {
+                subscriber,
+                workers,
+                permissions,
+            } => {
+                let Some(client) = subscribers.get_mut(&subscriber) else {
+                    continue;
+                };
+                let replayed = replay_events(workers, permissions)
+                    .iter()
+                    .try_for_each(|event| client.send(event));
+                match replayed {
+                    Ok(()) => client.attached = true,
+                    Err(_) => {
+                        subscribers.remove(&subscriber);
+                    }
+                }
+            }
+            event => {
+                subscribers.retain(|_, client| !client.attached || client.send(&event).is_ok());
+            }
+        }
+    }
+}
+
+/// The events that bring a new client up to date.
+fn replay_events(
+    workers: Vec<WorkerReplay>,
+    permissions: Vec<(WorkerId, PermissionRequest)>,
+) -> Vec<WorkerEvent> {
+    let mut events = Vec::new();
+    for worker in workers {
+        let id = worker.snapshot.id;
+        events.push(WorkerEvent::Created(worker.snapshot));
+        events.extend(
+            worker
+                .logs
+                .into_iter()
+                .map(|line| WorkerEvent::Log { id, line }),
+        );
+    }
+    events.extend(
+        permissions
+            .into_iter()
+            .map(|(id, request)| WorkerEvent::PermissionRequested { id, request }),
+    );
+    events.push(WorkerEvent::Ready);
+    events
+}
+
+fn watch_config(repo_root: &Path, handle: &WorkerHandle) {
+    let mut watcher = ConfigWatcher::new(repo_root);
+    loop {
+        thread::sleep(CONFIG_POLL_INTERVAL);
+        if !watcher.poll() {
+            continue;
+        }
+        match Config::discover(repo_root) {
+            Ok((config, warnings)) => {
+                for warning in &warnings {
+                    eprintln!("{}", warning.display_relative(repo_root));
+                }
+                if handle.reload_config(config).is_err() {
+                    return;
+                }
+                eprintln!("workflow configuration reloaded");
+            }
+            Err(err) => eprintln!("keeping previous configuration: {err:#}"),
+        }
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::worker::{WorkerSnapshot, WorkerStatus};
+
+    #[test]
+    fn test_replay_announces_workers_then_ready() {
+        let snapshot = WorkerSnapshot {
+            id: WorkerId(3),
+            name: "worker-3".to_string(),
+            issue: None,
+            issue_title: None,
+            agent: "claude".to_string(),
+            worktree: ".worktrees/worker-3".to_string(),
+            branch: "gensui/worker-3".to_string(),
+            status: WorkerStatus::Running,
+            last_event: String::new(),
+            workflow: "default".to_string(),
+            total_steps: 1,
+            current_step: None,
+            session_id: None,
+            priority: 0,
+            queue_position: None,
+            pr_url: None,
+            base_ref: None,
+        };
+        let events = replay_events(
+            vec![WorkerReplay {
+                snapshot,
+                logs: vec!["line".to_string()],
+            }],
+            Vec::new(),
+        );
+
+        assert!(matches!(&events[0], WorkerEvent::Created(s) if s.id == WorkerId(3)));
+        assert!(matches!(&events[1], WorkerEvent::Log { line, .. } if line == "line"));
+        assert!(matches!(events[2], WorkerEvent::Ready));
+    }
+
+    #[test]
+    fn test_client_messages_round_trip() {
+        let line = serde_json::to_string(&ClientMessage::Command(Box::new(WorkerCommand::Pause {
+            id: WorkerId(7),
+        })))
+        .unwrap();
+        assert_eq!(line, r#"{"command":{"pause":{"id":7}}}"#);
+        assert!(matches!(
+            serde_json::from_str::<ClientMessage>(&line).unwrap(),
+            ClientMessage::Command(command)
+                if matches!(*command, WorkerCommand::Pause { id: WorkerId(7) })
+        ));
+
+        // Commands carrying channels never cross the socket
+        assert!(serde_json::from_str::<ClientMessage>(r#"{"command":{"persist":{"id":1}}}"#).is_err());
+        assert!(matches!(
+            serde_json::from_str::<ClientMessage>(r#""stop""#).unwrap(),
+            ClientMessage::Stop
+        ));
+    }
+}
@@ -0,0 +1,325 @@
+/// ワーカーの差分取得と unified diff の解析
+///
+/// ベースブランチとの分岐点からワークツリーまでの変更（コミット済み・未コミット・
+/// 未追跡ファイル）をファイル・ハンク単位に構造化する。
+use std::path::Path;
+
+use anyhow::{Context, Result};
+
+use crate::worker::git::{git, git_accepting};
+
+/// ワーカーの変更全体
+#[derive(Debug, Clone, Default)]
+pub struct WorkerDiff {
+    /// 比較元のブランチ
+    pub base: String,
+    pub files: Vec<FileDiff>,
+}
+
+impl WorkerDiff {
+    pub fn additions(&self) -> usize {
+        self.files.iter().map(FileDiff::additions).sum()
+    }
+
+    pub fn deletions(&self) -> usize {
+        self.files.iter().map(FileDiff::deletions).sum()
+    }
+
+    /// `3 ファイル変更 +20 -5` 形式の要約
+    pub fn summary(&self) -> String {
+        format!(
+            "{} ファイル変更 +{} -{}",
+            self.files.len(),
+            self.additions(),
+            self.deletions()
+        )
+    }
+}
+
+/// ファイルの変更種別
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileStatus {
+    Added,
+    Deleted,
+    Modified,
+    Renamed,
+}
+
+impl FileStatus {
+    /// `git status --short` と同じ1文字表記
+    pub fn letter(self) -> char {
+        match self {
+            FileStatus::Added => 'A',
+            FileStatus::Deleted => 'D',
+            FileStatus::Modified => 'M',
+            FileStatus::Renamed => 'R',
+        }
+    }
+}
+
+/// 1ファイル分の差分
+#[derive(Debug, Clone)]
+pub struct FileDiff {
+    pub path: String,
+    /// リネーム元のパス
+    pub old_path: Option<String>,
+    pub status: FileStatus,
+    pub binary: bool,
+    pub hunks: Vec<Hunk>,
+}
+
+impl FileDiff {
+    fn new(path: String) -> Self {
+        Self {
+            path,
+            old_path: None,
+            status: FileStatus::Modified,
+            binary: false,
+            hunks: Vec::new(),
+        }
+    }
+
+    pub fn additions(&self) -> usize {
+        self.count(LineKind::Added)
+    }
+
+    pub fn deletions(&self) -> usize {
+        self.count(LineKind::Removed)
+    }
+
+    fn count(&self, kind: LineKind) -> usize {
+        self.hunks
+            .iter()
+            .flat_map(|hunk| &hunk.lines)
+            .filter(|line| line.kind == kind)
+            .count()
+    }
+}
+
+/// `@@ -a,b +c,d @@` で始まる変更のまとまり
+#[derive(Debug, Clone)]
+pub struct Hunk {
+    pub header: String,
+    pub lines: Vec<DiffLine>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineKind {
+    Context,
+    Added,
+    Removed,
+    /// `\ No newline at end of file`
+    Marker,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiffLine {
+    pub kind: LineKind,
+    /// 先頭の `+`/`-`/空白を除いた内容
+    pub text: String,
+}
+
+/// ワークツリーの変更を `base` との分岐点から取得する
+///
+/// `git diff <base>...HEAD` に未コミットの変更と未追跡ファイルを加えたものになる。
+pub fn load(worktree: &Path, base: &str) -> Result<WorkerDiff> {
+    let merge_base = git(worktree, &["merge-base", base, "HEAD"])
+        .with_context(|| format!("failed to find the merge base of {base} and HEAD"))?;
+    let mut text = git(
+        worktree,
+        &[
+            "-c",
+            "core.quotepath=false",
+            "diff",
+            "--no-color",
+            "--no-ext-diff",
+            "-M",
+            merge_base.trim(),
+        ],
+    )?;
+
+    let untracked = git(worktree, &["ls-files", "--others", "--exclude-standard", "-z"])?;
+    for path in untracked.split('\0').filter(|path| !path.is_empty()) {
+        // --no-index exits with 1 when the files differ
+        text.push_str(&git_accepting(
+            worktree,
+            &["diff", "--no-color", "--no-ext-diff", "--no-index", "--", "/dev/null", path],
+            &[0, 1],
+        )?);
+    }
+
+    let mut files = parse(&text);
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(WorkerDiff {
+        base: base.to_string(),
+        files,
+    })
+}
+
+/// `git diff` の出力をファイルごとに分解する
+pub fn parse(text: &str) -> Vec<FileDiff> {
+    let mut files: Vec<FileDiff> = Vec::new();
+    // Lines still expected in the current hunk (old side, new side)
+    let mut remaining = (0usize, 0usize);
+
+    for line in text.lines() {
+        if remaining != (0, 0) || line.starts_with('\\') {
+            let Some(hunk) = files.last_mut().and_then(|file| file.hunks.last_mut()) else {
+                continue;
+            };
+            let (kind, text) = match line.split_at_checked(1) {
+                Some(("+", rest)) => (LineKind::Added, rest),
+                Some(("-", rest)) => (LineKind::Removed, rest),
+                Some(("\\", _)) => (LineKind::Marker, line),
+                Some((_, rest)) => (LineKind::Context, rest),
+                None => (LineKind::Context, ""),
+            };
+            match kind {
+                LineKind::Added => remaining.1 = remaining.1.saturating_sub(1),
+                LineKind::Removed => remaining.0 = remaining.0.saturating_sub(1),
+                LineKind::Context => {
+                    remaining.0 = remaining.0.saturating_sub(1);
+                    remaining.1 = remaining.1.saturating_sub(1);
+                }
+                LineKind::Marker => {}
+            }
+            hunk.lines.push(DiffLine {
+                kind,
+                text: text.to_string(),
+            });
+            continue;
+        }
+
+        if let Some(rest) = line.strip_prefix("diff --git ") {
+            let path = rest
+                .rsplit_once(" b/")
+                .map(|(_, path)| path)
+                .unwrap_or(rest)
+                .to_string();
+            files.push(FileDiff::new(path));
+            continue;
+        }
+        let Some(file) = files.last_mut() else {
+            continue;
+        };
+        if line.starts_with("@@") {
+            remaining = parse_hunk_header(line).unwrap_or((0, 0));
+            file.hunks.push(Hunk {
+                header: line.to_string(),
+                lines: Vec::new(),
+            });
+        } else if line.starts_with("new file mode") || line == "--- /dev/null" {
+            file.status = FileStatus::Added;
+        } else if line.starts_with("deleted file mode") || line == "+++ /dev/null" {
+            file.status = FileStatus::Deleted;
+        } else if let Some(path) = line.strip_prefix("rename from ") {
+            file.status = FileStatus::Renamed;
+            file.old_path = Some(path.to_string());
+        } else if let Some(path) = line.strip_prefix("rename to ") {
+            file.path = path.to_string();
+        } else if let Some(path) = line.strip_prefix("+++ b/") {
+            file.path = path.to_string();
+        } else if line.starts_with("Binary files ") {
+            file.binary = true;
+        }
+    }
+    files
+}
+
+/// `@@ -a,b +c,d @@` から旧・新それぞれの行数を読む
+fn parse_hunk_header(line: &str) -> Option<(usize, usize)> {
+    let mut parts = line.split_whitespace().skip(1);
+    let old = parts.next()?.strip_prefix('-')?;
+    let new = parts.next()?.strip_prefix('+')?;
+    let count = |range: &str| match range.split_once(',') {
+        Some((_, count)) => count.parse().ok(),
+        None => Some(1),
+    };
+    Some((count(old)?, count(new)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::worker::git::fixture::{init_repo, run_git};
+
+    const SAMPLE: &str = "\
+diff --git a/src/lib.rs b/src/lib.rs
+index 1111111..2222222 100644
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -1,3 +1,4 @@ mod tests
+ fn main() {
+--- not a header
++    println!(\"hi\");
++    run();
+ }
+@@ -10 +11 @@
+-old
++new
+\\ No newline at end of file
+diff --git a/README.md b/docs/README.md
+similarity index 90%
+rename from README.md
+rename to docs/README.md
+diff --git a/logo.png b/logo.png
+new file mode 100644
+index 0000000..3333333
+Binary files /dev/null and b/logo.png differ
+";
+
+    #[test]
+    fn test_parse_files_and_hunks() {
+        let files = parse(SAMPLE);
+        assert_eq!(files.len(), 3);
+
+        let lib = &files[0];
+        assert_eq!(lib.path, "src/lib.rs");
+        assert_eq!(lib.status, FileStatus::Modified);
+        assert_eq!(lib.hunks.len(), 2);
+        assert_eq!(lib.hunks[0].header, "@@ -1,3 +1,4 @@ mod tests");
+        // A removed line that looks like a file header stays inside the hunk
+        assert_eq!(
+            lib.hunks[0].lines[1],
+            DiffLine {
+                kind: LineKind::Removed,
+                text: "-- not a header".to_string()
+            }
+        );
+        assert_eq!(lib.hunks[1].lines.len(), 3);
+        assert_eq!(lib.hunks[1].lines[2].kind, LineKind::Marker);
+        assert_eq!((lib.additions(), lib.deletions()), (3, 2));
+
+        assert_eq!(files[1].status, FileStatus::Renamed);
+        assert_eq!(files[1].path, "docs/README.md");
+        assert_eq!(files[1].old_path.as_deref(), Some("README.md"));
+
+        assert_eq!(files[2].status, FileStatus::Added);
+        assert!(files[2].binary);
+    }
+
+    #[test]
+    fn test_load_includes_committed_uncommitted_and_untracked_changes() {
+        let dir = std::env::temp_dir().join(format!("gensui-diff-{}", std::process::id()));
+        init_repo(&dir);
+        let run = |args: &[&str]| run_git(&dir, args);
+        std::fs::write(dir.join("a.txt"), "one\n").unwrap();
+        run(&["add", "."]);
+        run(&["commit", "-q", "-m", "init"]);
+        run(&["checkout", "-q", "-b", "feature"]);
+        std::fs::write(dir.join("b.txt"), "committed\n").unwrap();
+        run(&["add", "."]);
+        run(&["commit", "-q", "-m", "add b"]);
+        std::fs::write(dir.join("a.txt"), "one\ntwo\n").unwrap();
+        std::fs::write(dir.join("c.txt"), "untracked\n").unwrap();
+
+        let diff = load(&dir, "main").unwrap();
+        let paths: Vec<_> = diff.files.iter().map(|file| file.path.as_str()).collect();
+        assert_eq!(paths, vec!["a.txt", "b.txt", "c.txt"]);
+        assert_eq!(diff.files[2].status, FileStatus::Added);
+        assert_eq!(diff.summary(), "3 ファイル変更 +3 -0");
+        assert!(load(&dir, "no-such-branch").is_err());
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
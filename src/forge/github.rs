@@ -0,0 +1,149 @@
+/// GitHub REST API でプルリクエストを作成するフォージ
+use anyhow::{Result, bail};
+use serde::{Deserialize, Serialize};
+
+use super::{ForgeApi, PullRequest};
+use crate::http::{HttpStatus, get_json, post_json};
+
+const DEFAULT_BASE_URL: &str = "https://api.github.com";
+
+#[derive(Serialize)]
+struct NewPull<'a> {
+    title: &'a str,
+    body: &'a str,
+    head: &'a str,
+    base: &'a str,
+    draft: bool,
+}
+
+#[derive(Deserialize)]
+struct GitHubPull {
+    html_url: String,
+}
+
+/// GitHub（GitHub Enterprise を含む）のフォージ
+pub struct GitHubForge {
+    base_url: String,
+    /// `owner/repo`
+    repo: String,
+    token: Option<String>,
+}
+
+impl GitHubForge {
+    pub fn new(base_url: Option<String>, repo: String, token: Option<String>) -> Self {
+        let base_url = base_url.unwrap_or_else(|| DEFAULT_BASE_URL.to_string());
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            repo,
+            token,
+        }
+    }
+}
+
+impl ForgeApi for GitHubForge {
+    fn name(&self) -> &str {
+        "github"
+    }
+
+    fn open(&self, request: &PullRequest) -> Result<String> {
+        if self.repo.is_empty() {
+            bail!("forge.repo is not configured");
+        }
+        let url = format!("{}/repos/{}/pulls", self.base_url, self.repo);
+        let authorization = self.token.as_ref().map(|token| format!("Bearer {token}"));
+        let mut headers = vec![
+            ("Accept", "application/vnd.github+json"),
+            ("X-GitHub-Api-Version", "2022-11-28"),
+        ];
+        if let Some(authorization) = &authorization {
+            headers.push(("Authorization", authorization));
+        }
+
+        let body = NewPull {
+            title: &request.title,
+            body: &request.body,
+            head: &request.head,
+            base: &request.base,
+            draft: request.draft,
+        };
+        match post_json::<_, GitHubPull>(&url, &headers, &body) {
+            Ok(pull) => Ok(pull.html_url),
+            // 422 is also returned when a pull request for the branch already exists
+            Err(err) if err.downcast_ref::<HttpStatus>().is_some_and(|e| e.status == 422) => {
+                let owner = self.repo.split('/').next().unwrap_or_default();
+                let existing = get_json::<Vec<GitHubPull>>(
+                    &format!("{url}?head={owner}:{}&state=open", request.head),
+                    &headers,
+                )?
+                .unwrap_or_default();
+                match existing.into_iter().next() {
+                    Some(pull) => Ok(pull.html_url),
+                    None => Err(err),
+                }
+            }
+            Err(err) => Err(err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::mock::MockServer;
+
+    fn request() -> PullRequest {
+        PullRequest {
+            title: "Fix login".to_string(),
+            body: "Closes #12".to_string(),
+            head: "gensui/worker-001".to_string(),
+            base: "main".to_string(),
+            draft: true,
+        }
+    }
+
+    #[test]
+    fn test_open_pull_request() {
+        let server = MockServer::start(vec![(
+            "POST /repos/owner/repo/pulls",
+            201,
+            r#"{"number": 7, "html_url": "https://github.com/owner/repo/pull/7"}"#.to_string(),
+        )]);
+        let forge = GitHubForge::new(
+            Some(server.url.clone()),
+            "owner/repo".to_string(),
+            Some("secret".to_string()),
+        );
+
+        let url = forge.open(&request()).unwrap();
+        assert_eq!(url, "https://github.com/owner/repo/pull/7");
+        let sent = &server.requests()[0];
+        assert!(sent.contains("Authorization: Bearer secret"));
+        assert!(sent.contains(r#""base":"main""#) && sent.contains(r#""draft":true"#), "{sent}");
+    }
+
+    #[test]
+    fn test_existing_pull_request_is_reused() {
+        let server = MockServer::start(vec![
+            (
+                "POST /repos/owner/repo/pulls",
+                422,
+                r#"{"message": "Validation Failed"}"#.to_string(),
+            ),
+            (
+                "GET /repos/owner/repo/pulls?head=owner:gensui/worker-001&state=open",
+                200,
+                r#"[{"html_url": "https://github.com/owner/repo/pull/3"}]"#.to_string(),
+            ),
+        ]);
+        let forge = GitHubForge::new(Some(server.url.clone()), "owner/repo".to_string(), None);
+        assert_eq!(
+            forge.open(&request()).unwrap(),
+            "https://github.com/owner/repo/pull/3"
+        );
+
+        // Any other validation error is reported as is
+        let forge = GitHubForge::new(Some(server.url.clone()), "owner/other".to_string(), None);
+        let err = forge.open(&request()).unwrap_err().to_string();
+        assert!(err.contains("HTTP 404"), "{err}");
+    }
+}
@@ -0,0 +1,133 @@
+/// GitLab REST API でマージリクエストを作成するフォージ
+use anyhow::{Result, bail};
+use serde::{Deserialize, Serialize};
+
+use super::{ForgeApi, PullRequest};
+use crate::http::{HttpStatus, get_json, post_json};
+
+const DEFAULT_BASE_URL: &str = "https://gitlab.com/api/v4";
+
+#[derive(Serialize)]
+struct NewMergeRequest<'a> {
+    source_branch: &'a str,
+    target_branch: &'a str,
+    title: String,
+    description: &'a str,
+}
+
+#[derive(Deserialize)]
+struct GitLabMergeRequest {
+    web_url: String,
+}
+
+/// GitLab（セルフホストを含む）のフォージ
+pub struct GitLabForge {
+    base_url: String,
+    /// `group/project`
+    project: String,
+    token: Option<String>,
+}
+
+impl GitLabForge {
+    pub fn new(base_url: Option<String>, project: String, token: Option<String>) -> Self {
+        let base_url = base_url.unwrap_or_else(|| DEFAULT_BASE_URL.to_string());
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            project,
+            token,
+        }
+    }
+}
+
+impl ForgeApi for GitLabForge {
+    fn name(&self) -> &str {
+        "gitlab"
+    }
+
+    fn open(&self, request: &PullRequest) -> Result<String> {
+        if self.project.is_empty() {
+            bail!("forge.repo is not configured");
+        }
+        // The project path is a single URL segment with its slashes encoded
+        let project = self.project.replace('/', "%2F");
+        let url = format!("{}/projects/{project}/merge_requests", self.base_url);
+        let mut headers = Vec::new();
+        if let Some(token) = &self.token {
+            headers.push(("PRIVATE-TOKEN", token.as_str()));
+        }
+
+        let body = NewMergeRequest {
+            source_branch: &request.head,
+            target_branch: &request.base,
+            // GitLab marks drafts through the title
+            title: if request.draft {
+                format!("Draft: {}", request.title)
+            } else {
+                request.title.clone()
+            },
+            description: &request.body,
+        };
+        match post_json::<_, GitLabMergeRequest>(&url, &headers, &body) {
+            Ok(merge_request) => Ok(merge_request.web_url),
+            // 409 means a merge request for the branch is already open
+            Err(err) if err.downcast_ref::<HttpStatus>().is_some_and(|e| e.status == 409) => {
+                let existing = get_json::<Vec<GitLabMergeRequest>>(
+                    &format!(
+                        "{url}?source_branch={}&target_branch={}&state=opened",
+                        request.head, request.base
+                    ),
+                    &headers,
+                )?
+                .unwrap_or_default();
+                match existing.into_iter().next() {
+                    Some(merge_request) => Ok(merge_request.web_url),
+                    None => Err(err),
+                }
+            }
+            Err(err) => Err(err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::mock::MockServer;
+
+    #[test]
+    fn test_open_merge_request() {
+        let server = MockServer::start(vec![
+            (
+                "POST /api/v4/projects/group%2Fproject/merge_requests",
+                409,
+                r#"{"message": ["Another open merge request already exists"]}"#.to_string(),
+            ),
+            (
+                "GET /api/v4/projects/group%2Fproject/merge_requests?source_branch=gensui/w&target_branch=main&state=opened",
+                200,
+                r#"[{"web_url": "https://gitlab.com/group/project/-/merge_requests/4"}]"#
+                    .to_string(),
+            ),
+        ]);
+        let forge = GitLabForge::new(
+            Some(format!("{}/api/v4", server.url)),
+            "group/project".to_string(),
+            Some("glpat".to_string()),
+        );
+        let request = PullRequest {
+            title: "Slow build".to_string(),
+            body: String::new(),
+            head: "gensui/w".to_string(),
+            base: "main".to_string(),
+            draft: true,
+        };
+
+        assert_eq!(
+            forge.open(&request).unwrap(),
+            "https://gitlab.com/group/project/-/merge_requests/4"
+        );
+        let sent = &server.requests()[0];
+        assert!(sent.contains("PRIVATE-TOKEN: glpat"));
+        assert!(sent.contains(r#""title":"Draft: Slow build""#), "{sent}");
+    }
+}
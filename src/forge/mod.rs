@@ -0,0 +1,216 @@
+//! フォージ（GitHub / GitLab）連携モジュール
+//!
+//! ワーカーのブランチを設定されたリモートへ push し、プルリクエスト（GitLab では
+//! マージリクエスト）を作成する。API のベース URL は設定で差し替えられる。
+
+mod github;
+mod gitlab;
+
+use std::env;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::sync::Arc;
+
+use anyhow::{Context, Result, bail};
+
+use crate::config::{Config, ForgeKind};
+
+pub use github::GitHubForge;
+pub use gitlab::GitLabForge;
+
+const DEFAULT_REMOTE: &str = "origin";
+
+/// 作成するプルリクエストの内容
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PullRequest {
+    pub title: String,
+    pub body: String,
+    /// 取り込むブランチ（ワーカーのブランチ）
+    pub head: String,
+    /// 取り込み先のブランチ
+    pub base: String,
+    pub draft: bool,
+}
+
+/// プルリクエストを作成する API
+pub trait ForgeApi: Send + Sync {
+    /// 表示用の名前
+    fn name(&self) -> &str;
+
+    /// プルリクエストを作成して URL を返す
+    ///
+    /// 同じブランチのプルリクエストが既に開いていれば、その URL を返す。
+    fn open(&self, request: &PullRequest) -> Result<String>;
+}
+
+/// 設定から組み立てたフォージ API と push 先
+#[derive(Clone)]
+pub struct Forge {
+    api: Arc<dyn ForgeApi>,
+    remote: String,
+    base: Option<String>,
+}
+
+impl Forge {
+    /// `forge` セクション（足りない値は `issues` セクション）から組み立てる
+    ///
+    /// プロバイダが決まらない場合は `None`。取り込み先が設定されていなければ
+    /// `default_base` を使う。
+    pub fn from_config(config: &Config, default_base: Option<String>) -> Option<Self> {
+        let forge = config.forge.effective(&config.issues)?;
+        let repo = forge.repo.clone().unwrap_or_default();
+        let token = |default_env: &str| {
+            let name = forge.token_env.as_deref().unwrap_or(default_env);
+            env::var(name).ok().filter(|token| !token.is_empty())
+        };
+        let api: Arc<dyn ForgeApi> = match forge.provider? {
+            ForgeKind::Github => Arc::new(GitHubForge::new(
+                forge.base_url.clone(),
+                repo,
+                token("GITHUB_TOKEN"),
+            )),
+            ForgeKind::Gitlab => Arc::new(GitLabForge::new(
+                forge.base_url.clone(),
+                repo,
+                token("GITLAB_TOKEN"),
+            )),
+        };
+        Some(Self {
+            api,
+            remote: forge.remote.unwrap_or_else(|| DEFAULT_REMOTE.to_string()),
+            base: forge.base.or(default_base),
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        self.api.name()
+    }
+
+    pub fn remote(&self) -> &str {
+        &self.remote
+    }
+
+    /// ステップで指定されなかったときの取り込み先
+    pub fn base(&self) -> Option<&str> {
+        self.base.as_deref()
+    }
+
+    /// `request.head` を push してプルリクエストを作成し、その URL を返す
+    pub fn publish(&self, worktree: &Path, request: &PullRequest) -> Result<String> {
+        if request.base.is_empty() {
+            bail!("no base branch for the pull request; set forge.base");
+        }
+        push_branch(worktree, &self.remote, &request.head)?;
+        self.api
+            .open(request)
+            .with_context(|| format!("failed to open a pull request on {}", self.api.name()))
+    }
+}
+
+/// `branch` を `remote` へ push し、上流ブランチとして設定する
+fn push_branch(worktree: &Path, remote: &str, branch: &str) -> Result<()> {
+    let output = Command::new("git")
+        .args(["push", "--set-upstream", remote, branch])
+        .current_dir(worktree)
+        // Fail instead of waiting for credentials nobody can type in
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .output()
+        .context("failed to execute git push")?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!("git push {remote} {branch} failed: {}", stderr.trim());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{ForgeConfig, IssueProviderKind, IssueTrackerConfig};
+    use crate::http::mock::MockServer;
+    use crate::worker::git::fixture::{init_repo, run_git};
+    use std::fs;
+
+    #[test]
+    fn test_from_config_inherits_issue_tracker() {
+        let mut config = Config::default();
+        assert!(Forge::from_config(&config, None).is_none());
+
+        config.issues = IssueTrackerConfig {
+            provider: IssueProviderKind::Gitlab,
+            repo: Some("group/project".to_string()),
+            ..Default::default()
+        };
+        let forge = Forge::from_config(&config, Some("main".to_string())).unwrap();
+        assert_eq!(forge.name(), "gitlab");
+        assert_eq!(forge.remote(), "origin");
+        assert_eq!(forge.base(), Some("main"));
+
+        config.forge = ForgeConfig {
+            provider: Some(ForgeKind::Github),
+            remote: Some("upstream".to_string()),
+            base: Some("develop".to_string()),
+            ..Default::default()
+        };
+        let forge = Forge::from_config(&config, Some("main".to_string())).unwrap();
+        assert_eq!(forge.name(), "github");
+        assert_eq!(forge.remote(), "upstream");
+        assert_eq!(forge.base(), Some("develop"));
+    }
+
+    #[test]
+    fn test_publish_pushes_branch_and_opens_pull_request() {
+        let root = env::temp_dir().join(format!("gensui-forge-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let (remote, work) = (root.join("remote.git"), root.join("work"));
+        fs::create_dir_all(&remote).unwrap();
+        run_git(&remote, &["init", "-q", "--bare"]);
+        init_repo(&work);
+        run_git(&work, &["commit", "-q", "--allow-empty", "-m", "work"]);
+        run_git(&work, &["branch", "-m", "gensui/worker-001"]);
+        run_git(&work, &["remote", "add", "origin", &remote.to_string_lossy()]);
+
+        let server = MockServer::start(vec![(
+            "POST /repos/owner/repo/pulls",
+            201,
+            r#"{"html_url": "https://github.example/owner/repo/pull/7"}"#.to_string(),
+        )]);
+        let config = Config {
+            forge: ForgeConfig {
+                provider: Some(ForgeKind::Github),
+                repo: Some("owner/repo".to_string()),
+                base_url: Some(server.url.clone()),
+                token_env: Some("GENSUI_TEST_UNSET_TOKEN".to_string()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let forge = Forge::from_config(&config, Some("main".to_string())).unwrap();
+        let request = PullRequest {
+            title: "Fix login".to_string(),
+            body: "Closes #12".to_string(),
+            head: "gensui/worker-001".to_string(),
+            base: "main".to_string(),
+            draft: false,
+        };
+
+        let url = forge.publish(&work, &request).unwrap();
+        assert_eq!(url, "https://github.example/owner/repo/pull/7");
+        run_git(&remote, &["rev-parse", "--verify", "refs/heads/gensui/worker-001"]);
+        let requests = server.requests();
+        assert!(requests[0].contains(r#""head":"gensui/worker-001""#), "{}", requests[0]);
+
+        // Without a base branch nothing is pushed or requested
+        let err = forge
+            .publish(&work, &PullRequest { base: String::new(), ..request })
+            .unwrap_err();
+        assert!(err.to_string().contains("forge.base"));
+        assert_eq!(server.requests().len(), 1);
+
+        let _ = fs::remove_dir_all(root);
+    }
+}
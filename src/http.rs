@@ -0,0 +1,150 @@
+/// Issue トラッカー・フォージ API 用の HTTP ヘルパ
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+const HTTP_TIMEOUT: Duration = Duration::from_secs(10);
+const USER_AGENT: &str = concat!("gensui/", env!("CARGO_PKG_VERSION"));
+
+/// 成功以外のステータスが返ったリクエスト
+#[derive(Debug, thiserror::Error)]
+#[error("{url} returned HTTP {status}: {body}")]
+pub struct HttpStatus {
+    pub url: String,
+    pub status: u16,
+    pub body: String,
+}
+
+/// `url` を GET して JSON をデコードする（404 は `None`）
+pub fn get_json<T: DeserializeOwned>(url: &str, headers: &[(&str, &str)]) -> Result<Option<T>> {
+    let mut request = ureq::get(url)
+        .timeout(HTTP_TIMEOUT)
+        .set("User-Agent", USER_AGENT);
+    for (name, value) in headers {
+        request = request.set(name, value);
+    }
+    match read_json(url, request.call()) {
+        Err(err) if err.downcast_ref::<HttpStatus>().is_some_and(|e| e.status == 404) => Ok(None),
+        result => result.map(Some),
+    }
+}
+
+/// `body` を JSON で POST してレスポンスをデコードする
+///
+/// 成功以外のステータスは [`HttpStatus`] エラーになる。
+pub fn post_json<B: Serialize, T: DeserializeOwned>(
+    url: &str,
+    headers: &[(&str, &str)],
+    body: &B,
+) -> Result<T> {
+    let mut request = ureq::post(url)
+        .timeout(HTTP_TIMEOUT)
+        .set("User-Agent", USER_AGENT)
+        .set("Content-Type", "application/json");
+    for (name, value) in headers {
+        request = request.set(name, value);
+    }
+    let payload = serde_json::to_string(body).context("failed to encode request body")?;
+    read_json(url, request.send_string(&payload))
+}
+
+fn read_json<T: DeserializeOwned>(
+    url: &str,
+    response: Result<ureq::Response, ureq::Error>,
+) -> Result<T> {
+    match response {
+        Ok(response) => {
+            let text = response
+                .into_string()
+                .with_context(|| format!("failed to read response from {url}"))?;
+            serde_json::from_str(&text).with_context(|| format!("unexpected response from {url}"))
+        }
+        Err(ureq::Error::Status(status, response)) => Err(HttpStatus {
+            url: url.to_string(),
+            status,
+            body: response.into_string().unwrap_or_default().trim().to_string(),
+        }
+        .into()),
+        Err(err) => Err(err).with_context(|| format!("request to {url} failed")),
+    }
+}
+
+/// テスト用の HTTP モックサーバ
+#[cfg(test)]
+pub mod mock {
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+
+    /// `(method path, status, body)` の組を返すサーバ。受け取ったリクエストを記録する
+    ///
+    /// ルートの先頭に `POST ` などのメソッドが無ければ、どのメソッドにも一致する。
+    pub struct MockServer {
+        pub url: String,
+        requests: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl MockServer {
+        pub fn start(routes: Vec<(&'static str, u16, String)>) -> Self {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let url = format!("http://{}", listener.local_addr().unwrap());
+            let requests = Arc::new(Mutex::new(Vec::new()));
+            let recorded = Arc::clone(&requests);
+            thread::spawn(move || {
+                for stream in listener.incoming() {
+                    let Ok(mut stream) = stream else { continue };
+                    let mut reader = BufReader::new(stream.try_clone().unwrap());
+                    let mut head = String::new();
+                    let mut content_length = 0;
+                    loop {
+                        let mut line = String::new();
+                        if reader.read_line(&mut line).unwrap_or(0) == 0 || line == "\r\n" {
+                            break;
+                        }
+                        if let Some((name, value)) = line.split_once(':')
+                            && name.eq_ignore_ascii_case("content-length")
+                        {
+                            content_length = value.trim().parse().unwrap_or(0);
+                        }
+                        head.push_str(&line);
+                    }
+                    let mut body = vec![0; content_length];
+                    let _ = reader.read_exact(&mut body);
+
+                    let mut parts = head.split_whitespace();
+                    let method = parts.next().unwrap_or_default().to_string();
+                    let path = parts.next().unwrap_or_default().to_string();
+                    recorded
+                        .lock()
+                        .unwrap()
+                        .push(format!("{head}\r\n{}", String::from_utf8_lossy(&body)));
+                    let (status, body) = routes
+                        .iter()
+                        .find(|(route, _, _)| match route.split_once(' ') {
+                            Some((route_method, route_path)) => {
+                                route_method == method && route_path == path
+                            }
+                            None => *route == path,
+                        })
+                        .map(|(_, status, body)| (*status, body.clone()))
+                        .unwrap_or((404, "{}".to_string()));
+                    let _ = write!(
+                        stream,
+                        "HTTP/1.1 {status} X\r\nContent-Type: application/json\r\n\
+                         Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                        body.len()
+                    );
+                }
+            });
+            Self { url, requests }
+        }
+
+        /// 受け取ったリクエストの先頭行・ヘッダ・本文
+        pub fn requests(&self) -> Vec<String> {
+            self.requests.lock().unwrap().clone()
+        }
+    }
+}
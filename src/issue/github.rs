@@ -0,0 +1,172 @@
+/// GitHub REST API から Issue を取得するプロバイダ
+use anyhow::{Result, bail};
+use serde::Deserialize;
+
+use super::{Issue, IssueComment, IssueProvider, issue_number};
+use crate::http::get_json;
+
+const DEFAULT_BASE_URL: &str = "https://api.github.com";
+
+#[derive(Deserialize)]
+struct GitHubIssue {
+    title: String,
+    #[serde(default)]
+    body: Option<String>,
+    #[serde(default)]
+    labels: Vec<GitHubLabel>,
+    #[serde(default)]
+    comments: usize,
+}
+
+#[derive(Deserialize)]
+struct GitHubLabel {
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct GitHubComment {
+    #[serde(default)]
+    user: Option<GitHubUser>,
+    #[serde(default)]
+    body: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct GitHubUser {
+    login: String,
+}
+
+/// GitHub（GitHub Enterprise を含む）の Issue プロバイダ
+pub struct GitHubIssueProvider {
+    base_url: String,
+    /// `owner/repo`
+    repo: String,
+    token: Option<String>,
+}
+
+impl GitHubIssueProvider {
+    pub fn new(base_url: Option<String>, repo: String, token: Option<String>) -> Self {
+        let base_url = base_url.unwrap_or_else(|| DEFAULT_BASE_URL.to_string());
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            repo,
+            token,
+        }
+    }
+
+    fn get<T: serde::de::DeserializeOwned>(&self, path: &str) -> Result<Option<T>> {
+        let url = format!("{}/repos/{}{path}", self.base_url, self.repo);
+        let authorization = self.token.as_ref().map(|token| format!("Bearer {token}"));
+        let mut headers = vec![
+            ("Accept", "application/vnd.github+json"),
+            ("X-GitHub-Api-Version", "2022-11-28"),
+        ];
+        if let Some(authorization) = &authorization {
+            headers.push(("Authorization", authorization));
+        }
+        get_json(&url, &headers)
+    }
+}
+
+impl IssueProvider for GitHubIssueProvider {
+    fn name(&self) -> &str {
+        "github"
+    }
+
+    fn fetch(&self, reference: &str) -> Result<Option<Issue>> {
+        if self.repo.is_empty() {
+            bail!("issues.repo is not configured");
+        }
+        let Some(number) = issue_number(reference) else {
+            return Ok(None);
+        };
+        let Some(issue) = self.get::<GitHubIssue>(&format!("/issues/{number}"))? else {
+            return Ok(None);
+        };
+
+        let comments = if issue.comments > 0 {
+            self.get::<Vec<GitHubComment>>(&format!("/issues/{number}/comments?per_page=100"))?
+                .unwrap_or_default()
+        } else {
+            Vec::new()
+        };
+
+        Ok(Some(Issue {
+            id: number,
+            title: issue.title,
+            body: issue.body.unwrap_or_default(),
+            labels: issue.labels.into_iter().map(|label| label.name).collect(),
+            acceptance_criteria: Vec::new(),
+            comments: comments
+                .into_iter()
+                .map(|comment| IssueComment {
+                    author: comment.user.map(|user| user.login).unwrap_or_default(),
+                    body: comment.body.unwrap_or_default(),
+                })
+                .collect(),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::mock::MockServer;
+
+    #[test]
+    fn test_fetch_issue_and_comments() {
+        let server = MockServer::start(vec![
+            (
+                "/repos/owner/repo/issues/12",
+                200,
+                r#"{"title": "Fix login", "body": "It crashes", "comments": 1,
+                    "labels": [{"name": "bug"}, {"name": "auth"}]}"#
+                    .to_string(),
+            ),
+            (
+                "/repos/owner/repo/issues/12/comments?per_page=100",
+                200,
+                r#"[{"user": {"login": "alice"}, "body": "Also on mobile"}]"#.to_string(),
+            ),
+        ]);
+        let provider = GitHubIssueProvider::new(
+            Some(format!("{}/", server.url)),
+            "owner/repo".to_string(),
+            Some("secret".to_string()),
+        );
+
+        let issue = provider.fetch("#12").unwrap().unwrap();
+        assert_eq!(issue.id, "12");
+        assert_eq!(issue.title, "Fix login");
+        assert_eq!(issue.body, "It crashes");
+        assert_eq!(issue.labels, vec!["bug", "auth"]);
+        assert_eq!(
+            issue.comments,
+            vec![IssueComment {
+                author: "alice".to_string(),
+                body: "Also on mobile".to_string(),
+            }]
+        );
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[0].contains("Authorization: Bearer secret"));
+
+        // Unknown issues and non-numeric references are not errors
+        assert!(provider.fetch("#13").unwrap().is_none());
+        assert!(provider.fetch("login-bug").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_fetch_reports_http_errors() {
+        let server = MockServer::start(vec![(
+            "/repos/owner/repo/issues/1",
+            401,
+            r#"{"message": "Bad credentials"}"#.to_string(),
+        )]);
+        let provider = GitHubIssueProvider::new(Some(server.url.clone()), "owner/repo".into(), None);
+
+        let err = provider.fetch("1").unwrap_err().to_string();
+        assert!(err.contains("HTTP 401"), "{err}");
+        assert!(!server.requests()[0].contains("Authorization"));
+    }
+}
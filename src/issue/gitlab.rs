@@ -0,0 +1,146 @@
+/// GitLab REST API から Issue を取得するプロバイダ
+use anyhow::{Result, bail};
+use serde::Deserialize;
+
+use super::{Issue, IssueComment, IssueProvider, issue_number};
+use crate::http::get_json;
+
+const DEFAULT_BASE_URL: &str = "https://gitlab.com/api/v4";
+
+#[derive(Deserialize)]
+struct GitLabIssue {
+    title: String,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    labels: Vec<String>,
+    #[serde(default)]
+    user_notes_count: usize,
+}
+
+#[derive(Deserialize)]
+struct GitLabNote {
+    #[serde(default)]
+    body: String,
+    #[serde(default)]
+    author: Option<GitLabUser>,
+    /// Notes generated by GitLab itself, e.g. label changes.
+    #[serde(default)]
+    system: bool,
+}
+
+#[derive(Deserialize)]
+struct GitLabUser {
+    username: String,
+}
+
+/// GitLab（セルフホストを含む）の Issue プロバイダ
+pub struct GitLabIssueProvider {
+    base_url: String,
+    /// `group/project`
+    project: String,
+    token: Option<String>,
+}
+
+impl GitLabIssueProvider {
+    pub fn new(base_url: Option<String>, project: String, token: Option<String>) -> Self {
+        let base_url = base_url.unwrap_or_else(|| DEFAULT_BASE_URL.to_string());
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            project,
+            token,
+        }
+    }
+
+    fn get<T: serde::de::DeserializeOwned>(&self, path: &str) -> Result<Option<T>> {
+        // The project path is a single URL segment with its slashes encoded
+        let project = self.project.replace('/', "%2F");
+        let url = format!("{}/projects/{project}{path}", self.base_url);
+        let mut headers = Vec::new();
+        if let Some(token) = &self.token {
+            headers.push(("PRIVATE-TOKEN", token.as_str()));
+        }
+        get_json(&url, &headers)
+    }
+}
+
+impl IssueProvider for GitLabIssueProvider {
+    fn name(&self) -> &str {
+        "gitlab"
+    }
+
+    fn fetch(&self, reference: &str) -> Result<Option<Issue>> {
+        if self.project.is_empty() {
+            bail!("issues.repo is not configured");
+        }
+        let Some(iid) = issue_number(reference) else {
+            return Ok(None);
+        };
+        let Some(issue) = self.get::<GitLabIssue>(&format!("/issues/{iid}"))? else {
+            return Ok(None);
+        };
+
+        let notes = if issue.user_notes_count > 0 {
+            self.get::<Vec<GitLabNote>>(&format!("/issues/{iid}/notes?sort=asc&per_page=100"))?
+                .unwrap_or_default()
+        } else {
+            Vec::new()
+        };
+
+        Ok(Some(Issue {
+            id: iid,
+            title: issue.title,
+            body: issue.description.unwrap_or_default(),
+            labels: issue.labels,
+            acceptance_criteria: Vec::new(),
+            comments: notes
+                .into_iter()
+                .filter(|note| !note.system)
+                .map(|note| IssueComment {
+                    author: note.author.map(|user| user.username).unwrap_or_default(),
+                    body: note.body,
+                })
+                .collect(),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::mock::MockServer;
+
+    #[test]
+    fn test_fetch_issue_and_notes() {
+        let server = MockServer::start(vec![
+            (
+                "/api/v4/projects/group%2Fproject/issues/3",
+                200,
+                r#"{"title": "Slow build", "description": "Takes ages",
+                    "labels": ["ci"], "user_notes_count": 2}"#
+                    .to_string(),
+            ),
+            (
+                "/api/v4/projects/group%2Fproject/issues/3/notes?sort=asc&per_page=100",
+                200,
+                r#"[{"body": "added ~ci label", "author": {"username": "bot"}, "system": true},
+                    {"body": "Cache deps?", "author": {"username": "bob"}, "system": false}]"#
+                    .to_string(),
+            ),
+        ]);
+        let provider = GitLabIssueProvider::new(
+            Some(format!("{}/api/v4", server.url)),
+            "group/project".to_string(),
+            Some("glpat".to_string()),
+        );
+
+        let issue = provider.fetch("#3").unwrap().unwrap();
+        assert_eq!(issue.title, "Slow build");
+        assert_eq!(issue.body, "Takes ages");
+        assert_eq!(issue.labels, vec!["ci"]);
+        assert_eq!(issue.comments.len(), 1);
+        assert_eq!(issue.comments[0].author, "bob");
+        assert!(server.requests()[0].contains("PRIVATE-TOKEN: glpat"));
+        assert!(provider.fetch("#4").unwrap().is_none());
+    }
+}
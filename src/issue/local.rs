@@ -0,0 +1,154 @@
+/// `.gensui/issues/<id>.md` を読むローカル Issue プロバイダ
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+use super::{Issue, IssueProvider, issue_key};
+
+/// Markdown ファイル先頭の YAML フロントマター
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct FrontMatter {
+    title: Option<String>,
+    labels: Vec<String>,
+    #[serde(alias = "acceptance")]
+    acceptance_criteria: Vec<String>,
+}
+
+/// Markdown ファイルを Issue として扱うプロバイダ
+pub struct LocalIssueProvider {
+    dir: PathBuf,
+}
+
+impl LocalIssueProvider {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+}
+
+impl IssueProvider for LocalIssueProvider {
+    fn name(&self) -> &str {
+        "local"
+    }
+
+    fn fetch(&self, reference: &str) -> Result<Option<Issue>> {
+        let Some(id) = issue_key(reference) else {
+            return Ok(None);
+        };
+        let path = self.dir.join(format!("{id}.md"));
+        if !path.is_file() {
+            return Ok(None);
+        }
+        let contents = fs::read_to_string(&path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        parse_issue(&id, &contents)
+            .with_context(|| format!("failed to parse {}", path.display()))
+            .map(Some)
+    }
+}
+
+/// フロントマター付き Markdown を Issue に変換する
+///
+/// フロントマターに `title` が無ければ本文最初の見出し、それも無ければ ID を使う。
+fn parse_issue(id: &str, contents: &str) -> Result<Issue> {
+    let (front, body) = split_front_matter(contents);
+    let front: FrontMatter = match front {
+        Some(yaml) if !yaml.trim().is_empty() => {
+            serde_yaml::from_str(yaml).context("invalid front matter")?
+        }
+        _ => FrontMatter::default(),
+    };
+
+    let body = body.trim().to_string();
+    let title = front
+        .title
+        .filter(|title| !title.trim().is_empty())
+        .or_else(|| {
+            body.lines()
+                .find_map(|line| line.strip_prefix("# "))
+                .map(|heading| heading.trim().to_string())
+        })
+        .unwrap_or_else(|| id.to_string());
+
+    Ok(Issue {
+        id: id.to_string(),
+        title,
+        body,
+        labels: front.labels,
+        acceptance_criteria: front.acceptance_criteria,
+        comments: Vec::new(),
+    })
+}
+
+/// `---` で囲まれた先頭ブロックと残りの本文に分割する
+fn split_front_matter(contents: &str) -> (Option<&str>, &str) {
+    let contents = contents.strip_prefix('\u{feff}').unwrap_or(contents);
+    let Some(rest) = contents
+        .strip_prefix("---\n")
+        .or_else(|| contents.strip_prefix("---\r\n"))
+    else {
+        return (None, contents);
+    };
+
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end() == "---" {
+            return (Some(&rest[..offset]), &rest[offset + line.len()..]);
+        }
+        offset += line.len();
+    }
+    (None, contents)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_issue_with_front_matter() {
+        let contents = "---\n\
+                        title: Fix login crash\n\
+                        labels: [bug, auth]\n\
+                        acceptance_criteria:\n  - No crash on empty password\n\
+                        ---\n\n\
+                        Steps to reproduce...\n";
+        let issue = parse_issue("12", contents).unwrap();
+        assert_eq!(issue.title, "Fix login crash");
+        assert_eq!(issue.labels, vec!["bug", "auth"]);
+        assert_eq!(issue.acceptance_criteria, vec!["No crash on empty password"]);
+        assert_eq!(issue.body, "Steps to reproduce...");
+    }
+
+    #[test]
+    fn test_parse_issue_without_front_matter_uses_heading() {
+        let issue = parse_issue("7", "# Add dark mode\n\nDetails").unwrap();
+        assert_eq!(issue.title, "Add dark mode");
+        assert!(issue.labels.is_empty());
+
+        let issue = parse_issue("8", "just a body").unwrap();
+        assert_eq!(issue.title, "8");
+        assert_eq!(issue.body, "just a body");
+    }
+
+    #[test]
+    fn test_parse_issue_rejects_invalid_front_matter() {
+        assert!(parse_issue("1", "---\nlabels: {oops\n---\nbody").is_err());
+    }
+
+    #[test]
+    fn test_fetch_reads_issue_file() {
+        let dir = std::env::temp_dir().join(format!("gensui-issues-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("12.md"), "---\ntitle: From file\n---\nbody").unwrap();
+
+        let provider = LocalIssueProvider::new(dir.clone());
+        let issue = provider.fetch("#12").unwrap().unwrap();
+        assert_eq!(issue.id, "12");
+        assert_eq!(issue.title, "From file");
+        assert!(provider.fetch("#13").unwrap().is_none());
+
+        let _ = fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,231 @@
+//! Issue トラッカー連携モジュール
+//!
+//! ワーカーに紐付けた Issue 参照（`#12` など）からタイトル・本文・ラベル・コメントを取得し、
+//! プロンプトのプレースホルダとワーカー一覧の表示に使う。取得元はローカルの Markdown
+//! ファイル・GitHub・GitLab から設定で選ぶ。
+
+mod github;
+mod gitlab;
+mod local;
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::config::{IssueProviderKind, IssueTrackerConfig};
+
+pub use github::GitHubIssueProvider;
+pub use gitlab::GitLabIssueProvider;
+pub use local::LocalIssueProvider;
+
+/// ローカル Issue ファイルを置くディレクトリ（リポジトリ直下からの相対パス）
+pub const LOCAL_ISSUES_DIR: &str = ".gensui/issues";
+
+/// リモートから取得した Issue のキャッシュ（リポジトリ直下からの相対パス）
+pub const CACHE_DIR: &str = ".gensui/cache/issues";
+
+/// キャッシュした Issue を使う期間（過ぎたらプロバイダから取り直す）
+pub const CACHE_TTL: Duration = Duration::from_secs(10 * 60);
+
+/// Issue のメタ情報
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Issue {
+    /// Issue を識別するキー（`#12` なら `12`）
+    pub id: String,
+    pub title: String,
+    pub body: String,
+    pub labels: Vec<String>,
+    #[serde(default)]
+    pub acceptance_criteria: Vec<String>,
+    #[serde(default)]
+    pub comments: Vec<IssueComment>,
+}
+
+/// Issue へのコメント
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IssueComment {
+    pub author: String,
+    pub body: String,
+}
+
+/// Issue の取得元
+pub trait IssueProvider: Send + Sync {
+    /// 表示用の名前
+    fn name(&self) -> &str;
+
+    /// Issue 参照からメタ情報を取得する
+    ///
+    /// 該当する Issue が存在しない場合は `Ok(None)` を返す。
+    fn fetch(&self, reference: &str) -> Result<Option<Issue>>;
+}
+
+/// 設定に従って Issue を取得し、リモートの Issue はディスクにキャッシュする
+pub struct IssueTracker {
+    provider: Box<dyn IssueProvider>,
+    /// キャッシュの保存先（ローカルプロバイダでは使わない）
+    cache_dir: Option<PathBuf>,
+}
+
+impl IssueTracker {
+    pub fn from_config(repo_root: &Path, config: &IssueTrackerConfig) -> Self {
+        let repo = config.repo.clone().unwrap_or_default();
+        let token = |default_env: &str| {
+            let name = config.token_env.as_deref().unwrap_or(default_env);
+            env::var(name).ok().filter(|token| !token.is_empty())
+        };
+        let (provider, cache_dir): (Box<dyn IssueProvider>, _) = match config.provider {
+            IssueProviderKind::Local => (
+                Box::new(LocalIssueProvider::new(repo_root.join(LOCAL_ISSUES_DIR))),
+                None,
+            ),
+            IssueProviderKind::Github => (
+                Box::new(GitHubIssueProvider::new(
+                    config.base_url.clone(),
+                    repo.clone(),
+                    token("GITHUB_TOKEN"),
+                )),
+                Some(repo_root.join(CACHE_DIR).join("github").join(&repo)),
+            ),
+            IssueProviderKind::Gitlab => (
+                Box::new(GitLabIssueProvider::new(
+                    config.base_url.clone(),
+                    repo.clone(),
+                    token("GITLAB_TOKEN"),
+                )),
+                Some(repo_root.join(CACHE_DIR).join("gitlab").join(&repo)),
+            ),
+        };
+        Self {
+            provider,
+            cache_dir,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        self.provider.name()
+    }
+
+    /// プロバイダから最新の Issue を取得し、キャッシュを更新する
+    pub fn fetch(&self, reference: &str) -> Result<Option<Issue>> {
+        let issue = self.provider.fetch(reference)?;
+        if let Some(issue) = &issue
+            && let Some(path) = self.cache_path(&issue.id)
+        {
+            // The cache only saves requests, so failing to write it is not an error
+            let _ = write_cache(&path, issue);
+        }
+        Ok(issue)
+    }
+
+    /// `CACHE_TTL` 以内にキャッシュした Issue を返す（プロバイダには問い合わせない）
+    pub fn cached(&self, reference: &str) -> Option<Issue> {
+        let path = issue_key(reference).and_then(|key| self.cache_path(&key))?;
+        let modified = fs::metadata(&path).and_then(|meta| meta.modified()).ok()?;
+        if SystemTime::now().duration_since(modified).unwrap_or_default() > CACHE_TTL {
+            return None;
+        }
+        let data = fs::read_to_string(path).ok()?;
+        serde_json::from_str(&data).ok()
+    }
+
+    fn cache_path(&self, key: &str) -> Option<PathBuf> {
+        self.cache_dir
+            .as_ref()
+            .map(|dir| dir.join(format!("{key}.json")))
+    }
+}
+
+fn write_cache(path: &Path, issue: &Issue) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, serde_json::to_string_pretty(issue)?)?;
+    Ok(())
+}
+
+/// Issue 番号だけを受け付ける参照のキー（GitHub / GitLab 用）
+fn issue_number(reference: &str) -> Option<String> {
+    issue_key(reference).filter(|key| key.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// Issue 参照からファイル名などに使うキーを取り出す
+///
+/// `#12`・`12`・Issue の URL（末尾の番号）・`login-bug` のような名前を受け付け、
+/// パス区切りや `..` を含む参照は `None` とする。
+pub fn issue_key(reference: &str) -> Option<String> {
+    let reference = reference.trim().trim_end_matches('/');
+    let last = reference.rsplit('/').next().unwrap_or(reference);
+    let key = last.strip_prefix('#').unwrap_or(last);
+    let valid = !key.is_empty()
+        && !key.starts_with('.')
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    valid.then(|| key.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::mock::MockServer;
+
+    #[test]
+    fn test_tracker_caches_remote_issues() {
+        let server = MockServer::start(vec![(
+            "/repos/owner/repo/issues/5",
+            200,
+            r#"{"title": "Cached", "body": null, "labels": [], "comments": 0}"#.to_string(),
+        )]);
+        let root = env::temp_dir().join(format!("gensui-issue-cache-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let config = IssueTrackerConfig {
+            provider: IssueProviderKind::Github,
+            repo: Some("owner/repo".to_string()),
+            base_url: Some(server.url.clone()),
+            token_env: Some("GENSUI_TEST_UNSET_TOKEN".to_string()),
+        };
+        let tracker = IssueTracker::from_config(&root, &config);
+
+        assert!(tracker.cached("#5").is_none());
+        assert_eq!(tracker.fetch("#5").unwrap().unwrap().title, "Cached");
+        assert!(root.join(CACHE_DIR).join("github/owner/repo/5.json").is_file());
+        // A later lookup is answered from the cache
+        assert_eq!(tracker.cached("5").unwrap().title, "Cached");
+        assert_eq!(server.requests().len(), 1);
+        // An explicit fetch always asks the provider
+        tracker.fetch("#5").unwrap();
+        assert_eq!(server.requests().len(), 2);
+
+        // An expired entry is fetched again and then cached afresh
+        let path = root.join(CACHE_DIR).join("github/owner/repo/5.json");
+        let expired = SystemTime::now() - CACHE_TTL - Duration::from_secs(1);
+        fs::File::options().write(true).open(&path).unwrap().set_modified(expired).unwrap();
+        assert!(tracker.cached("#5").is_none());
+        tracker.fetch("#5").unwrap();
+        assert_eq!(server.requests().len(), 3);
+        assert_eq!(tracker.cached("#5").unwrap().title, "Cached");
+        assert!(tracker.fetch("#6").unwrap().is_none());
+        assert!(tracker.cached("#6").is_none());
+
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn test_issue_key() {
+        assert_eq!(issue_key("#12").as_deref(), Some("12"));
+        assert_eq!(issue_key(" 12 ").as_deref(), Some("12"));
+        assert_eq!(
+            issue_key("https://github.com/owner/repo/issues/34").as_deref(),
+            Some("34")
+        );
+        assert_eq!(issue_key("login-bug").as_deref(), Some("login-bug"));
+        assert_eq!(issue_key(".."), None);
+        assert_eq!(issue_key(".hidden"), None);
+        assert_eq!(issue_key("fix login"), None);
+        assert_eq!(issue_key(""), None);
+    }
+}
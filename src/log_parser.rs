@@ -11,6 +11,7 @@ pub struct LogParser {
     // Parser state
     current_step_index: Option<usize>,
     current_step_name: Option<String>,
+    current_attempt: Option<(usize, usize)>,
     current_prompt: Vec<String>,
     current_result: Vec<String>,
     current_thought: Vec<String>,
@@ -37,6 +38,9 @@ impl LogParser {
         if line.starts_with("[STEP_START:") {
             self.handle_step_start(line);
             None
+        } else if line.starts_with("[STEP_ATTEMPT:") {
+            self.handle_step_attempt(line);
+            None
         } else if line == "─── Prompt ───" || line == "[PROMPT_START]" {
             self.start_prompt_section();
             None
@@ -86,12 +90,25 @@ impl LogParser {
                 if let Ok(idx) = parts[0].parse::<usize>() {
                     self.current_step_index = Some(idx);
                     self.current_step_name = Some(parts[1].to_string());
+                    self.current_attempt = None;
                     self.reset_buffers();
                 }
             }
         }
     }
 
+    /// 試行番号マーカー（`[STEP_ATTEMPT:2/3]`）を処理
+    fn handle_step_attempt(&mut self, line: &str) {
+        let attempt = line
+            .strip_prefix("[STEP_ATTEMPT:")
+            .and_then(|s| s.strip_suffix("]"))
+            .and_then(|s| s.split_once('/'))
+            .and_then(|(n, m)| Some((n.parse().ok()?, m.parse().ok()?)));
+        if attempt.is_some() {
+            self.current_attempt = attempt;
+        }
+    }
+
     /// プロンプトセクションを開始
     fn start_prompt_section(&mut self) {
         self.in_prompt = true;
@@ -170,6 +187,7 @@ impl LogParser {
         let status = match content {
             "Success" => StepStatus::Success,
             "Failed" => StepStatus::Failed,
+            "Skipped" => StepStatus::Skipped,
             _ => StepStatus::Running,
         };
 
@@ -181,11 +199,13 @@ impl LogParser {
                 result_lines: self.current_result.clone(),
                 thought_lines: self.current_thought.clone(),
                 status,
+                attempt: self.current_attempt,
             };
 
             // Reset state after creating entry
             self.current_step_index = None;
             self.current_step_name = None;
+            self.current_attempt = None;
             self.reset_buffers();
 
             Some(entry)
@@ -268,4 +288,44 @@ mod tests {
         assert_eq!(entry.prompt_lines, vec!["Alternate prompt marker"]);
         assert_eq!(entry.result_lines, vec!["Alternate result marker"]);
     }
+
+    #[test]
+    fn test_parse_retry_attempts() {
+        let mut parser = LogParser::new();
+
+        // 1回目の試行（失敗）
+        assert!(parser.parse_line("[STEP_START:0:Flaky]").is_none());
+        assert!(parser.parse_line("[STEP_ATTEMPT:1/2]").is_none());
+        assert!(parser.parse_line("[RESULT_START]").is_none());
+        assert!(parser.parse_line("Error: boom").is_none());
+        assert!(parser.parse_line("[RESULT_END]").is_none());
+        let first = parser.parse_line("[STEP_END:Failed]").unwrap();
+
+        // 2回目の試行（成功）
+        assert!(parser.parse_line("[STEP_START:0:Flaky]").is_none());
+        assert!(parser.parse_line("[STEP_ATTEMPT:2/2]").is_none());
+        let second = parser.parse_line("[STEP_END:Success]").unwrap();
+
+        assert_eq!(first.attempt, Some((1, 2)));
+        assert_eq!(first.status, StepStatus::Failed);
+        assert_eq!(first.result_lines, vec!["Error: boom"]);
+        assert_eq!(second.attempt, Some((2, 2)));
+        assert_eq!(second.status, StepStatus::Success);
+
+        // 試行マーカーのないステップ
+        assert!(parser.parse_line("[STEP_START:1:Once]").is_none());
+        let once = parser.parse_line("[STEP_END:Success]").unwrap();
+        assert_eq!(once.attempt, None);
+    }
+
+    #[test]
+    fn test_parse_skipped_step() {
+        let mut parser = LogParser::new();
+
+        assert!(parser.parse_line("[STEP_START:2:Fix]").is_none());
+        let entry = parser.parse_line("[STEP_END:Skipped]").unwrap();
+
+        assert_eq!(entry.step_name, "Fix");
+        assert_eq!(entry.status, StepStatus::Skipped);
+    }
 }
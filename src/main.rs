@@ -1,5 +1,12 @@
+mod agent;
 mod app;
+mod cli;
 mod config;
+mod daemon;
+mod diff;
+mod forge;
+mod http;
+mod issue;
 mod log_parser;
 mod session_import;
 mod state;
@@ -7,10 +14,11 @@ mod ui;
 mod worker;
 
 use std::io::{self, Stdout};
-use std::process::Command;
+use std::process::{Command, ExitCode};
 use std::time::{Duration, Instant};
 
 use anyhow::Result;
+use clap::Parser;
 use crossterm::event::{self, Event, KeyEventKind};
 use crossterm::terminal::{disable_raw_mode, enable_raw_mode};
 use ratatui::backend::CrosstermBackend;
@@ -18,9 +26,15 @@ use ratatui::Terminal;
 use time::OffsetDateTime;
 
 use app::App;
+use cli::Cli;
 use state::SessionHistory;
 
-fn main() -> Result<()> {
+fn main() -> Result<ExitCode> {
+    let cli = Cli::parse();
+    if let Some(command) = cli.command {
+        return cli::run(command);
+    }
+
     enable_raw_mode()?;
     let mut stdout = io::stdout();
     crossterm::execute!(stdout, crossterm::terminal::EnterAlternateScreen)?;
@@ -33,7 +47,7 @@ fn main() -> Result<()> {
         crossterm::terminal::LeaveAlternateScreen,
         crossterm::cursor::Show
     )?;
-    result
+    result.map(|()| ExitCode::SUCCESS)
 }
 
 fn run_app(terminal: &mut Terminal<CrosstermBackend<Stdout>>) -> Result<()> {
@@ -181,24 +195,27 @@ fn run_app(terminal: &mut Terminal<CrosstermBackend<Stdout>>) -> Result<()> {
                 worker.push_log("  3. セッション詳細を確認".to_string());
                 worker.push_log("".to_string());
 
-                // Check for file changes using git
-                if let Ok(output) = std::process::Command::new("git")
-                    .arg("status")
-                    .arg("--short")
-                    .current_dir(&request.worktree_path)
-                    .output()
+                // Summarise file changes against the base branch
+                let base = crate::worker::determine_base_ref(&app.repo_root)
+                    .unwrap_or_else(|| "HEAD".into());
+                if let Ok(changes) = diff::load(&request.worktree_path, &base)
+                    && !changes.files.is_empty()
                 {
-                    let changes = String::from_utf8_lossy(&output.stdout);
-                    if !changes.trim().is_empty() {
-                        worker.push_log("📝 変更されたファイル:".to_string());
-                        for line in changes.lines().take(10) {
-                            worker.push_log(format!("  {}", line));
-                        }
-                        if changes.lines().count() > 10 {
-                            worker.push_log(format!("  ... あと {} 件", changes.lines().count() - 10));
-                        }
-                        worker.push_log("".to_string());
+                    worker.push_log(format!("📝 変更されたファイル（{}）:", changes.summary()));
+                    for file in changes.files.iter().take(10) {
+                        worker.push_log(format!(
+                            "  {} +{} -{} {}",
+                            file.status.letter(),
+                            file.additions(),
+                            file.deletions(),
+                            file.path
+                        ));
+                    }
+                    if changes.files.len() > 10 {
+                        worker.push_log(format!("  ... あと {} 件", changes.files.len() - 10));
                     }
+                    worker.push_log("  v キーで差分を表示できます".to_string());
+                    worker.push_log("".to_string());
                 }
             }
 
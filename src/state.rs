@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::fs::{self, File, OpenOptions};
 use std::io::{BufRead, BufReader, Write};
 use std::path::PathBuf;
@@ -22,9 +23,41 @@ pub struct WorkerRecord {
     pub snapshot: WorkerSnapshotData,
     pub logs: Vec<String>,
     pub workflow: Workflow,
+    /// Index of the next step to run (equals the number of finished steps
+    /// for linear workflows).
     pub completed_steps: usize,
     #[serde(default)]
     pub session_history: Vec<SessionHistory>,
+    /// Latest outcome of each executed step, keyed by step id.
+    #[serde(default)]
+    pub step_outcomes: HashMap<String, StepOutcome>,
+}
+
+/// Result of the most recent run of a workflow step.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct StepOutcome {
+    pub status: StepOutcomeStatus,
+    /// Process exit code (None when the step did not run a process or it was killed).
+    #[serde(default)]
+    pub exit_code: Option<i32>,
+    /// Captured stdout/stderr of a command step.
+    #[serde(default)]
+    pub stdout: String,
+    /// Final result text reported by an agent step.
+    #[serde(default)]
+    pub result: Option<String>,
+    /// Commit made by a `commit` step or by auto-commit after an agent step.
+    #[serde(default)]
+    pub commit: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum StepOutcomeStatus {
+    #[default]
+    Success,
+    Failed,
+    Skipped,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -32,6 +65,8 @@ pub struct WorkerSnapshotData {
     pub id: usize,
     pub name: String,
     pub issue: Option<String>,
+    #[serde(default)]
+    pub issue_title: Option<String>,
     pub agent: String,
     pub worktree: String,
     pub branch: String,
@@ -42,6 +77,14 @@ pub struct WorkerSnapshotData {
     pub current_step: Option<String>,
     #[serde(default)]
     pub session_id: Option<String>,
+    #[serde(default)]
+    pub priority: i32,
+    /// Pull request (merge request) opened for the worker branch.
+    #[serde(default)]
+    pub pr_url: Option<String>,
+    /// Branch, tag or commit the worker branch was created from.
+    #[serde(default)]
+    pub base_ref: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -93,6 +136,16 @@ pub enum SessionEvent {
     },
 }
 
+impl SessionHistory {
+    /// Text of the last `Result` event, i.e. the agent's final answer.
+    pub fn final_result(&self) -> Option<&str> {
+        self.events.iter().rev().find_map(|event| match event {
+            SessionEvent::Result { text, .. } => Some(text.as_str()),
+            _ => None,
+        })
+    }
+}
+
 impl StateStore {
     pub fn new(base: PathBuf) -> Result<Self> {
         fs::create_dir_all(&base)
@@ -0,0 +1,535 @@
+/// 差分ビューの状態とレンダリング
+use std::collections::HashSet;
+
+use ratatui::layout::{Constraint, Direction, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+
+use crate::diff::{FileDiff, FileStatus, LineKind, WorkerDiff};
+
+use super::helpers::centered_rect;
+
+/// 選択中ファイルの表示行
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DiffRow {
+    /// ハンクの見出し (ハンク番号)
+    Header(usize),
+    /// ハンク内の行 (ハンク番号, 行番号)
+    Line(usize, usize),
+}
+
+/// ワーカーの差分ビュー
+pub struct DiffView {
+    pub worker_name: String,
+    pub diff: WorkerDiff,
+    pub selected_file: usize,
+    /// 選択中ファイル内のカーソル行
+    pub cursor: usize,
+    /// 折りたたんだハンク (ファイル番号, ハンク番号)
+    folded: HashSet<(usize, usize)>,
+}
+
+impl DiffView {
+    pub fn new(worker_name: String, diff: WorkerDiff) -> Self {
+        Self {
+            worker_name,
+            diff,
+            selected_file: 0,
+            cursor: 0,
+            folded: HashSet::new(),
+        }
+    }
+
+    /// 再取得した差分に差し替える（同じファイルを選択し続ける）
+    pub fn replace_diff(&mut self, diff: WorkerDiff) {
+        let path = self.current_file().map(|file| file.path.clone());
+        self.diff = diff;
+        self.folded.clear();
+        self.selected_file = path
+            .and_then(|path| self.diff.files.iter().position(|file| file.path == path))
+            .unwrap_or(0);
+        self.cursor = self.cursor.min(self.rows().len().saturating_sub(1));
+    }
+
+    pub fn current_file(&self) -> Option<&FileDiff> {
+        self.diff.files.get(self.selected_file)
+    }
+
+    pub fn next_file(&mut self) {
+        if self.selected_file + 1 < self.diff.files.len() {
+            self.selected_file += 1;
+            self.cursor = 0;
+        }
+    }
+
+    pub fn prev_file(&mut self) {
+        if self.selected_file > 0 {
+            self.selected_file -= 1;
+            self.cursor = 0;
+        }
+    }
+
+    pub fn move_cursor(&mut self, delta: isize) {
+        let max = self.rows().len().saturating_sub(1);
+        self.cursor = self.cursor.saturating_add_signed(delta).min(max);
+    }
+
+    pub fn cursor_to_end(&mut self) {
+        self.cursor = self.rows().len().saturating_sub(1);
+    }
+
+    /// 次のハンク見出しへ移動
+    pub fn next_hunk(&mut self) {
+        let rows = self.rows();
+        if let Some(pos) = rows
+            .iter()
+            .enumerate()
+            .skip(self.cursor + 1)
+            .find(|(_, row)| matches!(row, DiffRow::Header(_)))
+            .map(|(pos, _)| pos)
+        {
+            self.cursor = pos;
+        }
+    }
+
+    /// 前のハンク見出しへ移動
+    pub fn prev_hunk(&mut self) {
+        let rows = self.rows();
+        if let Some(pos) = rows
+            .iter()
+            .take(self.cursor)
+            .rposition(|row| matches!(row, DiffRow::Header(_)))
+        {
+            self.cursor = pos;
+        }
+    }
+
+    /// カーソル位置のハンクを折りたたむ／展開する
+    pub fn toggle_fold(&mut self) {
+        let Some(hunk) = self.cursor_hunk() else {
+            return;
+        };
+        let key = (self.selected_file, hunk);
+        if !self.folded.remove(&key) {
+            self.folded.insert(key);
+        }
+        // Keep the cursor on the hunk header so the next toggle hits the same hunk
+        if let Some(pos) = self.rows().iter().position(|row| *row == DiffRow::Header(hunk)) {
+            self.cursor = pos;
+        }
+    }
+
+    /// 選択中ファイルのハンクをすべて折りたたむ（すべて折りたたみ済みなら展開する）
+    pub fn toggle_fold_all(&mut self) {
+        let file = self.selected_file;
+        let count = self.current_file().map_or(0, |file| file.hunks.len());
+        if (0..count).all(|hunk| self.folded.contains(&(file, hunk))) {
+            self.folded.retain(|(folded_file, _)| *folded_file != file);
+        } else {
+            self.folded.extend((0..count).map(|hunk| (file, hunk)));
+        }
+        self.cursor = self.cursor.min(self.rows().len().saturating_sub(1));
+        if let Some(hunk) = self.cursor_hunk()
+            && let Some(pos) = self.rows().iter().position(|row| *row == DiffRow::Header(hunk))
+        {
+            self.cursor = pos;
+        }
+    }
+
+    fn cursor_hunk(&self) -> Option<usize> {
+        match self.rows().get(self.cursor)? {
+            DiffRow::Header(hunk) | DiffRow::Line(hunk, _) => Some(*hunk),
+        }
+    }
+
+    fn rows(&self) -> Vec<DiffRow> {
+        let Some(file) = self.current_file() else {
+            return Vec::new();
+        };
+        let mut rows = Vec::new();
+        for (idx, hunk) in file.hunks.iter().enumerate() {
+            rows.push(DiffRow::Header(idx));
+            if !self.folded.contains(&(self.selected_file, idx)) {
+                rows.extend((0..hunk.lines.len()).map(|line| DiffRow::Line(idx, line)));
+            }
+        }
+        rows
+    }
+}
+
+/// 差分ビューをレンダリング
+pub fn render_diff_view(frame: &mut ratatui::Frame<'_>, view: &DiffView) {
+    let area = centered_rect(90, 85, frame.area());
+    frame.render_widget(Clear, area);
+
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(30), Constraint::Percentage(70)])
+        .split(area);
+
+    // File list with per-file stats
+    let file_lines: Vec<Line> = if view.diff.files.is_empty() {
+        vec![Line::raw("変更はありません")]
+    } else {
+        view.diff
+            .files
+            .iter()
+            .enumerate()
+            .map(|(idx, file)| {
+                let mut spans = vec![
+                    Span::styled(
+                        format!("{} ", file.status.letter()),
+                        Style::default().fg(status_color(file.status)),
+                    ),
+                    Span::styled(
+                        format!("+{:<4}", file.additions()),
+                        Style::default().fg(Color::Green),
+                    ),
+                    Span::styled(
+                        format!("-{:<4} ", file.deletions()),
+                        Style::default().fg(Color::Red),
+                    ),
+                    Span::raw(file.path.clone()),
+                ];
+                if idx == view.selected_file {
+                    for span in &mut spans {
+                        span.style = span.style.bg(Color::DarkGray);
+                    }
+                }
+                Line::from(spans)
+            })
+            .collect()
+    };
+    let list_height = columns[0].height.saturating_sub(2) as usize;
+    let list_offset = view.selected_file.saturating_sub(list_height.saturating_sub(1));
+    let files = Paragraph::new(file_lines)
+        .scroll((list_offset as u16, 0))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!("{} – {}", view.worker_name, view.diff.summary())),
+        );
+    frame.render_widget(files, columns[0]);
+
+    let Some(file) = view.current_file() else {
+        let widget = Paragraph::new(vec![Line::raw(format!(
+            "{} からの変更はありません。",
+            view.diff.base
+        ))])
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Diff [Esc:閉じる r:再読み込み]"),
+        );
+        frame.render_widget(widget, columns[1]);
+        return;
+    };
+
+    let syntax = Syntax::for_path(&file.path);
+    let mut lines = Vec::new();
+    if file.binary {
+        lines.push(Line::styled("バイナリファイル", Style::default().fg(Color::Gray)));
+    } else if file.hunks.is_empty() {
+        lines.push(Line::styled(
+            "内容の変更はありません",
+            Style::default().fg(Color::Gray),
+        ));
+    }
+    for (pos, row) in view.rows().into_iter().enumerate() {
+        let mut line = match row {
+            DiffRow::Header(idx) => {
+                let hunk = &file.hunks[idx];
+                let folded = view.folded.contains(&(view.selected_file, idx));
+                let mut text = format!("{} {}", if folded { "▸" } else { "▾" }, hunk.header);
+                if folded {
+                    text.push_str(&format!("  ({} 行を折りたたみ)", hunk.lines.len()));
+                }
+                Line::styled(text, Style::default().fg(Color::Cyan))
+            }
+            DiffRow::Line(hunk, idx) => {
+                let diff_line = &file.hunks[hunk].lines[idx];
+                let (sign, base) = match diff_line.kind {
+                    LineKind::Added => ("+", Style::default().fg(Color::Green)),
+                    LineKind::Removed => ("-", Style::default().fg(Color::Red)),
+                    LineKind::Context => (" ", Style::default()),
+                    LineKind::Marker => ("", Style::default().fg(Color::Gray)),
+                };
+                let mut spans = vec![Span::styled(sign, base.add_modifier(Modifier::BOLD))];
+                if diff_line.kind == LineKind::Marker {
+                    spans.push(Span::styled(diff_line.text.clone(), base));
+                } else {
+                    spans.extend(highlight(syntax, &diff_line.text, base));
+                }
+                Line::from(spans)
+            }
+        };
+        if pos == view.cursor {
+            line = line.style(Style::default().bg(Color::DarkGray));
+        }
+        lines.push(line);
+    }
+
+    let height = columns[1].height.saturating_sub(2) as usize;
+    let offset = view.cursor.saturating_sub(height / 2);
+    let mut title = format!("{} ({}...HEAD)", file.path, view.diff.base);
+    if let Some(old_path) = &file.old_path {
+        title = format!("{old_path} → {title}");
+    }
+    let widget = Paragraph::new(lines).scroll((offset as u16, 0)).block(
+        Block::default().borders(Borders::ALL).title(format!(
+            "{title} [Tab:ファイル n/N:ハンク Space:折りたたみ r:再読込 Esc:閉じる]"
+        )),
+    );
+    frame.render_widget(widget, columns[1]);
+}
+
+fn status_color(status: FileStatus) -> Color {
+    match status {
+        FileStatus::Added => Color::Green,
+        FileStatus::Deleted => Color::Red,
+        FileStatus::Modified => Color::Yellow,
+        FileStatus::Renamed => Color::Cyan,
+    }
+}
+
+/// 拡張子ごとの簡易的な構文定義
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Syntax {
+    keywords: &'static [&'static str],
+    line_comments: &'static [&'static str],
+    quotes: &'static [char],
+}
+
+const RUST_KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "false",
+    "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref",
+    "return", "self", "Self", "static", "struct", "super", "trait", "true", "type", "unsafe",
+    "use", "where", "while",
+];
+const PYTHON_KEYWORDS: &[&str] = &[
+    "and", "as", "assert", "async", "await", "break", "class", "continue", "def", "del", "elif",
+    "else", "except", "False", "finally", "for", "from", "if", "import", "in", "is", "lambda",
+    "None", "not", "or", "pass", "raise", "return", "True", "try", "while", "with", "yield",
+];
+const JS_KEYWORDS: &[&str] = &[
+    "async", "await", "break", "case", "catch", "class", "const", "continue", "default", "else",
+    "export", "extends", "false", "finally", "for", "from", "function", "if", "import", "in",
+    "instanceof", "interface", "let", "new", "null", "return", "switch", "this", "throw", "true",
+    "try", "type", "typeof", "undefined", "var", "while",
+];
+const GO_KEYWORDS: &[&str] = &[
+    "break", "case", "chan", "const", "continue", "default", "defer", "else", "false", "for",
+    "func", "go", "if", "import", "interface", "map", "nil", "package", "range", "return",
+    "select", "struct", "switch", "true", "type", "var",
+];
+const C_KEYWORDS: &[&str] = &[
+    "break", "case", "class", "const", "continue", "default", "else", "enum", "false", "final",
+    "for", "if", "import", "include", "new", "null", "nullptr", "private", "protected", "public",
+    "return", "static", "struct", "switch", "this", "true", "void", "while",
+];
+const SHELL_KEYWORDS: &[&str] = &[
+    "case", "do", "done", "elif", "else", "esac", "export", "fi", "for", "function", "if", "in",
+    "local", "return", "then", "while",
+];
+const DATA_KEYWORDS: &[&str] = &["false", "null", "true"];
+
+impl Syntax {
+    const PLAIN: Syntax = Syntax {
+        keywords: &[],
+        line_comments: &[],
+        quotes: &[],
+    };
+
+    fn for_path(path: &str) -> Self {
+        let extension = path.rsplit_once('.').map(|(_, ext)| ext).unwrap_or_default();
+        let (keywords, line_comments, quotes): (_, &[&str], &[char]) = match extension {
+            "rs" => (RUST_KEYWORDS, &["//"], &['"']),
+            "py" => (PYTHON_KEYWORDS, &["#"], &['"', '\'']),
+            "js" | "jsx" | "ts" | "tsx" | "mjs" | "cjs" => (JS_KEYWORDS, &["//"], &['"', '\'', '`']),
+            "go" => (GO_KEYWORDS, &["//"], &['"', '`']),
+            "c" | "h" | "cc" | "cpp" | "hpp" | "java" | "kt" | "swift" | "cs" => {
+                (C_KEYWORDS, &["//"], &['"'])
+            }
+            "sh" | "bash" | "zsh" => (SHELL_KEYWORDS, &["#"], &['"', '\'']),
+            "toml" | "yaml" | "yml" => (DATA_KEYWORDS, &["#"], &['"', '\'']),
+            "json" => (DATA_KEYWORDS, &[], &['"']),
+            _ => return Self::PLAIN,
+        };
+        Self {
+            keywords,
+            line_comments,
+            quotes,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Token {
+    Plain,
+    Keyword,
+    String,
+    Comment,
+    Number,
+}
+
+/// 1行をトークンに分割する（複数行にまたがるコメントや文字列は扱わない）
+fn tokenize(syntax: Syntax, text: &str) -> Vec<(Token, &str)> {
+    // (token, start, end) with adjacent plain runs merged
+    let mut ranges: Vec<(Token, usize, usize)> = Vec::new();
+    let mut pos = 0;
+    while pos < text.len() {
+        let rest = &text[pos..];
+        let ch = rest.chars().next().unwrap_or_default();
+        let (token, len) = if syntax.line_comments.iter().any(|prefix| rest.starts_with(prefix)) {
+            (Token::Comment, rest.len())
+        } else if syntax.quotes.contains(&ch) {
+            let mut escaped = false;
+            let end = rest
+                .char_indices()
+                .skip(1)
+                .find(|&(_, c)| {
+                    let closing = c == ch && !escaped;
+                    escaped = c == '\\' && !escaped;
+                    closing
+                })
+                .map_or(rest.len(), |(idx, c)| idx + c.len_utf8());
+            (Token::String, end)
+        } else if ch.is_alphanumeric() || ch == '_' {
+            let number = ch.is_ascii_digit();
+            let end = rest
+                .find(|c: char| !(c.is_alphanumeric() || c == '_' || (number && c == '.')))
+                .unwrap_or(rest.len());
+            let token = if number {
+                Token::Number
+            } else if syntax.keywords.contains(&&rest[..end]) {
+                Token::Keyword
+            } else {
+                Token::Plain
+            };
+            (token, end)
+        } else {
+            (Token::Plain, ch.len_utf8())
+        };
+
+        match ranges.last_mut() {
+            Some((Token::Plain, _, last_end)) if token == Token::Plain => *last_end = pos + len,
+            _ => ranges.push((token, pos, pos + len)),
+        }
+        pos += len;
+    }
+    ranges
+        .into_iter()
+        .map(|(token, start, end)| (token, &text[start..end]))
+        .collect()
+}
+
+/// 構文に応じて色付けした Span を返す（通常の文字は `base` のスタイル）
+fn highlight(syntax: Syntax, text: &str, base: Style) -> Vec<Span<'static>> {
+    tokenize(syntax, text)
+        .into_iter()
+        .map(|(token, part)| {
+            let style = match token {
+                Token::Plain => base,
+                Token::Keyword => base.fg(Color::Magenta),
+                Token::String => base.fg(Color::Yellow),
+                Token::Comment => base.fg(Color::DarkGray).add_modifier(Modifier::ITALIC),
+                Token::Number => base.fg(Color::LightCyan),
+            };
+            Span::styled(part.to_string(), style)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diff::parse;
+
+    fn sample_view() -> DiffView {
+        let text = "\
+diff --git a/a.rs b/a.rs
+--- a/a.rs
++++ b/a.rs
+@@ -1,2 +1,2 @@
+-let a = 1;
++let a = 2;
+ }
+@@ -10 +10,2 @@
+ x
++y
+diff --git a/b.txt b/b.txt
+--- a/b.txt
++++ b/b.txt
+@@ -1 +1 @@
+-old
++new
+";
+        let diff = WorkerDiff {
+            base: "main".to_string(),
+            files: parse(text),
+        };
+        DiffView::new("worker-1".to_string(), diff)
+    }
+
+    #[test]
+    fn test_hunk_navigation_and_folding() {
+        let mut view = sample_view();
+        assert_eq!(view.rows().len(), 7);
+
+        view.next_hunk();
+        assert_eq!(view.cursor, 4);
+        view.move_cursor(1);
+        view.toggle_fold();
+        // Folding moves the cursor back to the hunk header
+        assert_eq!(view.cursor, 4);
+        assert_eq!(view.rows().len(), 5);
+        view.prev_hunk();
+        assert_eq!(view.cursor, 0);
+
+        view.toggle_fold_all();
+        assert_eq!(view.rows().len(), 2);
+        view.toggle_fold_all();
+        assert_eq!(view.rows().len(), 7);
+
+        view.next_file();
+        assert_eq!(view.current_file().unwrap().path, "b.txt");
+        assert_eq!(view.cursor, 0);
+        view.next_file();
+        assert_eq!(view.selected_file, 1);
+        view.cursor_to_end();
+        assert_eq!(view.cursor, 2);
+    }
+
+    #[test]
+    fn test_tokenize_by_extension() {
+        let syntax = Syntax::for_path("src/main.rs");
+        let tokens = tokenize(syntax, r#"let s = "a\"b"; // note 42"#);
+        assert_eq!(
+            tokens,
+            vec![
+                (Token::Keyword, "let"),
+                (Token::Plain, " s = "),
+                (Token::String, r#""a\"b""#),
+                (Token::Plain, "; "),
+                (Token::Comment, "// note 42"),
+            ]
+        );
+
+        let tokens = tokenize(Syntax::for_path("run.sh"), "x=1.5 # done");
+        assert_eq!(
+            tokens,
+            vec![
+                (Token::Plain, "x="),
+                (Token::Number, "1.5"),
+                (Token::Plain, " "),
+                (Token::Comment, "# done"),
+            ]
+        );
+
+        assert_eq!(
+            tokenize(Syntax::for_path("notes.txt"), "let x"),
+            vec![(Token::Plain, "let x")]
+        );
+    }
+}
@@ -65,6 +65,8 @@ pub fn status_color(status: WorkerStatus) -> Color {
         WorkerStatus::Running => Color::Green,
         WorkerStatus::Paused => Color::Yellow,
         WorkerStatus::Failed => Color::Red,
+        WorkerStatus::Interrupted => Color::Magenta,
+        WorkerStatus::Queued => Color::Cyan,
         WorkerStatus::Idle => Color::Gray,
         WorkerStatus::Archived => Color::Blue,
     }
@@ -87,6 +89,63 @@ pub fn permission_mode_label(permission_mode: &Option<String>) -> &str {
     }
 }
 
+/// プルリクエスト URL を一覧表示用の短いラベルに変換する
+///
+/// # Arguments
+/// * `url` - プルリクエスト（マージリクエスト）の URL
+///
+/// # Returns
+/// GitHub は `#番号`、GitLab は `!番号`、それ以外は URL そのもの。未作成なら `-`
+pub fn pr_label(url: Option<&str>) -> String {
+    let Some(url) = url else {
+        return "-".to_string();
+    };
+    let mut segments = url.trim_end_matches('/').rsplit('/');
+    let number = segments.next().unwrap_or_default();
+    if number.is_empty() || !number.chars().all(|c| c.is_ascii_digit()) {
+        return url.to_string();
+    }
+    match segments.next() {
+        Some("pull") => format!("#{number}"),
+        Some("merge_requests") => format!("!{number}"),
+        _ => url.to_string(),
+    }
+}
+
+/// 入力中の文字列で始まる ref を返す
+///
+/// # Arguments
+/// * `input` - 入力中の文字列
+/// * `refs` - ローカルのブランチとタグ
+pub fn matching_refs<'a>(input: &str, refs: &'a [String]) -> Vec<&'a str> {
+    refs.iter()
+        .map(String::as_str)
+        .filter(|candidate| candidate.starts_with(input))
+        .collect()
+}
+
+/// ベース ref の入力を補完する
+///
+/// # Arguments
+/// * `input` - 入力中の文字列
+/// * `refs` - ローカルのブランチとタグ
+///
+/// # Returns
+/// 一致する ref に共通する最長の文字列。入力より長くならない場合は None
+pub fn complete_ref(input: &str, refs: &[String]) -> Option<String> {
+    let mut candidates = matching_refs(input, refs).into_iter();
+    let first = candidates.next()?;
+    let common = candidates.fold(first, |common, candidate| {
+        let len = common
+            .char_indices()
+            .zip(candidate.chars())
+            .find(|((_, a), b)| a != b)
+            .map_or(common.len().min(candidate.len()), |((idx, _), _)| idx);
+        &common[..len]
+    });
+    (common.len() > input.len()).then(|| common.to_string())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -127,13 +186,36 @@ mod tests {
         assert_eq!(formatted, "[2024-01-01T00:00:00Z] Test message");
     }
 
+    #[test]
+    fn test_pr_label() {
+        assert_eq!(pr_label(None), "-");
+        assert_eq!(pr_label(Some("https://github.com/o/r/pull/7")), "#7");
+        assert_eq!(pr_label(Some("https://gitlab.com/g/p/-/merge_requests/4")), "!4");
+        assert_eq!(pr_label(Some("http://forge.local/pr")), "http://forge.local/pr");
+    }
+
+    #[test]
+    fn test_complete_ref() {
+        let refs: Vec<String> = ["main", "release/1.0", "release/1.1", "v1.0"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        assert_eq!(matching_refs("release/", &refs), vec!["release/1.0", "release/1.1"]);
+        assert_eq!(complete_ref("r", &refs).as_deref(), Some("release/1."));
+        assert_eq!(complete_ref("release/1.", &refs), None);
+        assert_eq!(complete_ref("m", &refs).as_deref(), Some("main"));
+        assert_eq!(complete_ref("x", &refs), None);
+    }
+
     #[test]
     fn test_status_color() {
         assert_eq!(status_color(WorkerStatus::Running), Color::Green);
         assert_eq!(status_color(WorkerStatus::Paused), Color::Yellow);
         assert_eq!(status_color(WorkerStatus::Failed), Color::Red);
+        assert_eq!(status_color(WorkerStatus::Interrupted), Color::Magenta);
         assert_eq!(status_color(WorkerStatus::Idle), Color::Gray);
         assert_eq!(status_color(WorkerStatus::Archived), Color::Blue);
+        assert_eq!(status_color(WorkerStatus::Queued), Color::Cyan);
     }
 
     #[test]
@@ -119,6 +119,7 @@ pub fn render_overview_tab(
                 StepStatus::Running => "Running",
                 StepStatus::Success => "✓ Success",
                 StepStatus::Failed => "✗ Failed",
+                StepStatus::Skipped => "- Skipped",
             };
 
             // Safe string truncation using chars instead of byte slicing
@@ -150,7 +151,7 @@ pub fn render_overview_tab(
 
             Row::new(vec![
                 Cell::from(format!("{}", entry.step_index)),
-                Cell::from(entry.step_name.clone()),
+                Cell::from(entry.display_name()),
                 Cell::from(status_str),
                 Cell::from(summary),
             ])
@@ -199,7 +200,8 @@ pub fn render_detail_tab(
     // Title
     lines.push(Line::from(format!(
         "Step #{}: {}",
-        entry.step_index, entry.step_name
+        entry.step_index,
+        entry.display_name()
     )));
     lines.push(Line::raw(""));
 
@@ -6,9 +6,9 @@ use ratatui::text::{Line, Span};
 use ratatui::widgets::{Block, Borders, Clear, Paragraph, Wrap};
 
 use crate::state::{SessionEvent, SessionHistory};
-use crate::worker::{ExistingWorktree, PermissionDecision, PermissionRequest};
-use super::helpers::permission_mode_label;
-use super::types::AVAILABLE_TOOLS;
+use crate::worker::{ExistingWorktree, PermissionDecision, PermissionRequest, UnsavedWork};
+use super::helpers::{matching_refs, permission_mode_label};
+use super::types::{delete_options, AVAILABLE_TOOLS, MERGE_OPTIONS};
 
 /// 汎用的なモーダルウィンドウをレンダリング
 ///
@@ -192,6 +192,7 @@ pub fn render_create_selection_modal(
         format!("  ワークフローを実行 ({})", workflow_name),
         "  自由入力でワーカーを作成".to_string(),
         "  既存worktreeを使用".to_string(),
+        "  Issue 一覧ファイルから一括作成".to_string(),
     ];
 
     let lines: Vec<Line> = vec![Line::raw("ワーカーの作成方法を選択してください"), Line::raw("")]
@@ -223,6 +224,128 @@ pub fn render_create_selection_modal(
     frame.render_widget(widget, area);
 }
 
+/// ブランチ取り込み方法の選択モーダルをレンダリング
+///
+/// # Arguments
+/// * `frame` - 描画フレーム
+/// * `area` - 描画領域
+/// * `worker_name` - 取り込むワーカーの名前
+/// * `selected` - 選択中の選択肢
+/// * `resolve_conflicts` - コンフリクトをエージェントに解消させるか
+pub fn render_merge_selection_modal(
+    frame: &mut ratatui::Frame<'_>,
+    area: Rect,
+    worker_name: &str,
+    selected: usize,
+    resolve_conflicts: bool,
+) {
+    let mut lines = vec![
+        Line::raw(format!("{} のブランチをベースブランチに取り込みます", worker_name)),
+        Line::raw(""),
+    ];
+    for (i, option) in MERGE_OPTIONS.iter().enumerate() {
+        if i == selected {
+            lines.push(Line::from(Span::styled(
+                format!(">   {}", option.label),
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            )));
+        } else {
+            lines.push(Line::raw(format!("    {}", option.label)));
+        }
+    }
+    lines.push(Line::raw(""));
+    lines.push(Line::from(vec![
+        Span::raw(if resolve_conflicts { "[x] " } else { "[ ] " }),
+        Span::raw("コンフリクト時はエージェントに解消を依頼する"),
+    ]));
+    lines.push(Line::raw(""));
+    lines.push(Line::raw("↑↓: 選択移動  Space: 解消依頼の切替  Enter: 実行  Esc: キャンセル"));
+
+    let widget = Paragraph::new(lines).wrap(Wrap { trim: false }).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title("Merge"),
+    );
+    frame.render_widget(Clear, area);
+    frame.render_widget(widget, area);
+}
+
+/// 削除確認モーダルに一覧表示する未保存の作業の上限
+const UNSAVED_PREVIEW_LIMIT: usize = 8;
+
+/// ワーカー削除の確認モーダルをレンダリング
+///
+/// # Arguments
+/// * `frame` - 描画フレーム
+/// * `area` - 描画領域
+/// * `worker_name` - 削除するワーカーの名前
+/// * `archived` - アーカイブ済みのワーカーか
+/// * `unsaved` - 削除で失われる未コミットの変更と未 push のコミット
+/// * `selected` - 選択中の選択肢
+pub fn render_delete_confirmation_modal(
+    frame: &mut ratatui::Frame<'_>,
+    area: Rect,
+    worker_name: &str,
+    archived: bool,
+    unsaved: &UnsavedWork,
+    selected: usize,
+) {
+    let warning = Style::default().fg(Color::Red).add_modifier(Modifier::BOLD);
+    let mut lines = vec![
+        Line::raw(format!("{} を削除します", worker_name)),
+        Line::raw(""),
+    ];
+    if unsaved.is_empty() {
+        lines.push(Line::raw("未コミットの変更・未 push のコミットはありません"));
+    }
+    for (title, entries) in [
+        ("未コミットの変更", &unsaved.uncommitted),
+        ("ベースブランチにもリモートにも無いコミット", &unsaved.unpushed),
+    ] {
+        if entries.is_empty() {
+            continue;
+        }
+        lines.push(Line::from(Span::styled(
+            format!("{} ({}件)", title, entries.len()),
+            warning,
+        )));
+        for entry in entries.iter().take(UNSAVED_PREVIEW_LIMIT) {
+            lines.push(Line::raw(format!("  {}", entry)));
+        }
+        if entries.len() > UNSAVED_PREVIEW_LIMIT {
+            lines.push(Line::raw(format!(
+                "  ...他 {}件",
+                entries.len() - UNSAVED_PREVIEW_LIMIT
+            )));
+        }
+    }
+    lines.push(Line::raw(""));
+    for (i, option) in delete_options(archived).iter().enumerate() {
+        if i == selected {
+            lines.push(Line::from(Span::styled(
+                format!(">   {}", option.label),
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            )));
+        } else {
+            lines.push(Line::raw(format!("    {}", option.label)));
+        }
+    }
+    lines.push(Line::raw(""));
+    lines.push(Line::raw("↑↓: 選択移動  Enter: 実行  Esc: キャンセル"));
+
+    let widget = Paragraph::new(lines).wrap(Wrap { trim: false }).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title("Delete"),
+    );
+    frame.render_widget(Clear, area);
+    frame.render_widget(widget, area);
+}
+
 /// ツール選択モーダルをレンダリング
 pub fn render_tool_selection_modal(
     frame: &mut ratatui::Frame<'_>,
@@ -332,11 +455,23 @@ pub fn render_worktree_selection_modal(
     frame.render_widget(widget, area);
 }
 
-/// Worker名前入力モーダルをレンダリング
+/// Worker名とベース ref の入力モーダルをレンダリング
+///
+/// # Arguments
+/// * `frame` - 描画フレーム
+/// * `area` - 描画領域
+/// * `textarea` - Worker名の入力欄
+/// * `base_ref` - ベース ref の入力欄
+/// * `editing_base` - ベース ref の入力欄にフォーカスがあるか
+/// * `refs` - 補完候補のローカルブランチとタグ
+/// * `workflow_name` - 実行するワークフロー名
 pub fn render_name_input_modal(
     frame: &mut ratatui::Frame<'_>,
     area: Rect,
     textarea: &tui_textarea::TextArea<'_>,
+    base_ref: &tui_textarea::TextArea<'_>,
+    editing_base: bool,
+    refs: &[String],
     workflow_name: &Option<String>,
 ) {
     use ratatui::layout::{Constraint, Layout, Direction};
@@ -346,12 +481,14 @@ pub fn render_name_input_modal(
         .map(|name| format!("ワークフロー: {}", name))
         .unwrap_or_else(|| "新規ワーカー".to_string());
 
-    // Split area into header, textarea, and footer
+    // Split area into header, name, base ref, candidates and footer
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
             Constraint::Length(5),  // Header
-            Constraint::Length(3),  // TextArea
+            Constraint::Length(3),  // Name
+            Constraint::Length(3),  // Base ref
+            Constraint::Min(1),     // Completion candidates
             Constraint::Length(4),  // Footer
         ])
         .split(area);
@@ -362,7 +499,7 @@ pub fn render_name_input_modal(
     // Render header
     let header_lines = vec![
         Line::from(Span::styled(
-            "Worker名を入力してください",
+            "Worker名とベースを入力してください",
             Style::default()
                 .fg(Color::Cyan)
                 .add_modifier(Modifier::BOLD),
@@ -374,17 +511,42 @@ pub fn render_name_input_modal(
         .block(Block::default().borders(Borders::TOP | Borders::LEFT | Borders::RIGHT).title("Worker Name"));
     frame.render_widget(header, chunks[0]);
 
-    // Render TextArea in the middle
-    let textarea_block = Block::default()
-        .borders(Borders::LEFT | Borders::RIGHT)
-        .style(Style::default().fg(Color::Yellow));
-    let mut textarea_clone = textarea.clone();
-    textarea_clone.set_block(textarea_block);
-    frame.render_widget(&textarea_clone, chunks[1]);
+    // Render both fields, highlighting the focused one
+    for (field, title, focused, chunk) in [
+        (textarea, "Worker名", !editing_base, chunks[1]),
+        (base_ref, "ベース（ブランチ・タグ・コミット。空欄で現在のブランチ）", editing_base, chunks[2]),
+    ] {
+        let color = if focused { Color::Yellow } else { Color::Gray };
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(title)
+            .style(Style::default().fg(color));
+        let mut field = field.clone();
+        field.set_block(block);
+        if !focused {
+            field.set_cursor_style(Style::default());
+        }
+        frame.render_widget(&field, chunk);
+    }
+
+    // Render completion candidates for the base ref
+    let input = base_ref.lines().join("");
+    let candidates: Vec<Line> = if editing_base {
+        matching_refs(&input, refs)
+            .into_iter()
+            .take(chunks[3].height.saturating_sub(1) as usize)
+            .map(|candidate| Line::styled(format!("  {candidate}"), Style::default().fg(Color::Gray)))
+            .collect()
+    } else {
+        Vec::new()
+    };
+    let candidates = Paragraph::new(candidates)
+        .block(Block::default().borders(Borders::LEFT | Borders::RIGHT));
+    frame.render_widget(candidates, chunks[3]);
 
     // Render footer
     let footer_lines = vec![
-        Line::raw("Enter: 確定 / Esc: スキップ（デフォルト名を使用）"),
+        Line::raw("Enter: 確定 / Tab: ベース入力・補完 / ↑↓: 入力欄の切替 / Esc: スキップ（デフォルトを使用）"),
         Line::raw(""),
         Line::from(vec![
             Span::styled("※", Style::default().fg(Color::Gray)),
@@ -396,7 +558,7 @@ pub fn render_name_input_modal(
     ];
     let footer = Paragraph::new(footer_lines)
         .block(Block::default().borders(Borders::BOTTOM | Borders::LEFT | Borders::RIGHT));
-    frame.render_widget(footer, chunks[2]);
+    frame.render_widget(footer, chunks[4]);
 }
 
 /// Worker名前変更モーダルをレンダリング
@@ -464,6 +626,64 @@ pub fn render_rename_worker_modal(
     frame.render_widget(footer, chunks[2]);
 }
 
+/// 一括作成ファイルのパス入力モーダルをレンダリング
+pub fn render_batch_file_modal(
+    frame: &mut ratatui::Frame<'_>,
+    area: Rect,
+    textarea: &tui_textarea::TextArea<'_>,
+    workflow_name: &str,
+) {
+    use ratatui::layout::{Constraint, Layout, Direction};
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(5),  // Header
+            Constraint::Length(3),  // TextArea
+            Constraint::Length(5),  // Footer
+        ])
+        .split(area);
+
+    frame.render_widget(Clear, area);
+
+    let header_lines = vec![
+        Line::from(Span::styled(
+            "Issue 一覧ファイルから一括作成",
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        )),
+        Line::raw(""),
+        Line::from(vec![
+            Span::raw("ワークフロー未指定の行: "),
+            Span::styled(workflow_name.to_string(), Style::default().fg(Color::Magenta)),
+        ]),
+    ];
+    let header = Paragraph::new(header_lines)
+        .block(Block::default().borders(Borders::TOP | Borders::LEFT | Borders::RIGHT).title("Batch Create"));
+    frame.render_widget(header, chunks[0]);
+
+    let textarea_block = Block::default()
+        .borders(Borders::LEFT | Borders::RIGHT)
+        .style(Style::default().fg(Color::Yellow));
+    let mut textarea_clone = textarea.clone();
+    textarea_clone.set_block(textarea_block);
+    frame.render_widget(&textarea_clone, chunks[1]);
+
+    let footer_lines = vec![
+        Line::raw("Enter: 作成 / Esc: キャンセル"),
+        Line::raw(""),
+        Line::from(Span::styled(
+            "※ リポジトリからの相対パス。改行区切り / .csv / .jsonl (issue,name,workflow,prompt,agent,priority)",
+            Style::default().fg(Color::Gray),
+        )),
+    ];
+    let footer = Paragraph::new(footer_lines)
+        .wrap(Wrap { trim: false })
+        .block(Block::default().borders(Borders::BOTTOM | Borders::LEFT | Borders::RIGHT));
+    frame.render_widget(footer, chunks[2]);
+}
+
 /// 許可されたツールの説明テキストを生成
 pub fn describe_allowed_tools(tools: &Option<Vec<String>>) -> String {
     match tools {
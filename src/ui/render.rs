@@ -5,7 +5,7 @@ use ratatui::text::{Line, Span};
 use ratatui::widgets::{Block, Borders, Cell, Paragraph, Row, Table};
 
 use crate::worker::{WorkerSnapshot, WorkerStatus};
-use super::helpers::status_color;
+use super::helpers::{pr_label, status_color};
 
 /// ヘッダー部分をレンダリング
 pub fn render_header(
@@ -129,7 +129,7 @@ pub fn render_table(
             } else {
                 (
                     Cell::from(worker.name.clone()),
-                    Cell::from(worker.status.label()),
+                    Cell::from(worker.status_text()),
                     Cell::from(worker.last_event.clone()),
                 )
             };
@@ -145,8 +145,9 @@ pub fn render_table(
             name_cell,
             Cell::from(
                 worker
-                    .issue
+                    .issue_title
                     .clone()
+                    .or_else(|| worker.issue.clone())
                     .unwrap_or_else(|| "Unassigned".into()),
             )
             .style(other_cell_style),
@@ -162,6 +163,7 @@ pub fn render_table(
             Cell::from(worker.agent.clone()).style(other_cell_style),
             Cell::from(worker.worktree.clone()).style(other_cell_style),
             Cell::from(worker.branch.clone()).style(other_cell_style),
+            Cell::from(pr_label(worker.pr_url.as_deref())).style(other_cell_style),
             status_cell,
             last_event_cell,
         ]);
@@ -182,6 +184,7 @@ pub fn render_table(
         Cell::from("AGENT"),
         Cell::from("WORKTREE"),
         Cell::from("BRANCH"),
+        Cell::from("PR"),
         Cell::from("STATUS"),
         Cell::from("LAST EVENT"),
     ])
@@ -189,12 +192,13 @@ pub fn render_table(
 
     let widths = [
         ratatui::layout::Constraint::Length(12),
-        ratatui::layout::Constraint::Length(10),
+        ratatui::layout::Constraint::Length(20),
         ratatui::layout::Constraint::Length(14),
         ratatui::layout::Constraint::Length(18),
         ratatui::layout::Constraint::Length(20),
         ratatui::layout::Constraint::Length(24),
         ratatui::layout::Constraint::Length(20),
+        ratatui::layout::Constraint::Length(6),
         ratatui::layout::Constraint::Length(10),
         ratatui::layout::Constraint::Min(24),
     ];
@@ -213,6 +217,7 @@ pub fn render_footer(
     frame: &mut ratatui::Frame<'_>,
     area: Rect,
     workflow_name: &str,
+    attached: bool,
 ) {
     let lines = vec![
         Line::from(vec![
@@ -224,6 +229,10 @@ pub fn render_footer(
             Span::raw(" delete  "),
             Span::styled("r", Style::default().fg(Color::Cyan)),
             Span::raw(" restart  "),
+            Span::styled("x", Style::default().fg(Color::Cyan)),
+            Span::raw(" cancel  "),
+            Span::styled("p", Style::default().fg(Color::Cyan)),
+            Span::raw(" pause/resume  "),
             Span::styled("n", Style::default().fg(Color::Cyan)),
             Span::raw(" rename  "),
             Span::styled("a", Style::default().fg(Color::Cyan)),
@@ -243,8 +252,13 @@ pub fn render_footer(
         ]),
     ];
 
+    let title = if attached {
+        "Controls (daemon: q で切断)"
+    } else {
+        "Controls"
+    };
     let footer =
-        Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title("Controls"));
+        Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title(title));
     frame.render_widget(footer, area);
 }
 
@@ -253,11 +267,14 @@ pub fn help_lines() -> Vec<Line<'static>> {
     vec![
         Line::raw("MVP ショートカット"),
         Line::raw(""),
-        Line::raw("c – ワーカーを作成（ワークフロー or 自由入力を選択）"),
-        Line::raw("d – ワーカー停止と worktree 削除（アーカイブは状態削除のみ）"),
-        Line::raw("r – ワーカーを再起動（アーカイブは不可）"),
+        Line::raw("c – ワーカーを作成（ワークフロー / 自由入力 / 既存worktree / Issue 一覧ファイルから一括作成）"),
+        Line::raw("d – ワーカー削除（未保存の作業を確認してアーカイブまたは破棄）"),
+        Line::raw("r – ワーカーを再起動（アーカイブは残したブランチから再有効化）"),
+        Line::raw("x – 実行中のワーカーをキャンセル（エージェント/コマンドのプロセスを終了）"),
+        Line::raw("p – ワーカーを一時停止 / 再開（Interrupted は中断ステップからセッション継続で再開）"),
         Line::raw("n – ワーカー名を変更"),
         Line::raw("i – 自由指示を送信（ワーカー選択時は追加指示、アーカイブは不可）"),
+        Line::raw("+/- – 選択ワーカーの優先度を上げる / 下げる（キュー待ちの順番に反映）"),
         Line::raw("a – ステータスフィルタを切り替え"),
         Line::raw("w – 使用するワークフローを切り替え"),
         Line::raw("j/k または ↑/↓ – 選択移動 (ログ表示時はスクロール)"),
@@ -265,10 +282,14 @@ pub fn help_lines() -> Vec<Line<'static>> {
         Line::raw("Home/End – ログの先頭/末尾へジャンプ"),
         Line::raw("l – 選択ワーカーのログを表示"),
         Line::raw("s – 選択ワーカーのセッション履歴を表示"),
+        Line::raw("v – 選択ワーカーのベースブランチからの差分を表示"),
         Line::raw("h – このヘルプを表示"),
         Line::raw("Shift+C – アクションログを圧縮"),
         Line::raw("Shift+I – インタラクティブClaude Code起動（権限を手動承認可能）"),
         Line::raw("Shift+A – ログの自動スクロールON/OFF切替"),
+        Line::raw("Shift+R – Interrupted のワーカーを中断ステップから新規セッションで再開"),
+        Line::raw("Shift+P – ブランチを push してプルリクエスト（GitLab はマージリクエスト）を作成"),
+        Line::raw("Shift+M – ブランチをベースブランチに取り込み（コンフリクト確認 / ff / squash / rebase）"),
         Line::raw("q – 終了"),
         Line::raw(""),
         Line::raw("入力モーダル操作:"),
@@ -276,7 +297,11 @@ pub fn help_lines() -> Vec<Line<'static>> {
         Line::raw("  名前入力/変更: Enter で確定 / Esc でキャンセル"),
         Line::raw("  矢印キー/Home/End でカーソル移動、複数行入力可能"),
         Line::raw(""),
-        Line::raw("ステータス: Running/Idle/Paused/Failed/Archived(青=履歴)"),
+        Line::raw("差分ビュー操作:"),
+        Line::raw("  Tab/←→ でファイル移動 / j/k で行移動 / n/N でハンク移動"),
+        Line::raw("  Space でハンクを折りたたみ / z で全ハンク / r で再読み込み / Esc で閉じる"),
+        Line::raw(""),
+        Line::raw("ステータス: Running/Queued(#=キュー順)/Idle/Paused/Failed/Archived(青=履歴)"),
     ]
 }
 
@@ -1,4 +1,6 @@
 /// UI関連の型定義
+use crate::config::MergeStrategy;
+use crate::worker::DeleteMode;
 
 /// ログビューのモード
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -14,6 +16,7 @@ pub enum StepStatus {
     Running,
     Success,
     Failed,
+    Skipped,
 }
 
 /// 構造化されたログエントリ
@@ -25,6 +28,20 @@ pub struct LogEntry {
     pub result_lines: Vec<String>,
     pub thought_lines: Vec<String>,
     pub status: StepStatus,
+    /// リトライ設定のあるステップの試行番号 (試行回数, 最大試行回数)
+    pub attempt: Option<(usize, usize)>,
+}
+
+impl LogEntry {
+    /// 試行番号付きのステップ名（例: "テスト (2/3)"）
+    pub fn display_name(&self) -> String {
+        match self.attempt {
+            Some((attempt, max_attempts)) => {
+                format!("{} ({}/{})", self.step_name, attempt, max_attempts)
+            }
+            None => self.step_name.clone(),
+        }
+    }
 }
 
 /// Claude Codeで利用可能なツールの定義
@@ -70,6 +87,67 @@ pub const AVAILABLE_TOOLS: &[ToolDef] = &[
     },
 ];
 
+/// ブランチ取り込みモーダルの選択肢
+#[derive(Debug, Clone, Copy)]
+pub struct MergeOption {
+    pub label: &'static str,
+    pub strategy: MergeStrategy,
+    /// コンフリクトの確認だけを行う
+    pub dry_run: bool,
+}
+
+/// ブランチ取り込みモーダルの選択肢一覧
+pub const MERGE_OPTIONS: &[MergeOption] = &[
+    MergeOption {
+        label: "コンフリクトを確認のみ",
+        strategy: MergeStrategy::FastForward,
+        dry_run: true,
+    },
+    MergeOption {
+        label: "fast-forward で取り込む",
+        strategy: MergeStrategy::FastForward,
+        dry_run: false,
+    },
+    MergeOption {
+        label: "squash して1コミットで取り込む",
+        strategy: MergeStrategy::Squash,
+        dry_run: false,
+    },
+    MergeOption {
+        label: "rebase してから fast-forward で取り込む",
+        strategy: MergeStrategy::Rebase,
+        dry_run: false,
+    },
+];
+
+/// ワーカー削除確認モーダルの選択肢
+#[derive(Debug, Clone, Copy)]
+pub struct DeleteOption {
+    pub label: &'static str,
+    pub mode: DeleteMode,
+}
+
+/// ワーカー削除確認モーダルの選択肢一覧
+pub const DELETE_OPTIONS: &[DeleteOption] = &[
+    DeleteOption {
+        label: "アーカイブ（worktree だけ削除し、ブランチと状態を残す）",
+        mode: DeleteMode::Archive,
+    },
+    DeleteOption {
+        label: "破棄（worktree・ブランチ・状態をすべて削除）",
+        mode: DeleteMode::Destroy,
+    },
+];
+
+/// 削除確認モーダルに表示する選択肢（アーカイブ済みのワーカーは破棄のみ）
+pub fn delete_options(archived: bool) -> &'static [DeleteOption] {
+    if archived {
+        &DELETE_OPTIONS[1..]
+    } else {
+        DELETE_OPTIONS
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -101,6 +179,14 @@ mod tests {
         assert!(tool_names.contains(&"Bash"));
     }
 
+    #[test]
+    fn test_delete_options_for_archived_worker() {
+        assert_eq!(delete_options(false).len(), 2);
+        let archived = delete_options(true);
+        assert_eq!(archived.len(), 1);
+        assert_eq!(archived[0].mode, DeleteMode::Destroy);
+    }
+
     #[test]
     fn test_log_entry_creation() {
         let entry = LogEntry {
@@ -110,9 +196,25 @@ mod tests {
             result_lines: vec!["result".to_string()],
             thought_lines: vec!["thought".to_string()],
             status: StepStatus::Success,
+            attempt: None,
         };
         assert_eq!(entry.step_index, 1);
         assert_eq!(entry.step_name, "Test Step");
         assert_eq!(entry.status, StepStatus::Success);
+        assert_eq!(entry.display_name(), "Test Step");
+    }
+
+    #[test]
+    fn test_log_entry_display_name_with_attempt() {
+        let entry = LogEntry {
+            step_index: 0,
+            step_name: "テスト".to_string(),
+            prompt_lines: vec![],
+            result_lines: vec![],
+            thought_lines: vec![],
+            status: StepStatus::Failed,
+            attempt: Some((2, 3)),
+        };
+        assert_eq!(entry.display_name(), "テスト (2/3)");
     }
 }
@@ -0,0 +1,350 @@
+//! Issue 一覧ファイル（テキスト・CSV・JSON Lines）からのワーカー一括作成
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+use super::CreateWorkerRequest;
+use super::name_validator::NameValidator;
+
+const MAX_NAME_LEN: usize = 64;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BatchFormat {
+    List,
+    Csv,
+    Jsonl,
+}
+
+impl BatchFormat {
+    /// Pick the format from the file extension, falling back to a plain list.
+    pub fn from_path(path: &Path) -> Self {
+        match path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| ext.to_ascii_lowercase())
+            .as_deref()
+        {
+            Some("csv") => Self::Csv,
+            Some("jsonl") | Some("ndjson") | Some("json") => Self::Jsonl,
+            _ => Self::List,
+        }
+    }
+}
+
+/// Values applied to lines that do not set them explicitly.
+#[derive(Clone, Debug, Default)]
+pub struct BatchDefaults {
+    pub workflow: Option<String>,
+    pub agent: Option<String>,
+}
+
+/// One parsed line of a batch file. `line` is 1-based.
+#[derive(Debug)]
+pub struct BatchEntry {
+    pub line: usize,
+    pub request: Result<CreateWorkerRequest, String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BatchLine {
+    issue: Option<String>,
+    name: Option<String>,
+    workflow: Option<String>,
+    prompt: Option<String>,
+    agent: Option<String>,
+    priority: Option<i32>,
+}
+
+pub fn load_batch(path: &Path, defaults: &BatchDefaults) -> Result<Vec<BatchEntry>> {
+    let contents = fs::read_to_string(path)
+        .with_context(|| format!("failed to read batch file {}", path.display()))?;
+    Ok(parse_batch(&contents, BatchFormat::from_path(path), defaults))
+}
+
+pub fn parse_batch(contents: &str, format: BatchFormat, defaults: &BatchDefaults) -> Vec<BatchEntry> {
+    let raw = match format {
+        BatchFormat::List => parse_list(contents),
+        BatchFormat::Csv => parse_csv(contents),
+        BatchFormat::Jsonl => parse_jsonl(contents),
+    };
+
+    let validator = NameValidator::new();
+    let mut seen = HashSet::new();
+    raw.into_iter()
+        .map(|(line, parsed)| {
+            let request = parsed.and_then(|entry| {
+                let request = build_request(entry, defaults, &validator)?;
+                if let Some(name) = &request.name
+                    && !seen.insert(name.clone())
+                {
+                    return Err(format!("worker name '{name}' is used more than once in this batch"));
+                }
+                Ok(request)
+            });
+            BatchEntry { line, request }
+        })
+        .collect()
+}
+
+fn parse_list(contents: &str) -> Vec<(usize, Result<BatchLine, String>)> {
+    contents
+        .lines()
+        .enumerate()
+        .filter_map(|(idx, line)| {
+            let line_no = idx + 1;
+            let issue = line.trim();
+            if issue.is_empty() || (issue.starts_with('#') && !is_issue_number(issue)) {
+                return None;
+            }
+            Some((
+                line_no,
+                Ok(BatchLine {
+                    issue: Some(issue.to_string()),
+                    ..BatchLine::default()
+                }),
+            ))
+        })
+        .collect()
+}
+
+fn parse_csv(contents: &str) -> Vec<(usize, Result<BatchLine, String>)> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(contents.as_bytes());
+
+    let headers = match reader.headers() {
+        Ok(headers) => headers.clone(),
+        Err(err) => return vec![(1, Err(format!("invalid CSV header: {err}")))],
+    };
+
+    reader
+        .records()
+        .enumerate()
+        .map(|(idx, record)| match record {
+            Ok(record) => {
+                let line = record
+                    .position()
+                    .map_or(idx + 2, |pos| pos.line() as usize);
+                let parsed = record
+                    .deserialize::<BatchLine>(Some(&headers))
+                    .map_err(|err| format!("invalid CSV record: {err}"));
+                (line, parsed)
+            }
+            Err(err) => {
+                let line = err.position().map_or(idx + 2, |pos| pos.line() as usize);
+                (line, Err(format!("invalid CSV record: {err}")))
+            }
+        })
+        .collect()
+}
+
+fn parse_jsonl(contents: &str) -> Vec<(usize, Result<BatchLine, String>)> {
+    contents
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(idx, line)| {
+            let parsed = serde_json::from_str::<BatchLine>(line)
+                .map_err(|err| format!("invalid JSON: {err}"));
+            (idx + 1, parsed)
+        })
+        .collect()
+}
+
+fn build_request(
+    entry: BatchLine,
+    defaults: &BatchDefaults,
+    validator: &NameValidator,
+) -> Result<CreateWorkerRequest, String> {
+    let issue = non_empty(entry.issue);
+    let prompt = non_empty(entry.prompt);
+    let workflow = non_empty(entry.workflow);
+
+    if issue.is_none() && prompt.is_none() {
+        return Err("either an issue or a prompt is required".to_string());
+    }
+    if workflow.is_some() && prompt.is_some() {
+        return Err("workflow and prompt cannot be set on the same line".to_string());
+    }
+
+    let name = match (non_empty(entry.name), &issue) {
+        (Some(name), _) => Some(name),
+        (None, Some(issue)) => Some(derive_name(issue)),
+        (None, None) => None,
+    };
+    if let Some(name) = &name {
+        validator
+            .validate(name)
+            .map_err(|err| format!("invalid worker name '{name}': {err}"))?;
+    }
+
+    let workflow = match (&workflow, &prompt) {
+        (Some(_), _) => workflow,
+        (None, None) => defaults.workflow.clone(),
+        (None, Some(_)) => None,
+    };
+
+    Ok(CreateWorkerRequest {
+        name,
+        issue,
+        agent: non_empty(entry.agent).or_else(|| defaults.agent.clone()),
+        workflow,
+        free_prompt: prompt,
+        priority: entry.priority.unwrap_or_default(),
+        ..CreateWorkerRequest::default()
+    })
+}
+
+fn non_empty(value: Option<String>) -> Option<String> {
+    value
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
+fn is_issue_number(value: &str) -> bool {
+    let digits = value.strip_prefix('#').unwrap_or(value);
+    !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit())
+}
+
+/// Derive a worker name from an issue reference.
+///
+/// Issue numbers (`#123`, `123`) and issue/PR URLs map to `issue-123`; any
+/// other text is slugified into the characters accepted by `NameValidator`.
+pub fn derive_name(issue: &str) -> String {
+    let issue = issue.trim().trim_end_matches('/');
+    let last = issue.rsplit('/').next().unwrap_or(issue);
+    if is_issue_number(last) {
+        return format!("issue-{}", last.trim_start_matches('#'));
+    }
+
+    let mut name = String::new();
+    for c in issue.chars() {
+        let c = if c.is_ascii_alphanumeric() || c == '_' || is_name_char(c) {
+            c
+        } else {
+            '-'
+        };
+        if c == '-' && (name.is_empty() || name.ends_with('-')) {
+            continue;
+        }
+        if name.len() + c.len_utf8() > MAX_NAME_LEN {
+            break;
+        }
+        name.push(c);
+    }
+    name.trim_end_matches('-').to_string()
+}
+
+fn is_name_char(c: char) -> bool {
+    matches!(c,
+        '\u{3040}'..='\u{309F}' |
+        '\u{30A0}'..='\u{30FF}' |
+        '\u{4E00}'..='\u{9FFF}'
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn requests(entries: &[BatchEntry]) -> Vec<&CreateWorkerRequest> {
+        entries
+            .iter()
+            .filter_map(|entry| entry.request.as_ref().ok())
+            .collect()
+    }
+
+    #[test]
+    fn test_format_from_extension() {
+        assert_eq!(BatchFormat::from_path(Path::new("a.csv")), BatchFormat::Csv);
+        assert_eq!(BatchFormat::from_path(Path::new("a.JSONL")), BatchFormat::Jsonl);
+        assert_eq!(BatchFormat::from_path(Path::new("issues.txt")), BatchFormat::List);
+        assert_eq!(BatchFormat::from_path(Path::new("issues")), BatchFormat::List);
+    }
+
+    #[test]
+    fn test_derive_name() {
+        assert_eq!(derive_name("#123"), "issue-123");
+        assert_eq!(derive_name("42"), "issue-42");
+        assert_eq!(
+            derive_name("https://github.com/owner/repo/issues/7"),
+            "issue-7"
+        );
+        assert_eq!(derive_name("Fix login: crash!"), "Fix-login-crash");
+        assert_eq!(derive_name("ログイン修正"), "ログイン修正");
+        assert!(derive_name(&"x".repeat(100)).len() <= MAX_NAME_LEN);
+    }
+
+    #[test]
+    fn test_parse_list_skips_blanks_and_comments() {
+        let defaults = BatchDefaults {
+            workflow: Some("default".into()),
+            agent: None,
+        };
+        let entries = parse_batch("#12\n\n# comment\nfix-docs\n", BatchFormat::List, &defaults);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].line, 1);
+        assert_eq!(entries[1].line, 4);
+
+        let reqs = requests(&entries);
+        assert_eq!(reqs[0].name.as_deref(), Some("issue-12"));
+        assert_eq!(reqs[0].issue.as_deref(), Some("#12"));
+        assert_eq!(reqs[0].workflow.as_deref(), Some("default"));
+        assert_eq!(reqs[1].name.as_deref(), Some("fix-docs"));
+    }
+
+    #[test]
+    fn test_parse_csv_reports_bad_lines_without_aborting() {
+        let contents = "issue,name,workflow,prompt,priority\n\
+                        #1,,review,,5\n\
+                        #2,bad name!,,,\n\
+                        ,adhoc,,write docs,\n\
+                        #3,,review,also prompt,\n";
+        let entries = parse_batch(contents, BatchFormat::Csv, &BatchDefaults::default());
+        assert_eq!(entries.len(), 4);
+
+        let first = entries[0].request.as_ref().unwrap();
+        assert_eq!(first.name.as_deref(), Some("issue-1"));
+        assert_eq!(first.workflow.as_deref(), Some("review"));
+        assert_eq!(first.priority, 5);
+
+        assert_eq!(entries[1].line, 3);
+        assert!(entries[1].request.as_ref().unwrap_err().contains("invalid worker name"));
+
+        let adhoc = entries[2].request.as_ref().unwrap();
+        assert_eq!(adhoc.free_prompt.as_deref(), Some("write docs"));
+        assert!(adhoc.workflow.is_none());
+
+        assert_eq!(entries[3].line, 5);
+        assert!(entries[3].request.is_err());
+    }
+
+    #[test]
+    fn test_parse_jsonl_detects_duplicates_and_unknown_fields() {
+        let contents = r##"{"issue": "#5", "agent": "codex"}
+{"issue": "https://gitlab.com/g/p/-/issues/5"}
+{"issue": "#6", "title": "typo"}
+{"prompt": "tidy up"}
+"##;
+        let defaults = BatchDefaults {
+            workflow: None,
+            agent: Some("claude".into()),
+        };
+        let entries = parse_batch(contents, BatchFormat::Jsonl, &defaults);
+        assert_eq!(entries.len(), 4);
+
+        let first = entries[0].request.as_ref().unwrap();
+        assert_eq!(first.agent.as_deref(), Some("codex"));
+        assert!(entries[1].request.as_ref().unwrap_err().contains("more than once"));
+        assert!(entries[2].request.as_ref().unwrap_err().contains("unknown field"));
+
+        let last = entries[3].request.as_ref().unwrap();
+        assert!(last.name.is_none());
+        assert_eq!(last.agent.as_deref(), Some("claude"));
+    }
+}
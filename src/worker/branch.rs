@@ -0,0 +1,107 @@
+//! 新しいワーカーのブランチ名の生成と検証
+
+use std::process::{Command, Stdio};
+
+/// Branch name used when the configuration does not set `branch_template`.
+pub const DEFAULT_BRANCH_TEMPLATE: &str = "gensui/worker-{{id}}-{{timestamp}}";
+
+/// Placeholders understood by `render_branch_name`.
+pub const BRANCH_PLACEHOLDERS: [&str; 4] = ["worker", "id", "issue", "timestamp"];
+
+/// Values available to `{{...}}` placeholders in a branch template.
+pub struct BranchContext<'a> {
+    pub worker: &'a str,
+    pub id: usize,
+    pub issue: Option<&'a str>,
+    pub timestamp: i64,
+}
+
+/// Substitute the worker name, the zero-padded worker id, the issue and the
+/// creation time. `{{issue}}` is the number of `#123` references and issue
+/// URLs, a slug of any other reference, and empty without an issue.
+pub fn render_branch_name(template: &str, ctx: &BranchContext<'_>) -> String {
+    template
+        .replace("{{worker}}", ctx.worker)
+        .replace("{{id}}", &format!("{:03}", ctx.id))
+        .replace("{{issue}}", &ctx.issue.map(issue_slug).unwrap_or_default())
+        .replace("{{timestamp}}", &ctx.timestamp.to_string())
+}
+
+/// Check `name` with `git check-ref-format --branch`. The error is git's reason.
+pub fn check_branch_name(name: &str) -> Result<(), String> {
+    let output = Command::new("git")
+        .args(["check-ref-format", "--branch", name])
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|err| format!("failed to execute git check-ref-format: {err}"))?;
+    if output.status.success() {
+        return Ok(());
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    Err(stderr.trim().trim_start_matches("fatal: ").to_string())
+}
+
+fn issue_slug(issue: &str) -> String {
+    let issue = issue.trim().trim_end_matches('/');
+    let last = issue.rsplit('/').next().unwrap_or(issue);
+    let digits = last.strip_prefix('#').unwrap_or(last);
+    if !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit()) {
+        return digits.to_string();
+    }
+
+    let mut slug = String::new();
+    for c in issue.chars() {
+        if c.is_alphanumeric() {
+            slug.extend(c.to_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    slug.trim_end_matches('-').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ctx(issue: Option<&str>) -> BranchContext<'_> {
+        BranchContext {
+            worker: "login-fix",
+            id: 7,
+            issue,
+            timestamp: 1700000000,
+        }
+    }
+
+    #[test]
+    fn test_render_branch_name() {
+        assert_eq!(
+            render_branch_name(DEFAULT_BRANCH_TEMPLATE, &ctx(None)),
+            "gensui/worker-007-1700000000"
+        );
+        let template = "fix/{{issue}}-{{worker}}";
+        assert_eq!(render_branch_name(template, &ctx(Some("#42"))), "fix/42-login-fix");
+        assert_eq!(
+            render_branch_name(template, &ctx(Some("https://github.com/o/r/issues/9/"))),
+            "fix/9-login-fix"
+        );
+        assert_eq!(
+            render_branch_name(template, &ctx(Some("Login: broken?"))),
+            "fix/login-broken-login-fix"
+        );
+    }
+
+    #[test]
+    fn test_check_branch_name() {
+        for valid in ["main", "fix/42-login", "feature/ログイン", "a.b/c_d"] {
+            assert_eq!(check_branch_name(valid), Ok(()), "{valid}");
+        }
+        for invalid in ["", "-x", "a b", "a~1", "a:b", "a..b", "a//b", "a/", ".a", "a.lock"] {
+            assert!(check_branch_name(invalid).is_err(), "{invalid:?}");
+        }
+        assert_eq!(
+            check_branch_name("a b").unwrap_err(),
+            "'a b' is not a valid branch name"
+        );
+    }
+}
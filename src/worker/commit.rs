@@ -0,0 +1,95 @@
+//! エージェントがワークツリーに残した変更のコミット
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use anyhow::{Context, Result, bail};
+
+use super::git::{git, git_output};
+
+/// Stage every change in the worktree. Returns whether anything is staged.
+pub fn stage_all(worktree: &Path) -> Result<bool> {
+    git(worktree, &["add", "--all"])?;
+    let output = git_output(worktree, &["diff", "--cached", "--quiet"])?;
+    match output.status.code() {
+        Some(0) => Ok(false),
+        Some(1) => Ok(true),
+        _ => bail!(
+            "git diff --cached failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ),
+    }
+}
+
+/// Summary and patch of the staged changes, for writing a message.
+pub fn staged_diff(worktree: &Path) -> Result<String> {
+    let stat = git(worktree, &["diff", "--cached", "--stat", "--no-color"])?;
+    let patch = git(worktree, &["diff", "--cached", "--no-color", "--no-ext-diff"])?;
+    Ok(format!("{stat}\n{patch}"))
+}
+
+/// Commit the staged changes and return the new commit.
+pub fn commit_staged(worktree: &Path, message: &str) -> Result<String> {
+    let mut child = Command::new("git")
+        .args(["commit", "--quiet", "--file", "-"])
+        .current_dir(worktree)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("failed to execute git commit")?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(message.as_bytes())
+            .context("failed to pass the commit message to git")?;
+    }
+    let output = child
+        .wait_with_output()
+        .context("failed to wait for git commit")?;
+    if !output.status.success() {
+        bail!(
+            "git commit failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(git(worktree, &["rev-parse", "HEAD"])?.trim().to_string())
+}
+
+/// Stage and commit every change. Returns None when there was nothing to commit.
+pub fn commit_all(worktree: &Path, message: &str) -> Result<Option<String>> {
+    if !stage_all(worktree)? {
+        return Ok(None);
+    }
+    commit_staged(worktree, message).map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::worker::git::fixture::{init_repo, run_git};
+    use std::fs;
+
+    #[test]
+    fn test_commit_all_includes_untracked_files() {
+        let dir = std::env::temp_dir().join(format!("gensui-commit-{}", std::process::id()));
+        init_repo(&dir);
+        fs::write(dir.join("a.txt"), "one\n").unwrap();
+        let first = commit_all(&dir, "init").unwrap().unwrap();
+
+        assert_eq!(commit_all(&dir, "nothing").unwrap(), None);
+
+        fs::write(dir.join("a.txt"), "two\n").unwrap();
+        fs::write(dir.join("b.txt"), "new\n").unwrap();
+        assert!(stage_all(&dir).unwrap());
+        assert!(staged_diff(&dir).unwrap().contains("+new"));
+        let second = commit_staged(&dir, "Update a\n\nAnd add b").unwrap();
+        assert_ne!(first, second);
+        assert_eq!(
+            run_git(&dir, &["log", "-1", "--format=%s|%b"]),
+            "Update a|And add b"
+        );
+        assert!(run_git(&dir, &["status", "--porcelain"]).is_empty());
+
+        let _ = fs::remove_dir_all(dir);
+    }
+}
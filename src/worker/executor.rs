@@ -0,0 +1,1724 @@
+use std::collections::{HashMap, VecDeque};
+use std::os::unix::process::CommandExt;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use anyhow::{Context, Result, anyhow};
+
+use crate::agent::{Agent, AgentInvocation, AgentRegistry, DEFAULT_AGENT, run_agent_command};
+use crate::config::{ClaudeStep, CommitStep, FailureAction, MergeStep, Workflow, WorkflowStep};
+use crate::forge::{Forge, PullRequest};
+use crate::issue::Issue;
+use crate::state::{SessionHistory, StepOutcome, StepOutcomeStatus};
+
+use super::commit;
+use super::merge::{self, MergeConflict, MergeOutcome, MergeRequest};
+use super::process::{KILL_GRACE_PERIOD, ProcessTracker};
+use super::template::{
+    COMMIT_MESSAGE_PROMPT, DEFAULT_RESOLVE_PROMPT, TemplateContext, render_commit_message,
+    render_merge, render_prompt, render_pull_request, with_conflicting_files, with_staged_diff,
+};
+use super::{
+    NEXT_PERMISSION_REQUEST_ID, PermissionDecision, PermissionRequest, WorkerCommand, WorkerEvent,
+    WorkerId, WorkerSnapshot, WorkerStatus,
+};
+
+const PAUSE_POLL_INTERVAL: Duration = Duration::from_millis(100);
+const STEP_INTERVAL: Duration = Duration::from_millis(400);
+const MAX_LOGS: usize = 1000;
+
+/// Everything the agent thread needs to run a workflow.
+pub(super) struct AgentContext {
+    pub worker_id: WorkerId,
+    pub state: Arc<Mutex<WorkerSnapshot>>,
+    pub cancel: Arc<AtomicBool>,
+    pub paused: Arc<AtomicBool>,
+    pub processes: ProcessTracker,
+    pub worktree_path: PathBuf,
+    pub evt_tx: Sender<WorkerEvent>,
+    pub workflow: Workflow,
+    pub completed_steps: Arc<AtomicUsize>,
+    pub logs: Arc<Mutex<VecDeque<String>>>,
+    pub cmd_tx: Sender<WorkerCommand>,
+    pub session_histories: Arc<Mutex<Vec<SessionHistory>>>,
+    pub step_outcomes: Arc<Mutex<HashMap<String, StepOutcome>>>,
+    pub agents: Arc<AgentRegistry>,
+    pub default_sandbox_mode: bool,
+    /// Metadata of the worker's issue for `{{issue.*}}` placeholders.
+    pub issue: Option<Issue>,
+    /// Forge that `pr` steps open pull requests on.
+    pub forge: Option<Forge>,
+    /// Branch the worker was created from; `merge` steps merge into it by default.
+    pub base_ref: Option<String>,
+    /// Commit after agent steps that do not set `auto_commit` themselves.
+    pub auto_commit: bool,
+    /// Set for children of a parallel group: logs are collected here and
+    /// published as one block when the child finishes.
+    pub log_buffer: Option<Arc<Mutex<Vec<String>>>>,
+}
+
+/// A command or agent process that ran but exited unsuccessfully.
+#[derive(Debug, thiserror::Error)]
+#[error("{message}")]
+pub struct CommandFailed {
+    pub message: String,
+    pub exit_code: Option<i32>,
+    /// Output captured before the failure, if any.
+    pub output: Vec<String>,
+}
+
+/// Output of one successful attempt.
+struct AttemptOutput {
+    /// Lines shown in the step's result section
+    lines: Vec<String>,
+    /// Final result text reported by an agent
+    result: Option<String>,
+    /// Commit made by the attempt
+    commit: Option<String>,
+}
+
+/// What a step runs once its permissions are settled.
+enum StepAction {
+    Agent {
+        config: ClaudeStep,
+        prompt: String,
+        agent_name: String,
+        agent: Arc<dyn Agent>,
+        /// Commit the agent's changes once it finished.
+        auto_commit: bool,
+    },
+    Shell(String),
+    /// A rendering failure fails the step like a failed push would.
+    PullRequest(Result<PullRequest>),
+    Merge {
+        request: Result<MergeRequest>,
+        /// Agent that resolves conflicts, when the step asks for it.
+        resolver: Option<HelperAgent>,
+    },
+    Commit {
+        /// Message used unless `writer` comes up with one.
+        message: String,
+        /// Agent that writes the message from the staged diff.
+        writer: Option<HelperAgent>,
+    },
+    Noop,
+}
+
+/// An agent run on behalf of a `merge` or `commit` step. The conflicting files
+/// or the staged diff are appended to the prompt.
+struct HelperAgent {
+    config: ClaudeStep,
+    prompt: String,
+    agent_name: String,
+    agent: Arc<dyn Agent>,
+}
+
+pub(super) fn agent_simulation(ctx: AgentContext) {
+    let workflow = &ctx.workflow;
+    let total_steps = workflow.steps().len();
+
+    if total_steps == 0 {
+        ctx.update_snapshot(|snapshot| {
+            snapshot.status = WorkerStatus::Idle;
+            snapshot.last_event = "No workflow steps defined".into();
+            snapshot.current_step = None;
+        });
+        return;
+    }
+
+    let start_step = ctx.completed_steps.load(Ordering::SeqCst);
+
+    if start_step == 0 {
+        ctx.update_snapshot(|snapshot| {
+            snapshot.status = WorkerStatus::Running;
+            snapshot.last_event = format!("ワークフロー '{}' を開始", workflow.name);
+            snapshot.current_step = None;
+        });
+    }
+
+    if ctx.is_cancelled() {
+        return;
+    }
+
+    // `completed_steps` holds the index of the next step to run
+    let mut pc = start_step;
+    let mut visits: HashMap<usize, u32> = HashMap::new();
+
+    while let Some(step) = workflow.steps().get(pc) {
+        // Hold at the step boundary while paused
+        while ctx.paused.load(Ordering::SeqCst) && !ctx.is_cancelled() {
+            thread::sleep(PAUSE_POLL_INTERVAL);
+        }
+
+        if ctx.is_cancelled() {
+            ctx.update_snapshot(|snapshot| {
+                snapshot.status = WorkerStatus::Paused;
+                snapshot.last_event = "ワーカーがキャンセルされました".into();
+                snapshot.current_step = None;
+            });
+            return;
+        }
+
+        let visit = visits.entry(pc).or_insert(0);
+        *visit += 1;
+        let visit = *visit;
+        if visit > step.max_visits() {
+            ctx.fail_workflow(
+                pc,
+                step,
+                format!(
+                    "step '{}' exceeded max_visits ({})",
+                    step.name,
+                    step.max_visits()
+                ),
+            );
+            return;
+        }
+
+        let next = match ctx.evaluate_when(step) {
+            Ok(true) => {
+                let Some((outcome, error)) = ctx.run_step(pc, step, visit) else {
+                    return;
+                };
+                let failed = outcome.status == StepOutcomeStatus::Failed;
+                ctx.record_outcome(step, outcome);
+
+                if failed {
+                    match step.on_failure.as_ref().unwrap_or(&FailureAction::Abort) {
+                        FailureAction::Abort => {
+                            ctx.fail_workflow(pc, step, error.unwrap_or_default());
+                            return;
+                        }
+                        FailureAction::Continue => {
+                            ctx.send_log(format!(
+                                "ステップ '{}' は失敗しましたが続行します",
+                                step.name
+                            ));
+                            Some(pc + 1)
+                        }
+                        FailureAction::Goto(target) => ctx.resolve_jump(pc, step, target),
+                    }
+                } else if let Some(target) = &step.goto {
+                    ctx.resolve_jump(pc, step, target)
+                } else {
+                    Some(pc + 1)
+                }
+            }
+            Ok(false) => {
+                ctx.skip_step(pc, step);
+                Some(pc + 1)
+            }
+            Err(err) => {
+                ctx.fail_workflow(pc, step, format!("{err:#}"));
+                return;
+            }
+        };
+        let Some(next) = next else {
+            return;
+        };
+        pc = next;
+
+        ctx.completed_steps.store(pc, Ordering::SeqCst);
+
+        // Persist the worker state to disk
+        let _ = ctx.cmd_tx.send(WorkerCommand::Persist { id: ctx.worker_id });
+
+        thread::sleep(STEP_INTERVAL);
+    }
+
+    ctx.update_snapshot(|snapshot| {
+        snapshot.status = WorkerStatus::Idle;
+        snapshot.last_event = format!("ワークフロー '{}' が完了", workflow.name);
+        snapshot.current_step = None;
+    });
+
+    ctx.send_log(format!("Workflow '{}' completed", workflow.name));
+}
+
+impl AgentContext {
+    /// Save a log line and forward it to the UI
+    fn send_log(&self, line: String) {
+        if let Some(buffer) = &self.log_buffer {
+            if let Ok(mut buffer) = buffer.lock() {
+                buffer.push(line);
+            }
+            return;
+        }
+
+        if let Ok(mut log_queue) = self.logs.lock() {
+            if log_queue.len() >= MAX_LOGS {
+                log_queue.pop_front();
+            }
+            log_queue.push_back(line.clone());
+        }
+
+        let _ = self.evt_tx.send(WorkerEvent::Log {
+            id: self.worker_id,
+            line,
+        });
+    }
+
+    /// Apply `f` to the shared snapshot and publish the result
+    fn update_snapshot(&self, f: impl FnOnce(&mut WorkerSnapshot)) -> WorkerSnapshot {
+        let mut snapshot = self
+            .state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        f(&mut snapshot);
+        let _ = self.evt_tx.send(WorkerEvent::Updated(snapshot.clone()));
+        snapshot.clone()
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancel.load(Ordering::SeqCst)
+    }
+
+    /// `current_step` label: position in the workflow, plus the visit count
+    /// when a jump brought us back to the step.
+    fn step_label(&self, idx: usize, step: &WorkflowStep, visit: u32) -> String {
+        let total_steps = self.workflow.steps().len();
+        if visit > 1 {
+            format!("{}/{}: {} ({}回目)", idx + 1, total_steps, step.name, visit)
+        } else {
+            format!("{}/{}: {}", idx + 1, total_steps, step.name)
+        }
+    }
+
+    fn evaluate_when(&self, step: &WorkflowStep) -> Result<bool> {
+        let Some(condition) = &step.when else {
+            return Ok(true);
+        };
+        let key = self
+            .workflow
+            .step_index(&condition.step)
+            .map(|idx| self.workflow.steps()[idx].key().to_string())
+            .unwrap_or_else(|| condition.step.clone());
+        let outcomes = self
+            .step_outcomes
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        condition
+            .matches(outcomes.get(&key))
+            .with_context(|| format!("failed to evaluate 'when' of step '{}'", step.name))
+    }
+
+    fn record_outcome(&self, step: &WorkflowStep, outcome: StepOutcome) {
+        if let Ok(mut outcomes) = self.step_outcomes.lock() {
+            outcomes.insert(step.key().to_string(), outcome);
+        }
+    }
+
+    fn skip_step(&self, idx: usize, step: &WorkflowStep) {
+        self.send_log(format!("[STEP_START:{}:{}]", idx, step.name));
+        self.send_log("[RESULT_START]".to_string());
+        self.send_log(format!("[{}] 条件を満たさないためスキップしました", step.name));
+        self.send_log("[RESULT_END]".to_string());
+        self.send_log("[STEP_END:Skipped]".to_string());
+        self.record_outcome(
+            step,
+            StepOutcome {
+                status: StepOutcomeStatus::Skipped,
+                ..Default::default()
+            },
+        );
+    }
+
+    /// Resolve a `goto`/`on_failure` target. Fails the workflow and returns
+    /// None when the target does not exist.
+    fn resolve_jump(&self, idx: usize, step: &WorkflowStep, target: &str) -> Option<usize> {
+        match self.workflow.step_index(target) {
+            Some(next) => {
+                self.send_log(format!(
+                    "→ ステップ '{}' へジャンプします",
+                    self.workflow.steps()[next].name
+                ));
+                Some(next)
+            }
+            None => {
+                self.fail_workflow(idx, step, format!("unknown jump target '{}'", target));
+                None
+            }
+        }
+    }
+
+    fn fail_workflow(&self, idx: usize, step: &WorkflowStep, message: String) {
+        let step_label = self.step_label(idx, step, 1);
+        self.update_snapshot(|snapshot| {
+            snapshot.status = WorkerStatus::Failed;
+            snapshot.last_event = format!("Command failed: {message}");
+            snapshot.current_step = Some(step_label);
+        });
+        let _ = self.evt_tx.send(WorkerEvent::Error {
+            id: Some(self.worker_id),
+            message,
+        });
+    }
+
+    /// Run one workflow step including its retries. Returns the outcome of the
+    /// last attempt with its error message, or None when the workflow must stop
+    /// right away (denial or cancellation).
+    fn run_step(
+        &self,
+        idx: usize,
+        step: &WorkflowStep,
+        visit: u32,
+    ) -> Option<(StepOutcome, Option<String>)> {
+        let step_label = self.step_label(idx, step, visit);
+        let step_desc = step
+            .description
+            .clone()
+            .unwrap_or_else(|| "ステップを実行".to_string());
+
+        // A pause that arrived after the boundary check must not be
+        // overwritten: the flag is read under the snapshot lock, which
+        // `handle_pause` also takes after raising it.
+        let snapshot_info = self.update_snapshot(|snapshot| {
+            snapshot.status = if self.paused.load(Ordering::SeqCst) {
+                WorkerStatus::Paused
+            } else {
+                WorkerStatus::Running
+            };
+            snapshot.current_step = Some(step_label.clone());
+            snapshot.last_event = step_desc.clone();
+        });
+
+        if !step.parallel.is_empty() {
+            return self.run_parallel(idx, step, &snapshot_info);
+        }
+
+        // Step start marker
+        self.send_log(format!("[STEP_START:{}:{}]", idx, step.name));
+        self.send_log(format!("[{}] {}", step.name, step_desc));
+
+        let action = self.prepare_step(step, &snapshot_info)?;
+        self.execute_step(idx, &step.name, step, &step_desc, &action)
+    }
+
+    /// Run a prepared step including its retries. `section` is the name its
+    /// log sections are shown under.
+    fn execute_step(
+        &self,
+        idx: usize,
+        section: &str,
+        step: &WorkflowStep,
+        step_desc: &str,
+        action: &StepAction,
+    ) -> Option<(StepOutcome, Option<String>)> {
+        let max_attempts = step.max_attempts();
+        let mut attempt = 1;
+        loop {
+            if max_attempts > 1 {
+                self.send_log(format!("[STEP_ATTEMPT:{}/{}]", attempt, max_attempts));
+            }
+
+            let result = self.run_with_timeout(step.timeout_duration(), || {
+                let output = self.run_action(action)?;
+                self.auto_commit(step, action, output)
+            });
+
+            // Result section
+            self.send_log("[RESULT_START]".to_string());
+
+            let err = match result {
+                Ok(output) => {
+                    for line in &output.lines {
+                        self.send_log(line.clone());
+                    }
+                    self.send_log("[RESULT_END]".to_string());
+                    // Step end marker (success)
+                    self.send_log("[STEP_END:Success]".to_string());
+                    return Some((
+                        StepOutcome {
+                            status: StepOutcomeStatus::Success,
+                            exit_code: Some(0),
+                            stdout: output.lines.join("\n"),
+                            result: output.result,
+                            commit: output.commit,
+                        },
+                        None,
+                    ));
+                }
+                Err(_) if self.is_cancelled() => {
+                    // The process was terminated by stop_agent; the caller owns the status
+                    self.send_log("ステップが中断されました".to_string());
+                    self.send_log("[RESULT_END]".to_string());
+                    self.send_log("[STEP_END:Failed]".to_string());
+                    return None;
+                }
+                Err(err) => err,
+            };
+
+            let failure = err.downcast_ref::<CommandFailed>();
+            for line in failure.map(|f| f.output.as_slice()).unwrap_or_default() {
+                self.send_log(line.clone());
+            }
+            self.send_log(format!("Error: {err}"));
+            self.send_log("[RESULT_END]".to_string());
+            // Step end marker (failed)
+            self.send_log("[STEP_END:Failed]".to_string());
+
+            if attempt >= max_attempts {
+                let outcome = StepOutcome {
+                    status: StepOutcomeStatus::Failed,
+                    exit_code: failure.and_then(|f| f.exit_code),
+                    stdout: failure.map(|f| f.output.join("\n")).unwrap_or_default(),
+                    result: None,
+                    commit: None,
+                };
+                return Some((outcome, Some(err.to_string())));
+            }
+
+            let delay = step.retry_delay(attempt);
+            self.update_snapshot(|snapshot| {
+                snapshot.last_event = format!(
+                    "リトライ待機中 ({}/{}, {}秒後)",
+                    attempt + 1,
+                    max_attempts,
+                    delay.as_secs()
+                );
+            });
+            if !self.wait_for_retry(delay) {
+                return None;
+            }
+
+            attempt += 1;
+            self.update_snapshot(|snapshot| {
+                snapshot.last_event = format!("{} (リトライ {}/{})", step_desc, attempt, max_attempts);
+            });
+            self.send_log(format!("[STEP_START:{}:{}]", idx, section));
+            self.send_log(format!(
+                "[{}] {} (リトライ {}/{})",
+                section, step_desc, attempt, max_attempts
+            ));
+        }
+    }
+
+    /// Run the children of a `parallel` group concurrently in the worktree.
+    /// Each child's sections are published in one block once it finishes so
+    /// the structured log never interleaves them.
+    fn run_parallel(
+        &self,
+        idx: usize,
+        group: &WorkflowStep,
+        snapshot_info: &WorkerSnapshot,
+    ) -> Option<(StepOutcome, Option<String>)> {
+        // Permission prompts are answered one at a time, so settle them up front
+        let mut prepared = Vec::with_capacity(group.parallel.len());
+        for child in &group.parallel {
+            prepared.push((child, self.prepare_step(child, snapshot_info)?));
+        }
+
+        let names: Vec<&str> = group.parallel.iter().map(|c| c.name.as_str()).collect();
+        self.send_log(format!("[{}] 並列実行: {}", group.name, names.join(", ")));
+
+        let publish = Mutex::new(());
+        let results: Vec<Option<(StepOutcome, Option<String>)>> = thread::scope(|scope| {
+            let handles: Vec<_> = prepared
+                .iter()
+                .map(|(child, action)| {
+                    let publish = &publish;
+                    scope.spawn(move || {
+                        let child_ctx = self.child_context();
+                        let section = format!("{} / {}", group.name, child.name);
+                        let desc = child
+                            .description
+                            .clone()
+                            .unwrap_or_else(|| "ステップを実行".to_string());
+                        child_ctx.send_log(format!("[STEP_START:{}:{}]", idx, section));
+                        child_ctx.send_log(format!("[{}] {}", section, desc));
+                        let result = child_ctx.execute_step(idx, &section, child, &desc, action);
+
+                        let _publishing = publish.lock().unwrap_or_else(|p| p.into_inner());
+                        for line in child_ctx.take_buffered_logs() {
+                            self.send_log(line);
+                        }
+                        if let Some((outcome, _)) = &result {
+                            self.record_outcome(child, outcome.clone());
+                        }
+                        result
+                    })
+                })
+                .collect();
+
+            handles
+                .into_iter()
+                .map(|handle| {
+                    handle.join().unwrap_or_else(|_| {
+                        let outcome = StepOutcome {
+                            status: StepOutcomeStatus::Failed,
+                            ..Default::default()
+                        };
+                        Some((outcome, Some("parallel step panicked".to_string())))
+                    })
+                })
+                .collect()
+        });
+
+        // A cancelled child means the whole worker was cancelled
+        let results: Vec<(StepOutcome, Option<String>)> = results.into_iter().collect::<Option<_>>()?;
+
+        let failed: Vec<&str> = group
+            .parallel
+            .iter()
+            .zip(&results)
+            .filter(|(_, (outcome, _))| outcome.status == StepOutcomeStatus::Failed)
+            .map(|(child, _)| child.name.as_str())
+            .collect();
+
+        // Summary section for the group itself
+        self.send_log(format!("[STEP_START:{}:{}]", idx, group.name));
+        self.send_log("[RESULT_START]".to_string());
+        for (child, (outcome, _)) in group.parallel.iter().zip(&results) {
+            match outcome.status {
+                StepOutcomeStatus::Failed => match outcome.exit_code {
+                    Some(code) => self.send_log(format!("✗ {} (exit {})", child.name, code)),
+                    None => self.send_log(format!("✗ {}", child.name)),
+                },
+                _ => self.send_log(format!("✓ {}", child.name)),
+            }
+        }
+        self.send_log("[RESULT_END]".to_string());
+
+        let stdout = group
+            .parallel
+            .iter()
+            .zip(&results)
+            .map(|(child, (outcome, _))| format!("--- {} ---\n{}", child.name, outcome.stdout))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        if failed.is_empty() {
+            self.send_log("[STEP_END:Success]".to_string());
+            let outcome = StepOutcome {
+                status: StepOutcomeStatus::Success,
+                exit_code: Some(0),
+                stdout,
+                result: None,
+                commit: None,
+            };
+            Some((outcome, None))
+        } else {
+            self.send_log("[STEP_END:Failed]".to_string());
+            let exit_code = results
+                .iter()
+                .find(|(outcome, _)| outcome.status == StepOutcomeStatus::Failed)
+                .and_then(|(outcome, _)| outcome.exit_code);
+            let outcome = StepOutcome {
+                status: StepOutcomeStatus::Failed,
+                exit_code,
+                stdout,
+                result: None,
+                commit: None,
+            };
+            Some((outcome, Some(format!("parallel steps failed: {}", failed.join(", ")))))
+        }
+    }
+
+    /// Context for one child of a parallel group: its own log buffer and a
+    /// scoped process tracker so a timeout only kills that child's processes.
+    fn child_context(&self) -> AgentContext {
+        AgentContext {
+            worker_id: self.worker_id,
+            state: Arc::clone(&self.state),
+            cancel: Arc::clone(&self.cancel),
+            paused: Arc::clone(&self.paused),
+            processes: self.processes.scoped(),
+            worktree_path: self.worktree_path.clone(),
+            evt_tx: self.evt_tx.clone(),
+            workflow: self.workflow.clone(),
+            completed_steps: Arc::clone(&self.completed_steps),
+            logs: Arc::clone(&self.logs),
+            cmd_tx: self.cmd_tx.clone(),
+            session_histories: Arc::clone(&self.session_histories),
+            step_outcomes: Arc::clone(&self.step_outcomes),
+            agents: Arc::clone(&self.agents),
+            default_sandbox_mode: self.default_sandbox_mode,
+            issue: self.issue.clone(),
+            forge: self.forge.clone(),
+            base_ref: self.base_ref.clone(),
+            auto_commit: self.auto_commit,
+            log_buffer: Some(Arc::new(Mutex::new(Vec::new()))),
+        }
+    }
+
+    fn take_buffered_logs(&self) -> Vec<String> {
+        self.log_buffer
+            .as_ref()
+            .and_then(|buffer| buffer.lock().ok().map(|mut lines| std::mem::take(&mut *lines)))
+            .unwrap_or_default()
+    }
+
+    /// Sleep before a retry. Paused time does not count towards the delay.
+    /// Returns false if the worker was cancelled meanwhile.
+    fn wait_for_retry(&self, delay: Duration) -> bool {
+        let mut waited = Duration::ZERO;
+        while waited < delay {
+            if self.is_cancelled() {
+                return false;
+            }
+            thread::sleep(PAUSE_POLL_INTERVAL);
+            if !self.paused.load(Ordering::SeqCst) {
+                waited += PAUSE_POLL_INTERVAL;
+            }
+        }
+        !self.is_cancelled()
+    }
+
+    /// Run `f`, killing the step's processes once `timeout` of unpaused time
+    /// has elapsed.
+    fn run_with_timeout<T>(
+        &self,
+        timeout: Option<Duration>,
+        f: impl FnOnce() -> Result<T>,
+    ) -> Result<T> {
+        let Some(timeout) = timeout else {
+            return f();
+        };
+
+        let timed_out = Arc::new(AtomicBool::new(false));
+        let (done_tx, done_rx) = mpsc::channel::<()>();
+        let watchdog = {
+            let timed_out = Arc::clone(&timed_out);
+            let paused = Arc::clone(&self.paused);
+            let processes = self.processes.clone();
+            thread::spawn(move || {
+                let mut elapsed = Duration::ZERO;
+                loop {
+                    match done_rx.recv_timeout(PAUSE_POLL_INTERVAL) {
+                        Err(RecvTimeoutError::Timeout) => {
+                            if !paused.load(Ordering::SeqCst) {
+                                elapsed += PAUSE_POLL_INTERVAL;
+                            }
+                            if elapsed >= timeout {
+                                timed_out.store(true, Ordering::SeqCst);
+                                processes.kill_running(KILL_GRACE_PERIOD);
+                                return;
+                            }
+                        }
+                        _ => return,
+                    }
+                }
+            })
+        };
+
+        let result = f();
+        drop(done_tx);
+        let _ = watchdog.join();
+
+        if timed_out.load(Ordering::SeqCst) {
+            return Err(anyhow!("step timed out after {}s", timeout.as_secs()));
+        }
+        result
+    }
+
+    /// Settle permissions and render the prompt. Returns None when the step
+    /// must not run; the snapshot has already been updated in that case.
+    fn prepare_step(&self, step: &WorkflowStep, snapshot_info: &WorkerSnapshot) -> Option<StepAction> {
+        if let Some(pr) = &step.pr {
+            let default_base = self.forge.as_ref().and_then(|forge| forge.base());
+            let request = self.with_template_context(snapshot_info, |ctx| {
+                render_pull_request(pr, default_base, ctx)
+            });
+            return Some(StepAction::PullRequest(request));
+        }
+        if let Some(merge) = &step.merge {
+            return self.prepare_merge(step, merge, snapshot_info);
+        }
+        if let Some(commit) = &step.commit {
+            return self.prepare_commit(step, commit, snapshot_info);
+        }
+        let Some(claude_cfg) = &step.claude else {
+            return Some(match &step.command {
+                Some(command) => StepAction::Shell(command.clone()),
+                None => StepAction::Noop,
+            });
+        };
+
+        let (config, agent_name, agent) = self.prepare_agent(step, claude_cfg, snapshot_info)?;
+        let prompt = self.with_template_context(snapshot_info, |ctx| {
+            render_prompt(&claude_cfg.prompt, ctx)
+        });
+
+        Some(StepAction::Agent {
+            prompt,
+            config,
+            agent_name,
+            agent,
+            auto_commit: claude_cfg.auto_commit.unwrap_or(self.auto_commit),
+        })
+    }
+
+    /// Render a `merge` step. With `resolve_conflicts` the resolving agent's
+    /// permissions are settled up front, like those of an agent step.
+    fn prepare_merge(
+        &self,
+        step: &WorkflowStep,
+        merge: &MergeStep,
+        snapshot_info: &WorkerSnapshot,
+    ) -> Option<StepAction> {
+        let request = self.with_template_context(snapshot_info, |ctx| {
+            render_merge(merge, self.base_ref.as_deref(), ctx)
+        });
+        let resolver = if merge.resolve_conflicts && !merge.dry_run && request.is_ok() {
+            let claude_cfg = ClaudeStep {
+                prompt: merge
+                    .resolve_prompt
+                    .clone()
+                    .unwrap_or_else(|| DEFAULT_RESOLVE_PROMPT.to_string()),
+                permission_mode: Some("acceptEdits".to_string()),
+                ..Default::default()
+            };
+            Some(self.prepare_helper(step, claude_cfg, snapshot_info)?)
+        } else {
+            None
+        };
+        Some(StepAction::Merge { request, resolver })
+    }
+
+    /// Render a `commit` step. With `generate_message` the writing agent's
+    /// permissions are settled up front.
+    fn prepare_commit(
+        &self,
+        step: &WorkflowStep,
+        commit: &CommitStep,
+        snapshot_info: &WorkerSnapshot,
+    ) -> Option<StepAction> {
+        let message = self.with_template_context(snapshot_info, |ctx| {
+            render_commit_message(commit.message.as_deref(), &step.name, ctx.session_result, ctx)
+        });
+        let writer = if commit.generate_message {
+            let claude_cfg = ClaudeStep {
+                prompt: COMMIT_MESSAGE_PROMPT.to_string(),
+                permission_mode: Some("plan".to_string()),
+                ..Default::default()
+            };
+            Some(self.prepare_helper(step, claude_cfg, snapshot_info)?)
+        } else {
+            None
+        };
+        Some(StepAction::Commit { message, writer })
+    }
+
+    /// Settle the permissions of an agent run on behalf of `step`.
+    fn prepare_helper(
+        &self,
+        step: &WorkflowStep,
+        claude_cfg: ClaudeStep,
+        snapshot_info: &WorkerSnapshot,
+    ) -> Option<HelperAgent> {
+        let (config, agent_name, agent) = self.prepare_agent(step, &claude_cfg, snapshot_info)?;
+        let prompt = self.with_template_context(snapshot_info, |ctx| {
+            render_prompt(&claude_cfg.prompt, ctx)
+        });
+        Some(HelperAgent {
+            config,
+            prompt,
+            agent_name,
+            agent,
+        })
+    }
+
+    /// Ask for the permissions of an agent run and resolve its backend.
+    /// Returns None when the run must not happen; the snapshot has already
+    /// been updated in that case.
+    fn prepare_agent(
+        &self,
+        step: &WorkflowStep,
+        claude_cfg: &ClaudeStep,
+        snapshot_info: &WorkerSnapshot,
+    ) -> Option<(ClaudeStep, String, Arc<dyn Agent>)> {
+        let request_id = NEXT_PERMISSION_REQUEST_ID.fetch_add(1, Ordering::SeqCst);
+        let permission_request = PermissionRequest {
+            request_id,
+            step_name: step.name.clone(),
+            description: step.description.clone(),
+            permission_mode: claude_cfg.permission_mode.clone(),
+            allowed_tools: claude_cfg.allowed_tools.clone(),
+        };
+
+        let (perm_tx, perm_rx) = mpsc::channel();
+        if let Err(err) = self.cmd_tx.send(WorkerCommand::PermissionPrompt {
+            id: self.worker_id,
+            request: permission_request.clone(),
+            respond_to: perm_tx,
+        }) {
+            self.send_log(format!("権限要求をエンキューできませんでした: {err}"));
+            self.update_snapshot(|snapshot| {
+                snapshot.status = WorkerStatus::Failed;
+                snapshot.last_event = "権限要求の送信に失敗しました".into();
+                snapshot.current_step = None;
+            });
+            return None;
+        }
+
+        self.update_snapshot(|snapshot| {
+            snapshot.last_event = format!("ステップ '{}' の権限確認を待機中", step.name);
+        });
+
+        let tools_label = describe_allowed_tools(permission_request.allowed_tools.as_ref());
+
+        self.send_log(format!(
+            "権限確認待ち (request #{}, tools: {})",
+            permission_request.request_id, tools_label
+        ));
+
+        let (effective_permission_mode, effective_allowed_tools) = match perm_rx.recv() {
+            Ok(PermissionDecision::Allow {
+                permission_mode,
+                allowed_tools,
+            }) => {
+                self.send_log("権限が承認されました".to_string());
+                (permission_mode, allowed_tools)
+            }
+            Ok(PermissionDecision::Deny) => {
+                self.send_log("権限が拒否されました。ステップを中断します".to_string());
+                self.update_snapshot(|snapshot| {
+                    snapshot.status = WorkerStatus::Paused;
+                    snapshot.last_event = format!("ステップ '{}' の権限が拒否されました", step.name);
+                    snapshot.current_step = None;
+                });
+                return None;
+            }
+            Err(_err) => {
+                self.send_log("権限確認中に内部エラーが発生したため中断します".to_string());
+                self.update_snapshot(|snapshot| {
+                    snapshot.status = WorkerStatus::Failed;
+                    snapshot.last_event = "権限確認中にエラーが発生".into();
+                    snapshot.current_step = None;
+                });
+                return None;
+            }
+        };
+
+        // Override claude_cfg with user-selected permissions
+        let mut config = claude_cfg.clone();
+        config.permission_mode =
+            effective_permission_mode.or_else(|| claude_cfg.permission_mode.clone());
+        config.allowed_tools = effective_allowed_tools.or_else(|| claude_cfg.allowed_tools.clone());
+
+        // Resolve the backend: step override, then the worker's agent, then the default
+        let requested_agent = step
+            .agent
+            .clone()
+            .unwrap_or_else(|| snapshot_info.agent.clone());
+        let (agent_name, agent) = match self.agents.get(&requested_agent) {
+            Some(agent) => (requested_agent, agent),
+            None => {
+                self.send_log(format!(
+                    "エージェント '{}' が見つからないため '{}' を使用します",
+                    requested_agent, DEFAULT_AGENT
+                ));
+                let agent = self
+                    .agents
+                    .get(DEFAULT_AGENT)
+                    .expect("default agent must always be registered");
+                (DEFAULT_AGENT.to_string(), agent)
+            }
+        };
+
+        Some((config, agent_name, agent))
+    }
+
+    /// Run `f` with the placeholder values of the current run.
+    fn with_template_context<T>(
+        &self,
+        snapshot_info: &WorkerSnapshot,
+        f: impl FnOnce(&TemplateContext<'_>) -> T,
+    ) -> T {
+        let outcomes = self
+            .step_outcomes
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let histories = self
+            .session_histories
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        f(&TemplateContext {
+            snapshot: snapshot_info,
+            workflow: &self.workflow,
+            outcomes: &outcomes,
+            worktree_path: &self.worktree_path,
+            issue: self.issue.as_ref(),
+            session_result: histories.iter().rev().find_map(SessionHistory::final_result),
+        })
+    }
+
+    /// Execute one attempt of a prepared step
+    fn run_action(&self, action: &StepAction) -> Result<AttemptOutput> {
+        match action {
+            StepAction::Agent {
+                config,
+                prompt,
+                agent_name,
+                agent,
+                ..
+            } => self.run_agent(config, prompt, agent_name, agent.as_ref()),
+            StepAction::Shell(command) => {
+                self.send_log(format!("$ {}", command));
+                let lines = run_shell_command(command, &self.worktree_path, &self.processes)?;
+                Ok(AttemptOutput {
+                    lines,
+                    result: None,
+                    commit: None,
+                })
+            }
+            StepAction::PullRequest(Ok(request)) => self.open_pull_request(request),
+            StepAction::PullRequest(Err(err)) => Err(anyhow!("{err:#}")),
+            StepAction::Merge {
+                request: Ok(request),
+                resolver,
+            } => self.run_merge(request, resolver.as_ref()),
+            StepAction::Merge { request: Err(err), .. } => Err(anyhow!("{err:#}")),
+            StepAction::Commit { message, writer } => self.run_commit(message, writer.as_ref()),
+            StepAction::Noop => Ok(AttemptOutput {
+                lines: vec!["(no-op step)".into()],
+                result: None,
+                commit: None,
+            }),
+        }
+    }
+
+    /// Push the worker branch and open (or look up) its pull request.
+    fn open_pull_request(&self, request: &PullRequest) -> Result<AttemptOutput> {
+        let forge = self
+            .forge
+            .as_ref()
+            .ok_or_else(|| anyhow!("no forge is configured; set the forge or issues section"))?;
+        self.send_log(format!("$ git push --set-upstream {} {}", forge.remote(), request.head));
+        self.send_log(format!(
+            "{} にプルリクエストを作成: {} → {} 「{}」",
+            forge.name(),
+            request.head,
+            request.base,
+            request.title
+        ));
+        let url = forge.publish(&self.worktree_path, request)?;
+        self.update_snapshot(|snapshot| {
+            snapshot.pr_url = Some(url.clone());
+            snapshot.last_event = format!("プルリクエストを作成しました: {url}");
+        });
+        Ok(AttemptOutput {
+            lines: vec![format!("PR: {url}")],
+            result: Some(url),
+            commit: None,
+        })
+    }
+
+    /// Merge the worker branch. Conflicts are handed to `resolver` once, after
+    /// which the merge is tried again.
+    fn run_merge(
+        &self,
+        request: &MergeRequest,
+        resolver: Option<&HelperAgent>,
+    ) -> Result<AttemptOutput> {
+        self.send_log(format!(
+            "{} を {} に取り込み ({}{})",
+            request.branch,
+            request.base,
+            request.strategy.label(),
+            if request.dry_run { ", コンフリクト確認のみ" } else { "" }
+        ));
+        let mut lines = Vec::new();
+        let mut resolved = false;
+        let outcome = loop {
+            let err = match merge::merge(&self.worktree_path, request) {
+                Ok(outcome) => break outcome,
+                Err(err) => err,
+            };
+            let (Some(conflict), Some(resolver)) = (err.downcast_ref::<MergeConflict>(), resolver)
+            else {
+                return Err(err);
+            };
+            if resolved {
+                return Err(err);
+            }
+            resolved = true;
+            lines.push(format!("コンフリクト: {}", conflict.files.join(", ")));
+            self.send_log(format!(
+                "コンフリクトが発生しました: {}。{} に解消を依頼します",
+                conflict.files.join(", "),
+                resolver.agent.display_name()
+            ));
+            let files = merge::start_conflict_resolution(&self.worktree_path, &request.base)?;
+            let prompt = with_conflicting_files(&resolver.prompt, &files);
+            if let Err(err) =
+                self.run_agent(&resolver.config, &prompt, &resolver.agent_name, resolver.agent.as_ref())
+            {
+                let _ = merge::abort_conflict_resolution(&self.worktree_path);
+                return Err(err.context("conflict resolution failed"));
+            }
+        };
+
+        let summary = match &outcome {
+            MergeOutcome::UpToDate => format!("{} は取り込み済みです", request.branch),
+            MergeOutcome::Mergeable => {
+                format!("{} は {} にコンフリクトなく取り込めます", request.branch, request.base)
+            }
+            MergeOutcome::Merged { commit } => {
+                format!("{} を {} に取り込みました ({})", request.branch, request.base, short_sha(commit))
+            }
+        };
+        self.update_snapshot(|snapshot| {
+            snapshot.last_event = summary.clone();
+        });
+        lines.push(summary);
+        let result = match outcome {
+            MergeOutcome::Merged { commit } => Some(commit),
+            _ => None,
+        };
+        Ok(AttemptOutput {
+            lines,
+            result,
+            commit: None,
+        })
+    }
+
+    /// Stage every change and commit it, with a message from `writer` when
+    /// given and `message` otherwise.
+    fn run_commit(&self, message: &str, writer: Option<&HelperAgent>) -> Result<AttemptOutput> {
+        self.send_log("$ git add --all".to_string());
+        if !commit::stage_all(&self.worktree_path)? {
+            return Ok(AttemptOutput {
+                lines: vec!["コミットする変更はありません".to_string()],
+                result: None,
+                commit: None,
+            });
+        }
+
+        let mut message = message.to_string();
+        if let Some(writer) = writer {
+            let prompt = with_staged_diff(&writer.prompt, &commit::staged_diff(&self.worktree_path)?);
+            let output =
+                self.run_agent(&writer.config, &prompt, &writer.agent_name, writer.agent.as_ref())?;
+            match output.result.as_deref().map(str::trim) {
+                Some(generated) if !generated.is_empty() => message = generated.to_string(),
+                _ => self.send_log(
+                    "コミットメッセージが返されなかったため既定のメッセージを使用します".to_string(),
+                ),
+            }
+        }
+
+        let sha = commit::commit_staged(&self.worktree_path, &message)?;
+        let line = self.commit_summary(&sha, &message);
+        Ok(AttemptOutput {
+            lines: vec![line],
+            result: None,
+            commit: Some(sha),
+        })
+    }
+
+    /// Commit what an agent step left behind when auto-commit applies to it.
+    /// Children of a parallel group share the index, so they never commit;
+    /// the next agent step picks their changes up.
+    fn auto_commit(
+        &self,
+        step: &WorkflowStep,
+        action: &StepAction,
+        mut output: AttemptOutput,
+    ) -> Result<AttemptOutput> {
+        if !matches!(action, StepAction::Agent { auto_commit: true, .. }) || self.log_buffer.is_some() {
+            return Ok(output);
+        }
+        let snapshot = self
+            .state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone();
+        let message = self.with_template_context(&snapshot, |ctx| {
+            render_commit_message(None, &step.name, output.result.as_deref(), ctx)
+        });
+        let sha = commit::commit_all(&self.worktree_path, &message)
+            .context("failed to commit the agent's changes")?;
+        if let Some(sha) = sha {
+            output.lines.push(self.commit_summary(&sha, &message));
+            output.commit = Some(sha);
+        }
+        Ok(output)
+    }
+
+    /// Log line for a new commit, also shown as the worker's last event.
+    fn commit_summary(&self, sha: &str, message: &str) -> String {
+        let line = format!(
+            "コミットしました {}: {}",
+            short_sha(sha),
+            message.lines().next().unwrap_or_default()
+        );
+        self.update_snapshot(|snapshot| {
+            snapshot.last_event = line.clone();
+        });
+        line
+    }
+
+    fn run_agent(
+        &self,
+        config: &ClaudeStep,
+        prompt: &str,
+        agent_name: &str,
+        agent: &dyn Agent,
+    ) -> Result<AttemptOutput> {
+        let agent_label = agent.display_name().to_string();
+
+        // Prompt section
+        self.send_log("[PROMPT_START]".to_string());
+        self.send_log("─── Prompt ───".to_string());
+        for line in prompt.lines() {
+            self.send_log(line.to_string());
+        }
+        self.send_log("[PROMPT_END]".to_string());
+
+        // Command details section
+        self.send_log(format!("─── {} コマンド ───", agent_label));
+
+        // Permission Mode
+        let effective_mode = config
+            .permission_mode
+            .as_deref()
+            .unwrap_or("bypassPermissions");
+        let permission_mode_str = match effective_mode {
+            "plan" => "プランモード (plan)".to_string(),
+            "acceptEdits" => "編集承認モード (acceptEdits)".to_string(),
+            "bypassPermissions" => "制限なしモード (bypassPermissions)".to_string(),
+            other => other.to_string(),
+        };
+        self.send_log(format!("Permission Mode: {}", permission_mode_str));
+
+        // Model
+        let model_str = config.model.as_deref().unwrap_or("デフォルト");
+        self.send_log(format!("Model: {}", model_str));
+
+        // Allowed Tools
+        let tools_str = match &config.allowed_tools {
+            Some(tools) if tools.is_empty() => "なし".to_string(),
+            Some(tools) => tools.join(", "),
+            None => "全て".to_string(),
+        };
+        self.send_log(format!("Allowed Tools: {}", tools_str));
+
+        // Session ids are only meaningful to the agent that issued them
+        let snapshot_info = self.update_snapshot(|snapshot| {
+            snapshot.last_event = format!("{}を実行中... ⏳", agent_label);
+        });
+        // Concurrent children of a parallel group must not resume the same session
+        let shares_session = agent_name == snapshot_info.agent && self.log_buffer.is_none();
+        let current_session_id = snapshot_info
+            .session_id
+            .as_deref()
+            .filter(|_| shares_session);
+        let session_str = if current_session_id.is_some() {
+            "継続"
+        } else {
+            "新規"
+        };
+        self.send_log(format!("Session: {}", session_str));
+
+        // Extra Args
+        if let Some(extra) = &config.extra_args
+            && !extra.is_empty()
+        {
+            self.send_log(format!("Extra Args: {}", extra.join(" ")));
+        }
+
+        self.send_log("".to_string());
+        self.send_log("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━".to_string());
+        self.send_log(format!("⏳ {}実行中...", agent_label));
+        self.send_log("   出力はリアルタイムで表示されます".to_string());
+        self.send_log("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━".to_string());
+        self.send_log("".to_string());
+
+        // Pass current session_id to continue the session
+        let invocation = AgentInvocation {
+            prompt,
+            workdir: &self.worktree_path,
+            session_id: current_session_id,
+            step: config,
+            default_sandbox_mode: self.default_sandbox_mode,
+        };
+        let result = run_agent_command(agent, &invocation, &self.processes, |line| {
+            self.send_log(line)
+        });
+
+        self.send_log("".to_string());
+        self.send_log("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━".to_string());
+        self.send_log(format!("✅ {}実行完了", agent_label));
+        self.send_log("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━".to_string());
+        self.send_log("".to_string());
+
+        let (new_session_id, session_history) = result?;
+
+        // Update session_id in snapshot
+        if let Some(sid) = new_session_id.filter(|_| shares_session) {
+            self.update_snapshot(|snapshot| {
+                snapshot.session_id = Some(sid);
+            });
+        }
+
+        let result = session_history.final_result().map(str::to_string);
+
+        // Add session history
+        if let Ok(mut histories) = self.session_histories.lock() {
+            histories.push(session_history);
+        }
+
+        Ok(AttemptOutput {
+            lines: vec![],
+            result,
+            commit: None,
+        })
+    }
+}
+
+fn run_shell_command(
+    command: &str,
+    dir: &Path,
+    processes: &ProcessTracker,
+) -> Result<Vec<String>> {
+    // Run in a dedicated process group so cancellation reaches every descendant
+    let child = Command::new("bash")
+        .arg("-lc")
+        .arg(command)
+        .current_dir(dir)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .process_group(0)
+        .spawn()
+        .with_context(|| format!("failed to execute shell command '{command}'"))?;
+    let _process_guard = processes.register(child.id());
+    let output = child
+        .wait_with_output()
+        .with_context(|| format!("failed to wait for shell command '{command}'"))?;
+
+    let mut lines = Vec::new();
+
+    // Process stdout
+    let stdout_str = String::from_utf8_lossy(&output.stdout);
+    for line in stdout_str.lines() {
+        if !line.trim().is_empty() {
+            lines.push(line.to_string());
+        }
+    }
+
+    // Process stderr
+    let stderr_str = String::from_utf8_lossy(&output.stderr);
+    for line in stderr_str.lines() {
+        if !line.trim().is_empty() {
+            lines.push(line.to_string());
+        }
+    }
+
+    if !output.status.success() {
+        return Err(CommandFailed {
+            message: format!("command '{command}' exited with status {}", output.status),
+            exit_code: output.status.code(),
+            output: lines,
+        }
+        .into());
+    }
+
+    Ok(lines)
+}
+
+pub(super) fn short_sha(commit: &str) -> &str {
+    commit.get(..7).unwrap_or(commit)
+}
+
+fn describe_allowed_tools(tools: Option<&Vec<String>>) -> String {
+    match tools {
+        None => "制限なし".to_string(),
+        Some(list) if list.is_empty() => "なし".to_string(),
+        Some(list) => list.join(", "),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::log_parser::LogParser;
+    use crate::ui::types::StepStatus;
+    use crate::worker::git::fixture::{init_repo, run_git};
+
+    fn shell_step(name: &str, command: &str) -> WorkflowStep {
+        WorkflowStep {
+            name: name.to_string(),
+            command: Some(command.to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn test_context(workflow: Workflow) -> AgentContext {
+        let snapshot = WorkerSnapshot {
+            id: WorkerId(1),
+            name: "worker-1".to_string(),
+            issue: None,
+            issue_title: None,
+            agent: DEFAULT_AGENT.to_string(),
+            worktree: String::new(),
+            branch: String::new(),
+            status: WorkerStatus::Running,
+            last_event: String::new(),
+            workflow: workflow.name.clone(),
+            total_steps: workflow.steps().len(),
+            current_step: None,
+            session_id: None,
+            priority: 0,
+            queue_position: None,
+            pr_url: None,
+            base_ref: None,
+        };
+        AgentContext {
+            worker_id: WorkerId(1),
+            state: Arc::new(Mutex::new(snapshot)),
+            cancel: Arc::new(AtomicBool::new(false)),
+            paused: Arc::new(AtomicBool::new(false)),
+            processes: ProcessTracker::new(),
+            worktree_path: std::env::temp_dir(),
+            evt_tx: mpsc::channel().0,
+            workflow,
+            completed_steps: Arc::new(AtomicUsize::new(0)),
+            logs: Arc::new(Mutex::new(VecDeque::new())),
+            cmd_tx: mpsc::channel().0,
+            session_histories: Arc::new(Mutex::new(Vec::new())),
+            step_outcomes: Arc::new(Mutex::new(HashMap::new())),
+            agents: Arc::new(AgentRegistry::from_config(&Config::default())),
+            default_sandbox_mode: true,
+            issue: None,
+            forge: None,
+            base_ref: None,
+            auto_commit: false,
+            log_buffer: None,
+        }
+    }
+
+    /// Allow every permission prompt; yields the number of prompts once the
+    /// workflow dropped its command sender.
+    fn approve_permissions(cmd_rx: mpsc::Receiver<WorkerCommand>) -> thread::JoinHandle<usize> {
+        thread::spawn(move || {
+            let mut prompts = 0;
+            for command in cmd_rx {
+                if let WorkerCommand::PermissionPrompt { respond_to, .. } = command {
+                    prompts += 1;
+                    let _ = respond_to.send(PermissionDecision::Allow {
+                        permission_mode: None,
+                        allowed_tools: None,
+                    });
+                }
+            }
+            prompts
+        })
+    }
+
+    #[test]
+    fn test_parallel_group_logs_each_child_and_fails_if_any_fails() {
+        let workflow = Workflow {
+            name: "checks".to_string(),
+            description: None,
+            steps: vec![WorkflowStep {
+                name: "checks".to_string(),
+                parallel: vec![
+                    shell_step("lint", "sleep 0.2; echo lint-ok"),
+                    shell_step("test", "echo test-out; exit 3"),
+                ],
+                ..Default::default()
+            }],
+            extends: None,
+        };
+        let ctx = test_context(workflow);
+        let logs = Arc::clone(&ctx.logs);
+        let state = Arc::clone(&ctx.state);
+        let outcomes = Arc::clone(&ctx.step_outcomes);
+
+        agent_simulation(ctx);
+
+        let mut parser = LogParser::new();
+        let entries: Vec<_> = logs
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(|line| parser.parse_line(line))
+            .collect();
+        let summary: Vec<_> = entries
+            .iter()
+            .map(|entry| (entry.step_name.as_str(), entry.status))
+            .collect();
+        assert_eq!(summary.len(), 3);
+        assert!(summary.contains(&("checks / lint", StepStatus::Success)));
+        assert!(summary.contains(&("checks / test", StepStatus::Failed)));
+        assert_eq!(summary[2], ("checks", StepStatus::Failed));
+
+        let lint = entries.iter().find(|e| e.step_name == "checks / lint").unwrap();
+        assert!(lint.result_lines.iter().any(|line| line == "lint-ok"));
+
+        let outcomes = outcomes.lock().unwrap();
+        assert_eq!(outcomes["test"].exit_code, Some(3));
+        assert_eq!(outcomes["checks"].status, StepOutcomeStatus::Failed);
+        assert_eq!(state.lock().unwrap().status, WorkerStatus::Failed);
+    }
+
+    #[test]
+    fn test_pause_before_step_start_keeps_paused_status() {
+        let workflow = Workflow {
+            name: "pause".to_string(),
+            description: None,
+            steps: vec![shell_step("build", "true")],
+            extends: None,
+        };
+        let mut ctx = test_context(workflow);
+        let (evt_tx, evt_rx) = mpsc::channel();
+        ctx.evt_tx = evt_tx;
+        // The pause lands after the boundary check but before the step starts
+        ctx.paused.store(true, Ordering::SeqCst);
+        ctx.state.lock().unwrap().status = WorkerStatus::Paused;
+
+        let step = ctx.workflow.steps()[0].clone();
+        ctx.run_step(0, &step, 1);
+
+        let first = evt_rx
+            .try_iter()
+            .find_map(|event| match event {
+                WorkerEvent::Updated(snapshot) => Some(snapshot),
+                _ => None,
+            })
+            .expect("step start publishes the snapshot");
+        assert_eq!(first.status, WorkerStatus::Paused);
+        assert_eq!(first.current_step.as_deref(), Some("1/1: build"));
+        assert_eq!(ctx.state.lock().unwrap().status, WorkerStatus::Paused);
+    }
+
+    #[test]
+    fn test_pr_step_records_pull_request_url() {
+        use crate::config::{ForgeConfig, ForgeKind, PullRequestStep};
+        use crate::http::mock::MockServer;
+
+        let root = std::env::temp_dir().join(format!("gensui-pr-step-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        let (remote, work) = (root.join("remote.git"), root.join("work"));
+        std::fs::create_dir_all(&remote).unwrap();
+        run_git(&remote, &["init", "-q", "--bare"]);
+        init_repo(&work);
+        run_git(&work, &["commit", "-q", "--allow-empty", "-m", "work"]);
+        run_git(&work, &["branch", "-m", "gensui/worker-001"]);
+        run_git(&work, &["remote", "add", "origin", &remote.to_string_lossy()]);
+
+        let server = MockServer::start(vec![(
+            "POST /repos/owner/repo/pulls",
+            201,
+            r#"{"html_url": "http://forge.test/owner/repo/pull/1"}"#.to_string(),
+        )]);
+        let config = Config {
+            forge: ForgeConfig {
+                provider: Some(ForgeKind::Github),
+                repo: Some("owner/repo".to_string()),
+                base_url: Some(server.url.clone()),
+                token_env: Some("GENSUI_TEST_UNSET_TOKEN".to_string()),
+                base: Some("main".to_string()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let workflow = Workflow {
+            name: "ship".to_string(),
+            description: None,
+            steps: vec![WorkflowStep {
+                name: "open".to_string(),
+                pr: Some(PullRequestStep {
+                    body: Some("Done by {{worker}}".to_string()),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }],
+            extends: None,
+        };
+        let mut ctx = test_context(workflow);
+        ctx.worktree_path = work.clone();
+        ctx.forge = Forge::from_config(&config, None);
+        ctx.state.lock().unwrap().branch = "gensui/worker-001".to_string();
+        let state = Arc::clone(&ctx.state);
+        let outcomes = Arc::clone(&ctx.step_outcomes);
+
+        agent_simulation(ctx);
+
+        let snapshot = state.lock().unwrap().clone();
+        assert_eq!(snapshot.status, WorkerStatus::Idle, "{}", snapshot.last_event);
+        assert_eq!(snapshot.pr_url.as_deref(), Some("http://forge.test/owner/repo/pull/1"));
+        assert_eq!(
+            outcomes.lock().unwrap()["open"].result.as_deref(),
+            Some("http://forge.test/owner/repo/pull/1")
+        );
+        let sent = &server.requests()[0];
+        assert!(sent.contains(r#""title":"worker-1""#) && sent.contains("Done by worker-1"), "{sent}");
+
+        // Without a forge the step fails instead of being skipped
+        let mut ctx = test_context(Workflow {
+            name: "ship".to_string(),
+            description: None,
+            steps: vec![WorkflowStep {
+                name: "open".to_string(),
+                pr: Some(PullRequestStep {
+                    base: Some("main".to_string()),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }],
+            extends: None,
+        });
+        ctx.worktree_path = work;
+        let state = Arc::clone(&ctx.state);
+        agent_simulation(ctx);
+        let snapshot = state.lock().unwrap().clone();
+        assert_eq!(snapshot.status, WorkerStatus::Failed);
+        assert!(snapshot.last_event.contains("no forge"), "{}", snapshot.last_event);
+
+        let _ = std::fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn test_merge_step_hands_conflicts_to_the_agent() {
+        use crate::config::{GenericAgentConfig, MergeStep, MergeStrategy};
+
+        let root = std::env::temp_dir().join(format!("gensui-merge-step-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        let (repo, work) = (root.join("repo"), root.join("work"));
+        init_repo(&repo);
+        std::fs::write(repo.join("a.txt"), "one\n").unwrap();
+        run_git(&repo, &["add", "."]);
+        run_git(&repo, &["commit", "-q", "-m", "init"]);
+        run_git(&repo, &["worktree", "add", "-q", "-b", "gensui/worker-1", &work.to_string_lossy()]);
+        std::fs::write(work.join("a.txt"), "worker\n").unwrap();
+        run_git(&work, &["commit", "-q", "-am", "worker"]);
+        std::fs::write(repo.join("a.txt"), "base\n").unwrap();
+        run_git(&repo, &["commit", "-q", "-am", "base"]);
+
+        let workflow = Workflow {
+            name: "ship".to_string(),
+            description: None,
+            steps: vec![WorkflowStep {
+                name: "merge".to_string(),
+                agent: Some("resolver".to_string()),
+                merge: Some(MergeStep {
+                    strategy: MergeStrategy::Squash,
+                    message: Some("Ship {{worker}}".to_string()),
+                    resolve_conflicts: true,
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }],
+            extends: None,
+        };
+        let config = Config {
+            agents: vec![GenericAgentConfig {
+                name: "resolver".to_string(),
+                command: "sh".to_string(),
+                args: vec!["-c".to_string(), "echo both > a.txt".to_string()],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let (cmd_tx, cmd_rx) = mpsc::channel();
+        let mut ctx = test_context(workflow);
+        ctx.worktree_path = work.clone();
+        ctx.base_ref = Some("main".to_string());
+        ctx.agents = Arc::new(AgentRegistry::from_config(&config));
+        ctx.cmd_tx = cmd_tx;
+        ctx.state.lock().unwrap().branch = "gensui/worker-1".to_string();
+        let state = Arc::clone(&ctx.state);
+        let logs = Arc::clone(&ctx.logs);
+        let approver = approve_permissions(cmd_rx);
+
+        agent_simulation(ctx);
+
+        let snapshot = state.lock().unwrap().clone();
+        assert_eq!(snapshot.status, WorkerStatus::Idle, "{}", snapshot.last_event);
+        assert!(logs.lock().unwrap().iter().any(|line| line == "- a.txt"));
+        assert_eq!(std::fs::read_to_string(repo.join("a.txt")).unwrap(), "both\n");
+        assert_eq!(run_git(&repo, &["log", "-1", "--format=%s", "main"]), "Ship worker-1");
+        assert_eq!(approver.join().unwrap(), 1);
+
+        let _ = std::fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn test_auto_commit_and_commit_step_record_their_commits() {
+        use crate::config::{CommitStep, GenericAgentConfig};
+
+        let dir = std::env::temp_dir().join(format!("gensui-commit-step-{}", std::process::id()));
+        init_repo(&dir);
+        run_git(&dir, &["commit", "-q", "--allow-empty", "-m", "init"]);
+
+        let workflow = Workflow {
+            name: "work".to_string(),
+            description: None,
+            steps: vec![
+                WorkflowStep {
+                    name: "実装".to_string(),
+                    agent: Some("writer".to_string()),
+                    claude: Some(ClaudeStep {
+                        prompt: "write".to_string(),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                },
+                shell_step("format", "echo formatted > b.txt"),
+                WorkflowStep {
+                    id: Some("save".to_string()),
+                    name: "保存".to_string(),
+                    commit: Some(CommitStep {
+                        message: Some("chore: {{worker}} after {{steps.実装.commit}}".to_string()),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                },
+                WorkflowStep {
+                    name: "empty".to_string(),
+                    commit: Some(CommitStep::default()),
+                    ..Default::default()
+                },
+            ],
+            extends: None,
+        };
+        let config = Config {
+            agents: vec![GenericAgentConfig {
+                name: "writer".to_string(),
+                command: "sh".to_string(),
+                args: vec!["-c".to_string(), "echo hello > a.txt".to_string()],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let (cmd_tx, cmd_rx) = mpsc::channel();
+        let mut ctx = test_context(workflow);
+        ctx.worktree_path = dir.clone();
+        ctx.auto_commit = true;
+        ctx.agents = Arc::new(AgentRegistry::from_config(&config));
+        ctx.cmd_tx = cmd_tx;
+        let state = Arc::clone(&ctx.state);
+        let outcomes = Arc::clone(&ctx.step_outcomes);
+        let approver = approve_permissions(cmd_rx);
+
+        agent_simulation(ctx);
+
+        let snapshot = state.lock().unwrap().clone();
+        assert_eq!(snapshot.status, WorkerStatus::Idle, "{}", snapshot.last_event);
+        assert_eq!(approver.join().unwrap(), 1);
+        let outcomes = outcomes.lock().unwrap();
+        let agent_commit = outcomes["実装"].commit.clone().unwrap();
+        let step_commit = outcomes["save"].commit.clone().unwrap();
+        assert_eq!(outcomes["empty"].commit, None);
+        assert_eq!(run_git(&dir, &["rev-parse", "HEAD"]), step_commit);
+        assert_eq!(run_git(&dir, &["log", "-1", "--format=%s", &agent_commit]), "実装 (worker-1)");
+        assert_eq!(
+            run_git(&dir, &["log", "-1", "--format=%s"]),
+            format!("chore: worker-1 after {agent_commit}")
+        );
+        assert_eq!(run_git(&dir, &["show", "--name-only", "--format=", &agent_commit]), "a.txt");
+        assert!(run_git(&dir, &["status", "--porcelain"]).is_empty());
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,66 @@
+//! リポジトリ・ワークツリーでの git の実行
+
+use std::path::Path;
+use std::process::{Command, Output, Stdio};
+
+use anyhow::{Context, Result, anyhow};
+
+/// Run git in `dir` and return its stdout, failing with git's stderr when it
+/// exits unsuccessfully.
+pub(crate) fn git(dir: &Path, args: &[&str]) -> Result<String> {
+    git_accepting(dir, args, &[0])
+}
+
+/// Like `git`, but any of `codes` counts as success, e.g. the 1 with which
+/// `git diff --no-index` reports that the files differ.
+pub(crate) fn git_accepting(dir: &Path, args: &[&str], codes: &[i32]) -> Result<String> {
+    let output = git_output(dir, args)?;
+    if !output.status.code().is_some_and(|code| codes.contains(&code)) {
+        return Err(anyhow!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Run git in `dir` and return its output whatever the exit status.
+pub(crate) fn git_output(dir: &Path, args: &[&str]) -> Result<Output> {
+    Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        // Never open an editor for merge or rebase messages
+        .env("GIT_EDITOR", "true")
+        .stdin(Stdio::null())
+        .output()
+        .with_context(|| format!("failed to execute git {}", args.join(" ")))
+}
+
+/// Throwaway repositories for tests.
+#[cfg(test)]
+pub(crate) mod fixture {
+    use std::fs;
+    use std::path::Path;
+    use std::process::Command;
+
+    /// Recreate `dir` as an empty repository on `main` with a commit identity.
+    pub(crate) fn init_repo(dir: &Path) {
+        let _ = fs::remove_dir_all(dir);
+        fs::create_dir_all(dir).unwrap();
+        run_git(dir, &["init", "-q", "-b", "main"]);
+        run_git(dir, &["config", "user.name", "gensui"]);
+        run_git(dir, &["config", "user.email", "gensui@example.com"]);
+    }
+
+    /// Run git in `dir`, panicking on failure, and return its trimmed stdout.
+    pub(crate) fn run_git(dir: &Path, args: &[&str]) -> String {
+        let output = Command::new("git").args(args).current_dir(dir).output().unwrap();
+        assert!(
+            output.status.success(),
+            "git {args:?} failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    }
+}
@@ -0,0 +1,399 @@
+//! ワーカーブランチのベースブランチへの取り込みとコンフリクト解消
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result, bail};
+
+use crate::config::MergeStrategy;
+
+use super::git::{git, git_output};
+
+/// A fully rendered merge of a worker branch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MergeRequest {
+    pub strategy: MergeStrategy,
+    /// Branch that receives the changes.
+    pub base: String,
+    /// The worker branch.
+    pub branch: String,
+    /// Commit message of the squash strategy.
+    pub message: String,
+    pub dry_run: bool,
+}
+
+/// Merging would conflict in `files`.
+#[derive(Debug, thiserror::Error)]
+#[error("merging {branch} into {base} conflicts in {}", files.join(", "))]
+pub struct MergeConflict {
+    pub base: String,
+    pub branch: String,
+    pub files: Vec<String>,
+}
+
+/// Result of a dry run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MergeCheck {
+    /// The base already contains every change of the branch.
+    UpToDate,
+    /// The branch merges cleanly; carries the tree of the merge result.
+    Clean(String),
+    Conflicts(Vec<String>),
+}
+
+/// What a merge did.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MergeOutcome {
+    UpToDate,
+    /// Dry run without conflicts.
+    Mergeable,
+    /// The base branch now points at `commit`.
+    Merged { commit: String },
+}
+
+/// Check whether merging `branch` into `base` would conflict.
+pub fn check(worktree: &Path, base: &str, branch: &str) -> Result<MergeCheck> {
+    if is_ancestor(worktree, branch, base)? {
+        return Ok(MergeCheck::UpToDate);
+    }
+    let output = git_output(
+        worktree,
+        &["merge-tree", "--write-tree", "--name-only", "--no-messages", base, branch],
+    )?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut lines = stdout.lines();
+    let tree = lines.next().unwrap_or_default().to_string();
+    match output.status.code() {
+        Some(0) if tree == rev_parse(worktree, &format!("{base}^{{tree}}"))? => {
+            // Every change of the branch is already in the base, e.g. after a squash
+            Ok(MergeCheck::UpToDate)
+        }
+        Some(0) => Ok(MergeCheck::Clean(tree)),
+        Some(1) => {
+            let mut files: Vec<String> = lines
+                .filter(|line| !line.is_empty())
+                .map(str::to_string)
+                .collect();
+            files.dedup();
+            Ok(MergeCheck::Conflicts(files))
+        }
+        _ => bail!(
+            "git merge-tree {base} {branch} failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ),
+    }
+}
+
+/// Merge the worker branch checked out in `worktree` into its base branch.
+///
+/// A resolution left behind by [`start_conflict_resolution`] is committed
+/// first. Conflicts fail with [`MergeConflict`].
+pub fn merge(worktree: &Path, request: &MergeRequest) -> Result<MergeOutcome> {
+    if request.branch == request.base {
+        bail!("the worker branch {} is the base branch", request.branch);
+    }
+    conclude_resolution(worktree)?;
+    ensure_clean(worktree)?;
+
+    let conflict = |files| MergeConflict {
+        base: request.base.clone(),
+        branch: request.branch.clone(),
+        files,
+    };
+    let tree = match check(worktree, &request.base, &request.branch)? {
+        MergeCheck::UpToDate => return Ok(MergeOutcome::UpToDate),
+        MergeCheck::Conflicts(files) => return Err(conflict(files).into()),
+        MergeCheck::Clean(tree) => tree,
+    };
+    if request.dry_run {
+        return Ok(MergeOutcome::Mergeable);
+    }
+
+    let old_base = rev_parse(worktree, &request.base)?;
+    let diverged = !is_ancestor(worktree, &request.base, &request.branch)?;
+    let commit = match request.strategy {
+        MergeStrategy::FastForward => {
+            if diverged {
+                bail!(
+                    "{} has commits that {} does not have; use the squash or rebase strategy",
+                    request.base,
+                    request.branch
+                );
+            }
+            rev_parse(worktree, &request.branch)?
+        }
+        MergeStrategy::Rebase => {
+            if diverged {
+                let output = git_output(worktree, &["rebase", &request.base])?;
+                if !output.status.success() {
+                    let _ = git_output(worktree, &["rebase", "--abort"]);
+                    bail!(
+                        "git rebase {} failed: {}",
+                        request.base,
+                        String::from_utf8_lossy(&output.stderr).trim()
+                    );
+                }
+            }
+            rev_parse(worktree, "HEAD")?
+        }
+        MergeStrategy::Squash => git(
+            worktree,
+            &["commit-tree", &tree, "-p", &old_base, "-m", &request.message],
+        )?
+        .trim()
+        .to_string(),
+    };
+
+    advance_branch(worktree, &request.base, &old_base, &commit)?;
+    Ok(MergeOutcome::Merged { commit })
+}
+
+/// Merge `base` into the worker branch, leaving the conflict markers in the
+/// worktree. Returns the conflicting files.
+pub fn start_conflict_resolution(worktree: &Path, base: &str) -> Result<Vec<String>> {
+    ensure_clean(worktree)?;
+    let output = git_output(worktree, &["merge", "--no-ff", "--no-commit", base])?;
+    let files = unmerged_files(worktree)?;
+    if !output.status.success() && files.is_empty() {
+        bail!(
+            "git merge {base} failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(files)
+}
+
+/// Give up on a resolution started by [`start_conflict_resolution`].
+pub fn abort_conflict_resolution(worktree: &Path) -> Result<()> {
+    if merge_in_progress(worktree)? {
+        git(worktree, &["merge", "--abort"])?;
+    }
+    Ok(())
+}
+
+/// Commit a pending resolution merge once no conflict markers remain.
+fn conclude_resolution(worktree: &Path) -> Result<()> {
+    if !merge_in_progress(worktree)? {
+        return Ok(());
+    }
+    let files = unmerged_files(worktree)?;
+    let remaining: Vec<String> = files
+        .iter()
+        .filter(|file| has_conflict_markers(&worktree.join(file)))
+        .cloned()
+        .collect();
+    if !remaining.is_empty() {
+        bail!("conflicts remain unresolved in {}", remaining.join(", "));
+    }
+    for file in &files {
+        git(worktree, &["add", "--", file])?;
+    }
+    git(worktree, &["commit", "--no-edit", "--quiet"])?;
+    Ok(())
+}
+
+fn has_conflict_markers(path: &Path) -> bool {
+    fs::read_to_string(path).is_ok_and(|contents| {
+        contents
+            .lines()
+            .any(|line| line.starts_with("<<<<<<< ") || line.starts_with(">>>>>>> "))
+    })
+}
+
+fn merge_in_progress(worktree: &Path) -> Result<bool> {
+    Ok(git_output(worktree, &["rev-parse", "-q", "--verify", "MERGE_HEAD"])?
+        .status
+        .success())
+}
+
+fn unmerged_files(worktree: &Path) -> Result<Vec<String>> {
+    let output = git(worktree, &["diff", "--name-only", "--diff-filter=U"])?;
+    let mut files: Vec<String> = output.lines().map(str::to_string).collect();
+    files.dedup();
+    Ok(files)
+}
+
+fn ensure_clean(worktree: &Path) -> Result<()> {
+    let status = git(worktree, &["status", "--porcelain", "--untracked-files=no"])?;
+    if !status.trim().is_empty() {
+        bail!("the worktree has uncommitted changes; commit or discard them first");
+    }
+    Ok(())
+}
+
+/// Fast-forward `base` from `old` to `new`.
+fn advance_branch(worktree: &Path, base: &str, old: &str, new: &str) -> Result<()> {
+    match checkout_of(worktree, base)? {
+        Some(checkout) => {
+            let output = git_output(Path::new(&checkout), &["merge", "--ff-only", "--quiet", new])?;
+            if !output.status.success() {
+                bail!(
+                    "failed to fast-forward {base} in {checkout}: {}",
+                    String::from_utf8_lossy(&output.stderr).trim()
+                );
+            }
+        }
+        None => {
+            git(
+                worktree,
+                &["update-ref", "-m", "gensui: merge", &format!("refs/heads/{base}"), new, old],
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Path of the worktree that has `branch` checked out.
+fn checkout_of(worktree: &Path, branch: &str) -> Result<Option<String>> {
+    let listing = git(worktree, &["worktree", "list", "--porcelain"])?;
+    let target = format!("branch refs/heads/{branch}");
+    let mut current = None;
+    for line in listing.lines() {
+        if let Some(path) = line.strip_prefix("worktree ") {
+            current = Some(path.to_string());
+        } else if line == target {
+            return Ok(current);
+        }
+    }
+    Ok(None)
+}
+
+fn is_ancestor(worktree: &Path, ancestor: &str, descendant: &str) -> Result<bool> {
+    let output = git_output(worktree, &["merge-base", "--is-ancestor", ancestor, descendant])?;
+    match output.status.code() {
+        Some(0) => Ok(true),
+        Some(1) => Ok(false),
+        _ => bail!(
+            "git merge-base {ancestor} {descendant} failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ),
+    }
+}
+
+fn rev_parse(worktree: &Path, rev: &str) -> Result<String> {
+    Ok(git(worktree, &["rev-parse", "--verify", "--quiet", rev])
+        .with_context(|| format!("unknown revision '{rev}'"))?
+        .trim()
+        .to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::worker::git::fixture::{init_repo, run_git};
+    use std::path::PathBuf;
+
+    fn commit_file(dir: &Path, file: &str, contents: &str) {
+        fs::write(dir.join(file), contents).unwrap();
+        run_git(dir, &["add", "."]);
+        run_git(dir, &["commit", "-q", "-m", file]);
+    }
+
+    /// A repository on `main` with a worker worktree on `gensui/w`.
+    fn setup(name: &str) -> (PathBuf, PathBuf) {
+        let root = std::env::temp_dir().join(format!("gensui-merge-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let repo = root.join("repo");
+        init_repo(&repo);
+        commit_file(&repo, "a.txt", "one\n");
+        let worktree = root.join("worker");
+        run_git(&repo, &["worktree", "add", "-q", "-b", "gensui/w", worktree.to_str().unwrap()]);
+        (repo, worktree)
+    }
+
+    fn request(strategy: MergeStrategy) -> MergeRequest {
+        MergeRequest {
+            strategy,
+            base: "main".to_string(),
+            branch: "gensui/w".to_string(),
+            message: "Squashed work".to_string(),
+            dry_run: false,
+        }
+    }
+
+    #[test]
+    fn test_fast_forward_and_squash() {
+        let (repo, worktree) = setup("ff");
+        commit_file(&worktree, "b.txt", "work\n");
+
+        let dry_run = MergeRequest {
+            dry_run: true,
+            ..request(MergeStrategy::FastForward)
+        };
+        assert_eq!(merge(&worktree, &dry_run).unwrap(), MergeOutcome::Mergeable);
+        assert!(!repo.join("b.txt").exists());
+
+        let outcome = merge(&worktree, &request(MergeStrategy::FastForward)).unwrap();
+        assert_eq!(
+            outcome,
+            MergeOutcome::Merged {
+                commit: run_git(&worktree, &["rev-parse", "HEAD"])
+            }
+        );
+        // The checkout of the base branch follows along
+        assert_eq!(fs::read_to_string(repo.join("b.txt")).unwrap(), "work\n");
+        assert_eq!(
+            merge(&worktree, &request(MergeStrategy::FastForward)).unwrap(),
+            MergeOutcome::UpToDate
+        );
+
+        // The base moves on, so only squash (or rebase) can bring in new work
+        commit_file(&repo, "c.txt", "base\n");
+        commit_file(&worktree, "d.txt", "more\n");
+        let err = merge(&worktree, &request(MergeStrategy::FastForward)).unwrap_err();
+        assert!(err.to_string().contains("squash or rebase"), "{err}");
+
+        merge(&worktree, &request(MergeStrategy::Squash)).unwrap();
+        assert_eq!(run_git(&repo, &["log", "-1", "--format=%s"]), "Squashed work");
+        assert!(repo.join("d.txt").exists());
+        assert_eq!(
+            merge(&worktree, &request(MergeStrategy::Squash)).unwrap(),
+            MergeOutcome::UpToDate
+        );
+    }
+
+    #[test]
+    fn test_rebase_then_fast_forward() {
+        let (repo, worktree) = setup("rebase");
+        commit_file(&worktree, "b.txt", "work\n");
+        commit_file(&repo, "c.txt", "base\n");
+
+        merge(&worktree, &request(MergeStrategy::Rebase)).unwrap();
+        assert_eq!(
+            run_git(&repo, &["rev-parse", "main"]),
+            run_git(&worktree, &["rev-parse", "HEAD"])
+        );
+        assert!(repo.join("b.txt").exists());
+        assert!(worktree.join("c.txt").exists());
+    }
+
+    #[test]
+    fn test_conflicts_are_reported_and_resolved() {
+        let (repo, worktree) = setup("conflict");
+        commit_file(&worktree, "a.txt", "worker\n");
+        commit_file(&repo, "a.txt", "base\n");
+
+        let err = merge(&worktree, &request(MergeStrategy::Squash)).unwrap_err();
+        let conflict = err.downcast_ref::<MergeConflict>().unwrap();
+        assert_eq!(conflict.files, vec!["a.txt"]);
+        // Nothing was touched by the check
+        assert_eq!(fs::read_to_string(repo.join("a.txt")).unwrap(), "base\n");
+
+        let files = start_conflict_resolution(&worktree, "main").unwrap();
+        assert_eq!(files, vec!["a.txt"]);
+        let err = merge(&worktree, &request(MergeStrategy::FastForward)).unwrap_err();
+        assert!(err.to_string().contains("unresolved"), "{err}");
+
+        fs::write(worktree.join("a.txt"), "both\n").unwrap();
+        merge(&worktree, &request(MergeStrategy::FastForward)).unwrap();
+        assert_eq!(fs::read_to_string(repo.join("a.txt")).unwrap(), "both\n");
+    }
+
+    #[test]
+    fn test_uncommitted_changes_block_the_merge() {
+        let (_repo, worktree) = setup("dirty");
+        commit_file(&worktree, "b.txt", "work\n");
+        fs::write(worktree.join("a.txt"), "edited\n").unwrap();
+        let err = merge(&worktree, &request(MergeStrategy::FastForward)).unwrap_err();
+        assert!(err.to_string().contains("uncommitted"), "{err}");
+    }
+}
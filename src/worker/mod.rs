@@ -1,13 +1,35 @@
+mod batch;
+mod branch;
+mod commit;
 mod name_validator;
+mod executor;
+pub(crate) mod git;
+mod merge;
 mod name_registry;
+mod process;
+mod template;
+mod unsaved;
 
 use name_validator::NameValidator;
+use executor::{AgentContext, agent_simulation, short_sha};
 use name_registry::NameRegistry;
+pub use batch::{BatchDefaults, BatchEntry, load_batch};
+pub use branch::{
+    BRANCH_PLACEHOLDERS, BranchContext, DEFAULT_BRANCH_TEMPLATE, check_branch_name,
+    render_branch_name,
+};
+pub use executor::CommandFailed;
+pub use process::ProcessTracker;
+pub use unsaved::UnsavedWork;
+use process::KILL_GRACE_PERIOD;
+use merge::{MergeConflict, MergeOutcome, MergeRequest};
+use template::{
+    DEFAULT_RESOLVE_PROMPT, TemplateContext, render_merge, render_prompt, render_pull_request,
+    with_conflicting_files,
+};
 
 use std::collections::{HashMap, VecDeque};
-use std::env;
 use std::fs;
-use std::io::Read;
 use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
 use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
@@ -16,10 +38,13 @@ use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::Duration;
 
-use portable_pty::{CommandBuilder, PtySize, native_pty_system};
-
-use crate::config::{ClaudeStep, Config, Workflow, WorkflowStep};
-use crate::state::{ManagerState, SessionEvent, SessionHistory, StateStore};
+use crate::agent::{AgentRegistry, DEFAULT_AGENT};
+use crate::config::{
+    ClaudeStep, Config, MergeStep, MergeStrategy, PullRequestStep, Workflow, WorkflowStep,
+};
+use crate::forge::{Forge, PullRequest};
+use crate::issue::{Issue, IssueTracker};
+use crate::state::{ManagerState, SessionHistory, StateStore, StepOutcome, WorkerRecord};
 use anyhow::{Context, Result, anyhow};
 use serde::{Deserialize, Serialize};
 use time::OffsetDateTime;
@@ -33,6 +58,10 @@ pub enum WorkerStatus {
     Running,
     Paused,
     Failed,
+    /// The workflow was mid-run when gensui exited and can be resumed.
+    Interrupted,
+    /// Waiting for a free slot under the concurrency limits.
+    Queued,
     Archived,
 }
 
@@ -43,6 +72,8 @@ impl WorkerStatus {
             WorkerStatus::Running => "Running",
             WorkerStatus::Paused => "Paused",
             WorkerStatus::Failed => "Failed",
+            WorkerStatus::Interrupted => "Interrupted",
+            WorkerStatus::Queued => "Queued",
             WorkerStatus::Archived => "Archived",
         }
     }
@@ -53,6 +84,9 @@ pub struct WorkerSnapshot {
     pub id: WorkerId,
     pub name: String,
     pub issue: Option<String>,
+    /// Title of the issue as reported by the issue provider.
+    #[serde(default)]
+    pub issue_title: Option<String>,
     pub agent: String,
     pub worktree: String,
     pub branch: String,
@@ -63,9 +97,34 @@ pub struct WorkerSnapshot {
     pub current_step: Option<String>,
     #[serde(default)]
     pub session_id: Option<String>,
+    /// Queued workers with a higher priority start first.
+    #[serde(default)]
+    pub priority: i32,
+    /// 1-based position in the queue while `Queued`.
+    #[serde(default)]
+    pub queue_position: Option<usize>,
+    /// Pull request opened for the worker branch.
+    #[serde(default)]
+    pub pr_url: Option<String>,
+    /// Branch, tag or commit the worker branch was created from, when one
+    /// was chosen at creation.
+    #[serde(default)]
+    pub base_ref: Option<String>,
+}
+
+impl WorkerSnapshot {
+    /// Status label including the queue position of a queued worker.
+    pub fn status_text(&self) -> String {
+        match self.queue_position {
+            Some(position) if self.status == WorkerStatus::Queued => {
+                format!("{} #{position}", self.status.label())
+            }
+            _ => self.status.label().to_string(),
+        }
+    }
 }
 
-#[derive(Default, Clone, Debug)]
+#[derive(Default, Clone, Debug, Serialize, Deserialize)]
 pub struct CreateWorkerRequest {
     pub name: Option<String>,
     pub issue: Option<String>,
@@ -74,22 +133,66 @@ pub struct CreateWorkerRequest {
     pub free_prompt: Option<String>,
     pub existing_worktree: Option<(PathBuf, String)>, // (worktree_path, branch_name)
     pub permission_mode: Option<String>,
+    #[serde(default)]
+    pub priority: i32,
+    /// Branch, tag or commit to create the worker branch from.
+    /// Default: the branch checked out in the main repository.
+    #[serde(default)]
+    pub base_ref: Option<String>,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub struct ExistingWorktree {
     pub path: PathBuf,
     pub branch: String,
 }
 
+/// What deleting a worker does with its branch and saved state.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeleteMode {
+    /// Remove the worktree, the branch and the saved state.
+    #[default]
+    Destroy,
+    /// Remove the worktree but keep the branch and the saved state, so the
+    /// worker can be reactivated later.
+    Archive,
+}
+
+/// Commands handled by the worker manager. Variants that carry channels or
+/// are only issued internally are not part of the daemon protocol.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum WorkerCommand {
     Create(CreateWorkerRequest),
     Delete {
         id: WorkerId,
+        #[serde(default)]
+        mode: DeleteMode,
+        /// Destroy even when the worker has uncommitted or unpushed work.
+        #[serde(default)]
+        force: bool,
+    },
+    /// Recreate the worktree of an archived worker from its kept branch.
+    Reactivate {
+        id: WorkerId,
     },
     Restart {
         id: WorkerId,
     },
+    Cancel {
+        id: WorkerId,
+    },
+    Pause {
+        id: WorkerId,
+    },
+    Resume {
+        id: WorkerId,
+    },
+    ResumeInterrupted {
+        id: WorkerId,
+        continue_session: bool,
+    },
     Continue {
         id: WorkerId,
         prompt: String,
@@ -99,9 +202,56 @@ pub enum WorkerCommand {
         id: WorkerId,
         new_name: String,
     },
+    /// Change the priority used to order the queue.
+    SetPriority {
+        id: WorkerId,
+        priority: i32,
+    },
+    /// Push the worker branch and open a pull request for it.
+    OpenPullRequest {
+        id: WorkerId,
+    },
+    /// Sent by the thread opening a pull request once the forge answered.
+    #[serde(skip)]
+    PullRequestOpened {
+        id: WorkerId,
+        result: Result<String, String>,
+    },
+    /// Bring the worker branch into its base branch. With `resolve_conflicts`
+    /// conflicts are handed to the worker's agent as a follow-up instruction.
+    Merge {
+        id: WorkerId,
+        #[serde(default)]
+        strategy: MergeStrategy,
+        #[serde(default)]
+        dry_run: bool,
+        #[serde(default)]
+        resolve_conflicts: bool,
+    },
+    /// Sent by the merging thread. `conflicts` is non-empty when a conflict
+    /// resolution was started in the worktree.
+    #[serde(skip)]
+    MergeFinished {
+        id: WorkerId,
+        result: Result<String, String>,
+        conflicts: Vec<String>,
+    },
+    /// Sent by the thread fetching a worker's issue that was not cached.
+    #[serde(skip)]
+    IssueFetched {
+        id: WorkerId,
+        result: Result<Option<Issue>, String>,
+    },
+    #[serde(skip)]
     Persist {
         id: WorkerId,
     },
+    /// Sent by an agent thread when it exits, freeing its slot.
+    #[serde(skip)]
+    AgentFinished {
+        id: WorkerId,
+    },
+    #[serde(skip)]
     PermissionPrompt {
         id: WorkerId,
         request: PermissionRequest,
@@ -112,9 +262,24 @@ pub enum WorkerCommand {
         request_id: u64,
         decision: PermissionDecision,
     },
+    /// Replace the configuration used for new workers. Existing workers keep
+    /// the workflow and agents they were started with.
+    #[serde(skip)]
+    ReloadConfig(Box<Config>),
+    /// Persist and stop every worker, then acknowledge on `done`.
+    #[serde(skip)]
+    Shutdown {
+        done: Sender<()>,
+    },
+    /// Emit `WorkerEvent::Attached` with the current state for a new subscriber.
+    #[serde(skip)]
+    Attach {
+        subscriber: u64,
+    },
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum WorkerEvent {
     Created(WorkerSnapshot),
     Updated(WorkerSnapshot),
@@ -145,9 +310,26 @@ pub enum WorkerEvent {
         request_id: u64,
         decision: PermissionDecision,
     },
+    /// Current state for a subscriber that has just attached to the daemon.
+    /// Consumed by the daemon, which replays it to that subscriber only.
+    Attached {
+        subscriber: u64,
+        workers: Vec<WorkerReplay>,
+        permissions: Vec<(WorkerId, PermissionRequest)>,
+    },
+    /// Every existing worker has been announced.
+    Ready,
+}
+
+/// A worker as announced to a newly attached subscriber.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct WorkerReplay {
+    pub snapshot: WorkerSnapshot,
+    pub logs: Vec<String>,
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum PermissionDecision {
     Allow {
         permission_mode: Option<String>,
@@ -156,7 +338,7 @@ pub enum PermissionDecision {
     Deny,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct PermissionRequest {
     pub request_id: u64,
     pub step_name: String,
@@ -173,24 +355,67 @@ pub struct WorkerHandle {
 }
 
 impl WorkerHandle {
+    pub fn new(cmd_tx: Sender<WorkerCommand>) -> Self {
+        Self { cmd_tx }
+    }
+
+    pub fn send(&self, command: WorkerCommand) -> Result<()> {
+        self.cmd_tx
+            .send(command)
+            .map_err(|err| anyhow!("failed to enqueue worker command: {err}"))
+    }
+
     pub fn create_worker(&self, request: CreateWorkerRequest) -> Result<()> {
         self.cmd_tx
             .send(WorkerCommand::Create(request))
             .map_err(|err| anyhow!("failed to enqueue worker creation: {err}"))
     }
 
-    pub fn delete_worker(&self, id: WorkerId) -> Result<()> {
+    pub fn delete_worker(&self, id: WorkerId, mode: DeleteMode, force: bool) -> Result<()> {
         self.cmd_tx
-            .send(WorkerCommand::Delete { id })
+            .send(WorkerCommand::Delete { id, mode, force })
             .map_err(|err| anyhow!("failed to enqueue worker deletion: {err}"))
     }
 
+    pub fn reactivate_worker(&self, id: WorkerId) -> Result<()> {
+        self.cmd_tx
+            .send(WorkerCommand::Reactivate { id })
+            .map_err(|err| anyhow!("failed to enqueue worker reactivation: {err}"))
+    }
+
     pub fn restart_worker(&self, id: WorkerId) -> Result<()> {
         self.cmd_tx
             .send(WorkerCommand::Restart { id })
             .map_err(|err| anyhow!("failed to enqueue worker restart: {err}"))
     }
 
+    pub fn cancel_worker(&self, id: WorkerId) -> Result<()> {
+        self.cmd_tx
+            .send(WorkerCommand::Cancel { id })
+            .map_err(|err| anyhow!("failed to enqueue worker cancellation: {err}"))
+    }
+
+    pub fn pause_worker(&self, id: WorkerId) -> Result<()> {
+        self.cmd_tx
+            .send(WorkerCommand::Pause { id })
+            .map_err(|err| anyhow!("failed to enqueue worker pause: {err}"))
+    }
+
+    pub fn resume_worker(&self, id: WorkerId) -> Result<()> {
+        self.cmd_tx
+            .send(WorkerCommand::Resume { id })
+            .map_err(|err| anyhow!("failed to enqueue worker resume: {err}"))
+    }
+
+    pub fn resume_interrupted_worker(&self, id: WorkerId, continue_session: bool) -> Result<()> {
+        self.cmd_tx
+            .send(WorkerCommand::ResumeInterrupted {
+                id,
+                continue_session,
+            })
+            .map_err(|err| anyhow!("failed to enqueue interrupted worker resume: {err}"))
+    }
+
     pub fn continue_worker(
         &self,
         id: WorkerId,
@@ -212,6 +437,35 @@ impl WorkerHandle {
             .map_err(|err| anyhow!("failed to enqueue worker rename: {err}"))
     }
 
+    pub fn set_priority(&self, id: WorkerId, priority: i32) -> Result<()> {
+        self.cmd_tx
+            .send(WorkerCommand::SetPriority { id, priority })
+            .map_err(|err| anyhow!("failed to enqueue priority change: {err}"))
+    }
+
+    pub fn open_pull_request(&self, id: WorkerId) -> Result<()> {
+        self.cmd_tx
+            .send(WorkerCommand::OpenPullRequest { id })
+            .map_err(|err| anyhow!("failed to enqueue pull request: {err}"))
+    }
+
+    pub fn merge(
+        &self,
+        id: WorkerId,
+        strategy: MergeStrategy,
+        dry_run: bool,
+        resolve_conflicts: bool,
+    ) -> Result<()> {
+        self.cmd_tx
+            .send(WorkerCommand::Merge {
+                id,
+                strategy,
+                dry_run,
+                resolve_conflicts,
+            })
+            .map_err(|err| anyhow!("failed to enqueue merge: {err}"))
+    }
+
     pub fn respond_permission(
         &self,
         id: WorkerId,
@@ -226,10 +480,37 @@ impl WorkerHandle {
             })
             .map_err(|err| anyhow!("failed to enqueue permission response: {err}"))
     }
+
+    pub fn reload_config(&self, config: Config) -> Result<()> {
+        self.cmd_tx
+            .send(WorkerCommand::ReloadConfig(Box::new(config)))
+            .map_err(|err| anyhow!("failed to enqueue config reload: {err}"))
+    }
+
+    /// Persist and stop every worker, waiting up to `timeout` for the manager.
+    pub fn shutdown(&self, timeout: Duration) -> Result<()> {
+        let (done_tx, done_rx) = mpsc::channel();
+        self.cmd_tx
+            .send(WorkerCommand::Shutdown { done: done_tx })
+            .map_err(|err| anyhow!("failed to enqueue shutdown: {err}"))?;
+        done_rx
+            .recv_timeout(timeout)
+            .map_err(|err| anyhow!("worker manager did not shut down: {err}"))
+    }
 }
 
 pub type WorkerEventReceiver = Receiver<WorkerEvent>;
 
+/// Whether a saved worker was cut off mid-run (or still queued) and can be
+/// resumed. A worker restored as interrupted keeps that status until it is resumed.
+fn was_interrupted(record: &WorkerRecord) -> bool {
+    let status = record.snapshot.status.as_str();
+    [WorkerStatus::Running, WorkerStatus::Interrupted, WorkerStatus::Queued]
+        .iter()
+        .any(|s| s.label() == status)
+        && record.completed_steps < record.workflow.steps().len()
+}
+
 pub fn spawn_worker_system(
     repo_root: PathBuf,
     config: Config,
@@ -262,19 +543,33 @@ pub fn spawn_worker_system(
 struct WorkerManager {
     repo_root: PathBuf,
     config: Config,
+    agents: Arc<AgentRegistry>,
+    issues: Arc<IssueTracker>,
+    /// None when neither the forge nor the issue tracker names a provider.
+    forge: Option<Forge>,
+    /// Branch checked out in the repository, which workers branch off from.
+    base_ref: Option<String>,
     state_store: StateStore,
     cmd_tx: Sender<WorkerCommand>,
     cmd_rx: Receiver<WorkerCommand>,
     evt_tx: Sender<WorkerEvent>,
     next_id: usize,
     workers: HashMap<WorkerId, WorkerRuntime>,
+    /// Saved workers whose worktree is gone; kept for replay to subscribers.
+    archived: Vec<WorkerSnapshot>,
     pending_permissions: HashMap<u64, PendingPermission>,
+    /// Workers waiting for a slot, highest priority first and FIFO within a priority.
+    queue: Vec<WorkerId>,
+    /// Workers whose issue is being fetched on a background thread, and
+    /// whether their agent starts once it arrives.
+    issue_fetches: HashMap<WorkerId, bool>,
     name_registry: NameRegistry,
     name_validator: NameValidator,
 }
 
 struct PendingPermission {
     worker_id: WorkerId,
+    request: PermissionRequest,
     respond_to: Sender<PermissionDecision>,
 }
 
@@ -288,16 +583,27 @@ impl WorkerManager {
         evt_tx: Sender<WorkerEvent>,
         next_id: usize,
     ) -> Self {
+        let agents = Arc::new(AgentRegistry::from_config(&config));
+        let issues = Arc::new(IssueTracker::from_config(&repo_root, &config.issues));
+        let base_ref = determine_base_ref(&repo_root);
+        let forge = Forge::from_config(&config, base_ref.clone());
         Self {
             repo_root,
             config,
+            agents,
+            issues,
+            forge,
+            base_ref,
             state_store,
             cmd_tx,
             cmd_rx,
             evt_tx,
             next_id,
             workers: HashMap::new(),
+            archived: Vec::new(),
             pending_permissions: HashMap::new(),
+            queue: Vec::new(),
+            issue_fetches: HashMap::new(),
             name_registry: NameRegistry::new(),
             name_validator: NameValidator::new(),
         }
@@ -323,6 +629,10 @@ impl WorkerManager {
             }
 
             let worktree_exists = worktree_path.exists();
+            // Paused workers stay paused until explicitly resumed
+            let restored_paused = record.snapshot.status == WorkerStatus::Paused.label();
+            // A worker saved as Running with steps left was cut off mid-run
+            let restored_interrupted = was_interrupted(&record);
 
             // Register worker name
             if let Err(err) = self.name_registry.register(record.snapshot.name.clone(), worker_id) {
@@ -335,57 +645,55 @@ impl WorkerManager {
                 id: worker_id,
                 name: record.snapshot.name.clone(),
                 issue: record.snapshot.issue.clone(),
+                issue_title: record.snapshot.issue_title.clone(),
                 agent: record.snapshot.agent.clone(),
                 worktree: record.snapshot.worktree.clone(),
                 branch: record.snapshot.branch.clone(),
-                status: if worktree_exists {
-                    WorkerStatus::Idle
-                } else {
+                status: if !worktree_exists {
                     WorkerStatus::Archived
-                },
-                last_event: if worktree_exists {
-                    "Restored from saved state".to_string()
+                } else if restored_paused {
+                    WorkerStatus::Paused
+                } else if restored_interrupted {
+                    WorkerStatus::Interrupted
                 } else {
+                    WorkerStatus::Idle
+                },
+                last_event: if !worktree_exists {
                     "Archived (worktree removed)".to_string()
+                } else if restored_paused {
+                    "Paused (restored from saved state)".to_string()
+                } else if restored_interrupted {
+                    format!(
+                        "Interrupted at step {}/{}",
+                        record.completed_steps + 1,
+                        record.workflow.steps().len()
+                    )
+                } else {
+                    "Restored from saved state".to_string()
                 },
                 workflow: record.workflow.name.clone(),
-                total_steps: record.workflow.steps.len(),
+                total_steps: record.workflow.steps().len(),
                 current_step: None,
                 session_id: record.snapshot.session_id.clone(),
+                priority: record.snapshot.priority,
+                queue_position: None,
+                pr_url: record.snapshot.pr_url.clone(),
+                base_ref: record.snapshot.base_ref.clone(),
             };
 
             if worktree_exists {
                 // Create WorkerRuntime for active workers
-                match WorkerRuntime::new(
-                    snapshot.clone(),
-                    worktree_path,
-                    record.snapshot.branch.clone(),
-                    record.workflow,
-                    self.cmd_tx.clone(),
-                    self.config.default_sandbox_mode,
-                ) {
+                let logs = record.logs.clone();
+                let name = record.snapshot.name.clone();
+                match self.restore_runtime(snapshot.clone(), worktree_path, record) {
                     Ok(runtime) => {
-                        runtime
-                            .completed_steps
-                            .store(record.completed_steps, Ordering::SeqCst);
-
-                        // Restore logs to runtime
-                        for log_line in &record.logs {
-                            runtime.add_log(log_line.clone());
-                        }
-
-                        // Restore session histories
-                        for session_history in &record.session_history {
-                            runtime.add_session_history(session_history.clone());
-                        }
-
                         self.workers.insert(worker_id, runtime);
 
                         // Notify UI
                         let _ = self.evt_tx.send(WorkerEvent::Created(snapshot.clone()));
 
                         // Send logs to UI
-                        for log_line in record.logs {
+                        for log_line in logs {
                             let _ = self.evt_tx.send(WorkerEvent::Log {
                                 id: worker_id,
                                 line: log_line,
@@ -393,12 +701,13 @@ impl WorkerManager {
                         }
                     }
                     Err(err) => {
-                        eprintln!("Failed to restore worker {}: {err}", record.snapshot.name);
+                        eprintln!("Failed to restore worker {name}: {err}");
                     }
                 }
             } else {
                 // For archived workers (no worktree), just send to UI for viewing
                 let _ = self.evt_tx.send(WorkerEvent::Created(snapshot.clone()));
+                self.archived.push(snapshot.clone());
 
                 // Send logs to UI
                 for log_line in record.logs {
@@ -411,12 +720,50 @@ impl WorkerManager {
         }
     }
 
+    /// Build the runtime of a saved worker, with its progress, logs and
+    /// session histories.
+    fn restore_runtime(
+        &self,
+        snapshot: WorkerSnapshot,
+        worktree_path: PathBuf,
+        record: WorkerRecord,
+    ) -> Result<WorkerRuntime> {
+        let runtime = WorkerRuntime::new(
+            snapshot,
+            worktree_path,
+            record.snapshot.branch.clone(),
+            record.workflow,
+            self.cmd_tx.clone(),
+            Arc::clone(&self.agents),
+            self.config.default_sandbox_mode,
+        )?;
+        runtime
+            .completed_steps
+            .store(record.completed_steps, Ordering::SeqCst);
+
+        // Restore logs to runtime
+        for log_line in record.logs {
+            runtime.add_log(log_line);
+        }
+
+        // Restore session histories
+        for session_history in record.session_history {
+            runtime.add_session_history(session_history);
+        }
+
+        if let Ok(mut outcomes) = runtime.step_outcomes.lock() {
+            *outcomes = record.step_outcomes;
+        }
+        Ok(runtime)
+    }
+
     fn run(mut self) {
         // Give UI time to start polling events before restoring workers
         thread::sleep(Duration::from_millis(100));
 
         // Restore workers before entering command loop
         self.restore_workers();
+        let _ = self.evt_tx.send(WorkerEvent::Ready);
 
         while let Ok(command) = self.cmd_rx.recv() {
             match command {
@@ -428,8 +775,16 @@ impl WorkerManager {
                         });
                     }
                 }
-                WorkerCommand::Delete { id } => {
-                    if let Err(err) = self.handle_delete(id) {
+                WorkerCommand::Delete { id, mode, force } => {
+                    if let Err(err) = self.handle_delete(id, mode, force) {
+                        let _ = self.evt_tx.send(WorkerEvent::Error {
+                            id: Some(id),
+                            message: err.to_string(),
+                        });
+                    }
+                }
+                WorkerCommand::Reactivate { id } => {
+                    if let Err(err) = self.handle_reactivate(id) {
                         let _ = self.evt_tx.send(WorkerEvent::Error {
                             id: Some(id),
                             message: err.to_string(),
@@ -444,6 +799,41 @@ impl WorkerManager {
                         });
                     }
                 }
+                WorkerCommand::Cancel { id } => {
+                    if let Err(err) = self.handle_cancel(id) {
+                        let _ = self.evt_tx.send(WorkerEvent::Error {
+                            id: Some(id),
+                            message: err.to_string(),
+                        });
+                    }
+                }
+                WorkerCommand::Pause { id } => {
+                    if let Err(err) = self.handle_pause(id) {
+                        let _ = self.evt_tx.send(WorkerEvent::Error {
+                            id: Some(id),
+                            message: err.to_string(),
+                        });
+                    }
+                }
+                WorkerCommand::Resume { id } => {
+                    if let Err(err) = self.handle_resume(id) {
+                        let _ = self.evt_tx.send(WorkerEvent::Error {
+                            id: Some(id),
+                            message: err.to_string(),
+                        });
+                    }
+                }
+                WorkerCommand::ResumeInterrupted {
+                    id,
+                    continue_session,
+                } => {
+                    if let Err(err) = self.handle_resume_interrupted(id, continue_session) {
+                        let _ = self.evt_tx.send(WorkerEvent::Error {
+                            id: Some(id),
+                            message: err.to_string(),
+                        });
+                    }
+                }
                 WorkerCommand::Continue {
                     id,
                     prompt,
@@ -464,9 +854,60 @@ impl WorkerManager {
                         });
                     }
                 }
+                WorkerCommand::SetPriority { id, priority } => {
+                    if let Err(err) = self.handle_set_priority(id, priority) {
+                        let _ = self.evt_tx.send(WorkerEvent::Error {
+                            id: Some(id),
+                            message: err.to_string(),
+                        });
+                    }
+                }
+                WorkerCommand::OpenPullRequest { id } => {
+                    if let Err(err) = self.handle_open_pull_request(id) {
+                        let _ = self.evt_tx.send(WorkerEvent::Error {
+                            id: Some(id),
+                            message: err.to_string(),
+                        });
+                    }
+                }
+                WorkerCommand::PullRequestOpened { id, result } => {
+                    self.handle_pull_request_opened(id, result);
+                }
+                WorkerCommand::Merge {
+                    id,
+                    strategy,
+                    dry_run,
+                    resolve_conflicts,
+                } => {
+                    let step = MergeStep {
+                        strategy,
+                        dry_run,
+                        resolve_conflicts,
+                        ..Default::default()
+                    };
+                    if let Err(err) = self.handle_merge(id, step) {
+                        let _ = self.evt_tx.send(WorkerEvent::Error {
+                            id: Some(id),
+                            message: err.to_string(),
+                        });
+                    }
+                }
+                WorkerCommand::MergeFinished {
+                    id,
+                    result,
+                    conflicts,
+                } => {
+                    self.handle_merge_finished(id, result, conflicts);
+                }
+                WorkerCommand::IssueFetched { id, result } => {
+                    self.handle_issue_fetched(id, result);
+                }
                 WorkerCommand::Persist { id } => {
                     self.persist_worker(id);
                 }
+                WorkerCommand::AgentFinished { id } => {
+                    self.persist_worker(id);
+                }
                 WorkerCommand::PermissionPrompt {
                     id,
                     request,
@@ -481,17 +922,85 @@ impl WorkerManager {
                 } => {
                     self.handle_permission_response(id, request_id, decision);
                 }
+                WorkerCommand::ReloadConfig(config) => {
+                    self.handle_reload_config(*config);
+                }
+                WorkerCommand::Attach { subscriber } => {
+                    self.handle_attach(subscriber);
+                }
+                WorkerCommand::Shutdown { done } => {
+                    self.shutdown_all();
+                    let _ = done.send(());
+                    return;
+                }
             }
+
+            // Any command may have freed a slot or changed the limits
+            self.schedule();
         }
 
         self.shutdown_all();
     }
 
+    fn handle_attach(&self, subscriber: u64) {
+        let mut workers: Vec<WorkerReplay> = self
+            .workers
+            .values()
+            .map(|runtime| WorkerReplay {
+                snapshot: runtime.snapshot(),
+                logs: runtime.get_logs(),
+            })
+            .chain(self.archived.iter().filter(|snapshot| {
+                // Archived records may have been removed by a client directly
+                matches!(self.state_store.load_worker(&snapshot.name), Ok(Some(_)))
+            }).map(|snapshot| WorkerReplay {
+                snapshot: snapshot.clone(),
+                logs: Vec::new(),
+            }))
+            .collect();
+        workers.sort_by_key(|replay| replay.snapshot.id.0);
+
+        let permissions = self
+            .pending_permissions
+            .values()
+            .map(|pending| (pending.worker_id, pending.request.clone()))
+            .collect();
+
+        let _ = self.evt_tx.send(WorkerEvent::Attached {
+            subscriber,
+            workers,
+            permissions,
+        });
+    }
+
+    fn handle_reload_config(&mut self, config: Config) {
+        // Runtimes hold their own registry handle, so running workers are unaffected
+        self.agents = Arc::new(AgentRegistry::from_config(&config));
+        self.issues = Arc::new(IssueTracker::from_config(&self.repo_root, &config.issues));
+        self.base_ref = determine_base_ref(&self.repo_root);
+        self.forge = Forge::from_config(&config, self.base_ref.clone());
+        self.config = config;
+    }
+
     fn handle_create(&mut self, request: CreateWorkerRequest) -> Result<()> {
+        // Reject an unknown agent before reserving a name or adding a worktree
+        let agent = request
+            .agent
+            .unwrap_or_else(|| DEFAULT_AGENT.to_string());
+        if !self.agents.contains(&agent) {
+            return Err(anyhow!(
+                "unknown agent '{}' (available: {})",
+                agent,
+                self.agents.names().join(", ")
+            ));
+        }
+
         let worker_id = WorkerId(self.next_id);
         self.next_id += 1;
         self.persist_manager_state();
 
+        let timestamp = OffsetDateTime::now_utc().unix_timestamp();
+
         // Determine worker name
         let name = if let Some(user_name) = request.name {
             // Validate user-provided name
@@ -506,16 +1015,58 @@ impl WorkerManager {
             user_name
         } else {
             // Generate default name
-            let timestamp = OffsetDateTime::now_utc().unix_timestamp();
             format!("worker-{}-{}", worker_id.0, timestamp)
         };
 
+        let new_branch = if request.existing_worktree.is_none() {
+            let template = self
+                .config
+                .branch_template
+                .as_deref()
+                .unwrap_or(DEFAULT_BRANCH_TEMPLATE);
+            let branch = render_branch_name(
+                template,
+                &BranchContext {
+                    worker: &name,
+                    id: worker_id.0,
+                    issue: request.issue.as_deref(),
+                    timestamp,
+                },
+            );
+            check_branch_name(&branch)
+                .map_err(|reason| anyhow!("invalid branch_template: {reason}"))?;
+            if git_succeeds(
+                &self.repo_root,
+                &["rev-parse", "--verify", "--quiet", &format!("refs/heads/{branch}")],
+            ) {
+                return Err(anyhow!("branch '{branch}' already exists"));
+            }
+
+            let base_ref = match request.base_ref.as_deref() {
+                Some(base_ref) => {
+                    if !git_succeeds(
+                        &self.repo_root,
+                        &["rev-parse", "--verify", "--quiet", &format!("{base_ref}^{{commit}}")],
+                    ) {
+                        return Err(anyhow!("base ref '{base_ref}' does not name a commit"));
+                    }
+                    base_ref.to_string()
+                }
+                None => determine_base_ref(&self.repo_root).unwrap_or_else(|| "HEAD".into()),
+            };
+            Some((branch, base_ref))
+        } else {
+            None
+        };
+
         // Register name
         self.name_registry.register(name.clone(), worker_id)
             .map_err(|e| anyhow!("Failed to register worker name: {}", e))?;
 
-        let (worktree_path, branch, rel_worktree) =
-            if let Some((existing_path, existing_branch)) = request.existing_worktree {
+        // An existing worktree keeps whatever its branch was created from
+        let chosen_base = new_branch.as_ref().and(request.base_ref.clone());
+        let (worktree_path, branch, rel_worktree) = match (request.existing_worktree, new_branch) {
+            (Some((existing_path, existing_branch)), _) => {
                 // Use existing worktree
                 let rel_path = existing_path
                     .strip_prefix(&self.repo_root)
@@ -523,19 +1074,16 @@ impl WorkerManager {
                     .to_string_lossy()
                     .to_string();
                 (existing_path, existing_branch, rel_path)
-            } else {
+            }
+            (None, Some((branch, base_ref))) => {
                 // Create new worktree
                 fs::create_dir_all(self.repo_root.join(".worktrees"))
                     .context("failed to create .worktrees directory")?;
 
-                let timestamp = OffsetDateTime::now_utc().unix_timestamp();
                 let worktree_name = format!("worker-{id:03}-{timestamp}", id = worker_id.0);
                 let rel_worktree = format!(".worktrees/{worktree_name}");
                 let worktree_path = self.repo_root.join(&rel_worktree);
 
-                let branch = format!("gensui/{worktree_name}");
-                let base_ref = determine_base_ref(&self.repo_root).unwrap_or_else(|| "HEAD".into());
-
                 let output = Command::new("git")
                     .args([
                         "worktree",
@@ -563,7 +1111,9 @@ impl WorkerManager {
                 }
 
                 (worktree_path, branch, rel_worktree)
-            };
+            }
+            (None, None) => unreachable!("a branch is prepared for every new worktree"),
+        };
 
         let workflow = if let Some(prompt) = request.free_prompt.clone() {
             let permission_mode = request.permission_mode.clone();
@@ -573,7 +1123,6 @@ impl WorkerManager {
                 description: Some("On-demand Claude execution".to_string()),
                 steps: vec![WorkflowStep {
                     name: "Free Prompt".to_string(),
-                    command: None,
                     claude: Some(ClaudeStep {
                         prompt,
                         model: None,
@@ -581,9 +1130,12 @@ impl WorkerManager {
                         permission_mode,
                         extra_args: None,
                         sandbox_mode: None, // Use global default
+                        auto_commit: None,
                     }),
                     description: Some("User supplied prompt".to_string()),
+                    ..Default::default()
                 }],
+                extends: None,
             }
         } else {
             request
@@ -591,28 +1143,41 @@ impl WorkerManager {
                 .as_deref()
                 .and_then(|name| self.config.workflow_by_name(name))
                 .cloned()
-                .unwrap_or_else(|| self.config.default_workflow().clone())
+                .unwrap_or_else(|| self.config.default_workflow())
         };
 
-        let agent = request
-            .agent
-            .unwrap_or_else(|| "Claude Code (simulated)".to_string());
         let issue = request.issue;
+        // An uncached issue is fetched in the background once the worker is added
+        let issue_title = issue
+            .as_deref()
+            .and_then(|reference| self.issues.cached(reference))
+            .map(|details| details.title);
         let total_steps = workflow.steps().len();
+        // Announce a worker that has to wait as queued right away
+        let status = if self.has_free_slot(&agent) {
+            WorkerStatus::Running
+        } else {
+            WorkerStatus::Queued
+        };
 
         let snapshot = WorkerSnapshot {
             id: worker_id,
             name: name.clone(),
+            issue_title,
             issue,
             agent,
             worktree: rel_worktree.clone(),
             branch: branch.clone(),
-            status: WorkerStatus::Running,
+            status,
             last_event: "Worktree provisioned".into(),
             workflow: workflow.name.clone(),
             total_steps,
             current_step: None,
             session_id: None,
+            priority: request.priority,
+            queue_position: None,
+            pr_url: None,
+            base_ref: chosen_base,
         };
 
         let runtime = WorkerRuntime::new(
@@ -621,9 +1186,10 @@ impl WorkerManager {
             branch,
             workflow,
             self.cmd_tx.clone(),
+            Arc::clone(&self.agents),
             self.config.default_sandbox_mode,
         );
-        let mut runtime = match runtime {
+        let runtime = match runtime {
             Ok(runtime) => runtime,
             Err(err) => {
                 let _ = self.evt_tx.send(WorkerEvent::Error {
@@ -649,24 +1215,64 @@ impl WorkerManager {
             }
         }
 
-        runtime.start_agent(&self.evt_tx);
         self.workers.insert(worker_id, runtime);
+        // Fetch now so that a queued worker shows its issue title too
+        self.refresh_issue(worker_id);
+        self.start_or_enqueue(worker_id);
 
         self.persist_worker(worker_id);
 
         Ok(())
     }
 
-    fn handle_delete(&mut self, id: WorkerId) -> Result<()> {
-        let mut runtime = self
+    fn handle_delete(&mut self, id: WorkerId, mode: DeleteMode, force: bool) -> Result<()> {
+        if let Some(pos) = self.archived.iter().position(|snapshot| snapshot.id == id) {
+            return match mode {
+                DeleteMode::Archive => Err(anyhow!("worker {:?} is already archived", id)),
+                DeleteMode::Destroy => self.destroy_archived(pos, force),
+            };
+        }
+
+        let runtime = self
             .workers
-            .remove(&id)
+            .get(&id)
             .ok_or_else(|| anyhow!("worker {:?} not found", id))?;
+        if mode == DeleteMode::Destroy && !force {
+            let base_ref = runtime.snapshot().base_ref.or_else(|| self.base_ref.clone());
+            let unsaved = UnsavedWork::inspect(
+                &self.repo_root,
+                Some(&runtime.worktree_path),
+                &runtime.branch,
+                base_ref.as_deref(),
+            )?;
+            if !unsaved.is_empty() {
+                return Err(anyhow!(
+                    "{} has {}; archive it or force the deletion",
+                    runtime.snapshot().name,
+                    unsaved.summary()
+                ));
+            }
+        }
 
-        let snapshot = runtime.snapshot();
+        self.dequeue(id);
+        // Release a pending permission prompt first so the agent thread can exit
+        self.cancel_pending_permissions_for_worker(id);
+        if let Some(runtime) = self.workers.get_mut(&id) {
+            runtime.stop_agent();
+        }
 
-        runtime.stop_agent();
+        match mode {
+            DeleteMode::Archive => self.archive_worker(id),
+            DeleteMode::Destroy => self.destroy_worker(id),
+        }
+    }
 
+    fn destroy_worker(&mut self, id: WorkerId) -> Result<()> {
+        let runtime = self
+            .workers
+            .remove(&id)
+            .ok_or_else(|| anyhow!("worker {:?} not found", id))?;
+        let snapshot = runtime.snapshot();
         let worktree_path = runtime.worktree_path.clone();
         let branch = runtime.branch.clone();
 
@@ -697,27 +1303,10 @@ impl WorkerManager {
             });
         }
 
-        let branch_output = Command::new("git")
-            .args(["branch", "-D", &branch])
-            .current_dir(&self.repo_root)
-            .stderr(Stdio::piped())
-            .stdout(Stdio::piped())
-            .output()
-            .with_context(|| "failed to execute git branch -D")?;
+        self.delete_branch(id, &branch)?;
 
-        if !branch_output.status.success() {
-            let stderr = String::from_utf8_lossy(&branch_output.stderr);
-            let stdout = String::from_utf8_lossy(&branch_output.stdout);
-            let message = format!(
-                "git branch -D {} failed: stdout='{}' stderr='{}'",
-                branch,
-                stdout.trim(),
-                stderr.trim()
-            );
-            let _ = self.evt_tx.send(WorkerEvent::Error {
-                id: Some(id),
-                message: message.clone(),
-            });
+        if let Err(err) = self.state_store.delete_worker(&snapshot.name) {
+            eprintln!("Failed to delete worker state {}: {err}", snapshot.name);
         }
 
         let _ = self.evt_tx.send(WorkerEvent::Deleted {
@@ -725,12 +1314,187 @@ impl WorkerManager {
             message: format!("Removed worktree {}", worktree_path.display()),
         });
 
-        if let Err(err) = self.state_store.delete_worker(&snapshot.name) {
-            eprintln!("Failed to delete worker state {}: {err}", snapshot.name);
+        Ok(())
+    }
+
+    /// Delete the branch an archived worker kept, then its saved state.
+    fn destroy_archived(&mut self, pos: usize, force: bool) -> Result<()> {
+        let snapshot = self.archived[pos].clone();
+        let branch_exists = git_succeeds(
+            &self.repo_root,
+            &["rev-parse", "--verify", "--quiet", &format!("refs/heads/{}", snapshot.branch)],
+        );
+        if branch_exists {
+            if !force {
+                let unsaved = UnsavedWork::inspect(
+                    &self.repo_root,
+                    None,
+                    &snapshot.branch,
+                    snapshot.base_ref.as_deref().or(self.base_ref.as_deref()),
+                )?;
+                if !unsaved.is_empty() {
+                    return Err(anyhow!(
+                        "{} has {}; force the deletion to drop them",
+                        snapshot.name,
+                        unsaved.summary()
+                    ));
+                }
+            }
+            self.delete_branch(snapshot.id, &snapshot.branch)?;
         }
 
-        self.cancel_pending_permissions_for_worker(id);
+        self.state_store
+            .delete_worker(&snapshot.name)
+            .with_context(|| format!("failed to delete worker state {}", snapshot.name))?;
+        self.archived.remove(pos);
+
+        let _ = self.evt_tx.send(WorkerEvent::Deleted {
+            id: snapshot.id,
+            message: if branch_exists {
+                format!("Removed archived worker and branch {}", snapshot.branch)
+            } else {
+                "Removed archived worker".to_string()
+            },
+        });
+
+        Ok(())
+    }
+
+    fn delete_branch(&self, id: WorkerId, branch: &str) -> Result<()> {
+        let branch_output = Command::new("git")
+            .args(["branch", "-D", branch])
+            .current_dir(&self.repo_root)
+            .stderr(Stdio::piped())
+            .stdout(Stdio::piped())
+            .output()
+            .with_context(|| "failed to execute git branch -D")?;
+
+        if !branch_output.status.success() {
+            let stderr = String::from_utf8_lossy(&branch_output.stderr);
+            let stdout = String::from_utf8_lossy(&branch_output.stdout);
+            let message = format!(
+                "git branch -D {} failed: stdout='{}' stderr='{}'",
+                branch,
+                stdout.trim(),
+                stderr.trim()
+            );
+            let _ = self.evt_tx.send(WorkerEvent::Error {
+                id: Some(id),
+                message: message.clone(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Commit whatever is left in the worktree onto the worker branch, remove
+    /// the worktree and keep the branch and saved state for reactivation.
+    fn archive_worker(&mut self, id: WorkerId) -> Result<()> {
+        let runtime = self
+            .workers
+            .get(&id)
+            .ok_or_else(|| anyhow!("worker {:?} not found", id))?;
+        let name = runtime.snapshot().name;
+        let worktree_path = runtime.worktree_path.clone();
+        let branch = runtime.branch.clone();
+
+        let saved = commit::commit_all(
+            &worktree_path,
+            &format!("wip: {name}\n\nUncommitted changes saved when the worker was archived."),
+        )
+        .context("failed to commit the uncommitted changes before archiving")?;
+
+        let remove_output = Command::new("git")
+            .args([
+                "worktree",
+                "remove",
+                "--force",
+                &worktree_path.to_string_lossy(),
+            ])
+            .current_dir(&self.repo_root)
+            .stderr(Stdio::piped())
+            .stdout(Stdio::piped())
+            .output()
+            .with_context(|| "failed to execute git worktree remove")?;
+        if !remove_output.status.success() {
+            return Err(anyhow!(
+                "git worktree remove failed: {}",
+                String::from_utf8_lossy(&remove_output.stderr).trim()
+            ));
+        }
+
+        let snapshot = {
+            let mut snapshot = runtime.state.lock().expect("worker snapshot poisoned");
+            snapshot.status = WorkerStatus::Archived;
+            snapshot.current_step = None;
+            snapshot.queue_position = None;
+            snapshot.last_event = match &saved {
+                Some(sha) => format!(
+                    "Archived (uncommitted changes saved as {} on {branch})",
+                    short_sha(sha)
+                ),
+                None => format!("Archived (branch {branch} kept)"),
+            };
+            snapshot.clone()
+        };
+        self.persist_worker(id);
+        self.workers.remove(&id);
+        self.archived.push(snapshot.clone());
+
+        let _ = self.evt_tx.send(WorkerEvent::Updated(snapshot));
+        Ok(())
+    }
+
+    /// Recreate the worktree of an archived worker from its kept branch.
+    fn handle_reactivate(&mut self, id: WorkerId) -> Result<()> {
+        let pos = self
+            .archived
+            .iter()
+            .position(|snapshot| snapshot.id == id)
+            .ok_or_else(|| anyhow!("worker {:?} is not archived", id))?;
+        let name = self.archived[pos].name.clone();
+        let record = self
+            .state_store
+            .load_worker(&name)?
+            .ok_or_else(|| anyhow!("saved state of {name} not found"))?;
+        let branch = record.snapshot.branch.clone();
+        let worktree_path = self.repo_root.join(&record.snapshot.worktree);
+
+        if !git_succeeds(
+            &self.repo_root,
+            &["rev-parse", "--verify", "--quiet", &format!("refs/heads/{branch}")],
+        ) {
+            return Err(anyhow!("branch {branch} of {name} no longer exists"));
+        }
+        // Drop the registration of the removed worktree so the path can be reused
+        let _ = git_succeeds(&self.repo_root, &["worktree", "prune"]);
+        let add_output = Command::new("git")
+            .args(["worktree", "add", &worktree_path.to_string_lossy(), &branch])
+            .current_dir(&self.repo_root)
+            .stderr(Stdio::piped())
+            .stdout(Stdio::piped())
+            .output()
+            .with_context(|| "failed to execute git worktree add")?;
+        if !add_output.status.success() {
+            return Err(anyhow!(
+                "git worktree add failed: {}",
+                String::from_utf8_lossy(&add_output.stderr).trim()
+            ));
+        }
+
+        let mut snapshot = self.archived.remove(pos);
+        snapshot.status = WorkerStatus::Idle;
+        snapshot.last_event = format!("Reactivated from branch {branch}");
+        let runtime = match self.restore_runtime(snapshot.clone(), worktree_path, record) {
+            Ok(runtime) => runtime,
+            Err(err) => {
+                self.archived.insert(pos, snapshot);
+                return Err(err);
+            }
+        };
+        self.workers.insert(id, runtime);
+        self.persist_worker(id);
 
+        let _ = self.evt_tx.send(WorkerEvent::Updated(snapshot));
         Ok(())
     }
 
@@ -746,6 +1510,9 @@ impl WorkerManager {
 
         // Reset completed steps to restart from beginning
         runtime.completed_steps.store(0, Ordering::SeqCst);
+        if let Ok(mut outcomes) = runtime.step_outcomes.lock() {
+            outcomes.clear();
+        }
 
         {
             let mut snapshot = runtime.state.lock().expect("worker snapshot poisoned");
@@ -755,7 +1522,145 @@ impl WorkerManager {
             let _ = self.evt_tx.send(WorkerEvent::Updated(snapshot.clone()));
         }
 
-        runtime.start_agent(&self.evt_tx);
+        self.start_or_enqueue(id);
+
+        self.persist_worker(id);
+
+        Ok(())
+    }
+
+    fn handle_cancel(&mut self, id: WorkerId) -> Result<()> {
+        self.cancel_pending_permissions_for_worker(id);
+        self.dequeue(id);
+
+        let runtime = self
+            .workers
+            .get_mut(&id)
+            .ok_or_else(|| anyhow!("worker {:?} not found", id))?;
+
+        runtime.stop_agent();
+
+        {
+            let mut snapshot = runtime.state.lock().expect("worker snapshot poisoned");
+            snapshot.status = WorkerStatus::Paused;
+            snapshot.last_event = "ワーカーがキャンセルされました".into();
+            snapshot.current_step = None;
+            let _ = self.evt_tx.send(WorkerEvent::Updated(snapshot.clone()));
+        }
+
+        self.persist_worker(id);
+
+        Ok(())
+    }
+
+    fn handle_pause(&mut self, id: WorkerId) -> Result<()> {
+        let runtime = self
+            .workers
+            .get(&id)
+            .ok_or_else(|| anyhow!("worker {:?} not found", id))?;
+
+        match runtime.snapshot().status {
+            WorkerStatus::Running => runtime.pause(),
+            // A queued worker leaves the queue until it is resumed
+            WorkerStatus::Queued => self.dequeue(id),
+            _ => return Err(anyhow!("worker {:?} is not running", id)),
+        }
+        let runtime = &self.workers[&id];
+
+        {
+            let mut snapshot = runtime.state.lock().expect("worker snapshot poisoned");
+            snapshot.status = WorkerStatus::Paused;
+            snapshot.last_event = "一時停止中".into();
+            let _ = self.evt_tx.send(WorkerEvent::Updated(snapshot.clone()));
+        }
+
+        let _ = self.evt_tx.send(WorkerEvent::Log {
+            id,
+            line: "ワーカーを一時停止しました".to_string(),
+        });
+
+        self.persist_worker(id);
+
+        Ok(())
+    }
+
+    fn handle_resume(&mut self, id: WorkerId) -> Result<()> {
+        let runtime = self
+            .workers
+            .get_mut(&id)
+            .ok_or_else(|| anyhow!("worker {:?} not found", id))?;
+
+        if runtime.snapshot().status != WorkerStatus::Paused {
+            return Err(anyhow!("worker {:?} is not paused", id));
+        }
+
+        {
+            let mut snapshot = runtime.state.lock().expect("worker snapshot poisoned");
+            snapshot.status = WorkerStatus::Running;
+            snapshot.last_event = "再開しました".into();
+            let _ = self.evt_tx.send(WorkerEvent::Updated(snapshot.clone()));
+        }
+
+        let _ = self.evt_tx.send(WorkerEvent::Log {
+            id,
+            line: "ワーカーを再開しました".to_string(),
+        });
+
+        if runtime.is_agent_running() {
+            runtime.resume();
+        } else {
+            // Nothing is running (e.g. restored from disk or stopped after a denial):
+            // pick the workflow up again from the first incomplete step
+            self.start_or_enqueue(id);
+        }
+
+        self.persist_worker(id);
+
+        Ok(())
+    }
+
+    fn handle_resume_interrupted(&mut self, id: WorkerId, continue_session: bool) -> Result<()> {
+        self.cancel_pending_permissions_for_worker(id);
+
+        let runtime = self
+            .workers
+            .get_mut(&id)
+            .ok_or_else(|| anyhow!("worker {:?} not found", id))?;
+
+        if runtime.snapshot().status != WorkerStatus::Interrupted {
+            return Err(anyhow!("worker {:?} was not interrupted", id));
+        }
+
+        let start_step = runtime.completed_steps.load(Ordering::SeqCst);
+        let total_steps = runtime.workflow.steps().len();
+
+        {
+            let mut snapshot = runtime.state.lock().expect("worker snapshot poisoned");
+            if !continue_session {
+                // Start the interrupted step over in a fresh agent session
+                snapshot.session_id = None;
+            }
+            snapshot.status = WorkerStatus::Running;
+            snapshot.last_event = format!("ステップ {}/{} から再開", start_step + 1, total_steps);
+            let _ = self.evt_tx.send(WorkerEvent::Updated(snapshot.clone()));
+        }
+
+        let session_label = if continue_session {
+            "前回のセッションを継続"
+        } else {
+            "新規セッション"
+        };
+        let _ = self.evt_tx.send(WorkerEvent::Log {
+            id,
+            line: format!(
+                "中断されたワークフローをステップ {}/{} から再開します（{}）",
+                start_step + 1,
+                total_steps,
+                session_label
+            ),
+        });
+
+        self.start_or_enqueue(id);
 
         self.persist_worker(id);
 
@@ -781,12 +1686,11 @@ impl WorkerManager {
         // Mark all current steps as completed
         runtime
             .completed_steps
-            .store(runtime.workflow.steps.len(), Ordering::SeqCst);
+            .store(runtime.workflow.steps().len(), Ordering::SeqCst);
 
         // Create a new workflow step with the continuation prompt
         let continue_step = WorkflowStep {
             name: "Continue".to_string(),
-            command: None,
             claude: Some(ClaudeStep {
                 prompt,
                 model: None,
@@ -794,8 +1698,10 @@ impl WorkerManager {
                 permission_mode,
                 extra_args: None,
                 sandbox_mode: None, // Use global default
+                auto_commit: None,
             }),
             description: Some("User follow-up instruction".to_string()),
+            ..Default::default()
         };
 
         // Add the new step to the workflow
@@ -805,7 +1711,7 @@ impl WorkerManager {
             let mut snapshot = runtime.state.lock().expect("worker snapshot poisoned");
             snapshot.status = WorkerStatus::Running;
             snapshot.last_event = "Continue with new instruction".into();
-            snapshot.total_steps = runtime.workflow.steps.len();
+            snapshot.total_steps = runtime.workflow.steps().len();
             let _ = self.evt_tx.send(WorkerEvent::Updated(snapshot.clone()));
         }
 
@@ -815,7 +1721,7 @@ impl WorkerManager {
         });
 
         // Restart agent with updated workflow
-        runtime.start_agent(&self.evt_tx);
+        self.start_or_enqueue(id);
 
         self.persist_worker(id);
 
@@ -869,60 +1775,499 @@ impl WorkerManager {
         Ok(())
     }
 
-    fn handle_permission_prompt(
-        &mut self,
-        id: WorkerId,
-        request: PermissionRequest,
-        respond_to: Sender<PermissionDecision>,
-    ) {
-        self.pending_permissions.insert(
-            request.request_id,
-            PendingPermission {
-                worker_id: id,
-                respond_to,
-            },
-        );
+    fn handle_set_priority(&mut self, id: WorkerId, priority: i32) -> Result<()> {
+        let runtime = self
+            .workers
+            .get(&id)
+            .ok_or_else(|| anyhow!("worker {:?} not found", id))?;
 
-        let _ = self
-            .evt_tx
-            .send(WorkerEvent::PermissionRequested { id, request });
-    }
+        {
+            let mut snapshot = runtime.state.lock().expect("worker snapshot poisoned");
+            snapshot.priority = priority;
+            let _ = self.evt_tx.send(WorkerEvent::Updated(snapshot.clone()));
+        }
 
-    fn handle_permission_response(
-        &mut self,
-        id: WorkerId,
-        request_id: u64,
-        decision: PermissionDecision,
-    ) {
-        if let Some(pending) = self.pending_permissions.remove(&request_id) {
-            let _ = pending.respond_to.send(decision.clone());
-            let _ = self.evt_tx.send(WorkerEvent::PermissionResolved {
-                id,
-                request_id,
-                decision,
-            });
-        } else {
-            let _ = self.evt_tx.send(WorkerEvent::Error {
-                id: Some(id),
-                message: format!("permission request {} not found", request_id),
-            });
+        if self.queue.contains(&id) {
+            // Move it behind the workers that now share its priority
+            self.start_or_enqueue(id);
         }
+
+        self.persist_worker(id);
+
+        Ok(())
     }
 
-    fn cancel_pending_permissions_for_worker(&mut self, id: WorkerId) {
-        let mut orphaned = Vec::new();
-        for (request_id, pending) in self.pending_permissions.iter() {
-            if pending.worker_id == id {
-                orphaned.push(*request_id);
-            }
+    /// Render the worker's pull request and open it on a background thread,
+    /// which reports back with `PullRequestOpened`.
+    fn handle_open_pull_request(&mut self, id: WorkerId) -> Result<()> {
+        let forge = self
+            .forge
+            .clone()
+            .ok_or_else(|| anyhow!("no forge is configured; set the forge or issues section"))?;
+        if !self.workers.contains_key(&id) {
+            return Err(anyhow!("worker {:?} not found", id));
+        }
+        self.refresh_issue(id);
+        let runtime = &self.workers[&id];
+        if runtime.is_agent_running() {
+            return Err(anyhow!("worker {:?} is still running", id));
         }
+        let request = runtime.render_pull_request(forge.base())?;
 
-        for request_id in orphaned {
-            if let Some(pending) = self.pending_permissions.remove(&request_id) {
-                let _ = pending.respond_to.send(PermissionDecision::Deny);
-            }
+        {
+            let mut snapshot = runtime.state.lock().expect("worker snapshot poisoned");
+            snapshot.last_event = "プルリクエストを作成中...".into();
+            let _ = self.evt_tx.send(WorkerEvent::Updated(snapshot.clone()));
         }
-    }
+
+        let worktree_path = runtime.worktree_path.clone();
+        let cmd_tx = self.cmd_tx.clone();
+        thread::Builder::new()
+            .name(format!("gensui-pr-{}", id.0))
+            .spawn(move || {
+                let result = forge
+                    .publish(&worktree_path, &request)
+                    .map_err(|err| format!("{err:#}"));
+                let _ = cmd_tx.send(WorkerCommand::PullRequestOpened { id, result });
+            })
+            .context("failed to spawn pull request thread")?;
+        Ok(())
+    }
+
+    fn handle_pull_request_opened(&mut self, id: WorkerId, result: Result<String, String>) {
+        let Some(runtime) = self.workers.get(&id) else {
+            return;
+        };
+        let line = {
+            let mut snapshot = runtime.state.lock().expect("worker snapshot poisoned");
+            let line = match &result {
+                Ok(url) => {
+                    snapshot.pr_url = Some(url.clone());
+                    snapshot.last_event = format!("プルリクエストを作成しました: {url}");
+                    snapshot.last_event.clone()
+                }
+                Err(message) => {
+                    snapshot.last_event = "プルリクエストの作成に失敗しました".into();
+                    format!("プルリクエストの作成に失敗しました: {message}")
+                }
+            };
+            let _ = self.evt_tx.send(WorkerEvent::Updated(snapshot.clone()));
+            line
+        };
+        runtime.add_log(line.clone());
+        let _ = self.evt_tx.send(WorkerEvent::Log { id, line });
+        if let Err(message) = result {
+            let _ = self.evt_tx.send(WorkerEvent::Error {
+                id: Some(id),
+                message,
+            });
+        }
+        self.persist_worker(id);
+    }
+
+    /// Branch `merge` steps target by default: the base the worker was created
+    /// from when that is a local branch, otherwise the repository's base branch.
+    fn merge_base(&self, snapshot: &WorkerSnapshot) -> Option<String> {
+        snapshot
+            .base_ref
+            .clone()
+            .filter(|base_ref| {
+                git_succeeds(
+                    &self.repo_root,
+                    &["rev-parse", "--verify", "--quiet", &format!("refs/heads/{base_ref}")],
+                )
+            })
+            .or_else(|| self.base_ref.clone())
+    }
+
+    /// Merge the worker branch on a background thread, which reports back
+    /// with `MergeFinished`.
+    fn handle_merge(&mut self, id: WorkerId, step: MergeStep) -> Result<()> {
+        self.refresh_issue(id);
+        let runtime = self
+            .workers
+            .get(&id)
+            .ok_or_else(|| anyhow!("worker {:?} not found", id))?;
+        if runtime.is_agent_running() {
+            return Err(anyhow!("worker {:?} is still running", id));
+        }
+        let base_ref = self.merge_base(&runtime.snapshot());
+        let request = runtime.render_merge(&step, base_ref.as_deref())?;
+
+        {
+            let mut snapshot = runtime.state.lock().expect("worker snapshot poisoned");
+            snapshot.last_event = if request.dry_run {
+                format!("{} とのコンフリクトを確認中...", request.base)
+            } else {
+                format!("{} に取り込み中 ({})...", request.base, request.strategy.label())
+            };
+            let _ = self.evt_tx.send(WorkerEvent::Updated(snapshot.clone()));
+        }
+
+        let worktree_path = runtime.worktree_path.clone();
+        let cmd_tx = self.cmd_tx.clone();
+        thread::Builder::new()
+            .name(format!("gensui-merge-{}", id.0))
+            .spawn(move || {
+                let mut conflicts = Vec::new();
+                let result = match merge::merge(&worktree_path, &request) {
+                    Ok(MergeOutcome::UpToDate) => {
+                        Ok(format!("{} は取り込み済みです", request.branch))
+                    }
+                    Ok(MergeOutcome::Mergeable) => Ok(format!(
+                        "{} は {} にコンフリクトなく取り込めます",
+                        request.branch, request.base
+                    )),
+                    Ok(MergeOutcome::Merged { commit }) => Ok(format!(
+                        "{} を {} に取り込みました ({})",
+                        request.branch,
+                        request.base,
+                        short_sha(&commit)
+                    )),
+                    Err(err) => {
+                        if step.resolve_conflicts && err.downcast_ref::<MergeConflict>().is_some() {
+                            match merge::start_conflict_resolution(&worktree_path, &request.base) {
+                                Ok(files) => conflicts = files,
+                                Err(start_err) => {
+                                    let _ = cmd_tx.send(WorkerCommand::MergeFinished {
+                                        id,
+                                        result: Err(format!("{err:#}; {start_err:#}")),
+                                        conflicts,
+                                    });
+                                    return;
+                                }
+                            }
+                        }
+                        Err(format!("{err:#}"))
+                    }
+                };
+                let _ = cmd_tx.send(WorkerCommand::MergeFinished {
+                    id,
+                    result,
+                    conflicts,
+                });
+            })
+            .context("failed to spawn merge thread")?;
+        Ok(())
+    }
+
+    fn handle_merge_finished(
+        &mut self,
+        id: WorkerId,
+        result: Result<String, String>,
+        conflicts: Vec<String>,
+    ) {
+        let Some(runtime) = self.workers.get(&id) else {
+            return;
+        };
+        let line = {
+            let mut snapshot = runtime.state.lock().expect("worker snapshot poisoned");
+            let line = match &result {
+                Ok(summary) => {
+                    snapshot.last_event = summary.clone();
+                    summary.clone()
+                }
+                Err(message) => {
+                    snapshot.last_event = "ブランチの取り込みに失敗しました".into();
+                    format!("ブランチの取り込みに失敗しました: {message}")
+                }
+            };
+            let _ = self.evt_tx.send(WorkerEvent::Updated(snapshot.clone()));
+            line
+        };
+        runtime.add_log(line.clone());
+        let _ = self.evt_tx.send(WorkerEvent::Log { id, line });
+
+        if !conflicts.is_empty() {
+            // The agent resolves the conflicts; the user merges again afterwards
+            let prompt = with_conflicting_files(&runtime.render_resolve_prompt(), &conflicts);
+            if let Err(err) = self.handle_continue(id, prompt, None) {
+                let _ = self.evt_tx.send(WorkerEvent::Error {
+                    id: Some(id),
+                    message: err.to_string(),
+                });
+            }
+        } else if let Err(message) = result {
+            let _ = self.evt_tx.send(WorkerEvent::Error {
+                id: Some(id),
+                message,
+            });
+        }
+        self.persist_worker(id);
+    }
+
+    /// Whether another worker using `agent` may start under the configured
+    /// limits. Every worker whose agent thread is alive holds a slot,
+    /// including paused ones, and so does a started worker waiting for its
+    /// issue.
+    fn has_free_slot(&self, agent: &str) -> bool {
+        let running: Vec<String> = self
+            .workers
+            .iter()
+            .filter(|(id, runtime)| {
+                runtime.is_agent_running()
+                    || (self.issue_fetches.get(id) == Some(&true)
+                        && runtime.snapshot().status == WorkerStatus::Running)
+            })
+            .map(|(_, runtime)| runtime.snapshot().agent)
+            .collect();
+        let global = self
+            .config
+            .max_concurrent_workers
+            .is_none_or(|limit| running.len() < limit);
+        let per_agent = self
+            .config
+            .max_concurrent_workers_per_agent
+            .get(agent)
+            .is_none_or(|&limit| running.iter().filter(|a| *a == agent).count() < limit);
+        global && per_agent
+    }
+
+    /// Start the worker's agent if a slot is free, otherwise queue it behind
+    /// the workers with the same or a higher priority.
+    fn start_or_enqueue(&mut self, id: WorkerId) {
+        self.queue.retain(|queued| *queued != id);
+        let Some(runtime) = self.workers.get(&id) else {
+            return;
+        };
+        let snapshot = runtime.snapshot();
+        if self.has_free_slot(&snapshot.agent) {
+            self.start_queued(id);
+            return;
+        }
+
+        {
+            let mut state = runtime.state.lock().expect("worker snapshot poisoned");
+            state.status = WorkerStatus::Queued;
+            state.queue_position = None;
+        }
+        let position = self
+            .queue
+            .iter()
+            .position(|queued| {
+                self.workers
+                    .get(queued)
+                    .is_some_and(|other| other.snapshot().priority < snapshot.priority)
+            })
+            .unwrap_or(self.queue.len());
+        self.queue.insert(position, id);
+        self.announce_queue();
+    }
+
+    /// Start a worker's agent, marking it running if it was queued. A worker
+    /// whose issue is still being fetched keeps its slot and starts once
+    /// `IssueFetched` arrives.
+    fn start_queued(&mut self, id: WorkerId) {
+        let Some(runtime) = self.workers.get(&id) else {
+            return;
+        };
+        {
+            let mut snapshot = runtime.state.lock().expect("worker snapshot poisoned");
+            if snapshot.status == WorkerStatus::Queued {
+                snapshot.status = WorkerStatus::Running;
+                snapshot.queue_position = None;
+                snapshot.last_event = "実行枠が空いたため開始しました".into();
+                let _ = self.evt_tx.send(WorkerEvent::Updated(snapshot.clone()));
+            }
+        }
+        if self.refresh_issue(id) {
+            self.start_agent(id);
+        } else {
+            self.issue_fetches.insert(id, true);
+        }
+    }
+
+    fn start_agent(&mut self, id: WorkerId) {
+        let base_ref = match self.workers.get(&id) {
+            Some(runtime) => self.merge_base(&runtime.snapshot()),
+            None => return,
+        };
+        let Some(runtime) = self.workers.get_mut(&id) else {
+            return;
+        };
+        runtime.forge = self.forge.clone();
+        runtime.base_ref = base_ref;
+        runtime.auto_commit = self.config.auto_commit;
+        runtime.start_agent(&self.evt_tx);
+    }
+
+    /// Look up the worker's issue before a run. A cached issue is used right
+    /// away and true returned; otherwise it is fetched on a background thread
+    /// that reports back with `IssueFetched`.
+    fn refresh_issue(&mut self, id: WorkerId) -> bool {
+        let Some(runtime) = self.workers.get_mut(&id) else {
+            return true;
+        };
+        let Some(reference) = runtime.snapshot().issue else {
+            return true;
+        };
+        if let Some(issue) = self.issues.cached(&reference) {
+            runtime.state.lock().expect("worker snapshot poisoned").issue_title =
+                Some(issue.title.clone());
+            runtime.issue = Some(issue);
+            return true;
+        }
+        if self.issue_fetches.contains_key(&id) {
+            return false;
+        }
+
+        let issues = Arc::clone(&self.issues);
+        let cmd_tx = self.cmd_tx.clone();
+        let spawned = thread::Builder::new()
+            .name(format!("gensui-issue-{}", id.0))
+            .spawn(move || {
+                let result = issues.fetch(&reference).map_err(|err| format!("{err:#}"));
+                let _ = cmd_tx.send(WorkerCommand::IssueFetched { id, result });
+            });
+        if spawned.is_err() {
+            // Run with what was known before rather than not at all
+            return true;
+        }
+        self.issue_fetches.insert(id, false);
+        false
+    }
+
+    /// Apply a fetched issue and start the worker if it was waiting for it.
+    /// A failed lookup keeps what was known before.
+    fn handle_issue_fetched(&mut self, id: WorkerId, result: Result<Option<Issue>, String>) {
+        let start = self.issue_fetches.remove(&id).unwrap_or(false);
+        let Some(runtime) = self.workers.get_mut(&id) else {
+            return;
+        };
+        let status = match result {
+            Ok(issue) => {
+                let mut snapshot = runtime.state.lock().expect("worker snapshot poisoned");
+                snapshot.issue_title = issue.as_ref().map(|issue| issue.title.clone());
+                let _ = self.evt_tx.send(WorkerEvent::Updated(snapshot.clone()));
+                drop(snapshot);
+                runtime.issue = issue;
+                runtime.snapshot().status
+            }
+            Err(message) => {
+                let line = format!(
+                    "Issue の読み込みに失敗しました ({}): {message}",
+                    self.issues.name()
+                );
+                runtime.add_log(line.clone());
+                let _ = self.evt_tx.send(WorkerEvent::Log { id, line });
+                runtime.snapshot().status
+            }
+        };
+        // A cancel or pause while waiting leaves the worker stopped
+        if start && status == WorkerStatus::Running {
+            self.start_agent(id);
+        }
+        self.persist_worker(id);
+    }
+
+    /// Remove a worker from the queue, e.g. when it is cancelled or deleted.
+    fn dequeue(&mut self, id: WorkerId) {
+        let Some(position) = self.queue.iter().position(|queued| *queued == id) else {
+            return;
+        };
+        self.queue.remove(position);
+        if let Some(runtime) = self.workers.get(&id) {
+            runtime.state.lock().expect("worker snapshot poisoned").queue_position = None;
+        }
+        self.announce_queue();
+    }
+
+    /// Start queued workers in queue order while their agent has a free slot.
+    fn schedule(&mut self) {
+        let mut idx = 0;
+        let mut started = false;
+        while idx < self.queue.len() {
+            let id = self.queue[idx];
+            let Some(agent) = self.workers.get(&id).map(|runtime| runtime.snapshot().agent) else {
+                self.queue.remove(idx);
+                continue;
+            };
+            if self.has_free_slot(&agent) {
+                self.queue.remove(idx);
+                self.start_queued(id);
+                self.persist_worker(id);
+                started = true;
+            } else {
+                idx += 1;
+            }
+        }
+        if started {
+            self.announce_queue();
+        }
+    }
+
+    /// Publish the queue position of every queued worker whose position changed.
+    fn announce_queue(&self) {
+        for (idx, id) in self.queue.iter().enumerate() {
+            let Some(runtime) = self.workers.get(id) else {
+                continue;
+            };
+            let position = idx + 1;
+            let mut snapshot = runtime.state.lock().expect("worker snapshot poisoned");
+            if snapshot.queue_position != Some(position) {
+                snapshot.status = WorkerStatus::Queued;
+                snapshot.queue_position = Some(position);
+                snapshot.last_event = format!("実行枠の空き待ち（{position}番目）");
+                let _ = self.evt_tx.send(WorkerEvent::Updated(snapshot.clone()));
+            }
+        }
+    }
+
+    fn handle_permission_prompt(
+        &mut self,
+        id: WorkerId,
+        request: PermissionRequest,
+        respond_to: Sender<PermissionDecision>,
+    ) {
+        self.pending_permissions.insert(
+            request.request_id,
+            PendingPermission {
+                worker_id: id,
+                request: request.clone(),
+                respond_to,
+            },
+        );
+
+        let _ = self
+            .evt_tx
+            .send(WorkerEvent::PermissionRequested { id, request });
+    }
+
+    fn handle_permission_response(
+        &mut self,
+        id: WorkerId,
+        request_id: u64,
+        decision: PermissionDecision,
+    ) {
+        if let Some(pending) = self.pending_permissions.remove(&request_id) {
+            let _ = pending.respond_to.send(decision.clone());
+            let _ = self.evt_tx.send(WorkerEvent::PermissionResolved {
+                id,
+                request_id,
+                decision,
+            });
+        } else {
+            let _ = self.evt_tx.send(WorkerEvent::Error {
+                id: Some(id),
+                message: format!("permission request {} not found", request_id),
+            });
+        }
+    }
+
+    fn cancel_pending_permissions_for_worker(&mut self, id: WorkerId) {
+        let mut orphaned = Vec::new();
+        for (request_id, pending) in self.pending_permissions.iter() {
+            if pending.worker_id == id {
+                orphaned.push(*request_id);
+            }
+        }
+
+        for request_id in orphaned {
+            if let Some(pending) = self.pending_permissions.remove(&request_id) {
+                let _ = pending.respond_to.send(PermissionDecision::Deny);
+            }
+        }
+    }
 
     fn shutdown_all(&mut self) {
         // Persist all workers before shutting down
@@ -930,14 +2275,15 @@ impl WorkerManager {
             self.persist_worker(id);
         }
 
+        // Release pending permission prompts so agent threads can observe cancellation
+        for (_, pending) in self.pending_permissions.drain() {
+            let _ = pending.respond_to.send(PermissionDecision::Deny);
+        }
+
         // Then stop all workers
         for (_, mut runtime) in self.workers.drain() {
             runtime.stop_agent();
         }
-
-        for (_, pending) in self.pending_permissions.drain() {
-            let _ = pending.respond_to.send(PermissionDecision::Deny);
-        }
     }
 
     fn persist_manager_state(&self) {
@@ -961,6 +2307,7 @@ impl WorkerManager {
                     id: snapshot.id.0,
                     name: snapshot.name.clone(),
                     issue: snapshot.issue.clone(),
+                    issue_title: snapshot.issue_title.clone(),
                     agent: snapshot.agent.clone(),
                     worktree: snapshot.worktree.clone(),
                     branch: snapshot.branch.clone(),
@@ -970,11 +2317,15 @@ impl WorkerManager {
                     total_steps: snapshot.total_steps,
                     current_step: snapshot.current_step.clone(),
                     session_id: snapshot.session_id.clone(),
+                    priority: snapshot.priority,
+                    pr_url: snapshot.pr_url.clone(),
+                    base_ref: snapshot.base_ref.clone(),
                 },
                 logs,
                 workflow: runtime.workflow.clone(),
                 completed_steps: runtime.completed_steps.load(Ordering::SeqCst),
                 session_history,
+                step_outcomes: runtime.get_step_outcomes(),
             };
 
             if let Err(err) = self.state_store.save_worker(&record) {
@@ -987,7 +2338,12 @@ impl WorkerManager {
 struct WorkerRuntime {
     state: Arc<Mutex<WorkerSnapshot>>,
     cancel_flag: Arc<AtomicBool>,
+    pause_flag: Arc<AtomicBool>,
+    processes: ProcessTracker,
     handle: Option<thread::JoinHandle<()>>,
+    /// Cleared by the agent thread right before it sends `AgentFinished`, so
+    /// the scheduling that follows already sees the slot as free.
+    agent_active: Arc<AtomicBool>,
     worktree_path: PathBuf,
     branch: String,
     workflow: Workflow,
@@ -995,7 +2351,17 @@ struct WorkerRuntime {
     logs: Arc<Mutex<VecDeque<String>>>,
     cmd_tx: Sender<WorkerCommand>,
     session_histories: Arc<Mutex<Vec<SessionHistory>>>,
+    step_outcomes: Arc<Mutex<HashMap<String, StepOutcome>>>,
+    agents: Arc<AgentRegistry>,
     default_sandbox_mode: bool,
+    /// Issue metadata handed to prompt templates, refreshed before each run.
+    issue: Option<Issue>,
+    /// Forge for `pr` steps, taken from the configuration at each start.
+    forge: Option<Forge>,
+    /// Default target of `merge` steps, refreshed at each start.
+    base_ref: Option<String>,
+    /// Global auto-commit setting, taken from the configuration at each start.
+    auto_commit: bool,
 }
 
 impl WorkerRuntime {
@@ -1005,12 +2371,16 @@ impl WorkerRuntime {
         branch: String,
         workflow: Workflow,
         cmd_tx: Sender<WorkerCommand>,
+        agents: Arc<AgentRegistry>,
         default_sandbox_mode: bool,
     ) -> Result<Self> {
         Ok(Self {
             state: Arc::new(Mutex::new(snapshot)),
             cancel_flag: Arc::new(AtomicBool::new(false)),
+            pause_flag: Arc::new(AtomicBool::new(false)),
+            processes: ProcessTracker::new(),
             handle: None,
+            agent_active: Arc::new(AtomicBool::new(false)),
             worktree_path,
             branch,
             workflow,
@@ -1018,7 +2388,13 @@ impl WorkerRuntime {
             logs: Arc::new(Mutex::new(VecDeque::new())),
             cmd_tx,
             session_histories: Arc::new(Mutex::new(Vec::new())),
+            step_outcomes: Arc::new(Mutex::new(HashMap::new())),
+            agents,
             default_sandbox_mode,
+            issue: None,
+            forge: None,
+            base_ref: None,
+            auto_commit: false,
         })
     }
 
@@ -1029,6 +2405,13 @@ impl WorkerRuntime {
             .unwrap_or_default()
     }
 
+    fn get_step_outcomes(&self) -> HashMap<String, StepOutcome> {
+        self.step_outcomes
+            .lock()
+            .map(|outcomes| outcomes.clone())
+            .unwrap_or_default()
+    }
+
     fn add_session_history(&self, history: SessionHistory) {
         if let Ok(mut histories) = self.session_histories.lock() {
             histories.push(history);
@@ -1056,847 +2439,130 @@ impl WorkerRuntime {
             .unwrap_or_default()
     }
 
+    /// Run `f` with the placeholder values of the worker's latest run.
+    fn with_template_context<T>(&self, f: impl FnOnce(&TemplateContext<'_>) -> T) -> T {
+        let snapshot = self.snapshot();
+        let outcomes = self.get_step_outcomes();
+        let histories = self.get_session_histories();
+        f(&TemplateContext {
+            snapshot: &snapshot,
+            workflow: &self.workflow,
+            outcomes: &outcomes,
+            worktree_path: &self.worktree_path,
+            issue: self.issue.as_ref(),
+            session_result: histories.iter().rev().find_map(SessionHistory::final_result),
+        })
+    }
+
+    /// The pull request the pull request action opens, with default settings.
+    fn render_pull_request(&self, default_base: Option<&str>) -> Result<PullRequest> {
+        self.with_template_context(|ctx| {
+            render_pull_request(&PullRequestStep::default(), default_base, ctx)
+        })
+    }
+
+    /// The merge the merge action performs.
+    fn render_merge(&self, step: &MergeStep, default_base: Option<&str>) -> Result<MergeRequest> {
+        self.with_template_context(|ctx| render_merge(step, default_base, ctx))
+    }
+
+    /// Instruction handed to the agent after the merge action started a
+    /// conflict resolution.
+    fn render_resolve_prompt(&self) -> String {
+        self.with_template_context(|ctx| render_prompt(DEFAULT_RESOLVE_PROMPT, ctx))
+    }
+
     fn start_agent(&mut self, evt_tx: &Sender<WorkerEvent>) {
         self.stop_agent();
 
-        let state = Arc::clone(&self.state);
         let cancel = Arc::new(AtomicBool::new(false));
         self.cancel_flag = Arc::clone(&cancel);
-        let evt_tx = evt_tx.clone();
-        let worktree_path = self.worktree_path.clone();
-        let workflow = self.workflow.clone();
-        let completed_steps = Arc::clone(&self.completed_steps);
-        let logs = Arc::clone(&self.logs);
-        let cmd_tx = self.cmd_tx.clone();
-        let session_histories = Arc::clone(&self.session_histories);
-        let default_sandbox_mode = self.default_sandbox_mode;
+        self.pause_flag.store(false, Ordering::SeqCst);
+        self.processes = ProcessTracker::new();
+
+        let ctx = AgentContext {
+            worker_id: self.snapshot().id,
+            state: Arc::clone(&self.state),
+            cancel,
+            paused: Arc::clone(&self.pause_flag),
+            processes: self.processes.clone(),
+            worktree_path: self.worktree_path.clone(),
+            evt_tx: evt_tx.clone(),
+            workflow: self.workflow.clone(),
+            completed_steps: Arc::clone(&self.completed_steps),
+            logs: Arc::clone(&self.logs),
+            cmd_tx: self.cmd_tx.clone(),
+            session_histories: Arc::clone(&self.session_histories),
+            step_outcomes: Arc::clone(&self.step_outcomes),
+            agents: Arc::clone(&self.agents),
+            default_sandbox_mode: self.default_sandbox_mode,
+            issue: self.issue.clone(),
+            forge: self.forge.clone(),
+            base_ref: self.base_ref.clone(),
+            auto_commit: self.auto_commit,
+            log_buffer: None,
+        };
 
+        let worker_id = ctx.worker_id;
+        let cmd_tx = self.cmd_tx.clone();
+        let active = Arc::new(AtomicBool::new(true));
+        self.agent_active = Arc::clone(&active);
         let handle = thread::Builder::new()
             .name(format!("gensui-agent-{}", self.snapshot().name))
             .spawn(move || {
-                agent_simulation(
-                    state,
-                    cancel,
-                    worktree_path,
-                    evt_tx,
-                    workflow,
-                    completed_steps,
-                    logs,
-                    cmd_tx,
-                    session_histories,
-                    default_sandbox_mode,
-                )
+                agent_simulation(ctx);
+                active.store(false, Ordering::SeqCst);
+                let _ = cmd_tx.send(WorkerCommand::AgentFinished { id: worker_id });
             })
             .expect("failed to spawn agent simulation");
 
         self.handle = Some(handle);
     }
 
+    fn is_agent_running(&self) -> bool {
+        // The thread check covers an agent thread that panicked
+        self.agent_active.load(Ordering::SeqCst)
+            && self
+                .handle
+                .as_ref()
+                .is_some_and(|handle| !handle.is_finished())
+    }
+
+    /// Suspend the current step's processes and hold the workflow at the
+    /// next step boundary.
+    fn pause(&self) {
+        self.pause_flag.store(true, Ordering::SeqCst);
+        self.processes.suspend_all();
+    }
+
+    fn resume(&self) {
+        self.pause_flag.store(false, Ordering::SeqCst);
+        self.processes.resume_all();
+    }
+
+    /// Cancel the running workflow, terminating any agent or shell process
+    /// it spawned, and wait for the agent thread to finish.
     fn stop_agent(&mut self) {
         self.cancel_flag.store(true, Ordering::SeqCst);
+        self.processes.terminate_all(KILL_GRACE_PERIOD);
         if let Some(handle) = self.handle.take() {
             let _ = handle.join();
         }
     }
 }
 
-fn agent_simulation(
-    state: Arc<Mutex<WorkerSnapshot>>,
-    cancel: Arc<AtomicBool>,
-    worktree_path: PathBuf,
-    evt_tx: Sender<WorkerEvent>,
-    workflow: Workflow,
-    completed_steps: Arc<AtomicUsize>,
-    logs: Arc<Mutex<VecDeque<String>>>,
-    cmd_tx: Sender<WorkerCommand>,
-    session_histories: Arc<Mutex<Vec<SessionHistory>>>,
-    default_sandbox_mode: bool,
-) {
-    // Helper function to save log and send event
-    let send_log = |line: String, worker_id: WorkerId| {
-        // Add to logs
-        if let Ok(mut log_queue) = logs.lock() {
-            const MAX_LOGS: usize = 1000;
-            if log_queue.len() >= MAX_LOGS {
-                log_queue.pop_front();
-            }
-            log_queue.push_back(line.clone());
-        }
-
-        // Send event to UI
-        let _ = evt_tx.send(WorkerEvent::Log {
-            id: worker_id,
-            line,
-        });
-    };
-
-    let worker_id = {
-        state
-            .lock()
-            .map(|snapshot| snapshot.id)
-            .unwrap_or(WorkerId(0))
-    };
+fn git_succeeds(dir: &Path, args: &[&str]) -> bool {
+    Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success())
+}
 
-    let total_steps = workflow.steps().len();
-
-    if total_steps == 0 {
-        if let Ok(mut snapshot) = state.lock() {
-            snapshot.status = WorkerStatus::Idle;
-            snapshot.last_event = "No workflow steps defined".into();
-            snapshot.current_step = None;
-            let _ = evt_tx.send(WorkerEvent::Updated(snapshot.clone()));
-        }
-        return;
-    }
-
-    let start_step = completed_steps.load(Ordering::SeqCst);
-
-    if start_step == 0 {
-        if let Ok(mut snapshot) = state.lock() {
-            snapshot.status = WorkerStatus::Running;
-            snapshot.last_event = format!("ワークフロー '{}' を開始", workflow.name);
-            snapshot.current_step = None;
-            let _ = evt_tx.send(WorkerEvent::Updated(snapshot.clone()));
-        }
-    }
-
-    if cancel.load(Ordering::SeqCst) {
-        return;
-    }
-
-    for (idx, step) in workflow.steps().iter().enumerate() {
-        // Skip already completed steps
-        if idx < start_step {
-            continue;
-        }
-
-        if cancel.load(Ordering::SeqCst) {
-            if let Ok(mut snapshot) = state.lock() {
-                snapshot.status = WorkerStatus::Paused;
-                snapshot.last_event = "ワーカーがキャンセルされました".into();
-                snapshot.current_step = None;
-                let _ = evt_tx.send(WorkerEvent::Updated(snapshot.clone()));
-            }
-            return;
-        }
-
-        let step_desc = step
-            .description
-            .clone()
-            .unwrap_or_else(|| "ステップを実行".to_string());
-
-        let snapshot_info = if let Ok(mut snapshot) = state.lock() {
-            snapshot.status = WorkerStatus::Running;
-            snapshot.current_step = Some(format!("{}/{}: {}", idx + 1, total_steps, step.name));
-            snapshot.last_event = step_desc.clone();
-            let cloned = snapshot.clone();
-            let _ = evt_tx.send(WorkerEvent::Updated(snapshot.clone()));
-            cloned
-        } else {
-            WorkerSnapshot {
-                id: worker_id,
-                name: "unknown".into(),
-                issue: None,
-                agent: "Claude".into(),
-                worktree: worktree_path.to_string_lossy().into_owned(),
-                branch: "".into(),
-                status: WorkerStatus::Running,
-                last_event: step_desc.clone(),
-                workflow: workflow.name.clone(),
-                total_steps,
-                current_step: Some(format!("{}/{}: {}", idx + 1, total_steps, step.name)),
-                session_id: None,
-            }
-        };
-
-        // Step start marker
-        send_log(format!("[STEP_START:{}:{}]", idx, step.name), worker_id);
-        send_log(format!("[{}] {}", step.name, step_desc), worker_id);
-
-        let result = if let Some(claude_cfg) = &step.claude {
-            let request_id = NEXT_PERMISSION_REQUEST_ID.fetch_add(1, Ordering::SeqCst);
-            let permission_request = PermissionRequest {
-                request_id,
-                step_name: step.name.clone(),
-                description: step.description.clone(),
-                permission_mode: claude_cfg.permission_mode.clone(),
-                allowed_tools: claude_cfg.allowed_tools.clone(),
-            };
-
-            let (perm_tx, perm_rx) = mpsc::channel();
-            if let Err(err) = cmd_tx.send(WorkerCommand::PermissionPrompt {
-                id: worker_id,
-                request: permission_request.clone(),
-                respond_to: perm_tx,
-            }) {
-                send_log(
-                    format!("権限要求をエンキューできませんでした: {err}"),
-                    worker_id,
-                );
-                if let Ok(mut snapshot) = state.lock() {
-                    snapshot.status = WorkerStatus::Failed;
-                    snapshot.last_event = "権限要求の送信に失敗しました".into();
-                    snapshot.current_step = None;
-                    let _ = evt_tx.send(WorkerEvent::Updated(snapshot.clone()));
-                }
-                return;
-            }
-
-            if let Ok(mut snapshot) = state.lock() {
-                snapshot.last_event = format!("ステップ '{}' の権限確認を待機中", step.name);
-                let _ = evt_tx.send(WorkerEvent::Updated(snapshot.clone()));
-            }
-
-            let tools_label = describe_allowed_tools(permission_request.allowed_tools.as_ref());
-
-            send_log(
-                format!(
-                    "権限確認待ち (request #{}, tools: {})",
-                    permission_request.request_id, tools_label
-                ),
-                worker_id,
-            );
-
-            let (effective_permission_mode, effective_allowed_tools) = match perm_rx.recv() {
-                Ok(PermissionDecision::Allow {
-                    permission_mode,
-                    allowed_tools,
-                }) => {
-                    send_log("権限が承認されました".to_string(), worker_id);
-                    (permission_mode, allowed_tools)
-                }
-                Ok(PermissionDecision::Deny) => {
-                    send_log(
-                        "権限が拒否されました。ステップを中断します".to_string(),
-                        worker_id,
-                    );
-                    if let Ok(mut snapshot) = state.lock() {
-                        snapshot.status = WorkerStatus::Paused;
-                        snapshot.last_event =
-                            format!("ステップ '{}' の権限が拒否されました", step.name);
-                        snapshot.current_step = None;
-                        let _ = evt_tx.send(WorkerEvent::Updated(snapshot.clone()));
-                    }
-                    return;
-                }
-                Err(_err) => {
-                    send_log(
-                        "権限確認中に内部エラーが発生したため中断します".to_string(),
-                        worker_id,
-                    );
-                    if let Ok(mut snapshot) = state.lock() {
-                        snapshot.status = WorkerStatus::Failed;
-                        snapshot.last_event = "権限確認中にエラーが発生".into();
-                        snapshot.current_step = None;
-                        let _ = evt_tx.send(WorkerEvent::Updated(snapshot.clone()));
-                    }
-                    return;
-                }
-            };
-
-            let prompt = render_prompt(&claude_cfg.prompt, &snapshot_info);
-
-            // Prompt section
-            send_log("[PROMPT_START]".to_string(), worker_id);
-            send_log("─── Prompt ───".to_string(), worker_id);
-            for line in prompt.lines() {
-                send_log(line.to_string(), worker_id);
-            }
-            send_log("[PROMPT_END]".to_string(), worker_id);
-
-            // Command details section
-            send_log("─── Claude Code コマンド ───".to_string(), worker_id);
-
-            // Permission Mode
-            let effective_mode = claude_cfg
-                .permission_mode
-                .as_deref()
-                .unwrap_or("bypassPermissions");
-            let permission_mode_str = match effective_mode {
-                "plan" => "プランモード (plan)".to_string(),
-                "acceptEdits" => "編集承認モード (acceptEdits)".to_string(),
-                "bypassPermissions" => "制限なしモード (bypassPermissions)".to_string(),
-                other => format!("{}", other),
-            };
-            send_log(
-                format!("Permission Mode: {}", permission_mode_str),
-                worker_id,
-            );
-
-            // Override claude_cfg with user-selected permissions
-            let mut claude_cfg_with_permissions = claude_cfg.clone();
-            claude_cfg_with_permissions.permission_mode = effective_permission_mode.or_else(|| claude_cfg.permission_mode.clone());
-            claude_cfg_with_permissions.allowed_tools = effective_allowed_tools.or_else(|| claude_cfg.allowed_tools.clone());
-
-            // Model
-            let model_str = claude_cfg_with_permissions.model.as_deref().unwrap_or("デフォルト");
-            send_log(format!("Model: {}", model_str), worker_id);
-
-            // Allowed Tools
-            let tools_str = if let Some(tools) = &claude_cfg_with_permissions.allowed_tools {
-                if tools.is_empty() {
-                    "なし".to_string()
-                } else {
-                    tools.join(", ")
-                }
-            } else {
-                "全て".to_string()
-            };
-            send_log(format!("Allowed Tools: {}", tools_str), worker_id);
-
-            // Session
-            let current_session_id = snapshot_info.session_id.as_deref();
-            let session_str = if current_session_id.is_some() {
-                "継続"
-            } else {
-                "新規"
-            };
-            send_log(format!("Session: {}", session_str), worker_id);
-
-            // Extra Args
-            if let Some(extra) = &claude_cfg_with_permissions.extra_args {
-                if !extra.is_empty() {
-                    send_log(format!("Extra Args: {}", extra.join(" ")), worker_id);
-                }
-            }
-
-            // Update status to show execution in progress
-            if let Ok(mut snapshot) = state.lock() {
-                snapshot.last_event = "Claude Codeを実行中... ⏳".into();
-                let _ = evt_tx.send(WorkerEvent::Updated(snapshot.clone()));
-            }
-
-            send_log("".to_string(), worker_id);
-            send_log("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━".to_string(), worker_id);
-            send_log("⏳ Claude Code実行中...".to_string(), worker_id);
-            send_log("   出力は実行完了後に表示されます".to_string(), worker_id);
-            send_log("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━".to_string(), worker_id);
-            send_log("".to_string(), worker_id);
-
-            // Pass current session_id to continue the session
-            let result = run_claude_command(
-                &claude_cfg_with_permissions,
-                &prompt,
-                &worktree_path,
-                current_session_id,
-                default_sandbox_mode,
-                |line| send_log(line, worker_id),
-            );
-
-            send_log("".to_string(), worker_id);
-            send_log("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━".to_string(), worker_id);
-            send_log("✅ Claude Code実行完了".to_string(), worker_id);
-            send_log("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━".to_string(), worker_id);
-            send_log("".to_string(), worker_id);
-
-            // Store the session_id and session_history
-            if let Ok((new_session_id, session_history)) = &result {
-                // Update session_id in snapshot
-                if let Some(sid) = new_session_id {
-                    if let Ok(mut snapshot) = state.lock() {
-                        snapshot.session_id = Some(sid.clone());
-                        let _ = evt_tx.send(WorkerEvent::Updated(snapshot.clone()));
-                    }
-                }
-
-                // Add session history
-                if let Ok(mut histories) = session_histories.lock() {
-                    histories.push(session_history.clone());
-                }
-            }
-
-            result.map(|_| vec![])
-        } else if let Some(command) = &step.command {
-            send_log(format!("$ {}", command), worker_id);
-            run_shell_command(command, &worktree_path)
-        } else {
-            Ok(vec!["(no-op step)".into()])
-        };
-
-        // Result section
-        send_log("[RESULT_START]".to_string(), worker_id);
-
-        match result {
-            Ok(lines) => {
-                for line in lines {
-                    send_log(line, worker_id);
-                }
-                send_log("[RESULT_END]".to_string(), worker_id);
-                // Step end marker (success)
-                send_log("[STEP_END:Success]".to_string(), worker_id);
-
-                // Increment completed steps
-                completed_steps.fetch_add(1, Ordering::SeqCst);
-
-                // Persist the worker state to disk
-                let _ = cmd_tx.send(WorkerCommand::Persist { id: worker_id });
-            }
-            Err(err) => {
-                send_log(format!("Error: {err}"), worker_id);
-                send_log("[RESULT_END]".to_string(), worker_id);
-                // Step end marker (failed)
-                send_log("[STEP_END:Failed]".to_string(), worker_id);
-
-                if let Ok(mut snapshot) = state.lock() {
-                    snapshot.status = WorkerStatus::Failed;
-                    snapshot.last_event = format!("Command failed: {err}");
-                    snapshot.current_step =
-                        Some(format!("{}/{}: {}", idx + 1, total_steps, step.name));
-                    let _ = evt_tx.send(WorkerEvent::Updated(snapshot.clone()));
-                }
-                let _ = evt_tx.send(WorkerEvent::Error {
-                    id: Some(worker_id),
-                    message: err.to_string(),
-                });
-                return;
-            }
-        }
-
-        thread::sleep(Duration::from_millis(400));
-    }
-
-    if let Ok(mut snapshot) = state.lock() {
-        snapshot.status = WorkerStatus::Idle;
-        snapshot.last_event = format!("ワークフロー '{}' が完了", workflow.name);
-        snapshot.current_step = None;
-        let _ = evt_tx.send(WorkerEvent::Updated(snapshot.clone()));
-    }
-
-    send_log(format!("Workflow '{}' completed", workflow.name), worker_id);
-}
-
-fn run_shell_command(command: &str, dir: &Path) -> Result<Vec<String>> {
-    let output = Command::new("bash")
-        .arg("-lc")
-        .arg(command)
-        .current_dir(dir)
-        .stdin(Stdio::null())
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
-        .output()
-        .with_context(|| format!("failed to execute shell command '{command}'"))?;
-
-    let mut lines = Vec::new();
-
-    // Process stdout
-    let stdout_str = String::from_utf8_lossy(&output.stdout);
-    for line in stdout_str.lines() {
-        if !line.trim().is_empty() {
-            lines.push(line.to_string());
-        }
-    }
-
-    // Process stderr
-    let stderr_str = String::from_utf8_lossy(&output.stderr);
-    for line in stderr_str.lines() {
-        if !line.trim().is_empty() {
-            lines.push(line.to_string());
-        }
-    }
-
-    if !output.status.success() {
-        return Err(anyhow!(
-            "command '{command}' exited with status {}",
-            output.status
-        ));
-    }
-
-    Ok(lines)
-}
-
-fn run_claude_command<F>(
-    step: &ClaudeStep,
-    prompt: &str,
-    dir: &Path,
-    session_id: Option<&str>,
-    default_sandbox_mode: bool,
-    mut log_fn: F,
-) -> Result<(Option<String>, SessionHistory)>
-where
-    F: FnMut(String),
-{
-    let start_time = OffsetDateTime::now_utc();
-    let binary = env::var("GENSUI_CLAUDE_BIN").unwrap_or_else(|_| {
-        // Try to find claude in common locations
-        let home = env::var("HOME").unwrap_or_else(|_| ".".to_string());
-        let local_claude = format!("{}/.claude/local/claude", home);
-        if Path::new(&local_claude).exists() {
-            local_claude
-        } else {
-            "claude".to_string()
-        }
-    });
-
-    // Initialize PTY system
-    let pty_system = native_pty_system();
-    let pty_size = PtySize {
-        rows: 24,
-        cols: 120,
-        pixel_width: 0,
-        pixel_height: 0,
-    };
-    let pty_pair = pty_system.openpty(pty_size)
-        .with_context(|| "failed to open PTY")?;
-
-    // Build command using CommandBuilder
-    let mut cmd = CommandBuilder::new(&binary);
-    cmd.cwd(dir);
-
-    // Set environment variables
-    cmd.env("TERM", "xterm-256color");
-
-    // Only set custom CLAUDE_CONFIG_DIR if GENSUI_CLAUDE_HOME is explicitly set
-    // Otherwise, use the user's default Claude configuration (including API keys)
-    if let Ok(custom_home) = env::var("GENSUI_CLAUDE_HOME") {
-        let claude_home = PathBuf::from(custom_home);
-        fs::create_dir_all(&claude_home).with_context(|| {
-            format!(
-                "failed to prepare Claude config directory at {}",
-                claude_home.display()
-            )
-        })?;
-        cmd.env("CLAUDE_CONFIG_DIR", claude_home.to_string_lossy().to_string());
-    }
-
-    // Build arguments
-    let mut args = vec!["--print".to_string(), prompt.to_string()];
-    args.push("--output-format".to_string());
-    args.push("stream-json".to_string());
-    args.push("--verbose".to_string());
-
-    // Continue existing session if session_id is provided
-    if session_id.is_some() {
-        args.push("--continue".to_string());
-    }
-
-    if let Some(model) = &step.model {
-        args.push("--model".to_string());
-        args.push(model.clone());
-    }
-
-    // If permission_mode is not set, use bypassPermissions by default
-    // This allows Claude to execute tools freely after user approves the step
-    let effective_mode = step
-        .permission_mode
-        .as_deref()
-        .unwrap_or("bypassPermissions");
-    args.push("--permission-mode".to_string());
-    args.push(effective_mode.to_string());
-
-    if let Some(tools) = &step.allowed_tools {
-        if !tools.is_empty() {
-            args.push("--allowedTools".to_string());
-            args.push(tools.join(","));
-        }
-    }
-
-    if let Some(extra) = &step.extra_args {
-        for arg in extra {
-            let replaced = arg
-                .replace("{{prompt}}", prompt)
-                .replace("{{workdir}}", &dir.to_string_lossy());
-            args.push(replaced);
-        }
-    }
-
-    // Sandboxing is controlled via .claude/settings.json
-    // Claude CLI automatically loads this file from the working directory
-    // The default_sandbox_mode and step.sandbox_mode settings are kept for
-    // future extensibility and documentation purposes, but currently have no effect
-    // on the CLI arguments (sandboxing is configured through settings file)
-    let _sandbox_enabled = step.sandbox_mode.unwrap_or(default_sandbox_mode);
-
-    cmd.args(args);
-
-    // Spawn command through PTY
-    let mut child = pty_pair.slave.spawn_command(cmd)
-        .with_context(|| "failed to spawn Claude Code process in PTY")?;
-
-    let mut extracted_session_id: Option<String> = None;
-    let mut session_events: Vec<SessionEvent> = Vec::new();
-    let mut total_tool_uses = 0;
-    let mut files_modified: Vec<String> = Vec::new();
-
-    // Read from PTY master in a separate thread
-    let mut reader = pty_pair.master.try_clone_reader()
-        .with_context(|| "failed to clone PTY reader")?;
-
-    let reader_thread = thread::spawn(move || {
-        let mut buffer = Vec::new();
-        let mut read_buf = [0u8; 8192];
-
-        loop {
-            match reader.read(&mut read_buf) {
-                Ok(0) => break, // EOF
-                Ok(n) => {
-                    buffer.extend_from_slice(&read_buf[..n]);
-                }
-                Err(_) => break,
-            }
-        }
-
-        buffer
-    });
-
-    // Process the output after child completes
-    let exit_status = child.wait()
-        .with_context(|| "failed to wait for Claude Code process")?;
-
-    // Get all output from the reader thread
-    let output_bytes = reader_thread.join()
-        .map_err(|_| anyhow!("PTY reader thread panicked"))?;
-
-    let output = String::from_utf8_lossy(&output_bytes);
-
-    // Process output line by line
-    for line in output.lines() {
-        let line = line.trim_end();
-
-        if line.is_empty() {
-            continue;
-        }
-
-        // Try to parse as JSON
-        if let Ok(json) = serde_json::from_str::<serde_json::Value>(line) {
-                let event_time = OffsetDateTime::now_utc().format(&time::format_description::well_known::Rfc3339)
-                    .unwrap_or_else(|_| "unknown".to_string());
-
-                // Extract thinking/analysis
-                let thought_lines = extract_thinking_lines(&json);
-                if !thought_lines.is_empty() {
-                    log_fn("[THOUGHT_START]".to_string());
-                    let mut thinking_content = String::new();
-                    for thought_line in thought_lines {
-                        log_fn(thought_line.clone());
-                        thinking_content.push_str(&thought_line);
-                        thinking_content.push('\n');
-                    }
-                    log_fn("[THOUGHT_END]".to_string());
-
-                    if !thinking_content.is_empty() {
-                        session_events.push(SessionEvent::ThinkingBlock {
-                            content: thinking_content.trim().to_string(),
-                            timestamp: event_time.clone(),
-                        });
-                    }
-                }
-
-                match json.get("type").and_then(|v| v.as_str()) {
-                    Some("system") => {
-                        // Session initialization - extract session_id but don't log
-                        if let Some(sid) = json.get("session_id").and_then(|v| v.as_str()) {
-                            extracted_session_id = Some(sid.to_string());
-                        }
-                    }
-                    Some("result") => {
-                        let is_error = json.get("is_error").and_then(|v| v.as_bool()).unwrap_or(false);
-
-                        // Check for errors first
-                        if is_error {
-                            log_fn("⚠️  Claude encountered an error".to_string());
-                        }
-
-                        // Final result
-                        if let Some(result_text) = json.get("result").and_then(|v| v.as_str()) {
-                            if !result_text.is_empty() {
-                                log_fn("─── Result ───".to_string());
-                                for result_line in result_text.lines() {
-                                    log_fn(result_line.to_string());
-                                }
-
-                                session_events.push(SessionEvent::Result {
-                                    text: result_text.to_string(),
-                                    is_error,
-                                    timestamp: event_time.clone(),
-                                });
-                            }
-                        }
-                    }
-                    Some("error") => {
-                        // API error response
-                        log_fn("❌ API Error:".to_string());
-                        let mut error_message = String::new();
-                        if let Some(error_obj) = json.get("error") {
-                            if let Some(message) = error_obj.get("message").and_then(|v| v.as_str()) {
-                                log_fn(format!("  {}", message));
-                                error_message = message.to_string();
-                            }
-                        }
-
-                        session_events.push(SessionEvent::Error {
-                            message: error_message,
-                            timestamp: event_time.clone(),
-                        });
-                    }
-                    Some("assistant") => {
-                        // Log assistant messages
-                        if let Some(text) = json.get("text").and_then(|v| v.as_str()) {
-                            if !text.trim().is_empty() {
-                                log_fn(format!("💬 {}", text));
-
-                                session_events.push(SessionEvent::AssistantMessage {
-                                    text: text.to_string(),
-                                    timestamp: event_time.clone(),
-                                });
-                            }
-                        }
-                    }
-                    Some("tool_use") => {
-                        // Log tool usage
-                        if let Some(tool_name) = json.get("name").and_then(|v| v.as_str()) {
-                            log_fn(format!("🔧 Using tool: {}", tool_name));
-
-                            total_tool_uses += 1;
-
-                            // Extract file path for file modification tools
-                            if matches!(tool_name, "Edit" | "Write") {
-                                if let Some(input) = json.get("input") {
-                                    if let Some(file_path) = input.get("file_path").and_then(|v| v.as_str()) {
-                                        if !files_modified.contains(&file_path.to_string()) {
-                                            files_modified.push(file_path.to_string());
-                                        }
-                                    }
-                                }
-                            }
-
-                            session_events.push(SessionEvent::ToolUse {
-                                name: tool_name.to_string(),
-                                timestamp: event_time.clone(),
-                                input: json.get("input").cloned(),
-                            });
-                        }
-                    }
-                    Some("tool_result") => {
-                        if let Some(tool_name) = json.get("tool_use_id").and_then(|v| v.as_str()) {
-                            let output = json.get("content")
-                                .and_then(|v| v.as_str())
-                                .map(|s| s.to_string());
-
-                            session_events.push(SessionEvent::ToolResult {
-                                name: tool_name.to_string(),
-                                timestamp: event_time.clone(),
-                                output,
-                            });
-                        }
-                    }
-                    _ => {
-                        // Log raw JSON for other event types for debugging
-                        // log_fn(format!("🔍 JSON: {}", line));
-                    }
-                }
-        } else {
-            // Non-JSON output
-            log_fn(line.to_string());
-        }
-    }
-
-    let end_time = OffsetDateTime::now_utc();
-
-    let exit_code = exit_status.exit_code();
-    if exit_code != 0 {
-        return Err(anyhow!("Claude CLI exited with status {}", exit_code));
-    }
-
-    // Build session history
-    let session_history = SessionHistory {
-        session_id: extracted_session_id.clone().unwrap_or_else(|| "unknown".to_string()),
-        started_at: start_time.format(&time::format_description::well_known::Rfc3339)
-            .unwrap_or_else(|_| "unknown".to_string()),
-        ended_at: Some(end_time.format(&time::format_description::well_known::Rfc3339)
-            .unwrap_or_else(|_| "unknown".to_string())),
-        prompt: prompt.to_string(),
-        events: session_events,
-        total_tool_uses,
-        files_modified,
-    };
-
-    Ok((extracted_session_id, session_history))
-}
-
-fn extract_thinking_lines(json: &serde_json::Value) -> Vec<String> {
-    fn walk(value: &serde_json::Value, acc: &mut Vec<String>, in_thinking: bool) {
-        match value {
-            serde_json::Value::String(text) => {
-                if in_thinking {
-                    for line in text.lines() {
-                        let trimmed = line.trim_end();
-                        if trimmed.is_empty() {
-                            continue;
-                        }
-                        acc.push(trimmed.to_string());
-                    }
-                }
-            }
-            serde_json::Value::Array(items) => {
-                for item in items {
-                    walk(item, acc, in_thinking);
-                }
-            }
-            serde_json::Value::Object(map) => {
-                let mut next_flag = in_thinking;
-                if let Some(ty) = map.get("type").and_then(|v| v.as_str()) {
-                    let ty_lc = ty.to_ascii_lowercase();
-                    if matches!(
-                        ty_lc.as_str(),
-                        "thinking" | "analysis" | "plan" | "reasoning"
-                    ) {
-                        next_flag = true;
-                    }
-                }
-
-                if let Some(thinking) = map.get("thinking") {
-                    walk(thinking, acc, true);
-                }
-
-                if let Some(text) = map.get("text") {
-                    walk(text, acc, next_flag);
-                }
-
-                if let Some(content) = map.get("content") {
-                    walk(content, acc, next_flag);
-                }
-
-                if let Some(message) = map.get("message") {
-                    walk(message, acc, next_flag);
-                }
-
-                for (key, value) in map {
-                    if matches!(key.as_str(), "thinking" | "analysis" | "reasoning" | "plan") {
-                        walk(value, acc, true);
-                    } else if !matches!(key.as_str(), "text" | "content" | "message") {
-                        walk(value, acc, next_flag);
-                    }
-                }
-            }
-            _ => {}
-        }
-    }
-
-    let mut acc = Vec::new();
-    walk(json, &mut acc, false);
-    acc
-}
-
-fn render_prompt(template: &str, snapshot: &WorkerSnapshot) -> String {
-    template
-        .replace(
-            "{{issue}}",
-            snapshot.issue.as_deref().unwrap_or("(no issue)"),
-        )
-        .replace("{{worker}}", snapshot.name.as_str())
-        .replace("{{branch}}", snapshot.branch.as_str())
-        .replace("{{worktree}}", snapshot.worktree.as_str())
-}
-
-fn describe_allowed_tools(tools: Option<&Vec<String>>) -> String {
-    match tools {
-        None => "制限なし".to_string(),
-        Some(list) if list.is_empty() => "なし".to_string(),
-        Some(list) => list.join(", "),
-    }
-}
-
-fn determine_base_ref(repo_root: &Path) -> Option<String> {
+pub fn determine_base_ref(repo_root: &Path) -> Option<String> {
     let output = Command::new("git")
         .args(["rev-parse", "--abbrev-ref", "HEAD"])
         .current_dir(repo_root)
@@ -1963,3 +2629,309 @@ pub fn list_existing_worktrees(repo_root: &Path) -> Result<Vec<ExistingWorktree>
 
     Ok(worktrees)
 }
+
+/// Local branches and tags, for completing the base ref of a new worker.
+pub fn list_local_refs(repo_root: &Path) -> Result<Vec<String>> {
+    let output = Command::new("git")
+        .args(["for-each-ref", "--format=%(refname:short)", "refs/heads", "refs/tags"])
+        .current_dir(repo_root)
+        .stderr(Stdio::piped())
+        .stdout(Stdio::piped())
+        .output()
+        .context("failed to execute git for-each-ref")?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(anyhow!("git for-each-ref failed: {}", stderr.trim()));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::to_string)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::GenericAgentConfig;
+    use crate::worker::git::fixture::{init_repo, run_git};
+
+    #[test]
+    fn test_queue_starts_workers_by_priority_within_limit() {
+        let repo = std::env::temp_dir().join(format!("gensui-queue-{}", std::process::id()));
+        init_repo(&repo);
+        run_git(&repo, &["commit", "-q", "--allow-empty", "-m", "init"]);
+
+        let config = Config {
+            workflows: vec![Workflow {
+                name: "wait".to_string(),
+                description: None,
+                steps: vec![WorkflowStep {
+                    name: "sleep".to_string(),
+                    command: Some("sleep 0.5".to_string()),
+                    ..Default::default()
+                }],
+                extends: None,
+            }],
+            max_concurrent_workers: Some(1),
+            ..Config::default()
+        };
+        let (handle, events) = spawn_worker_system(repo.clone(), config).unwrap();
+        for (name, priority) in [("first", 0), ("low", 0), ("high", 5)] {
+            handle
+                .create_worker(CreateWorkerRequest {
+                    name: Some(name.to_string()),
+                    workflow: Some("wait".to_string()),
+                    priority,
+                    ..Default::default()
+                })
+                .unwrap();
+        }
+
+        let mut started = Vec::new();
+        let mut positions = HashMap::new();
+        let mut finished = 0;
+        while finished < 3 {
+            match events.recv_timeout(Duration::from_secs(10)).unwrap() {
+                WorkerEvent::Created(snapshot) | WorkerEvent::Updated(snapshot) => {
+                    match snapshot.status {
+                        WorkerStatus::Running if !started.contains(&snapshot.name) => {
+                            started.push(snapshot.name);
+                        }
+                        WorkerStatus::Queued => {
+                            if let Some(position) = snapshot.queue_position {
+                                positions
+                                    .entry(snapshot.name)
+                                    .or_insert_with(Vec::new)
+                                    .push(position);
+                            }
+                        }
+                        WorkerStatus::Idle => finished += 1,
+                        _ => {}
+                    }
+                }
+                WorkerEvent::Error { message, .. } => panic!("{message}"),
+                _ => {}
+            }
+        }
+
+        assert_eq!(started, vec!["first", "high", "low"]);
+        // The high priority worker jumps ahead of the one already waiting
+        assert_eq!(positions["low"], vec![1, 2, 1]);
+        assert_eq!(positions["high"], vec![1]);
+
+        handle.shutdown(Duration::from_secs(5)).unwrap();
+        let _ = fs::remove_dir_all(&repo);
+    }
+
+    #[test]
+    fn test_queued_worker_starts_when_the_running_one_finishes() {
+        let repo = std::env::temp_dir().join(format!("gensui-slot-{}", std::process::id()));
+        init_repo(&repo);
+        run_git(&repo, &["commit", "-q", "--allow-empty", "-m", "init"]);
+
+        let config = Config {
+            workflows: vec![Workflow {
+                name: "quick".to_string(),
+                description: None,
+                steps: vec![WorkflowStep {
+                    name: "true".to_string(),
+                    command: Some("true".to_string()),
+                    ..Default::default()
+                }],
+                extends: None,
+            }],
+            max_concurrent_workers: Some(1),
+            ..Config::default()
+        };
+        let (handle, events) = spawn_worker_system(repo.clone(), config).unwrap();
+        let workers = 2;
+        for _ in 0..workers {
+            handle
+                .create_worker(CreateWorkerRequest {
+                    workflow: Some("quick".to_string()),
+                    ..Default::default()
+                })
+                .unwrap();
+        }
+
+        // Only the agents finishing free the slot; nothing else is sent
+        let mut finished = 0;
+        while finished < workers {
+            match events.recv_timeout(Duration::from_secs(10)).unwrap() {
+                WorkerEvent::Updated(snapshot) if snapshot.status == WorkerStatus::Idle => {
+                    finished += 1;
+                }
+                WorkerEvent::Error { message, .. } => panic!("{message}"),
+                _ => {}
+            }
+        }
+
+        handle.shutdown(Duration::from_secs(5)).unwrap();
+        let _ = fs::remove_dir_all(&repo);
+    }
+
+    #[test]
+    fn test_create_uses_branch_template_and_base_ref() {
+        let repo = std::env::temp_dir().join(format!("gensui-branch-{}", std::process::id()));
+        init_repo(&repo);
+        run_git(&repo, &["commit", "-q", "--allow-empty", "-m", "init"]);
+        run_git(&repo, &["tag", "v1"]);
+        run_git(&repo, &["commit", "-q", "--allow-empty", "-m", "second"]);
+
+        let config = Config {
+            workflows: vec![Workflow {
+                name: "noop".to_string(),
+                description: None,
+                steps: vec![WorkflowStep {
+                    name: "true".to_string(),
+                    command: Some("true".to_string()),
+                    ..Default::default()
+                }],
+                extends: None,
+            }],
+            branch_template: Some("fix/{{issue}}-{{worker}}".to_string()),
+            ..Config::default()
+        };
+        let (handle, events) = spawn_worker_system(repo.clone(), config).unwrap();
+        let create = |name: &str, base_ref: &str| {
+            handle
+                .create_worker(CreateWorkerRequest {
+                    name: Some(name.to_string()),
+                    issue: Some("#42".to_string()),
+                    workflow: Some("noop".to_string()),
+                    base_ref: Some(base_ref.to_string()),
+                    ..Default::default()
+                })
+                .unwrap();
+        };
+        let next_outcome = || loop {
+            match events.recv_timeout(Duration::from_secs(10)).unwrap() {
+                WorkerEvent::Created(snapshot) => break Ok(snapshot),
+                WorkerEvent::Error { message, .. } => break Err(message),
+                _ => {}
+            }
+        };
+
+        create("login", "v1");
+        let snapshot = next_outcome().unwrap();
+        assert_eq!(snapshot.branch, "fix/42-login");
+        assert_eq!(snapshot.base_ref.as_deref(), Some("v1"));
+        assert_eq!(
+            run_git(&repo, &["rev-parse", "fix/42-login"]),
+            run_git(&repo, &["rev-parse", "v1^{commit}"])
+        );
+
+        create("other", "no-such-ref");
+        assert!(next_outcome().unwrap_err().contains("no-such-ref"));
+        run_git(&repo, &["branch", "fix/42-taken"]);
+        create("taken", "v1");
+        assert!(next_outcome().unwrap_err().contains("already exists"));
+
+        handle.shutdown(Duration::from_secs(5)).unwrap();
+        let _ = fs::remove_dir_all(&repo);
+    }
+
+    #[test]
+    fn test_create_with_unknown_agent_leaves_no_worktree() {
+        let repo = std::env::temp_dir().join(format!("gensui-agent-{}", std::process::id()));
+        init_repo(&repo);
+        run_git(&repo, &["commit", "-q", "--allow-empty", "-m", "init"]);
+        let branches = run_git(&repo, &["branch", "--list"]);
+        let worktrees = run_git(&repo, &["worktree", "list", "--porcelain"]);
+
+        let (handle, events) = spawn_worker_system(repo.clone(), Config::default()).unwrap();
+        handle
+            .create_worker(CreateWorkerRequest {
+                name: Some("login".to_string()),
+                agent: Some("no-such-agent".to_string()),
+                ..Default::default()
+            })
+            .unwrap();
+        let message = loop {
+            match events.recv_timeout(Duration::from_secs(10)).unwrap() {
+                WorkerEvent::Error { message, .. } => break message,
+                WorkerEvent::Created(snapshot) => panic!("created {}", snapshot.name),
+                _ => {}
+            }
+        };
+        assert!(message.contains("unknown agent 'no-such-agent'"), "{message}");
+        assert_eq!(run_git(&repo, &["branch", "--list"]), branches);
+        assert_eq!(run_git(&repo, &["worktree", "list", "--porcelain"]), worktrees);
+
+        handle.shutdown(Duration::from_secs(5)).unwrap();
+        let _ = fs::remove_dir_all(&repo);
+    }
+
+    #[test]
+    fn test_uncached_issue_is_fetched_before_the_agent_starts() {
+        let repo = std::env::temp_dir().join(format!("gensui-issue-{}", std::process::id()));
+        init_repo(&repo);
+        run_git(&repo, &["commit", "-q", "--allow-empty", "-m", "init"]);
+        let issues = repo.join(crate::issue::LOCAL_ISSUES_DIR);
+        fs::create_dir_all(&issues).unwrap();
+        fs::write(issues.join("12.md"), "# Fix login\n").unwrap();
+
+        let config = Config {
+            agents: vec![GenericAgentConfig {
+                name: "echo".to_string(),
+                command: "sh".to_string(),
+                args: vec!["-c".to_string(), "echo \"$0\"".to_string(), "{{prompt}}".to_string()],
+                ..Default::default()
+            }],
+            workflows: vec![Workflow {
+                name: "issue".to_string(),
+                description: None,
+                steps: vec![WorkflowStep {
+                    name: "work".to_string(),
+                    agent: Some("echo".to_string()),
+                    claude: Some(ClaudeStep {
+                        prompt: "Work on {{issue.title}}".to_string(),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                }],
+                extends: None,
+            }],
+            ..Config::default()
+        };
+        let (handle, events) = spawn_worker_system(repo.clone(), config).unwrap();
+        handle
+            .create_worker(CreateWorkerRequest {
+                issue: Some("#12".to_string()),
+                workflow: Some("issue".to_string()),
+                ..Default::default()
+            })
+            .unwrap();
+
+        let mut titles = Vec::new();
+        let mut lines = Vec::new();
+        loop {
+            match events.recv_timeout(Duration::from_secs(10)).unwrap() {
+                WorkerEvent::Updated(snapshot) => {
+                    titles.push(snapshot.issue_title.clone());
+                    if snapshot.status == WorkerStatus::Idle {
+                        break;
+                    }
+                    assert_ne!(snapshot.status, WorkerStatus::Failed, "{}", snapshot.last_event);
+                }
+                WorkerEvent::Log { line, .. } => lines.push(line),
+                WorkerEvent::PermissionRequested { id, request } => {
+                    let allow = PermissionDecision::Allow {
+                        permission_mode: None,
+                        allowed_tools: None,
+                    };
+                    handle.respond_permission(id, request.request_id, allow).unwrap();
+                }
+                WorkerEvent::Error { message, .. } => panic!("{message}"),
+                _ => {}
+            }
+        }
+        assert!(titles.contains(&Some("Fix login".to_string())));
+        assert!(lines.iter().any(|line| line.contains("Work on Fix login")), "{lines:?}");
+
+        handle.shutdown(Duration::from_secs(5)).unwrap();
+        let _ = fs::remove_dir_all(&repo);
+    }
+}
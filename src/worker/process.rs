@@ -0,0 +1,240 @@
+//! ワーカーが起動したプロセスグループの管理
+
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How long a process group gets to exit after SIGTERM before it is killed.
+pub const KILL_GRACE_PERIOD: Duration = Duration::from_secs(3);
+
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Tracks the process groups spawned on behalf of a worker so they can be
+/// terminated when the worker is cancelled.
+///
+/// Every child is expected to lead its own process group (PTY children call
+/// `setsid`, shell commands are spawned with `process_group(0)`), so signalling
+/// the group reaches the whole tree the agent or command started.
+#[derive(Clone, Default)]
+pub struct ProcessTracker {
+    groups: Arc<Mutex<HashSet<i32>>>,
+    /// Group sets of the trackers this one was scoped from. Registrations are
+    /// mirrored there so cancelling the worker still reaches every group.
+    parents: Vec<Arc<Mutex<HashSet<i32>>>>,
+    terminated: Arc<AtomicBool>,
+    suspended: Arc<AtomicBool>,
+}
+
+impl ProcessTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Derive a tracker whose `kill_running` only affects groups registered
+    /// through it, while `terminate_all`/`suspend_all` on this tracker still
+    /// reach them. Used to time out one of several concurrent steps.
+    pub fn scoped(&self) -> Self {
+        let mut parents = self.parents.clone();
+        parents.push(Arc::clone(&self.groups));
+        Self {
+            groups: Arc::default(),
+            parents,
+            terminated: Arc::clone(&self.terminated),
+            suspended: Arc::clone(&self.suspended),
+        }
+    }
+
+    /// Register a process group leader. The group is forgotten when the
+    /// returned guard is dropped.
+    ///
+    /// Groups registered after `terminate_all` are killed immediately, which
+    /// closes the race with a child spawned while the worker was cancelled.
+    /// Likewise, groups registered while suspended are stopped right away.
+    pub fn register(&self, pid: u32) -> ProcessGuard {
+        let pgid = pid as i32;
+        for set in self.sets() {
+            if let Ok(mut groups) = set.lock() {
+                groups.insert(pgid);
+            }
+        }
+        if self.terminated.load(Ordering::SeqCst) {
+            signal_group(pgid, libc::SIGKILL);
+        } else if self.suspended.load(Ordering::SeqCst) {
+            signal_group(pgid, libc::SIGSTOP);
+        }
+        ProcessGuard {
+            tracker: self.clone(),
+            pgid,
+        }
+    }
+
+    fn unregister(&self, pgid: i32) {
+        for set in self.sets() {
+            if let Ok(mut groups) = set.lock() {
+                groups.remove(&pgid);
+            }
+        }
+    }
+
+    fn sets(&self) -> impl Iterator<Item = &Arc<Mutex<HashSet<i32>>>> {
+        std::iter::once(&self.groups).chain(&self.parents)
+    }
+
+    fn tracked(&self) -> Vec<i32> {
+        self.groups
+            .lock()
+            .map(|groups| groups.iter().copied().collect())
+            .unwrap_or_default()
+    }
+
+    /// Send SIGTERM to every tracked group, then SIGKILL to groups still
+    /// alive after `grace`. Groups registered afterwards are killed as well.
+    pub fn terminate_all(&self, grace: Duration) {
+        self.terminated.store(true, Ordering::SeqCst);
+        self.kill_running(grace);
+    }
+
+    /// Like `terminate_all`, but later registrations are left alone. Used to
+    /// abort a single attempt (e.g. on timeout) without cancelling the worker.
+    pub fn kill_running(&self, grace: Duration) {
+        let groups = self.tracked();
+        if groups.is_empty() {
+            return;
+        }
+
+        for &pgid in &groups {
+            signal_group(pgid, libc::SIGTERM);
+            // A stopped group only acts on SIGTERM once it is continued
+            signal_group(pgid, libc::SIGCONT);
+        }
+
+        let deadline = Instant::now() + grace;
+        let mut alive: Vec<i32> = groups;
+        while !alive.is_empty() && Instant::now() < deadline {
+            thread::sleep(POLL_INTERVAL);
+            // Groups whose leader was reaped are unregistered by their guard
+            let tracked = self.tracked();
+            alive.retain(|pgid| tracked.contains(pgid) && group_alive(*pgid));
+        }
+
+        for pgid in alive {
+            signal_group(pgid, libc::SIGKILL);
+        }
+    }
+
+    /// Stop every tracked group with SIGSTOP.
+    pub fn suspend_all(&self) {
+        self.suspended.store(true, Ordering::SeqCst);
+        for pgid in self.tracked() {
+            signal_group(pgid, libc::SIGSTOP);
+        }
+    }
+
+    /// Continue every tracked group with SIGCONT.
+    pub fn resume_all(&self) {
+        self.suspended.store(false, Ordering::SeqCst);
+        for pgid in self.tracked() {
+            signal_group(pgid, libc::SIGCONT);
+        }
+    }
+}
+
+/// Removes a process group from its tracker when dropped.
+pub struct ProcessGuard {
+    tracker: ProcessTracker,
+    pgid: i32,
+}
+
+impl Drop for ProcessGuard {
+    fn drop(&mut self) {
+        self.tracker.unregister(self.pgid);
+    }
+}
+
+fn signal_group(pgid: i32, signal: i32) {
+    if pgid <= 0 {
+        return;
+    }
+    // SAFETY: kill(2) with a negative pid only signals the given process group.
+    unsafe {
+        libc::kill(-pgid, signal);
+    }
+}
+
+fn group_alive(pgid: i32) -> bool {
+    // SAFETY: signal 0 performs permission and existence checks only.
+    unsafe { libc::kill(-pgid, 0) == 0 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::process::CommandExt;
+    use std::process::Command;
+
+    #[test]
+    fn test_terminate_all_kills_process_group() {
+        let tracker = ProcessTracker::new();
+        // The trap makes the shell ignore SIGTERM so the SIGKILL fallback is exercised
+        let mut child = Command::new("sh")
+            .args(["-c", "trap '' TERM; sleep 30 & wait"])
+            .process_group(0)
+            .spawn()
+            .unwrap();
+        let _guard = tracker.register(child.id());
+
+        let started = Instant::now();
+        tracker.terminate_all(Duration::from_millis(200));
+        let status = child.wait().unwrap();
+
+        assert!(!status.success());
+        assert!(started.elapsed() < Duration::from_secs(10));
+    }
+
+    #[test]
+    fn test_suspend_and_resume_process_group() {
+        let tracker = ProcessTracker::new();
+        let mut child = Command::new("sh")
+            .args(["-c", "sleep 0.3"])
+            .process_group(0)
+            .spawn()
+            .unwrap();
+        let _guard = tracker.register(child.id());
+
+        tracker.suspend_all();
+        thread::sleep(Duration::from_millis(600));
+        // Still stopped, so it cannot have exited yet
+        assert!(child.try_wait().unwrap().is_none());
+
+        tracker.resume_all();
+        let status = child.wait().unwrap();
+        assert!(status.success());
+    }
+
+    #[test]
+    fn test_scoped_tracker_mirrors_registrations() {
+        let tracker = ProcessTracker::new();
+        let scoped = tracker.scoped();
+        let sibling = tracker.scoped();
+        {
+            let _guard = scoped.register(23456);
+            assert_eq!(scoped.tracked(), vec![23456]);
+            assert_eq!(tracker.tracked(), vec![23456]);
+            assert!(sibling.tracked().is_empty());
+        }
+        assert!(scoped.tracked().is_empty());
+        assert!(tracker.tracked().is_empty());
+    }
+
+    #[test]
+    fn test_guard_unregisters_on_drop() {
+        let tracker = ProcessTracker::new();
+        {
+            let _guard = tracker.register(12345);
+            assert_eq!(tracker.tracked(), vec![12345]);
+        }
+        assert!(tracker.tracked().is_empty());
+    }
+}
@@ -0,0 +1,572 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::sync::LazyLock;
+
+use regex::{Captures, Regex};
+
+use anyhow::{Result, bail};
+
+use crate::config::{MergeStep, PullRequestStep, Workflow};
+use crate::forge::PullRequest;
+use crate::issue::Issue;
+use crate::state::StepOutcome;
+
+use super::WorkerSnapshot;
+use super::merge::MergeRequest;
+
+/// Upper bound for a single substituted value. Prompts are passed as a
+/// command-line argument, which Linux limits to 128 KiB.
+const MAX_VALUE_BYTES: usize = 64 * 1024;
+
+static STEP_PLACEHOLDER: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\{\{\s*steps\.([^{}]+?)\.(result|stdout|exit_code|commit)\s*\}\}")
+        .expect("step placeholder pattern must compile")
+});
+
+const GIT_DIFF_PLACEHOLDER: &str = "{{git.diff}}";
+
+const DEFAULT_PR_TITLE: &str = "{{issue.title}}";
+const DEFAULT_PR_TITLE_WITHOUT_ISSUE: &str = "{{worker}}";
+const DEFAULT_PR_BODY: &str = "Closes {{issue}}\n\n{{session.result}}";
+const DEFAULT_PR_BODY_WITHOUT_ISSUE: &str = "{{session.result}}";
+
+/// Prompt asking the agent of a `commit` step with `generate_message` for a message.
+pub(super) const COMMIT_MESSAGE_PROMPT: &str = "次の変更に対する git のコミットメッセージを書いてください。\
+\n1行目は72文字以内の要約、空行を挟んで変更の理由と内容を説明してください。\
+\nコミットメッセージだけを出力し、ファイルは変更しないでください。";
+
+/// Prompt of the conflict resolution when a `merge` step does not set one.
+pub(super) const DEFAULT_RESOLVE_PROMPT: &str = "ブランチ {{branch}} に取り込み先ブランチをマージした結果、コンフリクトが発生しました。\
+\n各ファイルのコンフリクトマーカーを解消し、両方の変更の意図を保った内容にしてください。\
+\n解消後のステージングとコミットは gensui が行います。";
+
+/// Values available to `{{...}}` placeholders in step prompts.
+pub(super) struct TemplateContext<'a> {
+    pub snapshot: &'a WorkerSnapshot,
+    pub workflow: &'a Workflow,
+    /// Latest outcome per step key for the current run.
+    pub outcomes: &'a HashMap<String, StepOutcome>,
+    pub worktree_path: &'a Path,
+    /// Metadata of the worker's issue, when the issue provider knows it.
+    pub issue: Option<&'a Issue>,
+    /// Final result of the worker's most recent agent session.
+    pub session_result: Option<&'a str>,
+}
+
+impl TemplateContext<'_> {
+    fn outcome(&self, step: &str) -> Option<&StepOutcome> {
+        self.outcomes.get(step).or_else(|| {
+            let idx = self.workflow.step_index(step)?;
+            self.outcomes.get(self.workflow.steps()[idx].key())
+        })
+    }
+
+    fn step_value(&self, step: &str, field: &str) -> String {
+        let Some(outcome) = self.outcome(step) else {
+            return String::new();
+        };
+        match field {
+            "result" => truncate_head(outcome.result.as_deref().unwrap_or_default()),
+            // The end of a command's output usually holds the failure summary
+            "stdout" => truncate_tail(&outcome.stdout),
+            "exit_code" => outcome
+                .exit_code
+                .map(|code| code.to_string())
+                .unwrap_or_default(),
+            "commit" => outcome.commit.clone().unwrap_or_default(),
+            _ => String::new(),
+        }
+    }
+}
+
+/// Substitute worker, issue, step output, session and git placeholders in a
+/// prompt template.
+///
+/// Steps that have not run yet render as an empty string, as do the issue
+/// body, labels, acceptance criteria and comments when no issue metadata is
+/// available, and `{{session.result}}` before any agent session finished.
+/// `{{issue.title}}` then falls back to the issue reference itself. `{{git.diff}}`
+/// holds the uncommitted changes in the worktree and is only computed when
+/// the template uses it.
+pub(super) fn render_prompt(template: &str, ctx: &TemplateContext<'_>) -> String {
+    let snapshot = ctx.snapshot;
+    let issue_ref = snapshot.issue.as_deref().unwrap_or("(no issue)");
+    let (title, body, labels, criteria, comments) = match ctx.issue {
+        Some(issue) => (
+            issue.title.as_str(),
+            truncate_head(&issue.body),
+            issue.labels.join(", "),
+            issue
+                .acceptance_criteria
+                .iter()
+                .map(|item| format!("- {item}"))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            truncate_tail(
+                &issue
+                    .comments
+                    .iter()
+                    .map(|comment| format!("{}: {}", comment.author, comment.body))
+                    .collect::<Vec<_>>()
+                    .join("\n\n"),
+            ),
+        ),
+        None => (
+            issue_ref,
+            String::new(),
+            String::new(),
+            String::new(),
+            String::new(),
+        ),
+    };
+    let rendered = template
+        .replace("{{issue}}", issue_ref)
+        .replace("{{issue.title}}", title)
+        .replace("{{issue.body}}", &body)
+        .replace("{{issue.labels}}", &labels)
+        .replace("{{issue.acceptance_criteria}}", &criteria)
+        .replace("{{issue.comments}}", &comments)
+        .replace("{{worker}}", snapshot.name.as_str())
+        .replace("{{branch}}", snapshot.branch.as_str())
+        .replace("{{worktree}}", snapshot.worktree.as_str())
+        .replace(
+            "{{session.result}}",
+            &truncate_head(ctx.session_result.unwrap_or_default()),
+        );
+
+    let mut rendered = STEP_PLACEHOLDER
+        .replace_all(&rendered, |caps: &Captures| {
+            ctx.step_value(caps[1].trim(), &caps[2])
+        })
+        .into_owned();
+
+    if rendered.contains(GIT_DIFF_PLACEHOLDER) {
+        rendered = rendered.replace(GIT_DIFF_PLACEHOLDER, &git_diff(ctx.worktree_path));
+    }
+
+    rendered
+}
+
+/// Render the pull request for a `pr` step (or the pull request action, with
+/// default settings). `default_base` is the forge's target branch.
+pub(super) fn render_pull_request(
+    step: &PullRequestStep,
+    default_base: Option<&str>,
+    ctx: &TemplateContext<'_>,
+) -> Result<PullRequest> {
+    let has_issue = ctx.snapshot.issue.is_some();
+    let title = step.title.as_deref().unwrap_or(if has_issue {
+        DEFAULT_PR_TITLE
+    } else {
+        DEFAULT_PR_TITLE_WITHOUT_ISSUE
+    });
+    let body = step.body.as_deref().unwrap_or(if has_issue {
+        DEFAULT_PR_BODY
+    } else {
+        DEFAULT_PR_BODY_WITHOUT_ISSUE
+    });
+
+    let title = render_prompt(title, ctx).trim().to_string();
+    if title.is_empty() {
+        bail!("pull request title is empty");
+    }
+    let Some(base) = step.base.as_deref().or(default_base) else {
+        bail!("no base branch for the pull request; set forge.base");
+    };
+    Ok(PullRequest {
+        title,
+        body: render_prompt(body, ctx).trim().to_string(),
+        head: ctx.snapshot.branch.clone(),
+        base: base.to_string(),
+        draft: step.draft,
+    })
+}
+
+/// Render the merge of a `merge` step (or the merge action, with default
+/// settings). `default_base` is the branch the worker was created from.
+pub(super) fn render_merge(
+    step: &MergeStep,
+    default_base: Option<&str>,
+    ctx: &TemplateContext<'_>,
+) -> Result<MergeRequest> {
+    let message = step.message.as_deref().unwrap_or(if ctx.snapshot.issue.is_some() {
+        DEFAULT_PR_TITLE
+    } else {
+        DEFAULT_PR_TITLE_WITHOUT_ISSUE
+    });
+    let message = render_prompt(message, ctx).trim().to_string();
+    if message.is_empty() {
+        bail!("merge commit message is empty");
+    }
+    let Some(base) = step.into.as_deref().or(default_base) else {
+        bail!("no branch to merge into; set merge.into");
+    };
+    Ok(MergeRequest {
+        strategy: step.strategy,
+        base: base.to_string(),
+        branch: ctx.snapshot.branch.clone(),
+        message,
+        dry_run: step.dry_run,
+    })
+}
+
+/// Message of a commit made after `step_name`: the template when given,
+/// otherwise the step name and issue title as the subject and the agent's
+/// result as the body.
+pub(super) fn render_commit_message(
+    template: Option<&str>,
+    step_name: &str,
+    result: Option<&str>,
+    ctx: &TemplateContext<'_>,
+) -> String {
+    if let Some(template) = template {
+        let message = render_prompt(template, ctx).trim().to_string();
+        if !message.is_empty() {
+            return message;
+        }
+    }
+    let snapshot = ctx.snapshot;
+    let subject = match (ctx.issue, snapshot.issue.as_deref()) {
+        (Some(issue), _) => format!("{step_name}: {}", issue.title),
+        (None, Some(reference)) => format!("{step_name}: {reference}"),
+        (None, None) => format!("{step_name} ({})", snapshot.name),
+    };
+    let mut message = subject;
+    if let Some(result) = result.map(str::trim).filter(|result| !result.is_empty()) {
+        message.push_str("\n\n");
+        message.push_str(&truncate_head(result));
+    }
+    if let Some(reference) = &snapshot.issue {
+        message.push_str(&format!("\n\nIssue: {reference}"));
+    }
+    message
+}
+
+/// Append the staged changes to a rendered commit message prompt.
+pub(super) fn with_staged_diff(prompt: &str, diff: &str) -> String {
+    format!("{prompt}\n\n{}", truncate_head(diff))
+}
+
+/// Append the conflicting files to a rendered resolution prompt.
+pub(super) fn with_conflicting_files(prompt: &str, files: &[String]) -> String {
+    let list = files
+        .iter()
+        .map(|file| format!("- {file}"))
+        .collect::<Vec<_>>()
+        .join("\n");
+    format!("{prompt}\n\nコンフリクトしているファイル:\n{list}")
+}
+
+fn git_diff(worktree: &Path) -> String {
+    let output = Command::new("git")
+        .args(["diff", "HEAD"])
+        .current_dir(worktree)
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output();
+
+    match output {
+        Ok(output) if output.status.success() => {
+            let diff = String::from_utf8_lossy(&output.stdout);
+            if diff.trim().is_empty() {
+                "(no changes)".to_string()
+            } else {
+                truncate_head(&diff)
+            }
+        }
+        _ => "(git diff unavailable)".to_string(),
+    }
+}
+
+fn truncate_head(value: &str) -> String {
+    if value.len() <= MAX_VALUE_BYTES {
+        return value.to_string();
+    }
+    let mut end = MAX_VALUE_BYTES;
+    while !value.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!("{}\n... (truncated)", &value[..end])
+}
+
+fn truncate_tail(value: &str) -> String {
+    if value.len() <= MAX_VALUE_BYTES {
+        return value.to_string();
+    }
+    let mut start = value.len() - MAX_VALUE_BYTES;
+    while !value.is_char_boundary(start) {
+        start += 1;
+    }
+    format!("(truncated) ...\n{}", &value[start..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::WorkflowStep;
+    use crate::issue::IssueComment;
+    use crate::state::StepOutcomeStatus;
+    use crate::worker::{WorkerId, WorkerStatus};
+
+    fn snapshot() -> WorkerSnapshot {
+        WorkerSnapshot {
+            id: WorkerId(1),
+            name: "worker-1".to_string(),
+            issue: Some("#42".to_string()),
+            issue_title: None,
+            agent: "claude".to_string(),
+            worktree: "/tmp/wt".to_string(),
+            branch: "gensui/worker-1".to_string(),
+            status: WorkerStatus::Running,
+            last_event: String::new(),
+            workflow: "default".to_string(),
+            total_steps: 2,
+            current_step: None,
+            session_id: None,
+            priority: 0,
+            queue_position: None,
+            pr_url: None,
+            base_ref: None,
+        }
+    }
+
+    fn workflow() -> Workflow {
+        Workflow {
+            name: "default".to_string(),
+            description: None,
+            steps: vec![
+                WorkflowStep {
+                    id: Some("test".to_string()),
+                    name: "テスト".to_string(),
+                    ..Default::default()
+                },
+                WorkflowStep {
+                    name: "analyze".to_string(),
+                    ..Default::default()
+                },
+            ],
+            extends: None,
+        }
+    }
+
+    #[test]
+    fn test_render_step_outputs() {
+        let snapshot = snapshot();
+        let workflow = workflow();
+        let mut outcomes = HashMap::new();
+        outcomes.insert(
+            "test".to_string(),
+            StepOutcome {
+                status: StepOutcomeStatus::Failed,
+                exit_code: Some(101),
+                stdout: "1 failed".to_string(),
+                result: None,
+                commit: None,
+            },
+        );
+        outcomes.insert(
+            "analyze".to_string(),
+            StepOutcome {
+                result: Some("looks fine".to_string()),
+                ..Default::default()
+            },
+        );
+        let ctx = TemplateContext {
+            snapshot: &snapshot,
+            workflow: &workflow,
+            outcomes: &outcomes,
+            worktree_path: Path::new("/nonexistent"),
+            issue: None,
+            session_result: None,
+        };
+
+        let rendered = render_prompt(
+            "{{issue}} on {{branch}}: {{steps.test.exit_code}} / {{ steps.テスト.stdout }} / \
+             {{steps.analyze.result}} / [{{steps.review.result}}]",
+            &ctx,
+        );
+        assert_eq!(
+            rendered,
+            "#42 on gensui/worker-1: 101 / 1 failed / looks fine / []"
+        );
+    }
+
+    #[test]
+    fn test_render_issue_metadata() {
+        let snapshot = snapshot();
+        let workflow = workflow();
+        let outcomes = HashMap::new();
+        let template = "{{issue.title}} [{{issue.labels}}]\n{{issue.body}}\n\
+                        {{issue.acceptance_criteria}}\n{{issue.comments}}";
+        let mut ctx = TemplateContext {
+            snapshot: &snapshot,
+            workflow: &workflow,
+            outcomes: &outcomes,
+            worktree_path: Path::new("/nonexistent"),
+            issue: None,
+            session_result: None,
+        };
+        assert_eq!(render_prompt(template, &ctx), "#42 []\n\n\n");
+
+        let issue = Issue {
+            id: "42".to_string(),
+            title: "Fix login".to_string(),
+            body: "It crashes".to_string(),
+            labels: vec!["bug".to_string(), "auth".to_string()],
+            acceptance_criteria: vec!["no crash".to_string(), "tests pass".to_string()],
+            comments: vec![IssueComment {
+                author: "alice".to_string(),
+                body: "same on mobile".to_string(),
+            }],
+        };
+        ctx.issue = Some(&issue);
+        assert_eq!(
+            render_prompt(template, &ctx),
+            "Fix login [bug, auth]\nIt crashes\n- no crash\n- tests pass\nalice: same on mobile"
+        );
+    }
+
+    #[test]
+    fn test_render_pull_request() {
+        let snapshot = snapshot();
+        let workflow = workflow();
+        let outcomes = HashMap::new();
+        let issue = Issue {
+            id: "42".to_string(),
+            title: "Fix login".to_string(),
+            ..Default::default()
+        };
+        let mut ctx = TemplateContext {
+            snapshot: &snapshot,
+            workflow: &workflow,
+            outcomes: &outcomes,
+            worktree_path: Path::new("/nonexistent"),
+            issue: Some(&issue),
+            session_result: Some("Handled the empty password case."),
+        };
+
+        let request = render_pull_request(&PullRequestStep::default(), Some("main"), &ctx).unwrap();
+        assert_eq!(request.title, "Fix login");
+        assert_eq!(request.body, "Closes #42\n\nHandled the empty password case.");
+        assert_eq!(request.head, "gensui/worker-1");
+        assert_eq!(request.base, "main");
+
+        let step = PullRequestStep {
+            title: Some("[{{worker}}] {{issue.title}}".to_string()),
+            base: Some("develop".to_string()),
+            draft: true,
+            ..Default::default()
+        };
+        let request = render_pull_request(&step, None, &ctx).unwrap();
+        assert_eq!(request.title, "[worker-1] Fix login");
+        assert_eq!(request.base, "develop");
+        assert!(request.draft);
+        assert!(render_pull_request(&PullRequestStep::default(), None, &ctx).is_err());
+
+        let snapshot = WorkerSnapshot {
+            issue: None,
+            ..snapshot.clone()
+        };
+        ctx.snapshot = &snapshot;
+        ctx.issue = None;
+        ctx.session_result = None;
+        let request = render_pull_request(&PullRequestStep::default(), Some("main"), &ctx).unwrap();
+        assert_eq!(request.title, "worker-1");
+        assert_eq!(request.body, "");
+    }
+
+    #[test]
+    fn test_render_merge() {
+        let snapshot = snapshot();
+        let workflow = workflow();
+        let outcomes = HashMap::new();
+        let ctx = TemplateContext {
+            snapshot: &snapshot,
+            workflow: &workflow,
+            outcomes: &outcomes,
+            worktree_path: Path::new("/nonexistent"),
+            issue: None,
+            session_result: None,
+        };
+
+        let request = render_merge(&MergeStep::default(), Some("main"), &ctx).unwrap();
+        assert_eq!(request.message, "#42");
+        assert_eq!(request.base, "main");
+        assert_eq!(request.branch, "gensui/worker-1");
+        assert!(!request.dry_run);
+
+        let step = MergeStep {
+            into: Some("develop".to_string()),
+            message: Some("Merge {{worker}}".to_string()),
+            dry_run: true,
+            ..Default::default()
+        };
+        let request = render_merge(&step, None, &ctx).unwrap();
+        assert_eq!((request.base.as_str(), request.message.as_str()), ("develop", "Merge worker-1"));
+        assert!(request.dry_run);
+        assert!(render_merge(&MergeStep::default(), None, &ctx).is_err());
+    }
+
+    #[test]
+    fn test_render_commit_message() {
+        let snapshot = snapshot();
+        let workflow = workflow();
+        let outcomes = HashMap::new();
+        let issue = Issue {
+            id: "42".to_string(),
+            title: "Fix login".to_string(),
+            ..Default::default()
+        };
+        let mut ctx = TemplateContext {
+            snapshot: &snapshot,
+            workflow: &workflow,
+            outcomes: &outcomes,
+            worktree_path: Path::new("/nonexistent"),
+            issue: Some(&issue),
+            session_result: None,
+        };
+
+        assert_eq!(
+            render_commit_message(None, "実装", Some("Handled empty passwords.\n"), &ctx),
+            "実装: Fix login\n\nHandled empty passwords.\n\nIssue: #42"
+        );
+        assert_eq!(
+            render_commit_message(Some("fix({{worker}}): {{issue.title}}"), "実装", None, &ctx),
+            "fix(worker-1): Fix login"
+        );
+
+        let snapshot = WorkerSnapshot {
+            issue: None,
+            ..snapshot.clone()
+        };
+        ctx.snapshot = &snapshot;
+        ctx.issue = None;
+        assert_eq!(render_commit_message(Some("  "), "実装", None, &ctx), "実装 (worker-1)");
+    }
+
+    #[test]
+    fn test_git_diff_outside_repository() {
+        let snapshot = snapshot();
+        let workflow = workflow();
+        let outcomes = HashMap::new();
+        let dir = std::env::temp_dir();
+        let ctx = TemplateContext {
+            snapshot: &snapshot,
+            workflow: &workflow,
+            outcomes: &outcomes,
+            worktree_path: &dir.join("gensui-template-missing"),
+            issue: None,
+            session_result: None,
+        };
+
+        assert_eq!(render_prompt("{{git.diff}}", &ctx), "(git diff unavailable)");
+    }
+
+    #[test]
+    fn test_truncate_keeps_char_boundaries() {
+        let long = "あ".repeat(MAX_VALUE_BYTES);
+        assert!(truncate_head(&long).ends_with("(truncated)"));
+        assert!(truncate_tail(&long).starts_with("(truncated)"));
+    }
+}
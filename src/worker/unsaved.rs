@@ -0,0 +1,106 @@
+//! ワーカーの削除で失われる未コミット・未 push の作業の検出
+use std::path::Path;
+
+use anyhow::Result;
+use serde::Serialize;
+
+use super::git::git;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct UnsavedWork {
+    /// `git status --porcelain` lines, e.g. ` M src/lib.rs` or `?? notes.txt`.
+    pub uncommitted: Vec<String>,
+    /// `<short sha> <subject>` of commits that only exist on the worker branch.
+    pub unpushed: Vec<String>,
+}
+
+impl UnsavedWork {
+    /// Inspect a worker. `worktree` is None (or missing) for archived workers,
+    /// which can only have unpushed commits.
+    pub fn inspect(
+        repo_root: &Path,
+        worktree: Option<&Path>,
+        branch: &str,
+        base: Option<&str>,
+    ) -> Result<Self> {
+        let mut unsaved = Self::default();
+
+        if let Some(worktree) = worktree.filter(|path| path.exists()) {
+            unsaved.uncommitted = git(worktree, &["status", "--porcelain", "--untracked-files=all"])?
+                .lines()
+                .map(str::to_string)
+                .collect();
+        }
+
+        let branch_ref = format!("refs/heads/{branch}");
+        if git(repo_root, &["rev-parse", "--verify", "--quiet", &branch_ref]).is_ok() {
+            let mut args = vec!["log", "--format=%h %s", branch_ref.as_str(), "--not", "--remotes"];
+            args.extend(base);
+            unsaved.unpushed = git(repo_root, &args)?.lines().map(str::to_string).collect();
+        }
+
+        Ok(unsaved)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.uncommitted.is_empty() && self.unpushed.is_empty()
+    }
+
+    /// e.g. `2 uncommitted file(s) and 1 unpushed commit(s)`
+    pub fn summary(&self) -> String {
+        let mut parts = Vec::new();
+        if !self.uncommitted.is_empty() {
+            parts.push(format!("{} uncommitted file(s)", self.uncommitted.len()));
+        }
+        if !self.unpushed.is_empty() {
+            parts.push(format!("{} unpushed commit(s)", self.unpushed.len()));
+        }
+        parts.join(" and ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::worker::git::fixture::{init_repo, run_git};
+    use std::fs;
+
+    #[test]
+    fn test_inspect_finds_uncommitted_and_unpushed_work() {
+        let dir = std::env::temp_dir().join(format!("gensui-unsaved-{}", std::process::id()));
+        init_repo(&dir);
+        let run = |args: &[&str]| run_git(&dir, args);
+        fs::write(dir.join("a.txt"), "one\n").unwrap();
+        run(&["add", "."]);
+        run(&["commit", "-q", "-m", "init"]);
+        run(&["checkout", "-q", "-b", "feature"]);
+
+        let clean = UnsavedWork::inspect(&dir, Some(&dir), "feature", Some("main")).unwrap();
+        assert!(clean.is_empty());
+
+        fs::write(dir.join("b.txt"), "new\n").unwrap();
+        run(&["add", "."]);
+        run(&["commit", "-q", "-m", "Add b"]);
+        fs::write(dir.join("a.txt"), "two\n").unwrap();
+        fs::write(dir.join("c.txt"), "untracked\n").unwrap();
+
+        let unsaved = UnsavedWork::inspect(&dir, Some(&dir), "feature", Some("main")).unwrap();
+        assert_eq!(unsaved.uncommitted, vec![" M a.txt", "?? c.txt"]);
+        assert_eq!(unsaved.unpushed.len(), 1);
+        assert!(unsaved.unpushed[0].ends_with(" Add b"));
+        assert_eq!(
+            unsaved.summary(),
+            "2 uncommitted file(s) and 1 unpushed commit(s)"
+        );
+
+        // Commits pushed to a remote-tracking branch are safe
+        run(&["update-ref", "refs/remotes/origin/feature", "feature"]);
+        let pushed = UnsavedWork::inspect(&dir, None, "feature", Some("main")).unwrap();
+        assert!(pushed.is_empty());
+
+        let gone = UnsavedWork::inspect(&dir, None, "no-such-branch", None).unwrap();
+        assert!(gone.is_empty());
+
+        let _ = fs::remove_dir_all(dir);
+    }
+}
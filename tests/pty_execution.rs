@@ -27,6 +27,9 @@ fn test_pty_execution_detects_tty() {
     let mut child = pty_pair.slave.spawn_command(cmd)
         .expect("Failed to spawn command");
 
+    // Drop the slave so the master reader sees EOF when the child exits
+    drop(pty_pair.slave);
+
     let mut reader = pty_pair.master.try_clone_reader()
         .expect("Failed to clone reader");
 
@@ -83,6 +86,9 @@ fn test_pty_sets_term_variable() {
     let mut child = pty_pair.slave.spawn_command(cmd)
         .expect("Failed to spawn command");
 
+    // Drop the slave so the master reader sees EOF when the child exits
+    drop(pty_pair.slave);
+
     let mut reader = pty_pair.master.try_clone_reader()
         .expect("Failed to clone reader");
 